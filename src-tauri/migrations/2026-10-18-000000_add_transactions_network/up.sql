@@ -0,0 +1,6 @@
+ALTER TABLE transactions ADD COLUMN network TEXT NOT NULL DEFAULT '';
+
+-- Ethereum history recorded so far was all on mainnet.
+UPDATE transactions SET network = 'mainnet' WHERE chain = 'ethereum';
+-- The Bitcoin network comes from the app config, bitcoin rows are backfilled
+-- with it on start, see `TxRepository::backfill_network`.
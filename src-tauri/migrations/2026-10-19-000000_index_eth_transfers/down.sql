@@ -0,0 +1,43 @@
+DROP TABLE IF EXISTS sync_checkpoints;
+
+CREATE TABLE transactions_old (
+    tx_hash         TEXT PRIMARY KEY,
+    wallet_name     TEXT    NOT NULL,
+    chain           TEXT    NOT NULL,
+    account_index   INTEGER NOT NULL,
+    direction       SMALLINT NOT NULL,
+    status          TEXT    NOT NULL,
+    from_address    TEXT,
+    to_address      TEXT,
+    amount          BIGINT  NOT NULL,
+    fee             INTEGER,
+    block_height    BIGINT,
+    chain_data      OBJECT,
+    created_at      BIGINT  NOT NULL,
+    confirmed_at    BIGINT,
+    network         TEXT    NOT NULL DEFAULT ''
+);
+
+-- Only one row per hash fits the old key, the one the wallet sent wins.
+INSERT OR IGNORE INTO transactions_old (
+    tx_hash, wallet_name, chain, account_index, direction, status, from_address,
+    to_address, amount, fee, block_height, chain_data, created_at, confirmed_at,
+    network
+)
+SELECT
+    tx_hash, wallet_name, chain, account_index, direction, status, from_address,
+    to_address, amount, fee, block_height, chain_data, created_at, confirmed_at,
+    network
+FROM transactions
+ORDER BY log_index;
+
+DROP INDEX IF EXISTS idx_tx_lookup;
+DROP TABLE transactions;
+ALTER TABLE transactions_old RENAME TO transactions;
+
+CREATE INDEX idx_tx_lookup ON transactions (
+    wallet_name,
+    chain,
+    account_index,
+    created_at DESC
+);
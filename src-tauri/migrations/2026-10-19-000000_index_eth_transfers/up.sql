@@ -0,0 +1,55 @@
+-- One row per transfer rather than per transaction: a transaction can move
+-- several tokens and concern several accounts of the same wallet. Rows that
+-- aren't tied to a `Transfer` event keep log_index -1.
+CREATE TABLE transactions_new (
+    tx_hash         TEXT    NOT NULL,
+    log_index       BIGINT  NOT NULL DEFAULT -1,
+    wallet_name     TEXT    NOT NULL,
+    chain           TEXT    NOT NULL,
+    account_index   INTEGER NOT NULL,
+    direction       SMALLINT NOT NULL,
+    status          TEXT    NOT NULL,
+    from_address    TEXT,
+    to_address      TEXT,
+    amount          BIGINT  NOT NULL,
+    fee             INTEGER,
+    block_height    BIGINT,
+    chain_data      OBJECT,
+    created_at      BIGINT  NOT NULL,
+    confirmed_at    BIGINT,
+    network         TEXT    NOT NULL DEFAULT '',
+    PRIMARY KEY (wallet_name, chain, account_index, tx_hash, log_index)
+);
+
+INSERT INTO transactions_new (
+    tx_hash, wallet_name, chain, account_index, direction, status, from_address,
+    to_address, amount, fee, block_height, chain_data, created_at, confirmed_at,
+    network
+)
+SELECT
+    tx_hash, wallet_name, chain, account_index, direction, status, from_address,
+    to_address, amount, fee, block_height, chain_data, created_at, confirmed_at,
+    network
+FROM transactions;
+
+DROP INDEX IF EXISTS idx_tx_lookup;
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
+
+CREATE INDEX idx_tx_lookup ON transactions (
+    wallet_name,
+    chain,
+    account_index,
+    created_at DESC
+);
+
+-- Last block a history scan covered, per address and kind of scan.
+CREATE TABLE sync_checkpoints (
+    wallet_name     TEXT    NOT NULL,
+    chain           TEXT    NOT NULL,
+    network         TEXT    NOT NULL,
+    address         TEXT    NOT NULL,
+    scope           TEXT    NOT NULL,
+    block_height    BIGINT  NOT NULL,
+    PRIMARY KEY (wallet_name, chain, network, address, scope)
+);
@@ -0,0 +1,10 @@
+-- (token, spender) pairs an address ever approved. Allowances themselves are
+-- read from the token contracts, they change without further events.
+CREATE TABLE token_approvals (
+    wallet_name     TEXT    NOT NULL,
+    network         TEXT    NOT NULL,
+    owner           TEXT    NOT NULL,
+    token           TEXT    NOT NULL,
+    spender         TEXT    NOT NULL,
+    PRIMARY KEY (wallet_name, network, owner, token, spender)
+);
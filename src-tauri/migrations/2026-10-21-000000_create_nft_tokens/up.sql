@@ -0,0 +1,11 @@
+-- Token ids an address received from a collection without ERC-721
+-- Enumerable. Ownership is checked against the contract, the ids may have
+-- been sent away since.
+CREATE TABLE nft_tokens (
+    wallet_name     TEXT    NOT NULL,
+    network         TEXT    NOT NULL,
+    owner           TEXT    NOT NULL,
+    collection      TEXT    NOT NULL,
+    token_id        TEXT    NOT NULL,
+    PRIMARY KEY (wallet_name, network, owner, collection, token_id)
+);
@@ -0,0 +1,10 @@
+-- ABIs the user loaded to call a contract, the same address being another
+-- contract on every network.
+CREATE TABLE contract_abis (
+    network         TEXT    NOT NULL,
+    address         TEXT    NOT NULL,
+    name            TEXT    NOT NULL,
+    abi             TEXT    NOT NULL,
+    updated_at      BIGINT  NOT NULL,
+    PRIMARY KEY (network, address)
+);
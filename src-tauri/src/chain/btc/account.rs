@@ -1,20 +1,28 @@
 use std::collections::{HashMap, HashSet};
 
-use bitcoin::{Address, Network, OutPoint, address::NetworkChecked};
-use serde::Deserialize;
+use bitcoin::{Address, Network, OutPoint, Txid, address::NetworkChecked, bip32::Xpriv};
+use serde::{Deserialize, Serialize};
 use specta::Type;
 
 use crate::{
     chain::btc::{
-        Prk, coin_selection,
-        dtos::{ActiveAccountView, OutPointRef},
+        Prk,
+        chain_tip::TipTracker,
+        coin_selection,
+        dtos::{ActiveAccountView, OutPointRef, UtxoView},
+        dust::DustState,
         key_derivation::{
             Change, Child, KeyDerivationPath, KeyDeriviationPathSlice, LabeledKeyDerivationPath,
             Proposal,
         },
+        receive_addresses::ReceiveAddressManager,
+        recovery::RecoveryPolicy,
+        silent_payments::SilentPaymentState,
         utxo::Utxo,
+        zero_conf::ZeroConfAssessment,
     },
     chain_trait::{AccountIndex, SecureKey},
+    config::explorer::Explorer,
 };
 
 #[derive(Clone)]
@@ -23,6 +31,9 @@ pub struct Account {
     pub name: String,
     pub keychain: KeyChain,
     pub utxo_set: UtxoSet,
+    pub silent_payments: SilentPaymentState,
+    /// Timelocked backup key committed into new taproot outputs.
+    pub recovery: Option<RecoveryPolicy>,
 }
 
 impl Account {
@@ -33,10 +44,61 @@ impl Account {
             keychain: KeyChain::default(network, account),
             utxo_set: UtxoSet {
                 entries: HashMap::new(),
+                locked: HashSet::new(),
+                zero_conf: HashMap::new(),
+                dust: HashMap::new(),
+                unverified: HashSet::new(),
+                spent: HashSet::new(),
             },
+            silent_payments: SilentPaymentState::default(),
+            recovery: None,
         }
     }
 
+    /// Derive a key of this account. Taproot outputs commit to the recovery
+    /// leaf while a recovery policy is set.
+    pub fn derive(&self, path: &KeyDerivationPath, xpriv: &Xpriv) -> Result<Child, String> {
+        path.derive_with_recovery(xpriv, self.recovery.as_ref())
+    }
+
+    /// Key controlling `utxo`. Coins received before the recovery policy was
+    /// set sit on plain BIP86 outputs. Silent payment outputs have no BIP32
+    /// key, see `silent_payments::prepare_input`.
+    pub fn derive_for_utxo(&self, utxo: &Utxo, xpriv: &Xpriv) -> Result<Child, String> {
+        if utxo.silent_payment.is_some() {
+            return Err(format!(
+                "utxo {} is a silent payment without a derivation path",
+                utxo.outpoint()
+            ));
+        }
+        let child = self.derive(&utxo.derivation, xpriv)?;
+        let script = child.address_for(utxo.derivation.purpose).script_pubkey();
+        if self.recovery.is_none() || script == utxo.output.script_pubkey {
+            return Ok(child);
+        }
+        utxo.derivation.derive(xpriv)
+    }
+
+    /// Offer the silent payment outputs found so far to coin selection. The
+    /// UTXO set is rebuilt from the watched addresses on every sync, which
+    /// don't cover them.
+    pub fn merge_silent_payments(&mut self, network: Network) {
+        let derivation =
+            KeyDerivationPath::new(Proposal::Taproot, network, self.index, Change::External, 0);
+        for output in self.silent_payments.outputs.values() {
+            self.utxo_set
+                .entries
+                .entry(output.outpoint)
+                .or_insert_with(|| output.to_utxo(derivation.clone()));
+        }
+    }
+
+    /// `UtxoSet::spend`, also forgetting spent silent payment outputs.
+    pub fn spend(&mut self, outpoints: &[OutPoint]) {
+        self.utxo_set.spend(outpoints);
+        self.silent_payments.remove(outpoints);
+    }
+
     pub fn main_key(
         &self,
         prk: &Prk,
@@ -44,9 +106,7 @@ impl Account {
     ) -> Result<(Child, KeyDerivationPath), String> {
         let main_key_derive_path =
             KeyDerivationPath::new(Proposal::Taproot, network, self.index, Change::External, 0);
-        let child = main_key_derive_path
-            .derive(prk.expose())
-            .map_err(|e| e.to_string())?;
+        let child = self.derive(&main_key_derive_path, prk.expose())?;
         Ok((child, main_key_derive_path))
     }
 
@@ -59,7 +119,7 @@ impl Account {
             .paths
             .iter()
             .filter_map(|schema| {
-                schema.path.derive(prk.expose()).ok().map(|child| {
+                self.derive(&schema.path, prk.expose()).ok().map(|child| {
                     (
                         child.address_for(schema.path.purpose).clone(),
                         schema.path.clone(),
@@ -68,29 +128,39 @@ impl Account {
             })
             .collect();
         map.insert(main_addr.0.taproot_address, main_addr.1);
+        if self.recovery.is_some() {
+            // keep watching coins received before the policy was set
+            for schema in &self.keychain.paths {
+                if let Ok(child) = schema.path.derive(prk.expose()) {
+                    map.insert(
+                        child.address_for(schema.path.purpose).clone(),
+                        schema.path.clone(),
+                    );
+                }
+            }
+        }
         map
     }
 
-    pub fn info(&self, prk: &Prk, network: Network) -> Result<ActiveAccountView, String> {
-        let (mainkey, _) = self.main_key(prk, network)?;
-        let address_label_map = self.keychain.to_label_map();
-
-        let mut utxo: Vec<_> = self
+    pub fn info(
+        &self,
+        prk: &Prk,
+        network: Network,
+        tip: &TipTracker,
+        explorer: Option<&Explorer>,
+    ) -> Result<ActiveAccountView, String> {
+        // Show the first unused receive address instead of pinning index 0.
+        let receive_key = match ReceiveAddressManager::new(self, network).current() {
+            Some(path) => self.derive(&path, prk.expose())?,
+            None => self.main_key(prk, network)?.0,
+        };
+        let utxo = self
             .utxo_set
-            .entries
-            .values()
-            .map(|utxo| utxo.to_view(&address_label_map))
-            .collect();
-        utxo.sort_by(|a, b| {
-            b.value
-                .parse::<u64>()
-                .unwrap_or(0)
-                .cmp(&a.value.parse::<u64>().unwrap_or(0))
-        });
+            .to_views(&self.keychain.to_label_map(), tip, explorer);
 
         Ok(ActiveAccountView {
             index: self.index,
-            address: mainkey.taproot_address.to_string(),
+            address: receive_key.taproot_address.to_string(),
             total_balance: self.utxo_set.total_value().to_string(),
             utxo,
         })
@@ -100,11 +170,42 @@ impl Account {
 #[derive(Clone)]
 pub struct KeyChain {
     pub paths: Vec<LabeledKeyDerivationPath>,
+    /// On-chain usage of revealed addresses.
+    pub usage: HashMap<KeyDerivationPath, AddressUsage>,
+    /// Change paths held by a pending transaction. Not persisted, they only
+    /// enter `paths` once the transaction is broadcast.
+    pub reserved: HashSet<KeyDerivationPath>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AddressUsage {
+    /// The address has on-chain history, even if its coins are gone.
+    pub has_history: bool,
+    /// Outputs seen paying to the address.
+    pub funding: HashSet<OutPoint>,
+}
+
+impl AddressUsage {
+    pub fn is_used(&self) -> bool {
+        self.has_history || !self.funding.is_empty()
+    }
+
+    /// Paid to by more than one transaction.
+    pub fn is_reused(&self) -> bool {
+        self.funding
+            .iter()
+            .map(|o| o.txid)
+            .collect::<HashSet<_>>()
+            .len()
+            > 1
+    }
 }
 
 impl KeyChain {
     fn default(network: Network, account: AccountIndex) -> Self {
         Self {
+            usage: HashMap::new(),
+            reserved: HashSet::new(),
             paths: vec![LabeledKeyDerivationPath {
                 label: "main".to_string(),
                 path: KeyDerivationPath::new(
@@ -118,12 +219,19 @@ impl KeyChain {
         }
     }
 
-    /// Returns the first index that hasn't been used yet for a specific change
-    /// type.
+    /// Returns the first index that isn't in the keychain or reserved yet for
+    /// a specific change type. This says nothing about on-chain usage, see
+    /// `ReceiveAddressManager` for handing out receive addresses.
     pub fn next_unused_index(&self, change: Change) -> u32 {
         let occupied: HashSet<u32> = self
             .paths_by_change(&change)
             .map(|a| a.path.index)
+            .chain(
+                self.reserved
+                    .iter()
+                    .filter(|p| p.change == change)
+                    .map(|p| p.index),
+            )
             .collect();
 
         (1..).find(|i| !occupied.contains(i)).unwrap_or(0)
@@ -151,9 +259,43 @@ impl KeyChain {
             .collect()
     }
 
+    pub fn is_used(&self, path: &KeyDerivationPath) -> bool {
+        self.usage.get(path).is_some_and(AddressUsage::is_used)
+    }
+
+    pub fn is_reused(&self, path: &KeyDerivationPath) -> bool {
+        self.usage.get(path).is_some_and(AddressUsage::is_reused)
+    }
+
+    /// Record on-chain history found for `path`.
+    pub fn mark_used(&mut self, path: &KeyDerivationPath) {
+        self.usage.entry(path.clone()).or_default().has_history = true;
+    }
+
+    /// Record an output paying to `path`.
+    pub fn record_funding(&mut self, path: &KeyDerivationPath, outpoint: OutPoint) {
+        self.usage
+            .entry(path.clone())
+            .or_default()
+            .funding
+            .insert(outpoint);
+    }
+
+    /// Hold `path` for a pending transaction so no other build picks it.
+    pub fn reserve(&mut self, path: KeyDerivationPath) {
+        self.reserved.insert(path);
+    }
+
+    pub fn release(&mut self, path: &KeyDerivationPath) {
+        self.reserved.remove(path);
+    }
+
     /// Registers a new path in the keychain.
     pub fn push(&mut self, child: LabeledKeyDerivationPath) {
-        self.paths.push(child);
+        self.reserved.remove(&child.path);
+        if !self.contains_path(child.path.clone()) {
+            self.paths.push(child);
+        }
     }
 }
 
@@ -161,9 +303,22 @@ impl KeyChain {
 pub struct UtxoSet {
     /// A map of outpoints to their corresponding UTXO data.
     pub entries: HashMap<OutPoint, Utxo>,
+    /// Outpoints spent by a pending transaction. Automatic selection skips
+    /// them until the transaction is broadcast or abandoned.
+    pub locked: HashSet<OutPoint>,
+    /// Risk of incoming unconfirmed transactions, refreshed on every sync.
+    pub zero_conf: HashMap<Txid, ZeroConfAssessment>,
+    /// Suspected dust and whether the user released it.
+    pub dust: HashMap<OutPoint, DustState>,
+    /// Coins the primary server dropped that only a quorum witness still
+    /// reports. Automatic selection skips them until the primary agrees.
+    pub unverified: HashSet<OutPoint>,
+    /// Outpoints spent by our broadcast transactions, kept until the primary
+    /// server stops reporting them so a lagging server can't bring them back.
+    pub spent: HashSet<OutPoint>,
 }
 
-#[derive(Clone, Type, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Type, Serialize, Deserialize)]
 pub enum UtxoSelectionStrategy {
     Manual(Vec<OutPointRef>),
     Auto(u32),
@@ -175,6 +330,26 @@ impl UtxoSet {
         self.entries.clear();
         self.entries
             .extend(utxos.into_iter().map(|u| (u.outpoint(), u)));
+        self.dust
+            .retain(|outpoint, _| self.entries.contains_key(outpoint));
+        self.unverified
+            .retain(|outpoint| self.entries.contains_key(outpoint));
+    }
+
+    /// Drops coins we already spent from a server's report and forgets spends
+    /// the server no longer reports.
+    pub fn drop_spent(&mut self, utxos: &mut Vec<Utxo>) {
+        self.spent
+            .retain(|outpoint| utxos.iter().any(|u| u.outpoint() == *outpoint));
+        utxos.retain(|u| !self.spent.contains(&u.outpoint()));
+    }
+
+    pub fn is_unverified(&self, outpoint: &OutPoint) -> bool {
+        self.unverified.contains(outpoint)
+    }
+
+    pub fn mark_unverified(&mut self, outpoints: impl IntoIterator<Item = OutPoint>) {
+        self.unverified = outpoints.into_iter().collect();
     }
 
     /// Calculates the sum of all unspent outputs in satoshis.
@@ -185,8 +360,90 @@ impl UtxoSet {
             .sum()
     }
 
-    /// Selects a subset of UTXOs based on the provided strategy.
-    pub fn select(&self, method: UtxoSelectionStrategy) -> Vec<&Utxo> {
+    pub fn is_locked(&self, outpoint: &OutPoint) -> bool {
+        self.locked.contains(outpoint)
+    }
+
+    pub fn lock(&mut self, outpoints: impl IntoIterator<Item = OutPoint>) {
+        self.locked.extend(outpoints);
+    }
+
+    pub fn unlock(&mut self, outpoints: &[OutPoint]) {
+        for outpoint in outpoints {
+            self.locked.remove(outpoint);
+        }
+    }
+
+    /// Drop outputs spent by a broadcast transaction ahead of the next sync.
+    pub fn spend(&mut self, outpoints: &[OutPoint]) {
+        for outpoint in outpoints {
+            self.locked.remove(outpoint);
+            self.entries.remove(outpoint);
+            self.dust.remove(outpoint);
+            self.unverified.remove(outpoint);
+            self.spent.insert(*outpoint);
+        }
+    }
+
+    pub fn is_quarantined(&self, outpoint: &OutPoint) -> bool {
+        self.dust.get(outpoint) == Some(&DustState::Quarantined)
+    }
+
+    pub fn quarantine(&mut self, outpoints: impl IntoIterator<Item = OutPoint>) {
+        self.dust
+            .extend(outpoints.into_iter().map(|o| (o, DustState::Quarantined)));
+    }
+
+    /// Let a quarantined coin back into automatic selection. It won't be
+    /// flagged again.
+    pub fn release(&mut self, outpoint: &OutPoint) -> Result<(), String> {
+        match self.dust.get_mut(outpoint) {
+            Some(state @ DustState::Quarantined) => {
+                *state = DustState::Released;
+                Ok(())
+            }
+            _ => Err(format!("utxo {outpoint} is not quarantined")),
+        }
+    }
+
+    /// How likely an unconfirmed coin is to disappear. Our own change is
+    /// trusted, incoming coins not assessed yet count as unverified.
+    pub fn zero_conf_risk(&self, utxo: &Utxo) -> ZeroConfAssessment {
+        if utxo.height > 0 {
+            return ZeroConfAssessment::default();
+        }
+        if utxo.derivation.change == Change::Internal {
+            return ZeroConfAssessment::low();
+        }
+        self.zero_conf
+            .get(&utxo.tx_id)
+            .cloned()
+            .unwrap_or_else(ZeroConfAssessment::unverified)
+    }
+
+    /// Views of all coins, largest first.
+    pub fn to_views(
+        &self,
+        address_label_map: &KeyDerivationPathLabelMap,
+        tip: &TipTracker,
+        explorer: Option<&Explorer>,
+    ) -> Vec<UtxoView> {
+        let mut utxos: Vec<&Utxo> = self.entries.values().collect();
+        utxos.sort_by(|a, b| b.output.value.cmp(&a.output.value));
+        utxos
+            .into_iter()
+            .map(|u| u.to_view(address_label_map, tip, self, explorer))
+            .collect()
+    }
+
+    /// Selects a subset of UTXOs based on the provided strategy. Automatic
+    /// selection leaves out risky unconfirmed coins unless
+    /// `spend_risky_unconfirmed` is set.
+    pub fn select(
+        &self,
+        method: UtxoSelectionStrategy,
+        spend_risky_unconfirmed: bool,
+    ) -> Vec<&Utxo> {
         match method {
             UtxoSelectionStrategy::Manual(out_point_dtos) => {
                 let outpoins = out_point_dtos
@@ -196,14 +453,26 @@ impl UtxoSet {
                     .unwrap();
                 self.select_by_outpoints(outpoins)
             }
-            UtxoSelectionStrategy::Auto(min_value) => self.select_automatically(min_value as u64),
+            UtxoSelectionStrategy::Auto(min_value) => {
+                self.select_automatically(min_value as u64, spend_risky_unconfirmed)
+            }
         }
     }
 
     /// Automated coin selection: Branch and Bound for an exact match (no change
     /// output), falling back to largest-first when no exact solution exists.
-    fn select_automatically(&self, min_value: u64) -> Vec<&Utxo> {
-        let utxos: Vec<&Utxo> = self.entries.values().collect();
+    fn select_automatically(&self, min_value: u64, spend_risky_unconfirmed: bool) -> Vec<&Utxo> {
+        let utxos: Vec<&Utxo> = self
+            .entries
+            .values()
+            .filter(|u| {
+                let outpoint = u.outpoint();
+                !self.is_locked(&outpoint)
+                    && !self.is_quarantined(&outpoint)
+                    && !self.is_unverified(&outpoint)
+            })
+            .filter(|u| spend_risky_unconfirmed || !self.zero_conf_risk(u).is_risky())
+            .collect();
         let values: Vec<u64> = utxos.iter().map(|u| u.output.value.to_sat()).collect();
         coin_selection::select(&values, min_value)
             .into_iter()
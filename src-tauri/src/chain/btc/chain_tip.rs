@@ -0,0 +1,153 @@
+use std::ops::RangeInclusive;
+
+use crate::chain::btc::utxo::BlockHeader;
+
+/// Number of most recent headers kept to detect reorgs. Anything deeper is
+/// out of reach and only fixed by the next full UTXO sync.
+pub const HEADER_WINDOW: u32 = 12;
+
+/// Tracks the best chain through a short window of recent headers.
+#[derive(Debug, Clone, Default)]
+pub struct TipTracker {
+    /// Ascending by height.
+    headers: Vec<BlockHeader>,
+}
+
+/// Blocks at and above `fork_height` were replaced by another branch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reorg {
+    pub fork_height: u32,
+    pub old_tip: BlockHeader,
+    pub new_tip: BlockHeader,
+}
+
+impl TipTracker {
+    pub fn new(mut headers: Vec<BlockHeader>) -> Self {
+        headers.sort_by_key(|h| h.height);
+        Self { headers }
+    }
+
+    pub fn headers(&self) -> &[BlockHeader] {
+        &self.headers
+    }
+
+    pub fn tip(&self) -> Option<&BlockHeader> {
+        self.headers.last()
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        self.tip().map(|h| h.height)
+    }
+
+    /// Confirmations of a transaction mined at `height`, 0 when unconfirmed
+    /// or the tip is unknown.
+    pub fn confirmations(&self, height: u32) -> u32 {
+        match self.height() {
+            Some(tip) if height > 0 && height <= tip => tip - height + 1,
+            _ => 0,
+        }
+    }
+
+    /// Heights to fetch to refresh the window for a tip at `tip_height`.
+    pub fn window(tip_height: u32) -> RangeInclusive<u32> {
+        tip_height.saturating_sub(HEADER_WINDOW - 1)..=tip_height
+    }
+
+    /// Replace the window with `fresh` headers of the current best chain.
+    /// Returns the reorg if a stored block is no longer part of it.
+    ///
+    /// Only a different block at a known height counts. A server that lags
+    /// behind reports a lower tip on the same chain, the window is kept then.
+    pub fn update(&mut self, fresh: Vec<BlockHeader>) -> Option<Reorg> {
+        let fresh = Self::new(fresh);
+        let new_tip = fresh.tip()?.clone();
+        let fork_height = self
+            .headers
+            .iter()
+            .filter(|old| {
+                fresh
+                    .headers
+                    .iter()
+                    .any(|h| h.height == old.height && h.hash != old.hash)
+            })
+            .map(|h| h.height)
+            .min();
+        if fork_height.is_none() && self.height().is_some_and(|tip| tip > new_tip.height) {
+            return None;
+        }
+        let old_tip = self.tip().cloned();
+        self.headers = fresh.headers;
+        Some(Reorg {
+            fork_height: fork_height?,
+            old_tip: old_tip?,
+            new_tip,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{BlockHash, hashes::Hash};
+
+    use super::*;
+
+    fn header(height: u32, branch: u8) -> BlockHeader {
+        let mut bytes = [0u8; 32];
+        bytes[..4].copy_from_slice(&height.to_le_bytes());
+        bytes[31] = branch;
+        BlockHeader {
+            hash: BlockHash::from_byte_array(bytes),
+            height,
+        }
+    }
+
+    fn chain(heights: RangeInclusive<u32>, branch: impl Fn(u32) -> u8) -> Vec<BlockHeader> {
+        heights.map(|h| header(h, branch(h))).collect()
+    }
+
+    #[test]
+    fn counts_confirmations_from_tip() {
+        let tip = TipTracker::new(chain(TipTracker::window(120), |_| 0));
+        assert_eq!(tip.confirmations(120), 1);
+        assert_eq!(tip.confirmations(100), 21);
+        assert_eq!(tip.confirmations(0), 0);
+        assert_eq!(tip.confirmations(121), 0);
+        assert_eq!(TipTracker::default().confirmations(100), 0);
+    }
+
+    #[test]
+    fn extending_the_chain_is_not_a_reorg() {
+        let mut tip = TipTracker::new(chain(TipTracker::window(100), |_| 0));
+        assert_eq!(tip.update(chain(TipTracker::window(102), |_| 0)), None);
+        assert_eq!(tip.height(), Some(102));
+        assert_eq!(tip.headers().len(), HEADER_WINDOW as usize);
+    }
+
+    #[test]
+    fn detects_replaced_blocks() {
+        let mut tip = TipTracker::new(chain(TipTracker::window(100), |_| 0));
+        let reorg = tip
+            .update(chain(TipTracker::window(101), |h| (h >= 99) as u8))
+            .unwrap();
+        assert_eq!(reorg.fork_height, 99);
+        assert_eq!(reorg.old_tip.height, 100);
+        assert_eq!(reorg.new_tip.height, 101);
+    }
+
+    #[test]
+    fn lagging_server_is_not_a_reorg() {
+        let mut tip = TipTracker::new(chain(TipTracker::window(100), |_| 0));
+        assert_eq!(tip.update(chain(TipTracker::window(98), |_| 0)), None);
+        assert_eq!(tip.height(), Some(100));
+    }
+
+    #[test]
+    fn detects_shorter_branch() {
+        let mut tip = TipTracker::new(chain(TipTracker::window(100), |_| 0));
+        let reorg = tip
+            .update(chain(TipTracker::window(98), |h| (h >= 97) as u8))
+            .unwrap();
+        assert_eq!(reorg.fork_height, 97);
+        assert_eq!(tip.height(), Some(98));
+    }
+}
@@ -1,26 +1,56 @@
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
-use bitcoin::{Address, Txid};
+use bitcoin::{Address, OutPoint, Txid, key::Secp256k1};
 use specta::specta;
 
 use crate::{
     chain::btc::{
+        account::{Account, UtxoSelectionStrategy},
+        chain_tip::TipTracker,
+        descriptor_account::{
+            self, BuildDescriptorPsbtParams, DescriptorAccount, DescriptorTemplate,
+        },
         discovery::WalletDiscoverer,
+        drafts::{self, TxDraft, TxDraftEdit},
         dtos::{
-            ActiveAccountView, BroadcastTxRequest, BroadcastTxResponse, BuildTxRequest,
-            BuildTxResponse, BumpFeeRequest, BumpFeeResponse, DerivedAddress, DiscoveryReportView,
-            UtxoView,
+            ActiveAccountView, BroadcastDescriptorPsbtRequest, BroadcastTxDraftRequest,
+            BroadcastTxRequest, BroadcastTxResponse, BtcQuorumEvent, BtcReorgEvent,
+            BuildDescriptorPsbtRequest, BuildTxRequest, BuildTxResponse, BumpFeeRequest,
+            BumpFeeResponse, ChainTipView, CreateDescriptorAccountRequest, CreateTxDraftRequest,
+            DerivedAddress, DescriptorAccountSource, DescriptorAccountView, DescriptorPsbtView,
+            DiscoveryReportView, FeeTiersView, ImportPsbtRequest, NextReceiveAddressRequest,
+            OutPointRef, ReceiveAddressView, RecoveryOutputView, RecoveryView,
+            ReestimateTxDraftRequest, RefreshRecoveryRequest, SetRecoveryPolicyRequest,
+            SilentPaymentsView, SpendQuarantinedUtxoRequest, TxDetailsRequest, TxDetailsView,
+            TxDraftRequest, TxDraftView, UpdateTxDraftRequest, UtxoView,
         },
+        dust::{self, DustSpendMode},
         fee_bump::{BuildCpfpParams, build_cpfp_psbt},
-        fee_estimator::estimate_fee_rate,
-        key_derivation::{Change, Proposal},
-        tx_builder::{BuildPsbtParams, build_psbt, sign_psbt},
+        fee_estimator::{
+            self, PRIORITY_FEE_TARGET_BLOCKS, STANDARD_FEE_TARGET_BLOCKS, resolve_fee_rate,
+        },
+        key_derivation::{Change, KeyDerivationPath, Proposal},
+        pending_tx::PendingTx,
+        policy,
+        providers::tweak_index::TweakIndexClient,
+        receive_addresses::{self, ReceiveAddressManager},
+        recovery::{self, DEFAULT_REFRESH_WINDOW_BLOCKS, RecoveryPolicy},
+        silent_payments::{self, SCAN_BATCH, SilentPaymentKeys, TweakSource},
+        tx_builder::{BuildPsbtParams, BuildTxResult, Recipient, build_psbt, sign_psbt},
+        tx_details::{self, Ownership},
+        utxo::Utxo,
+        zero_conf,
     },
-    chain_trait::SecureKey,
-    config::BlockChain,
+    chain_trait::{AccountIndex, SecureKey},
+    config::{BlockChain, explorer::Explorer},
+    event_emitter::{EventEmitter, EventEmitterTrait},
     repository::{BtcChainData, NewTx, TxDirection, TxRepository, TxStatus},
     session::SK,
     utils,
+    wallet::Wallet,
 };
 
 #[specta]
@@ -31,7 +61,12 @@ pub async fn account_info(sk: tauri::State<'_, SK>) -> Result<ActiveAccountView,
     let wallet = sk.wallet()?;
     let prk = wallet.btc.prk()?;
     let account = wallet.btc.active_account()?;
-    account.info(&prk, wallet.config.btc.network())
+    account.info(
+        &prk,
+        wallet.config.btc.network(),
+        &wallet.btc.tip,
+        wallet.config.btc.explorer().as_ref(),
+    )
 }
 
 #[specta]
@@ -51,10 +86,10 @@ pub async fn derive_external_address(
         .btc
         .new_deriviation_path(proposal, Change::External, index)?
         .with_label(label.clone());
-    let child_key = key_derivation_path
-        .path
-        .derive(prk.expose())
-        .map_err(|e| e.to_string())?;
+    let child_key = wallet
+        .btc
+        .active_account()?
+        .derive(&key_derivation_path.path, prk.expose())?;
 
     wallet
         .btc
@@ -94,42 +129,71 @@ pub async fn get_external_addresess(
     external_paths
         .into_iter()
         .map(|scheme| {
-            let key = scheme
-                .path
-                .derive(prk.expose())
-                .map_err(|e| e.to_string())?;
+            let key = account.derive(&scheme.path, prk.expose())?;
 
             Ok(DerivedAddress {
                 path: scheme.path.to_string(),
                 label: scheme.label.clone(),
                 address: key.address_for(scheme.path.purpose).to_string(),
+                used: account.keychain.is_used(&scheme.path),
+                reused: account.keychain.is_reused(&scheme.path),
             })
         })
         .collect::<Result<Vec<_>, String>>()
 }
 
+/// Receive address to show for the next incoming payment. Returns the lowest
+/// unused address, or reveals a new one when `force_new` is set or all
+/// revealed addresses have been paid to.
 #[specta]
 #[tauri::command]
-#[tracing::instrument(name = "get_utxos", skip_all, err)]
-pub async fn get_utxos(sk: tauri::State<'_, SK>) -> Result<Vec<UtxoView>, String> {
+#[tracing::instrument(name = "next_receive_address", skip_all, err)]
+pub async fn next_receive_address(
+    req: NextReceiveAddressRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<ReceiveAddressView, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
     let account = wallet.btc.active_account()?;
-    let address_label_map = account.keychain.to_label_map();
 
-    let mut utxos: Vec<_> = account
-        .utxo_set
-        .entries
-        .values()
-        .map(|utxo| utxo.to_view(&address_label_map))
-        .collect();
+    let next = ReceiveAddressManager::new(account, network).next(req.force_new);
+    let warning = receive_addresses::check_gap(&next, req.allow_beyond_gap)?;
+    let address = account
+        .derive(&next.path, prk.expose())?
+        .taproot_address
+        .to_string();
 
-    utxos.sort_by(|a, b| {
-        b.value
-            .parse::<u64>()
-            .unwrap_or(0)
-            .cmp(&a.value.parse::<u64>().unwrap_or(0))
-    });
+    if next.is_new {
+        wallet
+            .btc
+            .get_active_account_mut()?
+            .keychain
+            .push(next.path.with_label("Receive".to_string()));
+        wallet.persist()?;
+    }
+
+    Ok(ReceiveAddressView {
+        address,
+        path: next.path.to_string(),
+        unused_gap: next.gap,
+        warning,
+    })
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "get_utxos", skip_all, err)]
+pub async fn get_utxos(sk: tauri::State<'_, SK>) -> Result<Vec<UtxoView>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let account = wallet.btc.active_account()?;
+    let utxos = account.utxo_set.to_views(
+        &account.keychain.to_label_map(),
+        &wallet.btc.tip,
+        wallet.config.btc.explorer().as_ref(),
+    );
 
     const UTXO_DISPLAY_LIMIT: usize = 500;
     Ok(utxos.into_iter().take(UTXO_DISPLAY_LIMIT).collect())
@@ -165,44 +229,417 @@ pub async fn discover_wallet(sk: tauri::State<'_, SK>) -> Result<DiscoveryReport
 #[specta]
 #[tauri::command]
 #[tracing::instrument(name = "sync_utxos", skip_all, err)]
-pub async fn sync_utxos(sk: tauri::State<'_, SK>) -> Result<Vec<UtxoView>, String> {
+pub async fn sync_utxos(
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    event_emitter: tauri::State<'_, EventEmitter>,
+) -> Result<Vec<UtxoView>, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
+    // Confirmation counts are still useful when the header refresh fails.
+    let _ = refresh_chain_tip(wallet, &tx_repository, &event_emitter)
+        .await
+        .inspect_err(|e| tracing::warn!("chain tip refresh failed: {e}"));
     let prk = wallet.btc.prk()?;
     let address_path_map = wallet
         .btc
         .active_account()?
         .derive_address_path_map(&prk, wallet.config.btc.network());
 
-    let received_utxos = wallet
+    let mut received_utxos = wallet
         .btc
         .server
         .get_utxos(address_path_map.clone())
         .await
         .map_err(|e| e.to_string())?;
+    wallet
+        .btc
+        .get_active_account_mut()?
+        .utxo_set
+        .drop_spent(&mut received_utxos);
+
+    let mut unverified = Vec::new();
+    if wallet.btc.quorum.is_enabled() {
+        let addresses: Vec<_> = address_path_map.keys().cloned().collect();
+        let check = wallet
+            .btc
+            .quorum
+            .cross_check(
+                &wallet.btc.server,
+                &addresses,
+                &received_utxos,
+                &wallet.btc.active_account()?.utxo_set,
+            )
+            .await;
+        match check {
+            Ok(check) => {
+                if !check.discrepancies.is_empty() || !check.retained.is_empty() {
+                    tracing::warn!(
+                        servers = ?check.servers,
+                        discrepancies = check.discrepancies.len(),
+                        retained = check.retained.len(),
+                        "bitcoin servers disagree"
+                    );
+                    event_emitter.btc_quorum_mismatch(BtcQuorumEvent {
+                        servers: check.servers,
+                        discrepancies: check.discrepancies.iter().map(Into::into).collect(),
+                        retained: check.retained.iter().map(|u| u.outpoint().into()).collect(),
+                    });
+                }
+                unverified.extend(check.retained.iter().map(Utxo::outpoint));
+                received_utxos.extend(check.retained);
+            }
+            Err(e) => tracing::warn!("quorum check failed: {e}"),
+        }
+    }
+
+    // Receive addresses whose coins were already spent only show up in the
+    // history, so ask for it on the ones not yet known to be used.
+    let keychain = &wallet.btc.active_account()?.keychain;
+    let (unknown_addresses, unknown_paths): (Vec<_>, Vec<_>) = address_path_map
+        .into_iter()
+        .filter(|(_, path)| path.change == Change::External && !keychain.is_used(path))
+        .filter(|(_, path)| !received_utxos.iter().any(|u| u.derivation == *path))
+        .unzip();
+    let activity = wallet
+        .btc
+        .server
+        .batch_has_activity(&unknown_addresses)
+        .await?;
 
     let account = wallet.btc.get_active_account_mut()?;
+    for (path, used) in unknown_paths.iter().zip(activity) {
+        if used {
+            account.keychain.mark_used(path);
+        }
+    }
+    let dust = dust::detect(
+        &account.keychain,
+        &account.utxo_set,
+        &received_utxos,
+        wallet.config.btc.dust.threshold_sat as u64,
+    );
+    account.utxo_set.quarantine(dust);
+    for utxo in &received_utxos {
+        account
+            .keychain
+            .record_funding(&utxo.derivation, utxo.outpoint());
+    }
     account.utxo_set.replace_all(received_utxos);
+    account.utxo_set.mark_unverified(unverified);
+
+    // Silent payment outputs aren't on a watched address: forget the ones
+    // spent meanwhile and put the rest back into the set.
+    let network = wallet.config.btc.network();
+    let sp_addresses = account.silent_payments.addresses(network);
+    if !sp_addresses.is_empty() {
+        let unspent: HashSet<OutPoint> = wallet
+            .btc
+            .server
+            .list_unspent(&sp_addresses)
+            .await?
+            .into_iter()
+            .map(|u| u.outpoint)
+            .collect();
+        wallet
+            .btc
+            .get_active_account_mut()?
+            .silent_payments
+            .retain_unspent(&unspent);
+    }
+    wallet
+        .btc
+        .get_active_account_mut()?
+        .merge_silent_payments(network);
 
-    let address_label_map = account.keychain.to_label_map();
-    let mut result = account
+    let incoming: Vec<&Utxo> = wallet
+        .btc
+        .active_account()?
         .utxo_set
         .entries
         .values()
-        .map(|utxo| utxo.to_view(&address_label_map))
-        .collect::<Vec<_>>();
-
-    result.sort_by(|a, b| {
-        b.value
-            .parse::<u64>()
-            .unwrap_or(0)
-            .cmp(&a.value.parse::<u64>().unwrap_or(0))
-    });
+        .filter(|u| u.height == 0 && u.derivation.change == Change::External)
+        .collect();
+    let zero_conf = if incoming.is_empty() {
+        HashMap::new()
+    } else {
+        let estimate = fee_estimator::estimate_fee_rate(
+            &wallet.btc.server,
+            &wallet.config.btc,
+            STANDARD_FEE_TARGET_BLOCKS,
+        )
+        .await
+        .inspect_err(|e| tracing::warn!("fee estimate unavailable for 0-conf check: {e}"))
+        .ok();
+        zero_conf::assess_incoming(&wallet.btc.server, &incoming, estimate).await
+    };
+    let account = wallet.btc.get_active_account_mut()?;
+    account.utxo_set.zero_conf = zero_conf;
+    let result = account.utxo_set.to_views(
+        &account.keychain.to_label_map(),
+        &wallet.btc.tip,
+        wallet.config.btc.explorer().as_ref(),
+    );
 
     wallet.persist()?;
     Ok(result)
 }
 
+/// Suspected dust of the active account, held back from automatic selection.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_quarantined_utxos", skip_all, err)]
+pub async fn list_quarantined_utxos(sk: tauri::State<'_, SK>) -> Result<Vec<UtxoView>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let account = wallet.btc.active_account()?;
+    Ok(quarantined_views(
+        account,
+        &wallet.btc.tip,
+        wallet.config.btc.explorer().as_ref(),
+    ))
+}
+
+fn quarantined_views(
+    account: &Account,
+    tip: &TipTracker,
+    explorer: Option<&Explorer>,
+) -> Vec<UtxoView> {
+    account
+        .utxo_set
+        .to_views(&account.keychain.to_label_map(), tip, explorer)
+        .into_iter()
+        .filter(|u| u.quarantined)
+        .collect()
+}
+
+/// Trust a quarantined coin again. Returns the remaining quarantine.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "release_quarantined_utxo", skip_all, err)]
+pub async fn release_quarantined_utxo(
+    req: OutPointRef,
+    sk: tauri::State<'_, SK>,
+) -> Result<Vec<UtxoView>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let outpoint = req.try_into()?;
+    wallet
+        .btc
+        .get_active_account_mut()?
+        .utxo_set
+        .release(&outpoint)?;
+    wallet.persist()?;
+    Ok(quarantined_views(
+        wallet.btc.active_account()?,
+        &wallet.btc.tip,
+        wallet.config.btc.explorer().as_ref(),
+    ))
+}
+
+/// Get rid of a quarantined coin on its own, burning it or leaving it all to
+/// the miner, so it never gets linked to other coins of the wallet.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "spend_quarantined_utxo", skip_all, err)]
+pub async fn spend_quarantined_utxo(
+    req: SpendQuarantinedUtxoRequest,
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<BroadcastTxResponse, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let outpoint = req.utxo.try_into()?;
+    let account = wallet.btc.active_account()?;
+    if !account.utxo_set.is_quarantined(&outpoint) {
+        return Err(format!("utxo {outpoint} is not quarantined"));
+    }
+    let fee_rate = match req.mode {
+        DustSpendMode::Burn => {
+            resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, req.fee_mode).await?
+        }
+        DustSpendMode::FeeOnly => 0.0,
+    };
+    let prk = wallet.btc.prk()?;
+    let sweep = dust::build_sweep(account, prk.expose(), outpoint, req.mode, fee_rate)?;
+
+    let tx = sign_psbt(sweep.psbt, &prk)?;
+    let rejected = policy::check_mempool_accept(&wallet.config.btc, &tx).await;
+    policy::require_override(&rejected, req.override_policy)?;
+    let tx_id = wallet
+        .btc
+        .server
+        .broadcast_tx(&tx)
+        .await
+        .map_err(|e| format!("fail to broadcast tx: {e}"))?;
+
+    let chain_data = BtcChainData {
+        vsize: Some(tx.vsize() as u32),
+        rbf: true,
+        parent_tx_id: None,
+        change_value_sat: None,
+    };
+    let _ = tx_repository.insert(NewTx {
+        tx_hash: tx_id.clone(),
+        wallet_name: wallet.name.clone(),
+        chain: BlockChain::Bitcoin,
+        network: wallet.config.btc.network.as_str().to_string(),
+        account_index: wallet.btc.active_account as i32,
+        direction: TxDirection::Outgoing,
+        status: TxStatus::Pending,
+        from_address: None,
+        to_address: None,
+        amount: 0,
+        fee: Some(sweep.fee as i32),
+        block_height: None,
+        chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+        created_at: utils::now() as i64,
+    });
+    wallet.btc.get_active_account_mut()?.spend(&[outpoint]);
+    wallet.persist()?;
+
+    let explorer_url = wallet.config.btc.explorer().map(|e| e.tx_url(&tx_id));
+    Ok(BroadcastTxResponse {
+        tx_id,
+        explorer_url,
+    })
+}
+
+/// Decode a wallet transaction: every input and output with the wallet keys
+/// involved, the fee paid and CPFP links from the local history.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "get_tx_details", skip_all, err)]
+pub async fn get_tx_details(
+    req: TxDetailsRequest,
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<TxDetailsView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let tx_id = Txid::from_str(&req.tx_id).map_err(|e| format!("invalid tx_id: {e}"))?;
+    let record = tx_repository.get(&wallet.name, BlockChain::Bitcoin, &req.tx_id)?;
+
+    let tx = wallet
+        .btc
+        .server
+        .get_transactions(&[tx_id])
+        .await?
+        .pop()
+        .ok_or("transaction not found")?;
+    let prevouts = if tx.is_coinbase() {
+        vec![None; tx.input.len()]
+    } else {
+        let parent_ids: Vec<Txid> = tx.input.iter().map(|i| i.previous_output.txid).collect();
+        match wallet.btc.server.get_transactions(&parent_ids).await {
+            Ok(parents) => tx
+                .input
+                .iter()
+                .zip(parents)
+                .map(|(input, parent)| {
+                    parent
+                        .output
+                        .get(input.previous_output.vout as usize)
+                        .cloned()
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("can't fetch parents of {tx_id}: {e}");
+                vec![None; tx.input.len()]
+            }
+        }
+    };
+
+    let network = wallet.config.btc.network();
+    let account = match &record {
+        Some(r) => wallet.btc.get_account(r.account_index as u32)?,
+        None => wallet.btc.active_account()?,
+    };
+    let prk = wallet.btc.prk()?;
+    let addresses = account.derive_address_path_map(&prk, network);
+    let labels = account.keychain.to_label_map();
+    let mut details = tx_details::decode(
+        &tx,
+        &prevouts,
+        &Ownership {
+            addresses: &addresses,
+            labels: &labels,
+            network,
+        },
+    );
+
+    if let Some(record) = record {
+        details.block_height = record.block_height.and_then(|h| h.parse().ok());
+        details.cpfp_parent = serde_json::from_str::<BtcChainData>(&record.chain_data)
+            .ok()
+            .and_then(|d| d.parent_tx_id);
+    }
+    details.confirmations = details
+        .block_height
+        .map_or(0, |h| wallet.btc.tip.confirmations(h));
+    details.cpfp_children = tx_repository.btc_children(&wallet.name, &req.tx_id)?;
+    Ok(details)
+}
+
+/// Refresh the best chain window. On a reorg, coins and history entries
+/// confirmed in replaced blocks go back to pending and `btc_reorg` fires.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "sync_chain_tip", skip_all, err)]
+pub async fn sync_chain_tip(
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    event_emitter: tauri::State<'_, EventEmitter>,
+) -> Result<ChainTipView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let reorg = refresh_chain_tip(wallet, &tx_repository, &event_emitter).await?;
+    wallet.persist()?;
+    let tip = wallet.btc.tip.tip().ok_or("chain tip unavailable")?;
+    Ok(ChainTipView {
+        height: tip.height,
+        hash: tip.hash.to_string(),
+        reorg,
+    })
+}
+
+async fn refresh_chain_tip(
+    wallet: &mut Wallet,
+    tx_repository: &TxRepository,
+    event_emitter: &EventEmitter,
+) -> Result<Option<BtcReorgEvent>, String> {
+    let tip_height = wallet.btc.server.tip_height().await?;
+    let headers = wallet
+        .btc
+        .server
+        .block_headers(TipTracker::window(tip_height))
+        .await?;
+    let Some(reorg) = wallet.btc.tip.update(headers) else {
+        return Ok(None);
+    };
+    tracing::warn!(
+        fork_height = reorg.fork_height,
+        old_tip = %reorg.old_tip.hash,
+        new_tip = %reorg.new_tip.hash,
+        "bitcoin reorg detected"
+    );
+    let utxos = wallet.btc.rollback_utxos(reorg.fork_height);
+    let tx_ids = tx_repository.rollback_from_height(
+        &wallet.name,
+        BlockChain::Bitcoin,
+        reorg.fork_height as i64,
+    )?;
+    let event = BtcReorgEvent {
+        fork_height: reorg.fork_height,
+        old_tip_height: reorg.old_tip.height,
+        new_tip_height: reorg.new_tip.height,
+        utxos: utxos.into_iter().map(OutPointRef::from).collect(),
+        tx_ids,
+    };
+    event_emitter.btc_reorg(event.clone());
+    Ok(Some(event))
+}
+
 #[specta]
 #[tauri::command]
 #[tracing::instrument(name = "build_tx", skip_all, err)]
@@ -212,43 +649,90 @@ pub async fn build_tx(
 ) -> Result<BuildTxResponse, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
-    let account = wallet.btc.active_account()?;
-    let prk = wallet.btc.prk()?;
-    let xpriv = prk.expose();
+    // A new build replaces the previous draft and frees its coins.
+    wallet.btc.abandon_pending_tx()?;
 
-    let recipient: Address = Address::from_str(&req.recipient)
-        .map_err(|e| format!("invalid recipient address: {e}"))?
-        .require_network(wallet.config.btc.network())
-        .map_err(|e| format!("recipient address network mismatch: {e}"))?;
+    let send_value_sat = parse_sats(&req.value)?;
+    let miner_fee_vbytes =
+        resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, req.fee_mode).await?;
+    let pending_tx = build_checked_tx(
+        wallet,
+        wallet.btc.active_account,
+        &req.recipient,
+        send_value_sat,
+        req.utxo_selection_method,
+        miner_fee_vbytes,
+    )
+    .await?;
 
-    let send_value_sat = req
-        .value
+    let fee = pending_tx.fee;
+    let warnings = pending_tx.warnings.clone();
+    // Change is only reserved here, the keychain is updated on broadcast.
+    wallet.btc.stage_pending_tx(pending_tx, utils::now())?;
+
+    Ok(BuildTxResponse {
+        fee,
+        fee_rate_sat_vb: miner_fee_vbytes,
+        warnings,
+    })
+}
+
+fn parse_sats(value: &str) -> Result<u64, String> {
+    value
         .parse::<u64>()
-        .map_err(|e| format!("invalid value: {e}"))?;
+        .map_err(|e| format!("invalid value: {e}"))
+}
 
-    let miner_fee_vbytes = estimate_fee_rate(&wallet.btc.server, &wallet.config.btc).await?;
-    let pending_tx = build_psbt(&BuildPsbtParams {
+/// Build a spend from `account_index` at a fixed fee rate and run the fee
+/// policy on it.
+async fn build_checked_tx(
+    wallet: &Wallet,
+    account_index: AccountIndex,
+    recipient: &str,
+    send_value_sat: u64,
+    utxo_selection_method: UtxoSelectionStrategy,
+    fee_rate_sat_vb: f64,
+) -> Result<BuildTxResult, String> {
+    let prk = wallet.btc.prk()?;
+    let recipient = Recipient::parse(recipient, wallet.config.btc.network())?;
+    let mut built = build_psbt(&BuildPsbtParams {
         send_value_sat,
         recipient,
-        utxo_selection_method: req.utxo_selection_method,
-        miner_fee_vbytes,
+        utxo_selection_method,
+        miner_fee_vbytes: fee_rate_sat_vb,
         config: wallet.config.btc.clone(),
-        account,
-        xpriv,
+        account: wallet.btc.get_account(account_index)?,
+        xpriv: prk.expose(),
     })
     .map_err(|e| format!("failed to build PSBT: {e}"))?;
-    let change_key_path = pending_tx.change_key_path.clone();
+    let estimate = next_block_fee_rate(wallet).await;
+    built.warnings = policy::check_tx(&wallet.config.btc.fee_guard, &built, estimate);
+    Ok(built)
+}
 
-    let fee = pending_tx.fee;
-    wallet.btc.pending_tx = Some(pending_tx);
-    wallet
-        .btc
-        .get_active_account_mut()?
-        .keychain
-        .push(change_key_path);
-    wallet.persist()?;
+/// Next-block fee rate used as the reference for the relative fee check.
+/// `None` when the backend can't produce an estimate right now.
+async fn next_block_fee_rate(wallet: &Wallet) -> Option<f64> {
+    fee_estimator::estimate_fee_rate(
+        &wallet.btc.server,
+        &wallet.config.btc,
+        PRIORITY_FEE_TARGET_BLOCKS,
+    )
+    .await
+    .inspect_err(|e| tracing::warn!("fee estimate unavailable for policy check: {e}"))
+    .ok()
+}
 
-    Ok(BuildTxResponse { fee })
+/// Fee rates for the economy, standard and priority tiers, each with the
+/// expected confirmation time under the current mempool backlog.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "estimate_fee_tiers", skip_all, err)]
+pub async fn estimate_fee_tiers(sk: tauri::State<'_, SK>) -> Result<FeeTiersView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let tiers = fee_estimator::estimate_fee_tiers(&wallet.btc.server, &wallet.config.btc).await?;
+    Ok(tiers.into())
 }
 
 #[specta]
@@ -257,9 +741,11 @@ pub async fn build_tx(
 pub async fn bump_fee_cpfp(
     req: BumpFeeRequest,
     sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
 ) -> Result<BumpFeeResponse, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
+    wallet.btc.expire_pending_tx(utils::now())?;
     let prk = wallet.btc.prk()?;
     let parent_tx_id =
         Txid::from_str(&req.parent_tx_id).map_err(|e| format!("invalid parent_tx_id: {e}"))?;
@@ -271,10 +757,16 @@ pub async fn bump_fee_cpfp(
         account: wallet.btc.active_account()?,
         xpriv: prk.expose(),
     })?;
+    let estimate = next_block_fee_rate(wallet).await;
+    let mut warnings = policy::check_tx(&wallet.config.btc.fee_guard, &built, estimate);
+    policy::require_override(&warnings, req.override_policy)?;
     let change_key_path = built.change_key_path.clone();
     let fee = built.fee;
 
     let tx = sign_psbt(built.psbt, &prk)?;
+    let rejected = policy::check_mempool_accept(&wallet.config.btc, &tx).await;
+    policy::require_override(&rejected, req.override_policy)?;
+    warnings.extend(rejected);
     let child_tx_id = wallet
         .btc
         .server
@@ -282,6 +774,29 @@ pub async fn bump_fee_cpfp(
         .await
         .map_err(|e| format!("fail to broadcast cpfp tx: {e}"))?;
 
+    let chain_data = BtcChainData {
+        vsize: Some(tx.vsize() as u32),
+        rbf: tx.is_explicitly_rbf(),
+        parent_tx_id: Some(parent_tx_id.to_string()),
+        change_value_sat: tx.output.first().map(|o| o.value.to_sat()),
+    };
+    let _ = tx_repository.insert(NewTx {
+        tx_hash: child_tx_id.clone(),
+        wallet_name: wallet.name.clone(),
+        chain: BlockChain::Bitcoin,
+        network: wallet.config.btc.network.as_str().to_string(),
+        account_index: wallet.btc.active_account as i32,
+        direction: TxDirection::SelfTransfer,
+        status: TxStatus::Pending,
+        from_address: None,
+        to_address: None,
+        amount: 0,
+        fee: Some(fee as i32),
+        block_height: None,
+        chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+        created_at: utils::now() as i64,
+    });
+
     wallet
         .btc
         .get_active_account_mut()?
@@ -292,6 +807,7 @@ pub async fn bump_fee_cpfp(
     Ok(BumpFeeResponse {
         child_tx_id,
         child_fee: fee,
+        warnings,
     })
 }
 
@@ -299,23 +815,29 @@ pub async fn bump_fee_cpfp(
 #[tauri::command]
 #[tracing::instrument(name = "broadcast_tx", skip_all, err)]
 pub async fn broadcast_tx(
-    _req: BroadcastTxRequest,
+    req: BroadcastTxRequest,
     sk: tauri::State<'_, SK>,
     tx_repository: tauri::State<'_, TxRepository>,
 ) -> Result<BroadcastTxResponse, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
 
-    let tx_build_res = wallet.btc.pending_tx.take().expect("pending tx not found");
-    let fee = tx_build_res.fee;
-    let recipient = tx_build_res.recipient.clone();
-    let send_value_sat = tx_build_res.send_value_sat;
-    let psbt = tx_build_res.psbt;
-
+    wallet.btc.expire_pending_tx(utils::now())?;
     let prk = wallet.btc.prk()?;
-    let tx = sign_psbt(psbt, &prk)?;
+    let pending = wallet
+        .btc
+        .pending_tx
+        .as_mut()
+        .ok_or("no pending transaction to broadcast, it may have expired")?;
+    policy::require_override(&pending.built.warnings, req.override_policy)?;
+
+    let tx = pending.sign(&prk)?;
+    let rejected = policy::check_mempool_accept(&wallet.config.btc, &tx).await;
+    policy::require_override(&rejected, req.override_policy)?;
     let vsize = tx.vsize() as u32;
 
+    // On failure the transaction stays signed with its reservations held, so
+    // the user can retry or abandon it.
     let tx_id = wallet
         .btc
         .server
@@ -323,6 +845,27 @@ pub async fn broadcast_tx(
         .await
         .map_err(|e| format!("fail to broadcast tx: {}", e))?;
 
+    if let Some(mut pending) = wallet.btc.pending_tx.take() {
+        record_outgoing(&tx_repository, wallet, &tx_id, &pending, vsize);
+        pending.commit(wallet.btc.get_account_mut(pending.account)?);
+    }
+    wallet.persist()?;
+
+    let explorer_url = wallet.config.btc.explorer().map(|e| e.tx_url(&tx_id));
+    Ok(BroadcastTxResponse {
+        tx_id,
+        explorer_url,
+    })
+}
+
+/// Store a broadcast send in the local transaction history.
+fn record_outgoing(
+    tx_repository: &TxRepository,
+    wallet: &Wallet,
+    tx_id: &str,
+    pending: &PendingTx,
+    vsize: u32,
+) {
     let chain_data = BtcChainData {
         vsize: Some(vsize),
         rbf: true,
@@ -330,27 +873,780 @@ pub async fn broadcast_tx(
         change_value_sat: None,
     };
     let _ = tx_repository.insert(NewTx {
-        tx_hash: tx_id.clone(),
+        tx_hash: tx_id.to_string(),
         wallet_name: wallet.name.clone(),
         chain: BlockChain::Bitcoin,
-        account_index: wallet.btc.active_account as i32,
+        network: wallet.config.btc.network.as_str().to_string(),
+        account_index: pending.account as i32,
         direction: TxDirection::Outgoing,
         status: TxStatus::Pending,
         from_address: None,
-        to_address: recipient,
-        amount: send_value_sat as i64,
-        fee: Some(fee as i32),
+        to_address: pending.built.recipient.clone(),
+        amount: pending.built.send_value_sat as i64,
+        fee: Some(pending.built.fee as i32),
         block_height: None,
         chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
         created_at: utils::now() as i64,
     });
+}
+
+/// Drop the transaction prepared by `build_tx` and release its coins and
+/// change address. Returns false when there was nothing to abandon.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "abandon_tx", skip_all, err)]
+pub async fn abandon_tx(sk: tauri::State<'_, SK>) -> Result<bool, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    wallet.btc.abandon_pending_tx()
+}
+
+/// Save a named transaction from the active account for later review. Its
+/// coins are held back from other sends until it's broadcast or deleted.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "create_tx_draft", skip_all, err)]
+pub async fn create_tx_draft(
+    req: CreateTxDraftRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<TxDraftView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let name = drafts::validate_name(&req.name)?;
+    let send_value_sat = parse_sats(&req.value)?;
+    let fee_rate_sat_vb =
+        resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, req.fee_mode).await?;
+    let account_index = wallet.btc.active_account;
+    let built = build_checked_tx(
+        wallet,
+        account_index,
+        &req.recipient,
+        send_value_sat,
+        req.utxo_selection_method.clone(),
+        fee_rate_sat_vb,
+    )
+    .await?;
+
+    let now = utils::now();
+    let draft = TxDraft {
+        id: wallet.btc.next_draft_id(),
+        name,
+        note: req.note,
+        recipient: req.recipient,
+        send_value_sat,
+        utxo_selection: req.utxo_selection_method,
+        fee_rate_sat_vb,
+        tx: PendingTx::new(account_index, built, now),
+        updated_at: now,
+    };
+    let view = TxDraftView::from(&draft);
+    wallet.btc.put_draft(draft)?;
+    wallet.persist()?;
+    Ok(view)
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_tx_drafts", skip_all, err)]
+pub async fn list_tx_drafts(sk: tauri::State<'_, SK>) -> Result<Vec<TxDraftView>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let mut drafts: Vec<TxDraftView> = wallet
+        .btc
+        .drafts
+        .iter()
+        .map(TxDraftView::from)
+        .chain(wallet.btc.unreadable_drafts.iter().map(TxDraftView::from))
+        .collect();
+    drafts.sort_by_key(|d| d.id);
+    Ok(drafts)
+}
+
+/// Edit a draft. Changing the recipient, amount, coins or fee rebuilds the
+/// transaction and drops its signature.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "update_tx_draft", skip_all, err)]
+pub async fn update_tx_draft(
+    req: UpdateTxDraftRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<TxDraftView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let fee_rate_sat_vb = match req.fee_mode {
+        Some(mode) => Some(resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, mode).await?),
+        None => None,
+    };
+    let edit = TxDraftEdit {
+        name: req.name,
+        note: req.note,
+        recipient: req.recipient,
+        send_value_sat: req.value.as_deref().map(parse_sats).transpose()?,
+        utxo_selection: req.utxo_selection_method,
+        fee_rate_sat_vb,
+    };
+    edit_draft(wallet, req.id, edit).await
+}
 
-    {
-        // save change key
-        let account = wallet.btc.get_active_account_mut()?;
-        account.keychain.push(tx_build_res.change_key_path);
+/// Rebuild a draft at the current fee estimate for `fee_mode`.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "reestimate_tx_draft", skip_all, err)]
+pub async fn reestimate_tx_draft(
+    req: ReestimateTxDraftRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<TxDraftView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let fee_rate_sat_vb =
+        resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, req.fee_mode).await?;
+    let edit = TxDraftEdit {
+        fee_rate_sat_vb: Some(fee_rate_sat_vb),
+        ..Default::default()
+    };
+    edit_draft(wallet, req.id, edit).await
+}
+
+/// Apply `edit` to a draft. The draft stays as it was if the rebuild fails.
+async fn edit_draft(
+    wallet: &mut Wallet,
+    id: u32,
+    edit: TxDraftEdit,
+) -> Result<TxDraftView, String> {
+    // Taking the draft out frees its own coins for the rebuild.
+    let original = wallet.btc.take_draft(id)?;
+    let result = rebuild_draft(wallet, original.clone(), edit)
+        .await
+        .and_then(|draft| {
+            let view = TxDraftView::from(&draft);
+            wallet.btc.put_draft(draft).map(|_| view)
+        });
+    if result.is_err() {
+        wallet.btc.put_draft(original)?;
+    }
+    wallet.persist()?;
+    result
+}
+
+async fn rebuild_draft(
+    wallet: &Wallet,
+    mut draft: TxDraft,
+    edit: TxDraftEdit,
+) -> Result<TxDraft, String> {
+    let rebuild = edit.needs_rebuild();
+    edit.apply(&mut draft)?;
+    let now = utils::now();
+    if rebuild {
+        let built = build_checked_tx(
+            wallet,
+            draft.tx.account,
+            &draft.recipient,
+            draft.send_value_sat,
+            draft.utxo_selection.clone(),
+            draft.fee_rate_sat_vb,
+        )
+        .await?;
+        draft.tx = PendingTx::new(draft.tx.account, built, now);
+    }
+    draft.updated_at = now;
+    Ok(draft)
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "sign_tx_draft", skip_all, err)]
+pub async fn sign_tx_draft(
+    req: TxDraftRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<TxDraftView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let draft = wallet.btc.get_draft_mut(req.id)?;
+    draft.tx.sign(&prk)?;
+    let view = TxDraftView::from(&*draft);
+    wallet.persist()?;
+    Ok(view)
+}
+
+/// Sign if needed and broadcast a draft. The draft is removed once the
+/// backend accepts it.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "broadcast_tx_draft", skip_all, err)]
+pub async fn broadcast_tx_draft(
+    req: BroadcastTxDraftRequest,
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<BroadcastTxResponse, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let draft = wallet.btc.get_draft_mut(req.id)?;
+    policy::require_override(&draft.tx.built.warnings, req.override_policy)?;
+    let tx = draft.tx.sign(&prk)?;
+    let rejected = policy::check_mempool_accept(&wallet.config.btc, &tx).await;
+    policy::require_override(&rejected, req.override_policy)?;
+
+    let tx_id = wallet
+        .btc
+        .server
+        .broadcast_tx(&tx)
+        .await
+        .map_err(|e| format!("fail to broadcast tx: {}", e))?;
+
+    let mut draft = wallet.btc.take_draft(req.id)?;
+    record_outgoing(&tx_repository, wallet, &tx_id, &draft.tx, tx.vsize() as u32);
+    draft
+        .tx
+        .commit(wallet.btc.get_account_mut(draft.tx.account)?);
+    wallet.persist()?;
+
+    let explorer_url = wallet.config.btc.explorer().map(|e| e.tx_url(&tx_id));
+    Ok(BroadcastTxResponse {
+        tx_id,
+        explorer_url,
+    })
+}
+
+/// Delete a draft and release its coins and change address.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "delete_tx_draft", skip_all, err)]
+pub async fn delete_tx_draft(req: TxDraftRequest, sk: tauri::State<'_, SK>) -> Result<(), String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    wallet.btc.delete_draft(req.id)?;
+    wallet.persist()
+}
+
+/// Static BIP352 payment code of the active account and the silent payment
+/// outputs found so far.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "silent_payment_info", skip_all, err)]
+pub async fn silent_payment_info(sk: tauri::State<'_, SK>) -> Result<SilentPaymentsView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let account = wallet.btc.active_account()?;
+    let keys = SilentPaymentKeys::derive(prk.expose(), wallet.config.btc.network(), account.index)?;
+    Ok(account.silent_payments.to_view(&keys.address()))
+}
+
+/// Scan the configured tweak index for silent payments received by the
+/// active account since the last scan. Progress is saved after every batch
+/// of blocks, an interrupted scan resumes there.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "scan_silent_payments", skip_all, err)]
+pub async fn scan_silent_payments(sk: tauri::State<'_, SK>) -> Result<SilentPaymentsView, String> {
+    let (source, keys, account_index, from_height) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let index_url = wallet
+            .config
+            .btc
+            .silent_payments_index
+            .clone()
+            .filter(|url| !url.is_empty())
+            .ok_or("no silent payments tweak index configured")?;
+        let source = if wallet.config.tor.enabled {
+            TweakIndexClient::new_tor(&index_url, &wallet.config.tor.socks5_proxy)?
+        } else {
+            TweakIndexClient::new(&index_url)
+        };
+
+        let network = wallet.config.btc.network();
+        let prk = wallet.btc.prk()?;
+        let account = wallet.btc.active_account()?;
+        let keys = SilentPaymentKeys::derive(prk.expose(), network, account.index)?;
+        (
+            source,
+            keys,
+            account.index,
+            account.silent_payments.scanned_height(network),
+        )
+    };
+    let tip = source.tip_height().await?;
+    let mut scanned = from_height;
+    loop {
+        // Scanning walks every block since the last scan, the session stays
+        // usable meanwhile.
+        let to = tip.min(scanned.saturating_add(SCAN_BATCH));
+        let report = silent_payments::scan(&source, &keys, scanned, to).await?;
+        scanned = report.scanned_to;
+
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let network = wallet.config.btc.network();
+        let current =
+            SilentPaymentKeys::derive(wallet.btc.prk()?.expose(), network, account_index)?;
+        if current.address() != keys.address() {
+            return Err("wallet changed during the silent payment scan".to_string());
+        }
+        let account = wallet.btc.get_account_mut(account_index)?;
+        account.silent_payments.apply_scan(report);
+        account.merge_silent_payments(network);
+        let view = account.silent_payments.to_view(&keys.address());
         wallet.persist()?;
+        if scanned >= tip {
+            return Ok(view);
+        }
     }
+}
+
+/// Create a descriptor-defined account, either a k-of-n multisig from
+/// cosigner xpubs or an imported descriptor.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "create_descriptor_account", skip_all, err)]
+pub async fn create_descriptor_account(
+    req: CreateDescriptorAccountRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<DescriptorAccountView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
+    let index = wallet.btc.next_descriptor_account_index();
+
+    let account = match req.source {
+        DescriptorAccountSource::Template {
+            template,
+            threshold,
+            cosigners,
+        } => DescriptorAccount::from_template(
+            index,
+            req.name,
+            template,
+            threshold,
+            &cosigners,
+            prk.expose(),
+            network,
+        )?,
+        DescriptorAccountSource::Descriptor { descriptor } => {
+            DescriptorAccount::import(index, req.name, &descriptor)?
+        }
+        DescriptorAccountSource::Recovery {
+            descriptor,
+            delay_blocks,
+        } => {
+            let mut account = DescriptorAccount::import(index, req.name, &descriptor)?;
+            account.relative_timelock = Some(delay_blocks);
+            account
+        }
+    };
+    let view = account.to_view(network, prk.expose().fingerprint(&Secp256k1::new()))?;
+    wallet.btc.descriptor_accounts.push(account);
+    wallet.persist()?;
+    Ok(view)
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_descriptor_accounts", skip_all, err)]
+pub async fn list_descriptor_accounts(
+    sk: tauri::State<'_, SK>,
+) -> Result<Vec<DescriptorAccountView>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let fingerprint = prk.expose().fingerprint(&Secp256k1::new());
+    let network = wallet.config.btc.network();
+    wallet
+        .btc
+        .descriptor_accounts
+        .iter()
+        .map(|account| account.to_view(network, fingerprint))
+        .collect()
+}
+
+/// Gap-limit discovery on both descriptor branches followed by a UTXO
+/// refresh.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "sync_descriptor_account", skip_all, err)]
+pub async fn sync_descriptor_account(
+    account_index: u32,
+    sk: tauri::State<'_, SK>,
+) -> Result<DescriptorAccountView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
 
-    Ok(BroadcastTxResponse { tx_id })
+    let account = wallet.btc.get_descriptor_account(account_index)?;
+    let [receive, change] =
+        descriptor_account::discover_indexes(&wallet.btc.server, account, network).await?;
+    let account = wallet.btc.get_descriptor_account_mut(account_index)?;
+    account.receive_index = receive;
+    account.change_index = change;
+
+    let account = wallet.btc.get_descriptor_account(account_index)?;
+    let utxos = descriptor_account::fetch_utxos(&wallet.btc.server, account, network).await?;
+    let account = wallet.btc.get_descriptor_account_mut(account_index)?;
+    account.utxos = utxos.into_iter().map(|u| (u.outpoint, u)).collect();
+
+    let view = account.to_view(network, prk.expose().fingerprint(&Secp256k1::new()))?;
+    wallet.persist()?;
+    Ok(view)
+}
+
+/// Build a spend from a descriptor account and add our signatures. The
+/// returned PSBT is passed to cosigners until it is complete.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "build_descriptor_psbt", skip_all, err)]
+pub async fn build_descriptor_psbt(
+    req: BuildDescriptorPsbtRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<DescriptorPsbtView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
+
+    let recipient = Address::from_str(&req.recipient)
+        .map_err(|e| format!("invalid recipient address: {e}"))?
+        .require_network(network)
+        .map_err(|e| format!("recipient address network mismatch: {e}"))?;
+    let send_value_sat = req
+        .value
+        .parse::<u64>()
+        .map_err(|e| format!("invalid value: {e}"))?;
+    let fee_rate_sat_vb =
+        resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, req.fee_mode).await?;
+
+    let recipient_script = recipient.script_pubkey();
+    let account = wallet.btc.get_descriptor_account(req.account_index)?;
+    let mut pending = descriptor_account::build_psbt(&BuildDescriptorPsbtParams {
+        account,
+        recipient,
+        send_value_sat,
+        utxo_selection_method: req.utxo_selection_method,
+        fee_rate_sat_vb,
+    })
+    .map_err(|e| format!("failed to build PSBT: {e}"))?;
+    let tx = &pending.psbt.unsigned_tx;
+    let vsize = account.estimate_vbytes(tx.input.len(), tx.output.len())?;
+    // A sweep pays the recipient less than requested.
+    let sent_sat = tx
+        .output
+        .iter()
+        .filter(|o| o.script_pubkey == recipient_script)
+        .map(|o| o.value.to_sat())
+        .sum();
+    let estimate = next_block_fee_rate(wallet).await;
+    pending.warnings = policy::check_unsigned_tx(
+        &wallet.config.btc.fee_guard,
+        tx,
+        pending.fee,
+        vsize,
+        sent_sat,
+        estimate,
+    );
+    if account.can_sign(prk.expose().fingerprint(&Secp256k1::new())) {
+        descriptor_account::sign_psbt(&mut pending.psbt, prk.expose())?;
+    }
+
+    let view = DescriptorPsbtView::from(&pending);
+    wallet
+        .btc
+        .get_descriptor_account_mut(req.account_index)?
+        .pending = Some(pending);
+    wallet.persist()?;
+    Ok(view)
+}
+
+/// Merge a cosigner's signed copy into the pending PSBT.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "import_descriptor_psbt", skip_all, err)]
+pub async fn import_descriptor_psbt(
+    req: ImportPsbtRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<DescriptorPsbtView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let pending = wallet
+        .btc
+        .get_descriptor_account_mut(req.account_index)?
+        .pending
+        .as_mut()
+        .ok_or("no pending PSBT for this account")?;
+    descriptor_account::combine_psbt(&mut pending.psbt, &req.psbt)?;
+    let view = DescriptorPsbtView::from(&*pending);
+    wallet.persist()?;
+    Ok(view)
+}
+
+/// Finalize the fully signed PSBT and broadcast it.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "broadcast_descriptor_psbt", skip_all, err)]
+pub async fn broadcast_descriptor_psbt(
+    req: BroadcastDescriptorPsbtRequest,
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<BroadcastTxResponse, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let pending = wallet
+        .btc
+        .get_descriptor_account(req.account_index)?
+        .pending
+        .clone()
+        .ok_or("no pending PSBT for this account")?;
+    policy::require_override(&pending.warnings, req.override_policy)?;
+
+    let tx = descriptor_account::finalize_psbt(pending.psbt)?;
+    let rejected = policy::check_mempool_accept(&wallet.config.btc, &tx).await;
+    policy::require_override(&rejected, req.override_policy)?;
+    let tx_id = wallet
+        .btc
+        .server
+        .broadcast_tx(&tx)
+        .await
+        .map_err(|e| format!("fail to broadcast tx: {e}"))?;
+
+    // The recipient is output 0, see `descriptor_account::build_psbt`.
+    let payment = &tx.output[0];
+    let chain_data = BtcChainData {
+        vsize: Some(tx.vsize() as u32),
+        rbf: true,
+        parent_tx_id: None,
+        change_value_sat: None,
+    };
+    let _ = tx_repository.insert(NewTx {
+        tx_hash: tx_id.clone(),
+        wallet_name: wallet.name.clone(),
+        chain: BlockChain::Bitcoin,
+        network: wallet.config.btc.network.as_str().to_string(),
+        account_index: req.account_index as i32,
+        direction: TxDirection::Outgoing,
+        status: TxStatus::Pending,
+        from_address: None,
+        to_address: Address::from_script(&payment.script_pubkey, wallet.config.btc.network())
+            .ok()
+            .map(|a| a.to_string()),
+        amount: payment.value.to_sat() as i64,
+        fee: Some(pending.fee as i32),
+        block_height: None,
+        chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+        created_at: utils::now() as i64,
+    });
+
+    let account = wallet.btc.get_descriptor_account_mut(req.account_index)?;
+    account.pending = None;
+    for input in &tx.input {
+        account.utxos.remove(&input.previous_output);
+    }
+    if let Some(index) = pending.change_index {
+        account.mark_used(Change::Internal, index);
+    }
+    wallet.persist()?;
+    let explorer_url = wallet.config.btc.explorer().map(|e| e.tx_url(&tx_id));
+    Ok(BroadcastTxResponse {
+        tx_id,
+        explorer_url,
+    })
+}
+
+/// Recovery policy of the active account and the coins nearing its timelock.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "recovery_status", skip_all, err)]
+pub async fn recovery_status(sk: tauri::State<'_, SK>) -> Result<RecoveryView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    recovery_view(wallet).await
+}
+
+/// Commit new taproot outputs of the active account to a timelocked backup
+/// key, or stop doing so. Refused while coins still depend on the current
+/// policy, since changing it would lose track of their script tree.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "set_recovery_policy", skip_all, err)]
+pub async fn set_recovery_policy(
+    req: SetRecoveryPolicyRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<RecoveryView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let policy = req
+        .recovery_key
+        .map(|key| RecoveryPolicy::new(&key, req.delay_blocks))
+        .transpose()?;
+
+    let account = wallet.btc.get_active_account_mut()?;
+    for utxo in account.utxo_set.entries.values() {
+        if recovery::is_guarded(account, utxo, prk.expose())? {
+            return Err(format!(
+                "utxo {} is locked to the current recovery policy, spend or refresh it first",
+                utxo.outpoint()
+            ));
+        }
+    }
+    account.recovery = policy;
+    wallet.persist()?;
+    recovery_view(wallet).await
+}
+
+/// Move recovery-locked coins that are close to maturing to a fresh change
+/// output, restarting their timelock.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "refresh_recovery_outputs", skip_all, err)]
+pub async fn refresh_recovery_outputs(
+    req: RefreshRecoveryRequest,
+    sk: tauri::State<'_, SK>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<BroadcastTxResponse, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
+    let tip_height = wallet.btc.server.tip_height().await?;
+    let fee_rate = resolve_fee_rate(&wallet.btc.server, &wallet.config.btc, req.fee_mode).await?;
+
+    let account = wallet.btc.active_account()?;
+    let expiring = recovery::expiring_utxos(
+        account,
+        prk.expose(),
+        tip_height,
+        req.within_blocks.unwrap_or(DEFAULT_REFRESH_WINDOW_BLOCKS),
+    )?;
+    if expiring.is_empty() {
+        return Err("no coins are close to the recovery timelock".to_string());
+    }
+    let total: u64 = expiring.iter().map(|e| e.utxo.output.value.to_sat()).sum();
+    let selection = expiring.iter().map(|e| e.utxo.outpoint().into()).collect();
+
+    let destination_path = KeyDerivationPath::new(
+        Proposal::Taproot,
+        network,
+        account.index,
+        Change::Internal,
+        account.keychain.next_unused_index(Change::Internal),
+    );
+    let destination = account.derive(&destination_path, prk.expose())?;
+    let built = build_psbt(&BuildPsbtParams {
+        send_value_sat: total,
+        recipient: destination.taproot_address.into(),
+        utxo_selection_method: UtxoSelectionStrategy::Manual(selection),
+        miner_fee_vbytes: fee_rate,
+        config: wallet.config.btc.clone(),
+        account,
+        xpriv: prk.expose(),
+    })?;
+    let estimate = next_block_fee_rate(wallet).await;
+    let warnings = policy::check_tx(&wallet.config.btc.fee_guard, &built, estimate);
+    policy::require_override(&warnings, req.override_policy)?;
+    let refresh = PendingTx::new(account.index, built, utils::now());
+
+    let tx = sign_psbt(refresh.built.psbt.clone(), &prk)?;
+    let rejected = policy::check_mempool_accept(&wallet.config.btc, &tx).await;
+    policy::require_override(&rejected, req.override_policy)?;
+    let tx_id = wallet
+        .btc
+        .server
+        .broadcast_tx(&tx)
+        .await
+        .map_err(|e| format!("fail to broadcast refresh tx: {e}"))?;
+    // The coins come back to the account, only the fee leaves it.
+    let chain_data = BtcChainData {
+        vsize: Some(tx.vsize() as u32),
+        rbf: tx.is_explicitly_rbf(),
+        parent_tx_id: None,
+        change_value_sat: tx.output.first().map(|o| o.value.to_sat()),
+    };
+    let _ = tx_repository.insert(NewTx {
+        tx_hash: tx_id.clone(),
+        wallet_name: wallet.name.clone(),
+        chain: BlockChain::Bitcoin,
+        network: wallet.config.btc.network.as_str().to_string(),
+        account_index: refresh.account as i32,
+        direction: TxDirection::SelfTransfer,
+        status: TxStatus::Pending,
+        from_address: None,
+        to_address: None,
+        amount: 0,
+        fee: Some(refresh.built.fee as i32),
+        block_height: None,
+        chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+        created_at: utils::now() as i64,
+    });
+
+    let account = wallet.btc.get_active_account_mut()?;
+    account.spend(&refresh.inputs());
+    account
+        .keychain
+        .push(destination_path.with_label("Recovery refresh".to_string()));
+    wallet.persist()?;
+    let explorer_url = wallet.config.btc.explorer().map(|e| e.tx_url(&tx_id));
+    Ok(BroadcastTxResponse {
+        tx_id,
+        explorer_url,
+    })
+}
+
+/// Key expression to give to someone who names this wallet as their
+/// recovery key holder.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "export_recovery_key", skip_all, err)]
+pub async fn export_recovery_key(
+    account_index: u32,
+    sk: tauri::State<'_, SK>,
+) -> Result<String, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    descriptor_account::own_key_expression(
+        prk.expose(),
+        DescriptorTemplate::TaprootMultiA,
+        wallet.config.btc.network(),
+        account_index,
+    )
+}
+
+async fn recovery_view(wallet: &Wallet) -> Result<RecoveryView, String> {
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
+    let tip_height = wallet.btc.server.tip_height().await?;
+    let account = wallet.btc.active_account()?;
+    let Some(policy) = &account.recovery else {
+        return Ok(RecoveryView {
+            recovery_key: None,
+            delay_blocks: None,
+            descriptor: None,
+            tip_height,
+            expiring: Vec::new(),
+        });
+    };
+
+    let owner_key = recovery::owner_key_expression(prk.expose(), network, account.index)?;
+    let expiring = recovery::expiring_utxos(
+        account,
+        prk.expose(),
+        tip_height,
+        DEFAULT_REFRESH_WINDOW_BLOCKS,
+    )?;
+    Ok(RecoveryView {
+        recovery_key: Some(policy.key.to_string()),
+        delay_blocks: Some(policy.delay_blocks),
+        descriptor: Some(policy.descriptor(&owner_key)),
+        tip_height,
+        expiring: expiring
+            .into_iter()
+            .map(|e| RecoveryOutputView {
+                utxo_id: e.utxo.outpoint().into(),
+                value: e.utxo.output.value.to_sat().to_string(),
+                blocks_left: e.blocks_left,
+            })
+            .collect(),
+    })
 }
@@ -0,0 +1,728 @@
+use std::{collections::HashMap, str::FromStr};
+
+use bitcoin::{
+    Address, Amount, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+    absolute::LockTime,
+    bip32::{ChildNumber, DerivationPath, Fingerprint, Xpriv, Xpub},
+    key::Secp256k1,
+    psbt::Psbt,
+    transaction::Version,
+};
+use miniscript::{
+    DefiniteDescriptorKey, Descriptor, DescriptorPublicKey, ForEachKey, psbt::PsbtExt,
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    chain::btc::{
+        account::UtxoSelectionStrategy,
+        coin_selection,
+        key_derivation::{Change, coin_type},
+        policy::PolicyWarning,
+        providers::btc_node::BtcNode,
+    },
+    chain_trait::AccountIndex,
+};
+
+/// Number of consecutive unused addresses after which discovery stops.
+pub const DESCRIPTOR_GAP_LIMIT: u32 = 20;
+
+/// Receive and change branches appended to key expressions imported without
+/// a derivation suffix.
+const MULTIPATH_SUFFIX: &str = "/<0;1>/*";
+
+/// BIP341 "nothing up my sleeve" point: the internal key of script-only
+/// taproot descriptors, which makes the key path provably unspendable.
+const NUMS_INTERNAL_KEY: &str = "50929b74c1a04954b78b4b6035e97a5e078a5a0f28ec96d547bfee9ace803ac0";
+
+/// Standardness limit on the number of keys in a P2WSH `multi`.
+const MAX_WSH_MULTI_KEYS: usize = 20;
+
+/// Both account templates spend to P2WSH or P2TR outputs, 43 vbytes each.
+const OUTPUT_VBYTES: u64 = 43;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DescriptorTemplate {
+    /// `wsh(sortedmulti(k,...))`, our key at BIP48 `m/48'/coin'/account'/2'`.
+    SortedMultiWsh,
+    /// `tr(NUMS,sortedmulti_a(k,...))`, our key at BIP87 `m/87'/coin'/account'`.
+    TaprootMultiA,
+}
+
+impl DescriptorTemplate {
+    pub fn origin_path(
+        self,
+        network: Network,
+        account: AccountIndex,
+    ) -> Result<DerivationPath, String> {
+        let coin = coin_type(network);
+        let indexes: &[u32] = match self {
+            DescriptorTemplate::SortedMultiWsh => &[48, coin, account, 2],
+            DescriptorTemplate::TaprootMultiA => &[87, coin, account],
+        };
+        indexes
+            .iter()
+            .map(|&i| ChildNumber::from_hardened_idx(i).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, String>>()
+            .map(DerivationPath::from)
+    }
+
+    fn descriptor(self, threshold: usize, keys: &[String]) -> String {
+        let keys = keys.join(",");
+        match self {
+            DescriptorTemplate::SortedMultiWsh => format!("wsh(sortedmulti({threshold},{keys}))"),
+            DescriptorTemplate::TaprootMultiA => {
+                format!("tr({NUMS_INTERNAL_KEY},sortedmulti_a({threshold},{keys}))")
+            }
+        }
+    }
+}
+
+/// Our cosigner key expression, `[fingerprint/origin]xpub/<0;1>/*`, to be
+/// shared with the other participants.
+pub fn own_key_expression(
+    xpriv: &Xpriv,
+    template: DescriptorTemplate,
+    network: Network,
+    account: AccountIndex,
+) -> Result<String, String> {
+    let secp = Secp256k1::new();
+    let origin = template.origin_path(network, account)?;
+    let derived = xpriv
+        .derive_priv(&secp, &origin)
+        .map_err(|e| format!("failed to derive cosigner key: {e}"))?;
+    let xpub = Xpub::from_priv(&secp, &derived);
+    Ok(format!(
+        "[{}/{origin}]{xpub}{MULTIPATH_SUFFIX}",
+        xpriv.fingerprint(&secp)
+    ))
+}
+
+/// Accept a bare xpub or an origin-prefixed one and make sure it ends up
+/// ranged over the receive and change branches.
+pub(crate) fn normalize_cosigner_key(key: &str) -> Result<String, String> {
+    let key = key.trim();
+    let body = key.rsplit_once(']').map_or(key, |(_, body)| body);
+    let key = if body.contains('/') {
+        key.to_string()
+    } else {
+        format!("{key}{MULTIPATH_SUFFIX}")
+    };
+    let parsed = DescriptorPublicKey::from_str(&key)
+        .map_err(|e| format!("invalid cosigner key {key}: {e}"))?;
+    if !parsed.has_wildcard() {
+        return Err(format!("cosigner key {key} must end with /*"));
+    }
+    Ok(key)
+}
+
+#[derive(Debug, Clone)]
+pub struct DescriptorUtxo {
+    pub outpoint: OutPoint,
+    pub output: TxOut,
+    pub change: Change,
+    pub index: u32,
+    pub height: u32,
+}
+
+/// A partially signed spend waiting for cosigner signatures.
+#[derive(Debug, Clone)]
+pub struct PendingDescriptorPsbt {
+    pub psbt: Psbt,
+    pub fee: u32,
+    /// Change branch index used by the PSBT, reserved until broadcast.
+    pub change_index: Option<u32>,
+    /// Fee policy violations found when building, see `policy::check_tx`.
+    pub warnings: Vec<PolicyWarning>,
+}
+
+/// An account whose addresses come from an output descriptor (multisig or a
+/// taproot script tree) instead of the wallet's single-key keychain.
+#[derive(Debug, Clone)]
+pub struct DescriptorAccount {
+    pub index: AccountIndex,
+    pub name: String,
+    pub descriptor: Descriptor<DescriptorPublicKey>,
+    /// First unused index on the receive branch.
+    pub receive_index: u32,
+    /// First unused index on the change branch.
+    pub change_index: u32,
+    pub utxos: HashMap<OutPoint, DescriptorUtxo>,
+    pub pending: Option<PendingDescriptorPsbt>,
+    /// Relative timelock (blocks) the spending path waits for, e.g. the
+    /// `older(n)` of a recovery descriptor. Applied as the input sequence.
+    pub relative_timelock: Option<u16>,
+}
+
+impl DescriptorAccount {
+    pub fn new(
+        index: AccountIndex,
+        name: String,
+        descriptor: Descriptor<DescriptorPublicKey>,
+    ) -> Result<Self, String> {
+        descriptor
+            .sanity_check()
+            .map_err(|e| format!("unsafe descriptor: {e}"))?;
+        if !descriptor.has_wildcard() {
+            return Err("descriptor must derive addresses (end keys with /*)".to_string());
+        }
+        Ok(Self {
+            index,
+            name,
+            descriptor,
+            receive_index: 0,
+            change_index: 0,
+            utxos: HashMap::new(),
+            pending: None,
+            relative_timelock: None,
+        })
+    }
+
+    /// Parse a descriptor exported by another wallet or coordinator.
+    pub fn import(index: AccountIndex, name: String, descriptor: &str) -> Result<Self, String> {
+        let descriptor = Descriptor::<DescriptorPublicKey>::from_str(descriptor.trim())
+            .map_err(|e| format!("invalid descriptor: {e}"))?;
+        Self::new(index, name, descriptor)
+    }
+
+    /// Build a k-of-n account from our seed key plus the cosigners' xpubs.
+    pub fn from_template(
+        index: AccountIndex,
+        name: String,
+        template: DescriptorTemplate,
+        threshold: u32,
+        cosigners: &[String],
+        xpriv: &Xpriv,
+        network: Network,
+    ) -> Result<Self, String> {
+        let mut keys = vec![own_key_expression(xpriv, template, network, index)?];
+        for cosigner in cosigners {
+            keys.push(normalize_cosigner_key(cosigner)?);
+        }
+        let threshold = threshold as usize;
+        if threshold == 0 || threshold > keys.len() {
+            return Err(format!(
+                "threshold must be between 1 and {} keys",
+                keys.len()
+            ));
+        }
+        if template == DescriptorTemplate::SortedMultiWsh && keys.len() > MAX_WSH_MULTI_KEYS {
+            return Err(format!(
+                "wsh multisig supports at most {MAX_WSH_MULTI_KEYS} keys"
+            ));
+        }
+        let descriptor =
+            Descriptor::<DescriptorPublicKey>::from_str(&template.descriptor(threshold, &keys))
+                .map_err(|e| format!("invalid descriptor: {e}"))?;
+        Self::new(index, name, descriptor)
+    }
+
+    /// Whether the descriptor holds a key derived from our seed.
+    pub fn can_sign(&self, fingerprint: Fingerprint) -> bool {
+        self.descriptor
+            .for_any_key(|key| key.master_fingerprint() == fingerprint)
+    }
+
+    /// Descriptor of one branch. Single-path descriptors use the same
+    /// branch for receiving and change.
+    fn branch(&self, change: Change) -> Result<Descriptor<DescriptorPublicKey>, String> {
+        let mut branches = self
+            .descriptor
+            .clone()
+            .into_single_descriptors()
+            .map_err(|e| e.to_string())?;
+        let i = (change as usize).min(branches.len() - 1);
+        Ok(branches.swap_remove(i))
+    }
+
+    pub fn derive(
+        &self,
+        change: Change,
+        index: u32,
+    ) -> Result<Descriptor<DefiniteDescriptorKey>, String> {
+        self.branch(change)?
+            .at_derivation_index(index)
+            .map_err(|e| format!("failed to derive {}/{index}: {e}", change as u32))
+    }
+
+    pub fn address(&self, change: Change, index: u32, network: Network) -> Result<Address, String> {
+        self.derive(change, index)?
+            .address(network)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn next_index(&self, change: Change) -> u32 {
+        match change {
+            Change::External => self.receive_index,
+            Change::Internal => self.change_index,
+        }
+    }
+
+    /// Move the branch cursor past `index` once it is known to be used.
+    pub fn mark_used(&mut self, change: Change, index: u32) {
+        let next = match change {
+            Change::External => &mut self.receive_index,
+            Change::Internal => &mut self.change_index,
+        };
+        *next = (*next).max(index + 1);
+    }
+
+    /// Every used address plus a gap-limit window on both branches.
+    pub fn watched_addresses(
+        &self,
+        network: Network,
+    ) -> Result<Vec<(Address, Change, u32)>, String> {
+        let mut watched = Vec::new();
+        for change in [Change::External, Change::Internal] {
+            for index in 0..self.next_index(change) + DESCRIPTOR_GAP_LIMIT {
+                watched.push((self.address(change, index, network)?, change, index));
+            }
+        }
+        Ok(watched)
+    }
+
+    pub fn total_value(&self) -> u64 {
+        self.utxos.values().map(|u| u.output.value.to_sat()).sum()
+    }
+
+    fn select(&self, method: UtxoSelectionStrategy) -> Result<Vec<&DescriptorUtxo>, String> {
+        match method {
+            UtxoSelectionStrategy::Manual(refs) => refs
+                .into_iter()
+                .map(|r| {
+                    let outpoint = OutPoint::try_from(r.clone())?;
+                    self.utxos
+                        .get(&outpoint)
+                        .ok_or(format!("utxo {r} not found in account"))
+                })
+                .collect(),
+            UtxoSelectionStrategy::Auto(min_value) => {
+                let utxos: Vec<&DescriptorUtxo> = self.utxos.values().collect();
+                let values: Vec<u64> = utxos.iter().map(|u| u.output.value.to_sat()).collect();
+                Ok(coin_selection::select(&values, min_value as u64)
+                    .into_iter()
+                    .map(|i| utxos[i])
+                    .collect())
+            }
+        }
+    }
+
+    /// Virtual size of a spend with the given number of inputs and outputs,
+    /// using the descriptor's worst-case satisfaction weight per input.
+    pub fn estimate_vbytes(&self, inputs: usize, outputs: usize) -> Result<u64, String> {
+        let satisfaction_wu = self
+            .derive(Change::External, 0)?
+            .max_weight_to_satisfy()
+            .map_err(|e| format!("descriptor is not satisfiable: {e}"))?
+            .to_wu();
+        // outpoint, empty scriptSig and sequence, plus the witness discounted
+        let input_vbytes = 41 + satisfaction_wu.div_ceil(4);
+        // version, locktime, in/out counts and the segwit marker
+        Ok(11 + inputs as u64 * input_vbytes + outputs as u64 * OUTPUT_VBYTES)
+    }
+}
+
+pub async fn discover_indexes(
+    server: &BtcNode,
+    account: &DescriptorAccount,
+    network: Network,
+) -> Result<[u32; 2], String> {
+    let mut found = [0; 2];
+    for change in [Change::External, Change::Internal] {
+        let mut next = account.next_index(change);
+        loop {
+            let addresses = (next..next + DESCRIPTOR_GAP_LIMIT)
+                .map(|index| account.address(change, index, network))
+                .collect::<Result<Vec<_>, String>>()?;
+            let activity = server.batch_has_activity(&addresses).await?;
+            match activity.iter().rposition(|used| *used) {
+                Some(last) => next += last as u32 + 1,
+                None => break,
+            }
+        }
+        found[change as usize] = next;
+    }
+    Ok(found)
+}
+
+/// Current unspent outputs on the account's watched addresses.
+pub async fn fetch_utxos(
+    server: &BtcNode,
+    account: &DescriptorAccount,
+    network: Network,
+) -> Result<Vec<DescriptorUtxo>, String> {
+    let watched = account.watched_addresses(network)?;
+    let lookup: HashMap<ScriptBuf, (Change, u32)> = watched
+        .iter()
+        .map(|(address, change, index)| (address.script_pubkey(), (*change, *index)))
+        .collect();
+    let addresses: Vec<Address> = watched.into_iter().map(|(address, ..)| address).collect();
+
+    let unspent = server.list_unspent(&addresses).await?;
+    Ok(unspent
+        .into_iter()
+        .filter_map(|u| {
+            let (change, index) = *lookup.get(&u.output.script_pubkey)?;
+            Some(DescriptorUtxo {
+                outpoint: u.outpoint,
+                output: u.output,
+                change,
+                index,
+                height: u.height,
+            })
+        })
+        .collect())
+}
+
+pub struct BuildDescriptorPsbtParams<'a> {
+    pub account: &'a DescriptorAccount,
+    pub recipient: Address,
+    pub send_value_sat: u64,
+    pub utxo_selection_method: UtxoSelectionStrategy,
+    pub fee_rate_sat_vb: f64,
+}
+
+/// Build an unsigned spend from the account. The recipient is output 0 and
+/// change, when above dust, goes to the next unused change address as
+/// output 1. Inputs and change carry full descriptor metadata so any
+/// cosigner can sign and finalize.
+pub fn build_psbt(p: &BuildDescriptorPsbtParams) -> Result<PendingDescriptorPsbt, String> {
+    let utxos = p.account.select(p.utxo_selection_method.clone())?;
+    if utxos.is_empty() {
+        return Err("no utxos selected for transaction".to_string());
+    }
+    let total_input: u64 = utxos.iter().map(|u| u.output.value.to_sat()).sum();
+    let fee_for = |outputs: usize| -> Result<u64, String> {
+        let vbytes = p.account.estimate_vbytes(utxos.len(), outputs)?;
+        Ok((vbytes as f64 * p.fee_rate_sat_vb).ceil() as u64)
+    };
+
+    let change_index = p.account.next_index(Change::Internal);
+    let change_descriptor = p.account.derive(Change::Internal, change_index)?;
+    let change_script = change_descriptor.script_pubkey();
+
+    let mut output = Vec::with_capacity(2);
+    let (fee, has_change) = if p.send_value_sat == total_input {
+        // Sweep: the fee comes out of the amount sent.
+        let fee = fee_for(1)?;
+        let send = total_input
+            .checked_sub(fee)
+            .filter(|send| *send >= p.recipient.script_pubkey().minimal_non_dust().to_sat())
+            .ok_or("selected utxos don't cover the fee")?;
+        output.push(TxOut {
+            value: Amount::from_sat(send),
+            script_pubkey: p.recipient.script_pubkey(),
+        });
+        (fee, false)
+    } else {
+        let fee = fee_for(2)?;
+        let spend = p.send_value_sat + fee;
+        if total_input < p.send_value_sat + fee_for(1)? {
+            return Err(format!(
+                "insufficient funds: {total_input} sat selected, {spend} sat needed"
+            ));
+        }
+        output.push(TxOut {
+            value: Amount::from_sat(p.send_value_sat),
+            script_pubkey: p.recipient.script_pubkey(),
+        });
+        let change = total_input.saturating_sub(spend);
+        if change >= change_script.minimal_non_dust().to_sat() {
+            output.push(TxOut {
+                value: Amount::from_sat(change),
+                script_pubkey: change_script,
+            });
+            (fee, true)
+        } else {
+            // Leftover below dust goes to the miner.
+            (total_input - p.send_value_sat, false)
+        }
+    };
+
+    // Height-based relative locks stay below 0xfffffffe, so they still
+    // signal RBF.
+    let sequence = p
+        .account
+        .relative_timelock
+        .map_or(Sequence::ENABLE_RBF_NO_LOCKTIME, Sequence::from_height);
+    let input = utxos
+        .iter()
+        .map(|utxo| TxIn {
+            previous_output: utxo.outpoint,
+            script_sig: ScriptBuf::new(),
+            sequence,
+            witness: Witness::new(),
+        })
+        .collect();
+    let mut psbt = Psbt::from_unsigned_tx(Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input,
+        output,
+    })
+    .map_err(|e| format!("Failed to create PSBT: {e}"))?;
+
+    for (i, utxo) in utxos.iter().enumerate() {
+        psbt.inputs[i].witness_utxo = Some(utxo.output.clone());
+        let descriptor = p.account.derive(utxo.change, utxo.index)?;
+        psbt.update_input_with_descriptor(i, &descriptor)
+            .map_err(|e| format!("failed to update input {i}: {e:?}"))?;
+    }
+    if has_change {
+        psbt.update_output_with_descriptor(1, &change_descriptor)
+            .map_err(|e| format!("failed to update change output: {e:?}"))?;
+    }
+
+    Ok(PendingDescriptorPsbt {
+        psbt,
+        fee: u32::try_from(fee).map_err(|_| "fee out of range")?,
+        change_index: has_change.then_some(change_index),
+        warnings: Vec::new(),
+    })
+}
+
+/// Add our signatures for every input we hold a key for. Inputs owned by
+/// other cosigners are left untouched.
+pub fn sign_psbt(psbt: &mut Psbt, xpriv: &Xpriv) -> Result<(), String> {
+    let secp = Secp256k1::new();
+    psbt.sign(xpriv, &secp)
+        .map(|_| ())
+        .map_err(|(_, errors)| format!("Failed to sign PSBT: {errors:?}"))
+}
+
+/// Merge signatures from a cosigner's copy of the same PSBT.
+pub fn combine_psbt(psbt: &mut Psbt, other: &str) -> Result<(), String> {
+    let other = Psbt::from_str(other.trim()).map_err(|e| format!("invalid PSBT: {e}"))?;
+    if other.unsigned_tx.compute_txid() != psbt.unsigned_tx.compute_txid() {
+        return Err("PSBT spends a different transaction than the pending one".to_string());
+    }
+    psbt.combine(other)
+        .map_err(|e| format!("failed to combine PSBT: {e}"))
+}
+
+/// Fewest signatures collected on any input.
+pub fn signature_count(psbt: &Psbt) -> u32 {
+    psbt.inputs
+        .iter()
+        .map(|input| {
+            input.partial_sigs.len()
+                + input.tap_script_sigs.len()
+                + usize::from(input.tap_key_sig.is_some())
+        })
+        .min()
+        .unwrap_or(0) as u32
+}
+
+/// Whether every input has enough signatures to satisfy the descriptor.
+pub fn is_complete(psbt: &Psbt) -> bool {
+    let secp = Secp256k1::verification_only();
+    psbt.clone().finalize_mut(&secp).is_ok()
+}
+
+pub fn finalize_psbt(psbt: Psbt) -> Result<Transaction, String> {
+    let secp = Secp256k1::verification_only();
+    let psbt = psbt
+        .finalize(&secp)
+        .map_err(|(_, errors)| format!("PSBT is missing signatures: {errors:?}"))?;
+    psbt.extract_tx().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xpriv(seed: u8) -> Xpriv {
+        Xpriv::new_master(Network::Regtest, &[seed; 32]).unwrap()
+    }
+
+    fn cosigner(seed: u8, template: DescriptorTemplate) -> String {
+        own_key_expression(&xpriv(seed), template, Network::Regtest, 0).unwrap()
+    }
+
+    fn fund(account: &mut DescriptorAccount, change: Change, index: u32, value: u64) {
+        let script_pubkey = account.derive(change, index).unwrap().script_pubkey();
+        let outpoint = OutPoint {
+            txid: Transaction {
+                version: Version::TWO,
+                lock_time: LockTime::from_consensus(index + value as u32),
+                input: vec![],
+                output: vec![],
+            }
+            .compute_txid(),
+            vout: 0,
+        };
+        account.utxos.insert(
+            outpoint,
+            DescriptorUtxo {
+                outpoint,
+                output: TxOut {
+                    value: Amount::from_sat(value),
+                    script_pubkey,
+                },
+                change,
+                index,
+                height: 101,
+            },
+        );
+    }
+
+    fn two_of_three(template: DescriptorTemplate) -> (DescriptorAccount, Xpriv, Xpriv) {
+        let account = DescriptorAccount::from_template(
+            0,
+            "vault".to_string(),
+            template,
+            2,
+            &[cosigner(2, template), cosigner(3, template)],
+            &xpriv(1),
+            Network::Regtest,
+        )
+        .unwrap();
+        (account, xpriv(1), xpriv(2))
+    }
+
+    #[test]
+    fn own_key_expression_has_origin_and_multipath() {
+        let key = cosigner(1, DescriptorTemplate::SortedMultiWsh);
+        let fingerprint = xpriv(1).fingerprint(&Secp256k1::new());
+        assert!(key.starts_with(&format!("[{fingerprint}/48'/1'/0'/2']tpub")));
+        assert!(key.ends_with("/<0;1>/*"));
+    }
+
+    #[test]
+    fn bare_cosigner_xpub_gets_branches() {
+        let key = cosigner(2, DescriptorTemplate::SortedMultiWsh);
+        let bare = key
+            .rsplit_once(']')
+            .unwrap()
+            .1
+            .trim_end_matches(MULTIPATH_SUFFIX);
+        assert_eq!(
+            normalize_cosigner_key(bare).unwrap(),
+            format!("{bare}{MULTIPATH_SUFFIX}")
+        );
+        assert!(normalize_cosigner_key("not-a-key").is_err());
+    }
+
+    #[test]
+    fn rejects_bad_threshold() {
+        let template = DescriptorTemplate::SortedMultiWsh;
+        let err = DescriptorAccount::from_template(
+            0,
+            "vault".to_string(),
+            template,
+            3,
+            &[cosigner(2, template)],
+            &xpriv(1),
+            Network::Regtest,
+        )
+        .unwrap_err();
+        assert!(err.contains("threshold"));
+    }
+
+    #[test]
+    fn derives_distinct_branch_addresses() {
+        for template in [
+            DescriptorTemplate::SortedMultiWsh,
+            DescriptorTemplate::TaprootMultiA,
+        ] {
+            let (account, ..) = two_of_three(template);
+            let receive = account
+                .address(Change::External, 0, Network::Regtest)
+                .unwrap();
+            let change = account
+                .address(Change::Internal, 0, Network::Regtest)
+                .unwrap();
+            assert_ne!(receive, change);
+            match template {
+                DescriptorTemplate::SortedMultiWsh => assert!(receive.script_pubkey().is_p2wsh()),
+                DescriptorTemplate::TaprootMultiA => assert!(receive.script_pubkey().is_p2tr()),
+            }
+            assert!(account.can_sign(xpriv(1).fingerprint(&Secp256k1::new())));
+            assert!(!account.can_sign(xpriv(9).fingerprint(&Secp256k1::new())));
+        }
+    }
+
+    #[test]
+    fn imported_descriptor_round_trips() {
+        let (account, ..) = two_of_three(DescriptorTemplate::SortedMultiWsh);
+        let exported = account.descriptor.to_string();
+        let imported = DescriptorAccount::import(1, "copy".to_string(), &exported).unwrap();
+        assert_eq!(
+            imported.address(Change::External, 5, Network::Regtest),
+            account.address(Change::External, 5, Network::Regtest)
+        );
+    }
+
+    #[test]
+    fn mark_used_only_moves_forward() {
+        let (mut account, ..) = two_of_three(DescriptorTemplate::SortedMultiWsh);
+        account.mark_used(Change::External, 4);
+        account.mark_used(Change::External, 1);
+        assert_eq!(account.next_index(Change::External), 5);
+        assert_eq!(account.next_index(Change::Internal), 0);
+    }
+
+    #[test]
+    fn cosigner_signatures_complete_the_psbt() {
+        for template in [
+            DescriptorTemplate::SortedMultiWsh,
+            DescriptorTemplate::TaprootMultiA,
+        ] {
+            let (mut account, ours, theirs) = two_of_three(template);
+            fund(&mut account, Change::External, 0, 100_000);
+            let recipient = account
+                .address(Change::External, 7, Network::Regtest)
+                .unwrap();
+
+            let mut pending = build_psbt(&BuildDescriptorPsbtParams {
+                account: &account,
+                recipient,
+                send_value_sat: 40_000,
+                utxo_selection_method: UtxoSelectionStrategy::Auto(40_000),
+                fee_rate_sat_vb: 2.0,
+            })
+            .unwrap();
+            assert_eq!(pending.change_index, Some(0));
+            assert_eq!(pending.psbt.unsigned_tx.output.len(), 2);
+
+            sign_psbt(&mut pending.psbt, &ours).unwrap();
+            assert_eq!(signature_count(&pending.psbt), 1);
+            assert!(!is_complete(&pending.psbt));
+
+            let mut cosigner_copy = Psbt::from_str(&pending.psbt.to_string()).unwrap();
+            sign_psbt(&mut cosigner_copy, &theirs).unwrap();
+            combine_psbt(&mut pending.psbt, &cosigner_copy.to_string()).unwrap();
+            assert_eq!(signature_count(&pending.psbt), 2);
+            assert!(is_complete(&pending.psbt));
+
+            let tx = finalize_psbt(pending.psbt).unwrap();
+            let outputs: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+            assert_eq!(100_000 - outputs, pending.fee as u64);
+        }
+    }
+
+    #[test]
+    fn rejects_psbt_for_other_transaction() {
+        let (mut account, ..) = two_of_three(DescriptorTemplate::SortedMultiWsh);
+        fund(&mut account, Change::External, 0, 100_000);
+        fund(&mut account, Change::External, 1, 90_000);
+        let recipient = account
+            .address(Change::External, 7, Network::Regtest)
+            .unwrap();
+        let build = |value: u32| {
+            build_psbt(&BuildDescriptorPsbtParams {
+                account: &account,
+                recipient: recipient.clone(),
+                send_value_sat: value as u64,
+                utxo_selection_method: UtxoSelectionStrategy::Auto(value),
+                fee_rate_sat_vb: 2.0,
+            })
+            .unwrap()
+        };
+        let mut first = build(10_000);
+        let second = build(20_000);
+        assert!(combine_psbt(&mut first.psbt, &second.psbt.to_string()).is_err());
+    }
+}
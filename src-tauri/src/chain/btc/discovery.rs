@@ -24,6 +24,7 @@ use crate::{
     chain::btc::{
         BitcoinWallet,
         account::{Account, AddressPathMap},
+        dust,
         key_derivation::{Change, KeyDerivationPath, LabeledKeyDerivationPath, Proposal},
         providers::btc_node::BtcNode,
         utxo::Utxo,
@@ -281,6 +282,7 @@ impl BitcoinWallet {
     /// only when missing. Returns a summary of what was added.
     pub fn apply_discovery(&mut self, discovered: Vec<DiscoveredAccount>) -> DiscoveryReport {
         let network = self.config.btc.network();
+        let dust_threshold = self.config.btc.dust.threshold_sat as u64;
         let mut report = DiscoveryReport::default();
 
         for da in discovered {
@@ -301,7 +303,21 @@ impl BitcoinWallet {
                 .find(|a| a.index == da.index)
                 .expect("account inserted above");
 
+            // Before marking paths used, as that covers the dust's own address.
+            let dust = dust::detect(
+                &account.keychain,
+                &account.utxo_set,
+                &da.utxos,
+                dust_threshold,
+            );
+            if !dust.is_empty() {
+                tracing::info!(account = da.index, "quarantined {} dust utxos", dust.len());
+            }
+            account.utxo_set.quarantine(dust);
+
             for p in da.external_paths.into_iter().chain(da.internal_paths) {
+                // discovery only reports paths with on-chain history
+                account.keychain.mark_used(&p);
                 if !account.keychain.contains_path(p.clone()) {
                     account.keychain.push(LabeledKeyDerivationPath {
                         label: String::new(),
@@ -312,6 +328,7 @@ impl BitcoinWallet {
             }
             for u in da.utxos {
                 let op = u.outpoint();
+                account.keychain.record_funding(&u.derivation, op);
 
                 if !account.utxo_set.entries.contains_key(&op) {
                     report.total_value_sat = report
@@ -367,11 +384,12 @@ mod tests {
 
     fn make_dummy_server() -> BtcNode {
         use crate::chain::btc::{
-            config::BitcoinConfig, providers::electrum_adapter::ElectrumAdapter,
+            config::{BitcoinConfig, BtcNetwork},
+            providers::electrum_adapter::ElectrumAdapter,
         };
         BtcNode::Electrum(ElectrumAdapter::new(BitcoinConfig {
-            regtest: true,
-            electrum_server: None,
+            network: BtcNetwork::Regtest,
+            ..Default::default()
         }))
     }
 
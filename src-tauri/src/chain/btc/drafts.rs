@@ -0,0 +1,103 @@
+use crate::chain::btc::{account::UtxoSelectionStrategy, pending_tx::PendingTx};
+
+const MAX_DRAFT_NAME_LEN: usize = 64;
+
+/// Named transaction saved with the wallet so payouts can be prepared ahead
+/// and reviewed before sending. Its inputs and change path stay reserved on
+/// the spending account until it's broadcast or deleted.
+#[derive(Debug, Clone)]
+pub struct TxDraft {
+    pub id: u32,
+    pub name: String,
+    pub note: String,
+    /// Recipient as entered, an address or a silent payment code.
+    pub recipient: String,
+    pub send_value_sat: u64,
+    pub utxo_selection: UtxoSelectionStrategy,
+    pub fee_rate_sat_vb: f64,
+    pub tx: PendingTx,
+    pub updated_at: u64,
+}
+
+/// Changes to a draft. Anything that affects the transaction itself makes it
+/// rebuild, which also drops an existing signature.
+#[derive(Debug, Default)]
+pub struct TxDraftEdit {
+    pub name: Option<String>,
+    pub note: Option<String>,
+    pub recipient: Option<String>,
+    pub send_value_sat: Option<u64>,
+    pub utxo_selection: Option<UtxoSelectionStrategy>,
+    pub fee_rate_sat_vb: Option<f64>,
+}
+
+impl TxDraftEdit {
+    pub fn needs_rebuild(&self) -> bool {
+        self.recipient.is_some()
+            || self.send_value_sat.is_some()
+            || self.utxo_selection.is_some()
+            || self.fee_rate_sat_vb.is_some()
+    }
+
+    pub fn apply(self, draft: &mut TxDraft) -> Result<(), String> {
+        if let Some(name) = self.name {
+            draft.name = validate_name(&name)?;
+        }
+        if let Some(note) = self.note {
+            draft.note = note;
+        }
+        if let Some(recipient) = self.recipient {
+            draft.recipient = recipient;
+        }
+        if let Some(value) = self.send_value_sat {
+            draft.send_value_sat = value;
+        }
+        if let Some(selection) = self.utxo_selection {
+            draft.utxo_selection = selection;
+        }
+        if let Some(rate) = self.fee_rate_sat_vb {
+            draft.fee_rate_sat_vb = rate;
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("draft name can't be empty".to_string());
+    }
+    if name.chars().count() > MAX_DRAFT_NAME_LEN {
+        return Err(format!(
+            "draft name is longer than {MAX_DRAFT_NAME_LEN} characters"
+        ));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_is_trimmed_and_bounded() {
+        assert_eq!(validate_name("  payroll ").unwrap(), "payroll");
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name(&"x".repeat(MAX_DRAFT_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn only_transaction_fields_rebuild() {
+        let edit = TxDraftEdit {
+            name: Some("a".to_string()),
+            note: Some("b".to_string()),
+            ..Default::default()
+        };
+        assert!(!edit.needs_rebuild());
+        let edit = TxDraftEdit {
+            fee_rate_sat_vb: Some(3.0),
+            ..Default::default()
+        };
+        assert!(edit.needs_rebuild());
+    }
+}
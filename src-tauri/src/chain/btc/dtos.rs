@@ -1,15 +1,27 @@
 use std::str::FromStr;
 
-use bitcoin::{OutPoint, Txid};
+use bitcoin::{Network, OutPoint, Txid, bip32::Fingerprint};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
 use crate::{
     chain::btc::{
-        account::{KeyDerivationPathLabelMap, UtxoSelectionStrategy},
+        account::{KeyDerivationPathLabelMap, UtxoSelectionStrategy, UtxoSet},
+        chain_tip::TipTracker,
+        descriptor_account::{self, DescriptorAccount, DescriptorTemplate, PendingDescriptorPsbt},
+        drafts::TxDraft,
+        dust::DustSpendMode,
+        fee_estimator::{FeeMode, FeeTier, FeeTiers},
+        key_derivation::Change,
+        pending_tx::PendingTxState,
+        policy::PolicyWarning,
+        quorum::Discrepancy,
+        silent_payments::{self, SilentPaymentAddress, SilentPaymentState},
         utxo::Utxo,
+        zero_conf::{RiskReason, ZeroConfRisk},
     },
     chain_trait::AccountIndex,
+    config::explorer::Explorer,
 };
 
 #[derive(Type, Deserialize)]
@@ -17,19 +29,67 @@ pub struct BuildTxRequest {
     pub value: String,
     pub recipient: String,
     pub utxo_selection_method: UtxoSelectionStrategy,
+    #[serde(default)]
+    pub fee_mode: FeeMode,
 }
 
 #[derive(Type, Serialize)]
 pub struct BuildTxResponse {
     pub fee: u32,
+    pub fee_rate_sat_vb: f64,
+    /// Policy violations the user has to override before broadcasting.
+    pub warnings: Vec<PolicyWarning>,
+}
+
+#[derive(Type, Serialize)]
+pub struct FeeTierView {
+    pub fee_rate_sat_vb: f64,
+    pub target_blocks: u16,
+    /// Expected number of blocks until confirmation at this fee rate.
+    pub eta_blocks: u32,
+    pub eta_minutes: u32,
+}
+
+impl From<FeeTier> for FeeTierView {
+    fn from(tier: FeeTier) -> Self {
+        Self {
+            fee_rate_sat_vb: tier.fee_rate_sat_vb,
+            target_blocks: tier.target_blocks,
+            eta_blocks: tier.eta_blocks,
+            eta_minutes: tier.eta_minutes(),
+        }
+    }
+}
+
+#[derive(Type, Serialize)]
+pub struct FeeTiersView {
+    pub economy: FeeTierView,
+    pub standard: FeeTierView,
+    pub priority: FeeTierView,
+}
+
+impl From<FeeTiers> for FeeTiersView {
+    fn from(tiers: FeeTiers) -> Self {
+        Self {
+            economy: tiers.economy.into(),
+            standard: tiers.standard.into(),
+            priority: tiers.priority.into(),
+        }
+    }
 }
 
 #[derive(Type, Deserialize)]
-pub struct BroadcastTxRequest {}
+pub struct BroadcastTxRequest {
+    /// Broadcast even though the transaction violates the fee policy.
+    #[serde(default)]
+    pub override_policy: bool,
+}
 
 #[derive(Type, Serialize)]
 pub struct BroadcastTxResponse {
     pub tx_id: String,
+    /// Block explorer link, when one is configured for the network.
+    pub explorer_url: Option<String>,
 }
 
 #[derive(Type, Deserialize)]
@@ -40,12 +100,17 @@ pub struct BumpFeeRequest {
     /// is pulled in alongside; the package's effective rate is somewhere
     /// between this and the parent's own rate.
     pub target_fee_rate_sat_vb: f64,
+    /// Broadcast even though the child violates the fee policy.
+    #[serde(default)]
+    pub override_policy: bool,
 }
 
 #[derive(Type, Serialize)]
 pub struct BumpFeeResponse {
     pub child_tx_id: String,
     pub child_fee: u32,
+    /// Policy violations that were overridden for this child.
+    pub warnings: Vec<PolicyWarning>,
 }
 
 #[derive(Type, Serialize, Deserialize)]
@@ -53,6 +118,32 @@ pub struct DerivedAddress {
     pub label: String,
     pub path: String,
     pub address: String,
+    /// The address has received funds or has on-chain history.
+    #[serde(default)]
+    pub used: bool,
+    /// Paid to by more than one transaction.
+    #[serde(default)]
+    pub reused: bool,
+}
+
+#[derive(Type, Deserialize)]
+pub struct NextReceiveAddressRequest {
+    /// Reveal a new address even if an earlier one is still unused.
+    #[serde(default)]
+    pub force_new: bool,
+    /// Reveal past the gap limit after the user was warned.
+    #[serde(default)]
+    pub allow_beyond_gap: bool,
+}
+
+#[derive(Type, Serialize)]
+pub struct ReceiveAddressView {
+    pub address: String,
+    pub path: String,
+    /// Distance from the last used receive address.
+    pub unused_gap: u32,
+    /// Set when the address sits at or past the gap limit.
+    pub warning: Option<String>,
 }
 
 #[derive(Serialize, specta::Type)]
@@ -75,16 +166,45 @@ pub struct UtxoView {
     pub deriv_path: String,
     pub address_label: Option<String>,
     pub confirmed: bool,
+    /// 0 while unconfirmed or before the chain tip is known.
+    pub confirmations: u32,
+    /// Chance an unconfirmed coin gets double-spent, `none` once confirmed.
+    pub risk: ZeroConfRisk,
+    pub risk_reasons: Vec<RiskReason>,
+    /// Suspected dust, left out of automatic selection.
+    pub quarantined: bool,
+    /// Only a quorum witness still reports it, left out of automatic selection.
+    pub unverified: bool,
+    pub explorer_url: Option<String>,
 }
 
 impl Utxo {
-    pub fn to_view(&self, address_label_map: &KeyDerivationPathLabelMap) -> UtxoView {
+    pub fn to_view(
+        &self,
+        address_label_map: &KeyDerivationPathLabelMap,
+        tip: &TipTracker,
+        utxo_set: &UtxoSet,
+        explorer: Option<&Explorer>,
+    ) -> UtxoView {
+        let zero_conf = utxo_set.zero_conf_risk(self);
         UtxoView {
             value: self.output.value.to_sat().to_string(),
             utxo_id: self.outpoint().into(),
-            deriv_path: self.derivation.to_string(),
+            deriv_path: match self.silent_payment {
+                Some(_) => silent_payments::spend_key_path(
+                    self.derivation.network,
+                    self.derivation.account,
+                ),
+                None => self.derivation.to_string(),
+            },
             address_label: self.label(address_label_map),
             confirmed: self.height > 0,
+            confirmations: tip.confirmations(self.height),
+            risk: zero_conf.risk,
+            risk_reasons: zero_conf.reasons,
+            quarantined: utxo_set.is_quarantined(&self.outpoint()),
+            unverified: utxo_set.is_unverified(&self.outpoint()),
+            explorer_url: explorer.map(|e| e.output_url(&self.tx_id.to_string(), self.vout)),
         }
     }
 }
@@ -142,3 +262,437 @@ impl TryFrom<OutPointRef> for OutPoint {
         })
     }
 }
+
+#[derive(Type, Serialize)]
+pub struct SilentPaymentOutputView {
+    pub utxo_id: OutPointRef,
+    pub value: String,
+    pub height: u32,
+}
+
+#[derive(Type, Serialize)]
+pub struct SilentPaymentsView {
+    /// Static `sp1`/`tsp1` payment code.
+    pub address: String,
+    pub scan_height: u32,
+    pub total_balance: String,
+    pub outputs: Vec<SilentPaymentOutputView>,
+}
+
+impl SilentPaymentState {
+    pub fn to_view(&self, address: &SilentPaymentAddress) -> SilentPaymentsView {
+        let mut outputs: Vec<_> = self.outputs.values().collect();
+        outputs.sort_by_key(|o| std::cmp::Reverse(o.height));
+        SilentPaymentsView {
+            address: address.to_string(),
+            scan_height: self.scan_height,
+            total_balance: self.total_value().to_string(),
+            outputs: outputs
+                .into_iter()
+                .map(|o| SilentPaymentOutputView {
+                    utxo_id: o.outpoint.into(),
+                    value: o.output.value.to_sat().to_string(),
+                    height: o.height,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Type, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DescriptorAccountSource {
+    /// k-of-n multisig from our seed key plus the cosigners' xpubs.
+    Template {
+        template: DescriptorTemplate,
+        threshold: u32,
+        cosigners: Vec<String>,
+    },
+    /// Descriptor exported by a coordinator or another wallet.
+    Descriptor { descriptor: String },
+    /// Recovery descriptor of an account that named one of our keys as its
+    /// timelocked backup key.
+    Recovery {
+        descriptor: String,
+        delay_blocks: u16,
+    },
+}
+
+#[derive(Type, Deserialize)]
+pub struct CreateDescriptorAccountRequest {
+    pub name: String,
+    pub source: DescriptorAccountSource,
+}
+
+#[derive(Type, Serialize)]
+pub struct DescriptorUtxoView {
+    pub utxo_id: OutPointRef,
+    pub value: String,
+    /// `<branch>/<index>` within the descriptor.
+    pub deriv_path: String,
+    pub confirmed: bool,
+}
+
+#[derive(Type, Serialize)]
+pub struct DescriptorPsbtView {
+    /// Base64 PSBT to hand to cosigners.
+    pub psbt: String,
+    pub fee: u32,
+    /// Fewest signatures present on any input.
+    pub signatures: u32,
+    /// Enough signatures to finalize and broadcast.
+    pub complete: bool,
+    pub warnings: Vec<PolicyWarning>,
+}
+
+impl From<&PendingDescriptorPsbt> for DescriptorPsbtView {
+    fn from(pending: &PendingDescriptorPsbt) -> Self {
+        Self {
+            psbt: pending.psbt.to_string(),
+            fee: pending.fee,
+            signatures: descriptor_account::signature_count(&pending.psbt),
+            complete: descriptor_account::is_complete(&pending.psbt),
+            warnings: pending.warnings.clone(),
+        }
+    }
+}
+
+#[derive(Type, Serialize)]
+pub struct DescriptorAccountView {
+    pub index: u32,
+    pub name: String,
+    /// Full descriptor with checksum, for registering on cosigner devices.
+    pub descriptor: String,
+    /// The descriptor contains a key from this wallet's seed.
+    pub can_sign: bool,
+    pub receive_address: String,
+    pub total_balance: String,
+    pub utxo: Vec<DescriptorUtxoView>,
+    pub pending_psbt: Option<DescriptorPsbtView>,
+    /// Blocks a coin must age before this account can spend it.
+    pub relative_timelock: Option<u16>,
+}
+
+impl DescriptorAccount {
+    pub fn to_view(
+        &self,
+        network: Network,
+        fingerprint: Fingerprint,
+    ) -> Result<DescriptorAccountView, String> {
+        let mut utxos: Vec<_> = self.utxos.values().collect();
+        utxos.sort_by_key(|u| std::cmp::Reverse(u.output.value));
+        Ok(DescriptorAccountView {
+            index: self.index,
+            name: self.name.clone(),
+            descriptor: self.descriptor.to_string(),
+            can_sign: self.can_sign(fingerprint),
+            receive_address: self
+                .address(Change::External, self.receive_index, network)?
+                .to_string(),
+            total_balance: self.total_value().to_string(),
+            utxo: utxos
+                .into_iter()
+                .map(|u| DescriptorUtxoView {
+                    utxo_id: u.outpoint.into(),
+                    value: u.output.value.to_sat().to_string(),
+                    deriv_path: format!("{}/{}", u.change as u32, u.index),
+                    confirmed: u.height > 0,
+                })
+                .collect(),
+            pending_psbt: self.pending.as_ref().map(DescriptorPsbtView::from),
+            relative_timelock: self.relative_timelock,
+        })
+    }
+}
+
+#[derive(Type, Deserialize)]
+pub struct BuildDescriptorPsbtRequest {
+    pub account_index: u32,
+    pub value: String,
+    pub recipient: String,
+    pub utxo_selection_method: UtxoSelectionStrategy,
+    #[serde(default)]
+    pub fee_mode: FeeMode,
+}
+
+#[derive(Type, Deserialize)]
+pub struct ImportPsbtRequest {
+    pub account_index: u32,
+    /// Base64 PSBT carrying a cosigner's signatures.
+    pub psbt: String,
+}
+
+#[derive(Type, Deserialize)]
+pub struct BroadcastDescriptorPsbtRequest {
+    pub account_index: u32,
+    /// Broadcast despite the fee policy warnings or a failed mempool check.
+    #[serde(default)]
+    pub override_policy: bool,
+}
+
+#[derive(Type, Deserialize)]
+pub struct SetRecoveryPolicyRequest {
+    /// Backup key expression, e.g. `[fp/87'/0'/0']xpub`. `None` disables
+    /// recovery for new outputs.
+    pub recovery_key: Option<String>,
+    pub delay_blocks: u16,
+}
+
+#[derive(Type, Deserialize)]
+pub struct RefreshRecoveryRequest {
+    /// Refresh coins maturing within this many blocks. Defaults to about a
+    /// week.
+    #[serde(default)]
+    pub within_blocks: Option<u32>,
+    #[serde(default)]
+    pub fee_mode: FeeMode,
+    /// Broadcast even though the transaction violates the fee policy.
+    #[serde(default)]
+    pub override_policy: bool,
+}
+
+#[derive(Type, Serialize)]
+pub struct RecoveryOutputView {
+    pub utxo_id: OutPointRef,
+    pub value: String,
+    /// Blocks until the backup key can spend this coin.
+    pub blocks_left: u32,
+}
+
+#[derive(Type, Serialize)]
+pub struct RecoveryView {
+    pub recovery_key: Option<String>,
+    pub delay_blocks: Option<u16>,
+    /// Descriptor to hand to the backup key holder.
+    pub descriptor: Option<String>,
+    pub tip_height: u32,
+    /// Coins whose timelock matures within the default refresh window.
+    pub expiring: Vec<RecoveryOutputView>,
+}
+
+#[derive(Type, Deserialize)]
+pub struct CreateTxDraftRequest {
+    pub name: String,
+    #[serde(default)]
+    pub note: String,
+    pub recipient: String,
+    pub value: String,
+    pub utxo_selection_method: UtxoSelectionStrategy,
+    #[serde(default)]
+    pub fee_mode: FeeMode,
+}
+
+/// Fields left out keep their current value.
+#[derive(Type, Deserialize)]
+pub struct UpdateTxDraftRequest {
+    pub id: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub recipient: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub utxo_selection_method: Option<UtxoSelectionStrategy>,
+    #[serde(default)]
+    pub fee_mode: Option<FeeMode>,
+}
+
+#[derive(Type, Deserialize)]
+pub struct ReestimateTxDraftRequest {
+    pub id: u32,
+    #[serde(default)]
+    pub fee_mode: FeeMode,
+}
+
+#[derive(Type, Deserialize)]
+pub struct TxDraftRequest {
+    pub id: u32,
+}
+
+#[derive(Type, Deserialize)]
+pub struct BroadcastTxDraftRequest {
+    pub id: u32,
+    /// Broadcast even though the transaction violates the fee policy.
+    #[serde(default)]
+    pub override_policy: bool,
+}
+
+#[derive(Type, Serialize)]
+pub struct TxDraftView {
+    pub id: u32,
+    pub name: String,
+    pub note: String,
+    pub account_index: u32,
+    pub recipient: String,
+    pub value: String,
+    pub fee: u32,
+    pub fee_rate_sat_vb: f64,
+    pub state: PendingTxState,
+    /// Base64 PSBT for review or external signing.
+    pub psbt: String,
+    pub inputs: Vec<OutPointRef>,
+    /// Policy violations the user has to override before broadcasting.
+    pub warnings: Vec<PolicyWarning>,
+    /// Unix seconds of the last edit.
+    pub updated_at: u32,
+    /// Why the saved draft couldn't be loaded, it can only be deleted then.
+    pub error: Option<String>,
+}
+
+impl From<&TxDraft> for TxDraftView {
+    fn from(d: &TxDraft) -> Self {
+        TxDraftView {
+            id: d.id,
+            name: d.name.clone(),
+            note: d.note.clone(),
+            account_index: d.tx.account,
+            recipient: d.recipient.clone(),
+            value: d.send_value_sat.to_string(),
+            fee: d.tx.built.fee,
+            fee_rate_sat_vb: d.fee_rate_sat_vb,
+            state: d.tx.state,
+            psbt: d.tx.built.psbt.to_string(),
+            inputs: d.tx.inputs().into_iter().map(OutPointRef::from).collect(),
+            warnings: d.tx.built.warnings.clone(),
+            updated_at: d.updated_at as u32,
+            error: None,
+        }
+    }
+}
+
+/// Payload of the `btc_reorg` event: blocks from `fork_height` up were
+/// replaced and everything they confirmed is pending again.
+#[derive(Debug, Clone, Type, Serialize)]
+pub struct BtcReorgEvent {
+    pub fork_height: u32,
+    pub old_tip_height: u32,
+    pub new_tip_height: u32,
+    pub utxos: Vec<OutPointRef>,
+    pub tx_ids: Vec<String>,
+}
+
+/// Payload of the `btc_quorum_mismatch` event: the listed servers disagree
+/// with the primary one, which came first in `servers`.
+#[derive(Debug, Clone, Type, Serialize)]
+pub struct BtcQuorumEvent {
+    pub servers: Vec<String>,
+    pub discrepancies: Vec<QuorumDiscrepancyView>,
+    /// Coins kept although the primary server no longer reports them.
+    pub retained: Vec<OutPointRef>,
+}
+
+#[derive(Debug, Clone, Type, Serialize)]
+pub struct QuorumDiscrepancyView {
+    pub server: String,
+    pub address: String,
+    /// Confirmed coins the primary server reports and this one doesn't.
+    pub missing_utxos: Vec<OutPointRef>,
+    /// Confirmed coins only this server reports.
+    pub extra_utxos: Vec<OutPointRef>,
+    pub missing_tx_ids: Vec<String>,
+    pub extra_tx_ids: Vec<String>,
+}
+
+impl From<&Discrepancy> for QuorumDiscrepancyView {
+    fn from(d: &Discrepancy) -> Self {
+        Self {
+            server: d.server.clone(),
+            address: d.address.to_string(),
+            missing_utxos: d
+                .missing_utxos
+                .iter()
+                .copied()
+                .map(OutPointRef::from)
+                .collect(),
+            extra_utxos: d
+                .extra_utxos
+                .iter()
+                .copied()
+                .map(OutPointRef::from)
+                .collect(),
+            missing_tx_ids: d.missing_txs.iter().map(|t| t.to_string()).collect(),
+            extra_tx_ids: d.extra_txs.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Type, Serialize)]
+pub struct ChainTipView {
+    pub height: u32,
+    pub hash: String,
+    /// Set when this refresh found a reorg.
+    pub reorg: Option<BtcReorgEvent>,
+}
+
+#[derive(Type, Deserialize)]
+pub struct SpendQuarantinedUtxoRequest {
+    pub utxo: OutPointRef,
+    pub mode: DustSpendMode,
+    /// Only used to burn, a fee-only spend pays the whole value.
+    #[serde(default)]
+    pub fee_mode: FeeMode,
+    #[serde(default)]
+    pub override_policy: bool,
+}
+
+#[derive(Type, Deserialize)]
+pub struct TxDetailsRequest {
+    pub tx_id: String,
+}
+
+/// Wallet key controlling an input or output.
+#[derive(Debug, Type, Serialize)]
+pub struct OwnedOutputView {
+    pub deriv_path: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Type, Serialize)]
+pub struct TxInputView {
+    /// None for a coinbase input.
+    pub previous_output: Option<OutPointRef>,
+    pub sequence: u32,
+    /// Unknown when the parent transaction couldn't be fetched.
+    pub value_sat: Option<String>,
+    pub address: Option<String>,
+    pub owned: Option<OwnedOutputView>,
+}
+
+#[derive(Debug, Type, Serialize)]
+pub struct TxOutputView {
+    pub vout: u32,
+    pub value_sat: String,
+    /// None for scripts without an address form, e.g. OP_RETURN.
+    pub address: Option<String>,
+    pub owned: Option<OwnedOutputView>,
+}
+
+#[derive(Debug, Type, Serialize)]
+pub struct TxDetailsView {
+    pub tx_id: String,
+    pub version: i32,
+    pub lock_time: u32,
+    /// The lock time is enforced, i.e. non-zero with a non-final input.
+    pub lock_time_enabled: bool,
+    /// Signals replace-by-fee (BIP125).
+    pub rbf: bool,
+    pub size: u32,
+    pub vsize: u32,
+    pub weight: u32,
+    /// Known once every spent output could be looked up.
+    pub fee_sat: Option<String>,
+    pub fee_rate_sat_vb: Option<f64>,
+    /// Received minus spent by the wallet, negative for sends.
+    pub net_value_sat: String,
+    pub block_height: Option<u32>,
+    pub confirmations: u32,
+    /// Transaction this one bumps through CPFP.
+    pub cpfp_parent: Option<String>,
+    /// Recorded CPFP transactions bumping this one.
+    pub cpfp_children: Vec<String>,
+    pub inputs: Vec<TxInputView>,
+    pub outputs: Vec<TxOutputView>,
+}
@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+
+use bitcoin::{
+    Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness, absolute::LockTime,
+    bip32::Xpriv, psbt::Psbt, transaction::Version,
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::chain::btc::{
+    account::{Account, KeyChain, UtxoSet},
+    key_derivation::Change,
+    tx_builder::{self, estimate_taproot_vbytes},
+    utxo::Utxo,
+};
+
+/// Payload of the OP_RETURN output of a sweep. Keeps the transaction above
+/// the 65 byte minimum non-witness size relay policy requires.
+const SWEEP_MARKER: [u8; 4] = *b"dust";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum DustState {
+    /// Held back from automatic selection.
+    Quarantined,
+    /// The user vouched for it, it's spent like any other coin.
+    Released,
+}
+
+/// How a quarantined coin leaves the wallet. Either way it's the only input
+/// and no change comes back, so it can't be linked to the rest of the wallet.
+/// To send it somewhere instead, build a regular transaction selecting it
+/// alone.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Type)]
+pub enum DustSpendMode {
+    /// Burn the value minus the fee in an OP_RETURN output.
+    Burn,
+    /// Give the whole value to the miner.
+    FeeOnly,
+}
+
+/// New coins below `threshold_sat` paying to an external address that was
+/// already used by another transaction. Change and coins already known or
+/// judged before are left alone.
+pub fn detect(
+    keychain: &KeyChain,
+    known: &UtxoSet,
+    fresh: &[Utxo],
+    threshold_sat: u64,
+) -> Vec<OutPoint> {
+    let is_new = |u: &Utxo| {
+        !known.entries.contains_key(&u.outpoint()) && !known.dust.contains_key(&u.outpoint())
+    };
+    fresh
+        .iter()
+        .filter(|u| is_new(u))
+        .filter(|u| u.output.value.to_sat() < threshold_sat)
+        .filter(|u| u.derivation.change == Change::External)
+        .filter(|u| {
+            let mut funding: HashSet<_> = keychain
+                .usage
+                .get(&u.derivation)
+                .map(|usage| usage.funding.iter().map(|o| o.txid).collect())
+                .unwrap_or_default();
+            funding.extend(
+                fresh
+                    .iter()
+                    .filter(|other| other.derivation == u.derivation)
+                    .map(|other| other.tx_id),
+            );
+            funding.remove(&u.tx_id);
+            !funding.is_empty()
+                || keychain
+                    .usage
+                    .get(&u.derivation)
+                    .is_some_and(|a| a.has_history)
+        })
+        .map(Utxo::outpoint)
+        .collect()
+}
+
+pub struct DustSweep {
+    pub psbt: Psbt,
+    pub fee: u32,
+}
+
+/// Spend a single coin into an OP_RETURN output, see [`DustSpendMode`].
+pub fn build_sweep(
+    account: &Account,
+    xpriv: &Xpriv,
+    outpoint: OutPoint,
+    mode: DustSpendMode,
+    fee_rate_sat_vb: f64,
+) -> Result<DustSweep, String> {
+    let utxo = account
+        .utxo_set
+        .entries
+        .get(&outpoint)
+        .ok_or(format!("utxo {outpoint} not found"))?;
+    if account.utxo_set.is_locked(&outpoint) {
+        return Err(format!(
+            "utxo {outpoint} is reserved by a pending transaction"
+        ));
+    }
+    let value = utxo.output.value.to_sat();
+    let vsize = estimate_taproot_vbytes(1, 1);
+    let burn = match mode {
+        DustSpendMode::FeeOnly => 0,
+        DustSpendMode::Burn => value
+            .checked_sub((vsize as f64 * fee_rate_sat_vb).ceil() as u64)
+            .filter(|burn| *burn > 0)
+            .ok_or("coin doesn't cover the fee, spend it as fee only")?,
+    };
+    let fee = value - burn;
+    if fee < vsize {
+        return Err(format!(
+            "fee of {fee} sat is below the minimum relay fee for {vsize} vB"
+        ));
+    }
+
+    let mut psbt = Psbt::from_unsigned_tx(Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: outpoint,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(burn),
+            script_pubkey: ScriptBuf::new_op_return(SWEEP_MARKER),
+        }],
+    })
+    .map_err(|e| format!("Failed to create PSBT: {e}"))?;
+    tx_builder::add_input_keys(&mut psbt, &[utxo], account, xpriv)?;
+
+    Ok(DustSweep {
+        psbt,
+        fee: fee as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Network, Txid, hashes::Hash};
+
+    use super::*;
+    use crate::chain::btc::key_derivation::{KeyDerivationPath, Proposal};
+
+    fn utxo(byte: u8, index: u32, change: Change, value: u64) -> Utxo {
+        Utxo {
+            tx_id: Txid::from_byte_array([byte; 32]),
+            vout: 0,
+            output: TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::new(),
+            },
+            derivation: KeyDerivationPath::new(
+                Proposal::Taproot,
+                Network::Regtest,
+                0,
+                change,
+                index,
+            ),
+            height: 0,
+            silent_payment: None,
+        }
+    }
+
+    #[test]
+    fn flags_small_coins_on_used_addresses() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let paid = utxo(1, 0, Change::External, 50_000);
+        account
+            .keychain
+            .record_funding(&paid.derivation, paid.outpoint());
+        account.utxo_set.replace_all(vec![paid]);
+
+        let fresh = vec![
+            // dust on the used address
+            utxo(2, 0, Change::External, 500),
+            // first payment to a fresh address, however small
+            utxo(3, 1, Change::External, 500),
+            // our own small change
+            utxo(4, 0, Change::Internal, 500),
+            // large enough to be a real payment
+            utxo(5, 0, Change::External, 5_000),
+        ];
+        let flagged = detect(&account.keychain, &account.utxo_set, &fresh, 1_000);
+        assert_eq!(flagged, vec![fresh[0].outpoint()]);
+
+        // Once judged, it isn't flagged again.
+        account.utxo_set.quarantine(flagged.clone());
+        account.utxo_set.release(&flagged[0]).unwrap();
+        assert!(detect(&account.keychain, &account.utxo_set, &fresh, 1_000).is_empty());
+    }
+
+    #[test]
+    fn quarantined_coins_stay_out_of_auto_selection() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let dust = utxo(2, 0, Change::External, 500);
+        account
+            .utxo_set
+            .replace_all(vec![utxo(1, 0, Change::Internal, 10_000), dust.clone()]);
+        account.utxo_set.quarantine([dust.outpoint()]);
+
+        let auto = crate::chain::btc::account::UtxoSelectionStrategy::Auto(10_500);
+        assert!(account.utxo_set.select(auto.clone(), true).is_empty());
+        account.utxo_set.release(&dust.outpoint()).unwrap();
+        assert_eq!(account.utxo_set.select(auto, true).len(), 2);
+    }
+}
@@ -1,17 +1,13 @@
 use bitcoin::{
-    Amount, OutPoint, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
-    absolute::LockTime,
-    bip32::{KeySource, Xpriv},
-    key::Secp256k1,
-    psbt::Psbt,
-    transaction::Version,
+    Amount, OutPoint, Sequence, Transaction, TxIn, TxOut, Txid, Witness, absolute::LockTime,
+    bip32::Xpriv, psbt::Psbt, transaction::Version,
 };
 
 use crate::chain::btc::{
     account::Account,
     config::BitcoinConfig,
     key_derivation::{Change, KeyDerivationPath, LabeledKeyDerivationPath, Proposal},
-    tx_builder::{BuildTxResult, estimate_taproot_vbytes},
+    tx_builder::{BuildTxResult, add_input_keys, estimate_taproot_vbytes},
     utxo::Utxo,
 };
 
@@ -37,6 +33,7 @@ pub fn build_cpfp_psbt(p: &BuildCpfpParams) -> Result<BuildTxResult, String> {
         .entries
         .values()
         .filter(|u| u.tx_id == p.parent_tx_id)
+        .filter(|u| !p.account.utxo_set.is_locked(&u.outpoint()))
         .collect();
 
     if child_utxos.is_empty() {
@@ -66,8 +63,9 @@ pub fn build_cpfp_psbt(p: &BuildCpfpParams) -> Result<BuildTxResult, String> {
         Change::Internal,
         change_index,
     );
-    let change_child_key = change_key_path
-        .derive(p.xpriv)
+    let change_child_key = p
+        .account
+        .derive(&change_key_path, p.xpriv)
         .map_err(|e| format!("failed to derive change key: {e}"))?;
 
     let input: Vec<TxIn> = child_utxos
@@ -96,22 +94,7 @@ pub fn build_cpfp_psbt(p: &BuildCpfpParams) -> Result<BuildTxResult, String> {
     })
     .map_err(|e| format!("Failed to create PSBT: {e}"))?;
 
-    let secp = Secp256k1::new();
-    let master_fingerprint = p.xpriv.fingerprint(&secp);
-    for (i, utxo) in child_utxos.iter().enumerate() {
-        psbt.inputs[i].witness_utxo = Some(utxo.output.clone());
-        let child = utxo
-            .derivation
-            .derive(p.xpriv)
-            .map_err(|e| format!("failed to derive child key: {e}"))?;
-        let xonly_pubkey = child.keypair.x_only_public_key().0;
-        let derivation_path = utxo.derivation.to_path()?;
-        let key_source: KeySource = (master_fingerprint, derivation_path);
-        psbt.inputs[i]
-            .tap_key_origins
-            .insert(xonly_pubkey, (vec![], key_source));
-        psbt.inputs[i].tap_internal_key = Some(xonly_pubkey);
-    }
+    add_input_keys(&mut psbt, &child_utxos, p.account, p.xpriv)?;
 
     Ok(BuildTxResult {
         fee: fee as u32,
@@ -122,5 +105,6 @@ pub fn build_cpfp_psbt(p: &BuildCpfpParams) -> Result<BuildTxResult, String> {
         },
         recipient: None,
         send_value_sat: 0,
+        warnings: Vec::new(),
     })
 }
@@ -1,3 +1,6 @@
+use serde::Deserialize;
+use specta::Type;
+
 use crate::chain::btc::{
     config::BitcoinConfig,
     providers::{
@@ -7,38 +10,176 @@ use crate::chain::btc::{
     },
 };
 
+/// Target confirmation depth (in blocks) for the economy fee tier.
+pub const ECONOMY_FEE_TARGET_BLOCKS: u16 = 6;
+
 /// Target confirmation depth (in blocks) for the standard fee tier.
-/// When a per-tx `FeeMode` picker (mirror of the Ethereum side) is added,
-/// this becomes the target for the "Standard" variant.
 pub const STANDARD_FEE_TARGET_BLOCKS: u16 = 2;
 
+/// Target confirmation depth (in blocks) for the priority fee tier.
+pub const PRIORITY_FEE_TARGET_BLOCKS: u16 = 1;
+
 /// Floor for the sat/vB fee rate. Mainnet's default min relay fee is 1 sat/vB,
 /// so anything below this wouldn't be relayed anyway.
 pub const MIN_FEE_RATE_SAT_VB: f64 = 1.0;
 
-/// Estimate the sat/vB fee rate for the standard confirmation target.
+/// Maximum block size in virtual bytes (4M weight units / 4).
+const BLOCK_VSIZE: u64 = 1_000_000;
+
+/// Average block interval used to turn a block count into wall-clock time.
+const BLOCK_INTERVAL_MINUTES: u32 = 10;
+
+/// Per-transaction fee picker, the Bitcoin mirror of the Ethereum `FeeMode`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Type)]
+pub enum FeeMode {
+    Economy,
+    #[default]
+    Standard,
+    Priority,
+    /// Manual override in sat/vB.
+    Custom(f64),
+}
+
+impl FeeMode {
+    /// Confirmation target backing the tier, `None` for a manual override.
+    pub const fn target_blocks(self) -> Option<u16> {
+        match self {
+            FeeMode::Economy => Some(ECONOMY_FEE_TARGET_BLOCKS),
+            FeeMode::Standard => Some(STANDARD_FEE_TARGET_BLOCKS),
+            FeeMode::Priority => Some(PRIORITY_FEE_TARGET_BLOCKS),
+            FeeMode::Custom(_) => None,
+        }
+    }
+}
+
+/// One bucket of the `mempool.get_fee_histogram` response: `vsize` vbytes of
+/// transactions paying at least `fee_rate` sat/vB (and less than the previous,
+/// higher bucket).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeHistogramBin {
+    pub fee_rate: f64,
+    pub vsize: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier {
+    pub fee_rate_sat_vb: f64,
+    pub target_blocks: u16,
+    /// Blocks until a tx paying `fee_rate_sat_vb` is expected to confirm.
+    pub eta_blocks: u32,
+}
+
+impl FeeTier {
+    pub fn eta_minutes(&self) -> u32 {
+        self.eta_blocks.saturating_mul(BLOCK_INTERVAL_MINUTES)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeeTiers {
+    pub economy: FeeTier,
+    pub standard: FeeTier,
+    pub priority: FeeTier,
+}
+
+/// Resolve the sat/vB fee rate for the given fee mode. Tiers are estimated
+/// against the backend, a manual override is validated and used verbatim.
+pub async fn resolve_fee_rate(
+    server: &BtcNode,
+    config: &BitcoinConfig,
+    mode: FeeMode,
+) -> Result<f64, String> {
+    match mode {
+        FeeMode::Custom(rate) => {
+            if !rate.is_finite() || rate < MIN_FEE_RATE_SAT_VB {
+                return Err(format!(
+                    "custom fee rate must be at least {MIN_FEE_RATE_SAT_VB} sat/vB"
+                ));
+            }
+            Ok(rate)
+        }
+        tier => {
+            let target = tier.target_blocks().unwrap_or(STANDARD_FEE_TARGET_BLOCKS);
+            estimate_fee_rate(server, config, target).await
+        }
+    }
+}
+
+/// Estimate all fee tiers at once, each with an expected confirmation time
+/// derived from the current mempool fee histogram. When the backend can't
+/// serve a histogram the tier's own target is used as the estimate.
+pub async fn estimate_fee_tiers(
+    server: &BtcNode,
+    config: &BitcoinConfig,
+) -> Result<FeeTiers, String> {
+    let (economy, standard, priority, histogram) = tokio::join!(
+        estimate_fee_rate(server, config, ECONOMY_FEE_TARGET_BLOCKS),
+        estimate_fee_rate(server, config, STANDARD_FEE_TARGET_BLOCKS),
+        estimate_fee_rate(server, config, PRIORITY_FEE_TARGET_BLOCKS),
+        server.get_fee_histogram(),
+    );
+    let histogram = histogram
+        .inspect_err(|e| tracing::warn!("fee histogram unavailable: {e}"))
+        .ok();
+
+    // Estimates at different targets come from independent calls and may
+    // disagree slightly; keep the tiers monotonic.
+    let economy = economy?;
+    let standard = standard?.max(economy);
+    let priority = priority?.max(standard);
+
+    let tier = |fee_rate_sat_vb: f64, target_blocks: u16| FeeTier {
+        fee_rate_sat_vb,
+        target_blocks,
+        eta_blocks: histogram
+            .as_deref()
+            .map(|h| blocks_until_confirmed(h, fee_rate_sat_vb))
+            .unwrap_or(target_blocks as u32),
+    };
+
+    Ok(FeeTiers {
+        economy: tier(economy, ECONOMY_FEE_TARGET_BLOCKS),
+        standard: tier(standard, STANDARD_FEE_TARGET_BLOCKS),
+        priority: tier(priority, PRIORITY_FEE_TARGET_BLOCKS),
+    })
+}
+
+/// Number of blocks until a transaction paying `fee_rate` is mined, assuming
+/// miners fill blocks by fee rate and nothing new arrives: every vbyte paying
+/// at least as much is queued ahead of us.
+pub fn blocks_until_confirmed(histogram: &[FeeHistogramBin], fee_rate: f64) -> u32 {
+    let ahead: u64 = histogram
+        .iter()
+        .filter(|bin| bin.fee_rate >= fee_rate)
+        .map(|bin| bin.vsize)
+        .sum();
+    (ahead / BLOCK_VSIZE) as u32 + 1
+}
+
+/// Estimate the sat/vB fee rate for the given confirmation target.
 ///
 /// For the Electrum path: prefer mempool.space (current mempool state) and
 /// fall back to the Electrum server estimate (conservative bitcoind value).
 /// For the Esplora/Tor path: use the Esplora client directly (already proxied).
-pub async fn estimate_fee_rate(server: &BtcNode, config: &BitcoinConfig) -> Result<f64, String> {
+pub async fn estimate_fee_rate(
+    server: &BtcNode,
+    config: &BitcoinConfig,
+    target_blocks: u16,
+) -> Result<f64, String> {
     let raw = match server {
-        BtcNode::Electrum(electrum) => {
-            if config.regtest {
-                electrum_estimate(electrum).await?
-            } else {
-                match mempool_space_estimate().await {
-                    Ok(rate) => rate,
-                    Err(e) => {
-                        tracing::warn!(
-                            "mempool.space fee estimate failed, falling back to electrum: {e}"
-                        );
-                        electrum_estimate(electrum).await?
-                    }
+        BtcNode::Electrum(electrum) => match EsploraProvider::MempoolSpace.url(config.network()) {
+            None => electrum_estimate(electrum, target_blocks).await?,
+            Some(url) => match mempool_space_estimate(url, target_blocks).await {
+                Ok(rate) => rate,
+                Err(e) => {
+                    tracing::warn!(
+                        "mempool.space fee estimate failed, falling back to electrum: {e}"
+                    );
+                    electrum_estimate(electrum, target_blocks).await?
                 }
-            }
-        }
-        BtcNode::Esplora(esplora) => esplora_estimate(esplora).await?,
+            },
+        },
+        BtcNode::Esplora(esplora) => esplora_estimate(esplora, target_blocks).await?,
     };
     let rate = if raw.is_finite() && raw > 0.0 {
         raw
@@ -48,27 +189,61 @@ pub async fn estimate_fee_rate(server: &BtcNode, config: &BitcoinConfig) -> Resu
     Ok(rate.max(MIN_FEE_RATE_SAT_VB))
 }
 
-async fn mempool_space_estimate() -> Result<f64, String> {
-    let estimates = EsploraAdapter::new(EsploraProvider::MempoolSpace.main_net())
+async fn mempool_space_estimate(url: &str, target_blocks: u16) -> Result<f64, String> {
+    let estimates = EsploraAdapter::new(url)
         .get_fee_estimates()
         .await
         .map_err(|e| e.to_string())?;
-    (0..=STANDARD_FEE_TARGET_BLOCKS)
+    (0..=target_blocks)
         .rev()
         .find_map(|target| estimates.get(&target).copied())
         .ok_or_else(|| "no fee estimate at target depth".to_string())
 }
 
-async fn electrum_estimate(electrum: &ElectrumAdapter) -> Result<f64, String> {
+async fn electrum_estimate(electrum: &ElectrumAdapter, target_blocks: u16) -> Result<f64, String> {
     electrum
-        .estimate_fee(STANDARD_FEE_TARGET_BLOCKS as u32)
+        .estimate_fee(target_blocks as u32)
         .await
         .map_err(|e| format!("failed to estimate fee: {e}"))
 }
 
-async fn esplora_estimate(esplora: &EsploraAdapter) -> Result<f64, String> {
+async fn esplora_estimate(esplora: &EsploraAdapter, target_blocks: u16) -> Result<f64, String> {
     esplora
-        .estimate_fee_sat_vb(STANDARD_FEE_TARGET_BLOCKS)
+        .estimate_fee_sat_vb(target_blocks)
         .await
         .map_err(|e| e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bin(fee_rate: f64, vsize: u64) -> FeeHistogramBin {
+        FeeHistogramBin { fee_rate, vsize }
+    }
+
+    #[test]
+    fn empty_mempool_confirms_in_next_block() {
+        assert_eq!(blocks_until_confirmed(&[], 1.0), 1);
+    }
+
+    #[test]
+    fn counts_only_vbytes_paying_at_least_our_rate() {
+        let histogram = [bin(50.0, 600_000), bin(20.0, 900_000), bin(5.0, 3_000_000)];
+        // 1.5M vB ahead of us at 20 sat/vB → one full block, mined in the second.
+        assert_eq!(blocks_until_confirmed(&histogram, 20.0), 2);
+        // Outbid everything in the mempool.
+        assert_eq!(blocks_until_confirmed(&histogram, 60.0), 1);
+        // Queue behind the whole mempool (4.5M vB).
+        assert_eq!(blocks_until_confirmed(&histogram, 1.0), 5);
+    }
+
+    #[test]
+    fn custom_fee_mode_has_no_target() {
+        assert_eq!(FeeMode::Custom(3.5).target_blocks(), None);
+        assert_eq!(
+            FeeMode::Standard.target_blocks(),
+            Some(STANDARD_FEE_TARGET_BLOCKS)
+        );
+    }
+}
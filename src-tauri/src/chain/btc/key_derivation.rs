@@ -1,18 +1,29 @@
 use std::{fmt::Display, str::FromStr};
 
 use bitcoin::{
-    Address, Network,
+    Address, Network, TapNodeHash,
     bip32::{DerivationPath, Xpriv},
     key::{Keypair, Secp256k1},
 };
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::chain_trait::AccountIndex;
+use crate::{
+    chain::btc::{config::BtcNetwork, recovery::RecoveryPolicy},
+    chain_trait::AccountIndex,
+};
 
 /// m / purpose' / coin_type' / account' / change / address_index
 pub type KeyDeriviationPathSlice = [u32; 5];
 
+/// SLIP-44 coin type, 1 for every test network.
+pub fn coin_type(network: Network) -> u32 {
+    match network {
+        Network::Bitcoin => 0,
+        _ => 1,
+    }
+}
+
 #[cfg(test)]
 pub fn make_hardened(raw: KeyDeriviationPathSlice) -> KeyDeriviationPathSlice {
     [
@@ -34,6 +45,9 @@ pub struct Child {
     pub keypair: Keypair,
     pub taproot_address: Address,
     pub segwit_address: Address,
+    /// Script tree commitment of the taproot output, `None` for a BIP86
+    /// key-path-only output.
+    pub merkle_root: Option<TapNodeHash>,
 }
 
 impl Child {
@@ -87,7 +101,7 @@ impl TryFrom<u32> for Proposal {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(into = "KeyDeriviationPathSlice", try_from = "KeyDeriviationPathSlice")]
+#[serde(into = "KeyDerivationPathStored", try_from = "KeyDerivationPathStored")]
 pub struct KeyDerivationPath {
     pub purpose: Proposal,
     pub account: AccountIndex,
@@ -102,11 +116,44 @@ impl From<KeyDerivationPath> for KeyDeriviationPathSlice {
     }
 }
 
-impl TryFrom<KeyDeriviationPathSlice> for KeyDerivationPath {
+/// Coin type 1 is shared by all test networks, so the network is stored
+/// next to the indexes. Older wallets only kept the indexes and could only
+/// run on mainnet or regtest.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum KeyDerivationPathStored {
+    Tagged {
+        path: KeyDeriviationPathSlice,
+        network: BtcNetwork,
+    },
+    Legacy(KeyDeriviationPathSlice),
+}
+
+impl From<KeyDerivationPath> for KeyDerivationPathStored {
+    fn from(p: KeyDerivationPath) -> Self {
+        KeyDerivationPathStored::Tagged {
+            path: p.to_slice(),
+            network: p.network.into(),
+        }
+    }
+}
+
+impl TryFrom<KeyDerivationPathStored> for KeyDerivationPath {
     type Error = String;
 
-    fn try_from(v: KeyDeriviationPathSlice) -> Result<Self, Self::Error> {
-        KeyDerivationPath::from_slice(v)
+    fn try_from(stored: KeyDerivationPathStored) -> Result<Self, Self::Error> {
+        match stored {
+            KeyDerivationPathStored::Tagged { path, network } => {
+                KeyDerivationPath::from_slice(path, network.into())
+            }
+            KeyDerivationPathStored::Legacy(path) => {
+                let network = match path[1].checked_sub(HARDENED) {
+                    Some(0) => Network::Bitcoin,
+                    _ => Network::Regtest,
+                };
+                KeyDerivationPath::from_slice(path, network)
+            }
+        }
     }
 }
 
@@ -114,14 +161,14 @@ const HARDENED: u32 = 0x80000000;
 
 impl Display for KeyDerivationPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let network = match self.network {
-            Network::Bitcoin => 0,
-            _ => 1,
-        };
         write!(
             f,
             "m/{}'/{}'/{}'/{}/{}",
-            self.purpose as u32, network, self.account, self.change as i32, self.index
+            self.purpose as u32,
+            coin_type(self.network),
+            self.account,
+            self.change as i32,
+            self.index
         )
     }
 }
@@ -156,28 +203,28 @@ impl KeyDerivationPath {
     }
 
     pub fn to_slice(&self) -> KeyDeriviationPathSlice {
-        let network = match self.network {
-            Network::Bitcoin => 0,
-            _ => 1,
-        };
         [
             HARDENED + self.purpose as u32,
-            HARDENED + network,
+            HARDENED + coin_type(self.network),
             HARDENED + self.account,
             self.change as u32,
             self.index,
         ]
     }
 
-    pub fn from_slice(v: KeyDeriviationPathSlice) -> Result<Self, String> {
+    /// Parse the indexes of a path on `network`, whose coin type they have
+    /// to match.
+    pub fn from_slice(v: KeyDeriviationPathSlice, network: Network) -> Result<Self, String> {
         let purpose = Proposal::try_from(
             v[0].checked_sub(HARDENED)
                 .ok_or("purpose must be hardened")?,
         )?;
-        let network = match v[1].checked_sub(HARDENED) {
-            Some(0) => Network::Bitcoin,
-            _ => Network::Regtest,
-        };
+        let coin = v[1]
+            .checked_sub(HARDENED)
+            .ok_or("coin type must be hardened")?;
+        if coin != coin_type(network) {
+            return Err(format!("coin type {coin} is not valid for {network}"));
+        }
         let account = v[2]
             .checked_sub(HARDENED)
             .ok_or("account must be hardened")?;
@@ -193,6 +240,16 @@ impl KeyDerivationPath {
     }
 
     pub fn derive(&self, xpriv: &Xpriv) -> Result<Child, String> {
+        self.derive_with_recovery(xpriv, None)
+    }
+
+    /// Like [`Self::derive`], but the taproot output also commits to the
+    /// timelocked recovery leaf of `recovery` when one is given.
+    pub fn derive_with_recovery(
+        &self,
+        xpriv: &Xpriv,
+        recovery: Option<&RecoveryPolicy>,
+    ) -> Result<Child, String> {
         let secp = Secp256k1::new();
         // derive child private key
         let keypair = xpriv
@@ -203,13 +260,13 @@ impl KeyDerivationPath {
         // x-only pubkey for taproot
         let (internal_key, _parity) = keypair.x_only_public_key();
 
+        // no script tree = BIP86 key-path spend
+        let merkle_root = recovery
+            .map(|policy| policy.merkle_root(internal_key, self.change, self.index))
+            .transpose()?;
+
         // Create taproot address (BIP341 tweak is done automatically by rust-bitcoin)
-        let address = Address::p2tr(
-            &secp,
-            internal_key,
-            None, // no script tree = BIP86 key-path spend
-            self.network,
-        );
+        let address = Address::p2tr(&secp, internal_key, merkle_root, self.network);
 
         // Create native segwit (P2WPKH) address
         let public_key = keypair.public_key();
@@ -220,6 +277,7 @@ impl KeyDerivationPath {
             taproot_address: address,
             segwit_address,
             keypair,
+            merkle_root,
         })
     }
 }
@@ -268,7 +326,7 @@ mod tests {
     #[test]
     fn test_from_u32_vec() {
         let vec = make_hardened([86, 0, 0, 0, 0]);
-        let result = KeyDerivationPath::from_slice(vec);
+        let result = KeyDerivationPath::from_slice(vec, Network::Bitcoin);
         assert!(result.is_ok());
         let path = result.unwrap();
         assert_eq!(path.purpose, Proposal::Taproot);
@@ -288,7 +346,7 @@ mod tests {
             index: 5,
         };
         let vec = original.to_slice();
-        let parsed = KeyDerivationPath::from_slice(vec).unwrap();
+        let parsed = KeyDerivationPath::from_slice(vec, Network::Bitcoin).unwrap();
         assert_eq!(original, parsed);
     }
 
@@ -316,19 +374,21 @@ mod tests {
     #[test]
     fn test_from_u32_vec_invalid_purpose() {
         let vec = [44, 0, 0, 0, 0]; // Invalid purpose
-        assert!(KeyDerivationPath::from_slice(vec).is_err());
+        assert!(KeyDerivationPath::from_slice(vec, Network::Bitcoin).is_err());
     }
 
     #[test]
     fn test_from_u32_vec_invalid_change() {
         let vec = [86, 0, 0, 2, 0]; // Invalid change
-        assert!(KeyDerivationPath::from_slice(vec).is_err());
+        assert!(KeyDerivationPath::from_slice(vec, Network::Bitcoin).is_err());
     }
 
     #[test]
     fn test_from_u32_vec_invalid_network() {
         let vec = [86, 99, 0, 0, 0]; // Invalid network
-        assert!(KeyDerivationPath::from_slice(vec).is_err());
+        assert!(KeyDerivationPath::from_slice(vec, Network::Bitcoin).is_err());
+        let vec = make_hardened([86, 0, 0, 0, 0]);
+        assert!(KeyDerivationPath::from_slice(vec, Network::Signet).is_err());
     }
 
     #[test]
@@ -343,7 +403,28 @@ mod tests {
         let vec = path.to_slice();
         assert_eq!(vec, vec);
 
-        let parsed = KeyDerivationPath::from_slice(vec).unwrap();
+        let parsed = KeyDerivationPath::from_slice(vec, Network::Regtest).unwrap();
+        assert_eq!(parsed.network, Network::Regtest);
+    }
+
+    #[test]
+    fn test_network_survives_serde() {
+        for network in [Network::Testnet, Network::Testnet4, Network::Signet] {
+            let path = KeyDerivationPath::new(Proposal::Taproot, network, 2, Change::Internal, 7);
+            let json = serde_json::to_string(&path).unwrap();
+            let parsed: KeyDerivationPath = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, path);
+            assert_eq!(parsed.to_string(), "m/86'/1'/2'/1/7");
+        }
+    }
+
+    #[test]
+    fn test_legacy_slice_deserializes() {
+        let json = serde_json::to_string(&make_hardened([86, 1, 0, 0, 3])).unwrap();
+        let parsed: KeyDerivationPath = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.network, Network::Regtest);
+        let json = serde_json::to_string(&make_hardened([86, 0, 0, 0, 3])).unwrap();
+        let parsed: KeyDerivationPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.network, Network::Bitcoin);
     }
 }
@@ -1,16 +1,28 @@
 pub mod account;
+pub mod chain_tip;
 pub mod coin_selection;
 pub mod commands;
 pub mod config;
+pub mod descriptor_account;
 pub mod discovery;
+pub mod drafts;
 pub mod dtos;
+pub mod dust;
 pub mod fee_bump;
 pub mod fee_estimator;
 pub mod key_derivation;
+pub mod pending_tx;
 pub mod persistence;
+pub mod policy;
 pub mod providers;
+pub mod quorum;
+pub mod receive_addresses;
+pub mod recovery;
+pub mod silent_payments;
 pub mod tx_builder;
+pub mod tx_details;
 pub mod utxo;
 pub mod wallet;
+pub mod zero_conf;
 
 pub use wallet::*;
@@ -0,0 +1,234 @@
+use bitcoin::{OutPoint, Transaction};
+use serde::Serialize;
+use specta::Type;
+
+use crate::{
+    chain::btc::{
+        Prk,
+        account::Account,
+        tx_builder::{BuildTxResult, sign_psbt},
+    },
+    chain_trait::AccountIndex,
+};
+
+/// Reservations of an unsent transaction are released after this long so a
+/// forgotten draft doesn't keep coins locked for the rest of the session.
+pub const PENDING_TX_TTL_SECS: u64 = 30 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingTxState {
+    /// Built, inputs and change reserved.
+    Draft,
+    /// Signed but not accepted by the backend yet.
+    Signed,
+    /// Accepted by the backend, change committed to the keychain.
+    Broadcast,
+    /// Dropped by the user or expired, reservations released.
+    Abandoned,
+}
+
+/// Transaction between `build_tx` and `broadcast_tx`. Owns the reservation
+/// of its inputs and change path on the account it spends from.
+#[derive(Debug, Clone)]
+pub struct PendingTx {
+    pub account: AccountIndex,
+    pub built: BuildTxResult,
+    pub state: PendingTxState,
+    pub signed: Option<Transaction>,
+    pub created_at: u64,
+}
+
+impl PendingTx {
+    pub fn new(account: AccountIndex, built: BuildTxResult, now: u64) -> Self {
+        Self {
+            account,
+            built,
+            state: PendingTxState::Draft,
+            signed: None,
+            created_at: now,
+        }
+    }
+
+    /// Lock the inputs and reserve the change path of `built` on `account`.
+    pub fn reserve(account: &mut Account, built: BuildTxResult, now: u64) -> Self {
+        let tx = Self::new(account.index, built, now);
+        tx.hold(account);
+        tx
+    }
+
+    /// Apply the reservations of this transaction to `account`.
+    pub fn hold(&self, account: &mut Account) {
+        account.utxo_set.lock(self.inputs());
+        account
+            .keychain
+            .reserve(self.built.change_key_path.path.clone());
+    }
+
+    /// Lift the reservations without changing the state, e.g. to rebuild.
+    pub fn release(&self, account: &mut Account) {
+        account.utxo_set.unlock(&self.inputs());
+        account.keychain.release(&self.built.change_key_path.path);
+    }
+
+    pub fn inputs(&self) -> Vec<OutPoint> {
+        self.built
+            .psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|i| i.previous_output)
+            .collect()
+    }
+
+    /// Still holding reservations.
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, PendingTxState::Draft | PendingTxState::Signed)
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.is_open() && now.saturating_sub(self.created_at) >= PENDING_TX_TTL_SECS
+    }
+
+    /// Sign once; retries after a failed broadcast reuse the same transaction.
+    pub fn sign(&mut self, prk: &Prk) -> Result<Transaction, String> {
+        if !self.is_open() {
+            return Err(format!("transaction is {:?}", self.state));
+        }
+        if let Some(tx) = &self.signed {
+            return Ok(tx.clone());
+        }
+        let tx = sign_psbt(self.built.psbt.clone(), prk)?;
+        self.signed = Some(tx.clone());
+        self.state = PendingTxState::Signed;
+        Ok(tx)
+    }
+
+    /// The backend accepted the transaction: commit the change path and drop
+    /// the spent coins.
+    pub fn commit(&mut self, account: &mut Account) {
+        account.spend(&self.inputs());
+        if self.has_change() {
+            account.keychain.push(self.built.change_key_path.clone());
+        } else {
+            account.keychain.release(&self.built.change_key_path.path);
+        }
+        self.state = PendingTxState::Broadcast;
+    }
+
+    pub fn abandon(&mut self, account: &mut Account) {
+        if !self.is_open() {
+            return;
+        }
+        self.release(account);
+        self.state = PendingTxState::Abandoned;
+    }
+
+    /// `build_psbt` only adds a change output when it's above dust; the
+    /// reserved path is unused otherwise.
+    fn has_change(&self) -> bool {
+        self.built.psbt.unsigned_tx.output.len() > 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        Amount, Network, ScriptBuf, Sequence, TxIn, TxOut, Txid, Witness, absolute::LockTime,
+        hashes::Hash, psbt::Psbt, transaction::Version,
+    };
+
+    use super::*;
+    use crate::chain::btc::key_derivation::{Change, KeyDerivationPath, Proposal};
+
+    fn built(account: &Account, outputs: usize) -> BuildTxResult {
+        let input = vec![TxIn {
+            previous_output: OutPoint {
+                txid: Txid::all_zeros(),
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }];
+        let output = (0..outputs)
+            .map(|_| TxOut {
+                value: Amount::from_sat(1_000),
+                script_pubkey: ScriptBuf::new(),
+            })
+            .collect();
+        let psbt = Psbt::from_unsigned_tx(Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input,
+            output,
+        })
+        .unwrap();
+        let change = KeyDerivationPath::new(
+            Proposal::Taproot,
+            Network::Regtest,
+            account.index,
+            Change::Internal,
+            account.keychain.next_unused_index(Change::Internal),
+        );
+        BuildTxResult {
+            psbt,
+            change_key_path: change.with_label("Change".to_string()),
+            fee: 100,
+            recipient: None,
+            send_value_sat: 1_000,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn reservation_holds_change_index_and_inputs() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let tx = PendingTx::reserve(&mut account, built(&account, 2), 0);
+
+        assert!(account.utxo_set.is_locked(&tx.inputs()[0]));
+        assert_eq!(account.keychain.next_unused_index(Change::Internal), 2);
+        assert!(
+            !account
+                .keychain
+                .contains_path(tx.built.change_key_path.path.clone())
+        );
+    }
+
+    #[test]
+    fn abandon_releases_reservations() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let mut tx = PendingTx::reserve(&mut account, built(&account, 2), 0);
+        tx.abandon(&mut account);
+
+        assert_eq!(tx.state, PendingTxState::Abandoned);
+        assert!(account.utxo_set.locked.is_empty());
+        assert_eq!(account.keychain.next_unused_index(Change::Internal), 1);
+    }
+
+    #[test]
+    fn commit_pushes_change_only_when_present() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let mut tx = PendingTx::reserve(&mut account, built(&account, 2), 0);
+        tx.commit(&mut account);
+        assert_eq!(tx.state, PendingTxState::Broadcast);
+        assert!(
+            account
+                .keychain
+                .contains_path(tx.built.change_key_path.path.clone())
+        );
+
+        let mut tx = PendingTx::reserve(&mut account, built(&account, 1), 0);
+        tx.commit(&mut account);
+        assert!(account.keychain.reserved.is_empty());
+        assert_eq!(account.keychain.next_unused_index(Change::Internal), 2);
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let tx = PendingTx::reserve(&mut account, built(&account, 2), 100);
+        assert!(!tx.is_expired(100 + PENDING_TX_TTL_SECS - 1));
+        assert!(tx.is_expired(100 + PENDING_TX_TTL_SECS));
+    }
+}
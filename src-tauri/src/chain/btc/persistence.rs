@@ -1,14 +1,33 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
-use bitcoin::{Amount, OutPoint, ScriptBuf, TxOut, Txid, hashes::Hash};
+use bitcoin::{
+    Amount, BlockHash, OutPoint, ScriptBuf, TxOut, Txid,
+    consensus::encode::{deserialize_hex, serialize_hex},
+    hashes::Hash,
+    psbt::Psbt,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     chain::btc::{
-        account::{Account, KeyChain, UtxoSet},
-        key_derivation::{KeyDerivationPath, LabeledKeyDerivationPath},
+        account::{Account, AddressUsage, KeyChain, UtxoSelectionStrategy, UtxoSet},
+        chain_tip::TipTracker,
+        descriptor_account::{DescriptorAccount, DescriptorUtxo, PendingDescriptorPsbt},
+        drafts::TxDraft,
+        dtos::{OutPointRef, TxDraftView},
+        dust::DustState,
+        key_derivation::{Change, KeyDerivationPath, LabeledKeyDerivationPath},
+        pending_tx::{PendingTx, PendingTxState},
+        policy::PolicyWarning,
         providers::btc_node::select_btc_server,
-        utxo::Utxo,
+        quorum::Quorum,
+        recovery::RecoveryPolicy,
+        silent_payments::{SilentPaymentOutput, SilentPaymentState},
+        tx_builder::BuildTxResult,
+        utxo::{BlockHeader, Utxo},
         wallet::BitcoinWallet,
     },
     chain_trait::AccountIndex,
@@ -24,6 +43,8 @@ pub struct UtxoStored {
     script_pubkey: Vec<u8>,
     derivation: KeyDerivationPath,
     height: u32,
+    #[serde(default)]
+    silent_payment: Option<[u8; 32]>,
 }
 
 impl From<&Utxo> for UtxoStored {
@@ -35,6 +56,7 @@ impl From<&Utxo> for UtxoStored {
             script_pubkey: u.output.script_pubkey.to_bytes(),
             derivation: u.derivation.clone(),
             height: u.height,
+            silent_payment: u.silent_payment,
         }
     }
 }
@@ -50,6 +72,47 @@ impl From<UtxoStored> for Utxo {
             },
             derivation: dto.derivation,
             height: dto.height,
+            silent_payment: dto.silent_payment,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SilentPaymentOutputStored {
+    txid: [u8; 32],
+    vout: u32,
+    value: u64,
+    script_pubkey: Vec<u8>,
+    tweak: [u8; 32],
+    height: u32,
+}
+
+impl From<&SilentPaymentOutput> for SilentPaymentOutputStored {
+    fn from(o: &SilentPaymentOutput) -> Self {
+        SilentPaymentOutputStored {
+            txid: o.outpoint.txid.to_byte_array(),
+            vout: o.outpoint.vout,
+            value: o.output.value.to_sat(),
+            script_pubkey: o.output.script_pubkey.to_bytes(),
+            tweak: o.tweak,
+            height: o.height,
+        }
+    }
+}
+
+impl From<SilentPaymentOutputStored> for SilentPaymentOutput {
+    fn from(dto: SilentPaymentOutputStored) -> Self {
+        SilentPaymentOutput {
+            outpoint: OutPoint {
+                txid: Txid::from_byte_array(dto.txid),
+                vout: dto.vout,
+            },
+            output: TxOut {
+                script_pubkey: ScriptBuf::from_bytes(dto.script_pubkey),
+                value: Amount::from_sat(dto.value),
+            },
+            tweak: dto.tweak,
+            height: dto.height,
         }
     }
 }
@@ -60,6 +123,71 @@ struct AccountStored {
     index: AccountIndex,
     paths: Vec<LabeledKeyDerivationPath>,
     utxos: Vec<UtxoStored>,
+    #[serde(default)]
+    sp_outputs: Vec<SilentPaymentOutputStored>,
+    #[serde(default)]
+    sp_scan_height: u32,
+    #[serde(default)]
+    recovery: Option<RecoveryPolicyStored>,
+    #[serde(default)]
+    address_usage: Vec<AddressUsageStored>,
+    #[serde(default)]
+    dust: Vec<DustStored>,
+    #[serde(default)]
+    unverified: Vec<([u8; 32], u32)>,
+    #[serde(default)]
+    spent: Vec<([u8; 32], u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DustStored {
+    txid: [u8; 32],
+    vout: u32,
+    state: DustState,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AddressUsageStored {
+    path: KeyDerivationPath,
+    has_history: bool,
+    funding: Vec<([u8; 32], u32)>,
+}
+
+impl AddressUsageStored {
+    fn new(path: &KeyDerivationPath, usage: &AddressUsage) -> Self {
+        AddressUsageStored {
+            path: path.clone(),
+            has_history: usage.has_history,
+            funding: usage
+                .funding
+                .iter()
+                .map(|o| (o.txid.to_byte_array(), o.vout))
+                .collect(),
+        }
+    }
+}
+
+impl From<AddressUsageStored> for (KeyDerivationPath, AddressUsage) {
+    fn from(dto: AddressUsageStored) -> Self {
+        let usage = AddressUsage {
+            has_history: dto.has_history,
+            funding: dto
+                .funding
+                .into_iter()
+                .map(|(txid, vout)| OutPoint {
+                    txid: Txid::from_byte_array(txid),
+                    vout,
+                })
+                .collect(),
+        };
+        (dto.path, usage)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecoveryPolicyStored {
+    key: String,
+    delay_blocks: u16,
 }
 
 impl From<&Account> for AccountStored {
@@ -69,12 +197,43 @@ impl From<&Account> for AccountStored {
             index: a.index,
             paths: a.keychain.paths.clone(),
             utxos: a.utxo_set.entries.values().map(UtxoStored::from).collect(),
+            sp_outputs: a
+                .silent_payments
+                .outputs
+                .values()
+                .map(SilentPaymentOutputStored::from)
+                .collect(),
+            sp_scan_height: a.silent_payments.scan_height,
+            recovery: a.recovery.as_ref().map(|p| RecoveryPolicyStored {
+                key: p.key.to_string(),
+                delay_blocks: p.delay_blocks,
+            }),
+            address_usage: a
+                .keychain
+                .usage
+                .iter()
+                .map(|(path, usage)| AddressUsageStored::new(path, usage))
+                .collect(),
+            dust: a
+                .utxo_set
+                .dust
+                .iter()
+                .map(|(outpoint, state)| DustStored {
+                    txid: outpoint.txid.to_byte_array(),
+                    vout: outpoint.vout,
+                    state: *state,
+                })
+                .collect(),
+            unverified: stored_outpoints(&a.utxo_set.unverified),
+            spent: stored_outpoints(&a.utxo_set.spent),
         }
     }
 }
 
-impl From<AccountStored> for Account {
-    fn from(dto: AccountStored) -> Self {
+impl TryFrom<AccountStored> for Account {
+    type Error = String;
+
+    fn try_from(dto: AccountStored) -> Result<Self, String> {
         let entries: HashMap<OutPoint, Utxo> = dto
             .utxos
             .into_iter()
@@ -84,11 +243,349 @@ impl From<AccountStored> for Account {
             })
             .collect();
 
-        Account {
+        let outputs = dto
+            .sp_outputs
+            .into_iter()
+            .map(|o| {
+                let output = SilentPaymentOutput::from(o);
+                (output.outpoint, output)
+            })
+            .collect();
+
+        // Coins on the recovery outputs can't be told apart or signed without
+        // the policy, so the account isn't loaded without it.
+        let recovery = dto
+            .recovery
+            .map(|p| RecoveryPolicy::new(&p.key, p.delay_blocks))
+            .transpose()
+            .map_err(|e| format!("invalid recovery policy of account {}: {e}", dto.name))?;
+
+        Ok(Account {
             name: dto.name,
             index: dto.index,
-            keychain: KeyChain { paths: dto.paths },
-            utxo_set: UtxoSet { entries },
+            keychain: KeyChain {
+                paths: dto.paths,
+                usage: dto.address_usage.into_iter().map(Into::into).collect(),
+                reserved: HashSet::new(),
+            },
+            utxo_set: UtxoSet {
+                entries,
+                locked: HashSet::new(),
+                zero_conf: HashMap::new(),
+                dust: dto
+                    .dust
+                    .into_iter()
+                    .map(|d| {
+                        let outpoint = OutPoint {
+                            txid: Txid::from_byte_array(d.txid),
+                            vout: d.vout,
+                        };
+                        (outpoint, d.state)
+                    })
+                    .collect(),
+                unverified: outpoints(dto.unverified),
+                spent: outpoints(dto.spent),
+            },
+            silent_payments: SilentPaymentState {
+                outputs,
+                scan_height: dto.sp_scan_height,
+            },
+            recovery,
+        })
+    }
+}
+
+fn stored_outpoints(outpoints: &HashSet<OutPoint>) -> Vec<([u8; 32], u32)> {
+    outpoints
+        .iter()
+        .map(|o| (o.txid.to_byte_array(), o.vout))
+        .collect()
+}
+
+fn outpoints(stored: Vec<([u8; 32], u32)>) -> HashSet<OutPoint> {
+    stored
+        .into_iter()
+        .map(|(txid, vout)| OutPoint {
+            txid: Txid::from_byte_array(txid),
+            vout,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DescriptorUtxoStored {
+    txid: [u8; 32],
+    vout: u32,
+    value: u64,
+    script_pubkey: Vec<u8>,
+    change: u32,
+    index: u32,
+    height: u32,
+}
+
+impl From<&DescriptorUtxo> for DescriptorUtxoStored {
+    fn from(u: &DescriptorUtxo) -> Self {
+        DescriptorUtxoStored {
+            txid: u.outpoint.txid.to_byte_array(),
+            vout: u.outpoint.vout,
+            value: u.output.value.to_sat(),
+            script_pubkey: u.output.script_pubkey.to_bytes(),
+            change: u.change as u32,
+            index: u.index,
+            height: u.height,
+        }
+    }
+}
+
+impl TryFrom<DescriptorUtxoStored> for DescriptorUtxo {
+    type Error = String;
+
+    fn try_from(dto: DescriptorUtxoStored) -> Result<Self, String> {
+        Ok(DescriptorUtxo {
+            outpoint: OutPoint {
+                txid: Txid::from_byte_array(dto.txid),
+                vout: dto.vout,
+            },
+            output: TxOut {
+                script_pubkey: ScriptBuf::from_bytes(dto.script_pubkey),
+                value: Amount::from_sat(dto.value),
+            },
+            change: Change::try_from(dto.change)?,
+            index: dto.index,
+            height: dto.height,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PendingDescriptorPsbtStored {
+    /// Base64 encoded PSBT including the signatures collected so far.
+    psbt: String,
+    fee: u32,
+    change_index: Option<u32>,
+    #[serde(default)]
+    warnings: Vec<PolicyWarning>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DescriptorAccountStored {
+    name: String,
+    index: AccountIndex,
+    descriptor: String,
+    receive_index: u32,
+    change_index: u32,
+    utxos: Vec<DescriptorUtxoStored>,
+    pending: Option<PendingDescriptorPsbtStored>,
+    #[serde(default)]
+    relative_timelock: Option<u16>,
+}
+
+impl From<&DescriptorAccount> for DescriptorAccountStored {
+    fn from(a: &DescriptorAccount) -> Self {
+        DescriptorAccountStored {
+            name: a.name.clone(),
+            index: a.index,
+            descriptor: a.descriptor.to_string(),
+            receive_index: a.receive_index,
+            change_index: a.change_index,
+            utxos: a.utxos.values().map(DescriptorUtxoStored::from).collect(),
+            pending: a.pending.as_ref().map(|p| PendingDescriptorPsbtStored {
+                psbt: p.psbt.to_string(),
+                fee: p.fee,
+                change_index: p.change_index,
+                warnings: p.warnings.clone(),
+            }),
+            relative_timelock: a.relative_timelock,
+        }
+    }
+}
+
+impl TryFrom<DescriptorAccountStored> for DescriptorAccount {
+    type Error = String;
+
+    fn try_from(dto: DescriptorAccountStored) -> Result<Self, String> {
+        let mut account = DescriptorAccount::import(dto.index, dto.name, &dto.descriptor)?;
+        account.receive_index = dto.receive_index;
+        account.change_index = dto.change_index;
+        account.relative_timelock = dto.relative_timelock;
+        for utxo in dto.utxos {
+            let utxo = DescriptorUtxo::try_from(utxo)?;
+            account.utxos.insert(utxo.outpoint, utxo);
+        }
+        account.pending = dto
+            .pending
+            .map(|p| {
+                Ok::<_, String>(PendingDescriptorPsbt {
+                    psbt: Psbt::from_str(&p.psbt).map_err(|e| e.to_string())?,
+                    fee: p.fee,
+                    change_index: p.change_index,
+                    warnings: p.warnings,
+                })
+            })
+            .transpose()?;
+        Ok(account)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxDraftStored {
+    id: u32,
+    account: AccountIndex,
+    name: String,
+    note: String,
+    recipient: String,
+    send_value_sat: u64,
+    utxo_selection: UtxoSelectionStrategy,
+    fee_rate_sat_vb: f64,
+    /// Base64 PSBT.
+    psbt: String,
+    fee: u32,
+    change_path: LabeledKeyDerivationPath,
+    warnings: Vec<PolicyWarning>,
+    /// Consensus hex of the signed transaction.
+    signed_tx: Option<String>,
+    created_at: u64,
+    updated_at: u64,
+}
+
+impl From<&TxDraft> for TxDraftStored {
+    fn from(d: &TxDraft) -> Self {
+        TxDraftStored {
+            id: d.id,
+            account: d.tx.account,
+            name: d.name.clone(),
+            note: d.note.clone(),
+            recipient: d.recipient.clone(),
+            send_value_sat: d.send_value_sat,
+            utxo_selection: d.utxo_selection.clone(),
+            fee_rate_sat_vb: d.fee_rate_sat_vb,
+            psbt: d.tx.built.psbt.to_string(),
+            fee: d.tx.built.fee,
+            change_path: d.tx.built.change_key_path.clone(),
+            warnings: d.tx.built.warnings.clone(),
+            signed_tx: d.tx.signed.as_ref().map(serialize_hex),
+            created_at: d.tx.created_at,
+            updated_at: d.updated_at,
+        }
+    }
+}
+
+impl TryFrom<TxDraftStored> for TxDraft {
+    type Error = String;
+
+    fn try_from(dto: TxDraftStored) -> Result<Self, Self::Error> {
+        let signed = dto
+            .signed_tx
+            .map(|hex| deserialize_hex(&hex).map_err(|e| e.to_string()))
+            .transpose()?;
+        let built = BuildTxResult {
+            psbt: Psbt::from_str(&dto.psbt).map_err(|e| e.to_string())?,
+            change_key_path: dto.change_path,
+            fee: dto.fee,
+            recipient: Some(dto.recipient.clone()),
+            send_value_sat: dto.send_value_sat,
+            warnings: dto.warnings,
+        };
+        let mut tx = PendingTx::new(dto.account, built, dto.created_at);
+        if signed.is_some() {
+            tx.state = PendingTxState::Signed;
+            tx.signed = signed;
+        }
+        Ok(TxDraft {
+            id: dto.id,
+            name: dto.name,
+            note: dto.note,
+            recipient: dto.recipient,
+            send_value_sat: dto.send_value_sat,
+            utxo_selection: dto.utxo_selection,
+            fee_rate_sat_vb: dto.fee_rate_sat_vb,
+            tx,
+            updated_at: dto.updated_at,
+        })
+    }
+}
+
+/// A saved draft that couldn't be loaded. It's written back as it was and
+/// the coins of its PSBT stay held back until the user deletes it.
+#[derive(Debug, Clone)]
+pub struct UnreadableDraft {
+    pub stored: TxDraftStored,
+    pub error: String,
+}
+
+impl UnreadableDraft {
+    pub fn id(&self) -> u32 {
+        self.stored.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.stored.name
+    }
+
+    pub fn account(&self) -> AccountIndex {
+        self.stored.account
+    }
+
+    /// Coins the draft spends, none when its PSBT doesn't parse either.
+    pub fn inputs(&self) -> Vec<OutPoint> {
+        Psbt::from_str(&self.stored.psbt)
+            .map(|psbt| {
+                psbt.unsigned_tx
+                    .input
+                    .iter()
+                    .map(|i| i.previous_output)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl From<&UnreadableDraft> for TxDraftView {
+    fn from(d: &UnreadableDraft) -> Self {
+        let stored = &d.stored;
+        TxDraftView {
+            id: stored.id,
+            name: stored.name.clone(),
+            note: stored.note.clone(),
+            account_index: stored.account,
+            recipient: stored.recipient.clone(),
+            value: stored.send_value_sat.to_string(),
+            fee: stored.fee,
+            fee_rate_sat_vb: stored.fee_rate_sat_vb,
+            state: if stored.signed_tx.is_some() {
+                PendingTxState::Signed
+            } else {
+                PendingTxState::Draft
+            },
+            psbt: stored.psbt.clone(),
+            inputs: d.inputs().into_iter().map(OutPointRef::from).collect(),
+            warnings: stored.warnings.clone(),
+            updated_at: stored.updated_at as u32,
+            error: Some(d.error.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaderStored {
+    hash: [u8; 32],
+    height: u32,
+}
+
+impl From<&BlockHeader> for BlockHeaderStored {
+    fn from(h: &BlockHeader) -> Self {
+        BlockHeaderStored {
+            hash: h.hash.to_byte_array(),
+            height: h.height,
+        }
+    }
+}
+
+impl From<BlockHeaderStored> for BlockHeader {
+    fn from(dto: BlockHeaderStored) -> Self {
+        BlockHeader {
+            hash: BlockHash::from_byte_array(dto.hash),
+            height: dto.height,
         }
     }
 }
@@ -97,6 +594,12 @@ impl From<AccountStored> for Account {
 pub struct WalletStored {
     active_account: AccountIndex,
     accounts: Vec<AccountStored>,
+    #[serde(default)]
+    descriptor_accounts: Vec<DescriptorAccountStored>,
+    #[serde(default)]
+    drafts: Vec<TxDraftStored>,
+    #[serde(default)]
+    headers: Vec<BlockHeaderStored>,
 }
 
 impl From<&BitcoinWallet> for WalletStored {
@@ -104,20 +607,68 @@ impl From<&BitcoinWallet> for WalletStored {
         WalletStored {
             active_account: w.active_account,
             accounts: w.accounts.iter().map(AccountStored::from).collect(),
+            descriptor_accounts: w
+                .descriptor_accounts
+                .iter()
+                .map(DescriptorAccountStored::from)
+                .collect(),
+            drafts: {
+                let mut drafts: Vec<_> = w
+                    .drafts
+                    .iter()
+                    .map(TxDraftStored::from)
+                    .chain(w.unreadable_drafts.iter().map(|d| d.stored.clone()))
+                    .collect();
+                drafts.sort_by_key(|d| d.id);
+                drafts
+            },
+            headers: w
+                .tip
+                .headers()
+                .iter()
+                .map(BlockHeaderStored::from)
+                .collect(),
         }
     }
 }
 
 impl BitcoinWallet {
-    pub fn from_dto(dto: WalletStored, config: Config, secret: Secretik) -> Self {
+    pub fn from_dto(dto: WalletStored, config: Config, secret: Secretik) -> Result<Self, String> {
         let server = select_btc_server(&config);
-        BitcoinWallet {
+        let quorum = Quorum::new(&config);
+        let mut wallet = BitcoinWallet {
             secret,
-            accounts: dto.accounts.into_iter().map(Account::from).collect(),
+            accounts: dto
+                .accounts
+                .into_iter()
+                .map(Account::try_from)
+                .collect::<Result<_, _>>()?,
+            descriptor_accounts: dto
+                .descriptor_accounts
+                .into_iter()
+                .map(|stored| {
+                    let name = stored.name.clone();
+                    DescriptorAccount::try_from(stored)
+                        .map_err(|e| format!("invalid descriptor account {name}: {e}"))
+                })
+                .collect::<Result<_, _>>()?,
             active_account: dto.active_account,
             server,
+            quorum,
             config,
             pending_tx: None,
+            drafts: Vec::new(),
+            unreadable_drafts: Vec::new(),
+            tip: TipTracker::new(dto.headers.into_iter().map(Into::into).collect()),
+        };
+        // Re-apply the reservations of saved drafts.
+        for stored in dto.drafts {
+            if let Err(error) = TxDraft::try_from(stored.clone()).and_then(|d| wallet.put_draft(d))
+            {
+                tracing::error!("draft {} can't be loaded: {error}", stored.name);
+                wallet.keep_unreadable_draft(UnreadableDraft { stored, error });
+            }
         }
+        Ok(wallet)
     }
 }
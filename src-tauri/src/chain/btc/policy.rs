@@ -0,0 +1,350 @@
+use std::fmt;
+
+use bitcoin::{Script, Transaction};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::chain::btc::{
+    config::{BitcoinConfig, FeeGuardConfig},
+    providers::core_rpc::CoreRpcClient,
+    tx_builder::{BuildTxResult, estimate_taproot_vbytes},
+};
+
+/// Largest OP_RETURN output Bitcoin Core relays by default (`-datacarriersize`
+/// of 80 bytes plus the opcode and push overhead).
+const MAX_OP_RETURN_SCRIPT_LEN: usize = 83;
+
+/// A pre-broadcast policy violation. The transaction is held back until the
+/// user explicitly overrides the warnings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicyWarning {
+    AbsoluteFeeTooHigh {
+        fee_sat: u32,
+        max_fee_sat: u32,
+    },
+    FeeRateAboveEstimate {
+        fee_rate_sat_vb: f64,
+        estimate_sat_vb: f64,
+        max_multiplier: f64,
+    },
+    FeeShareTooHigh {
+        fee_sat: u32,
+        amount_sat: String,
+        max_percent: f64,
+    },
+    NonStandardOutput {
+        vout: u32,
+    },
+    DustOutput {
+        vout: u32,
+        value_sat: String,
+        dust_limit_sat: String,
+    },
+    MempoolRejected {
+        reason: String,
+    },
+}
+
+impl fmt::Display for PolicyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyWarning::AbsoluteFeeTooHigh {
+                fee_sat,
+                max_fee_sat,
+            } => write!(
+                f,
+                "fee of {fee_sat} sat exceeds the {max_fee_sat} sat limit"
+            ),
+            PolicyWarning::FeeRateAboveEstimate {
+                fee_rate_sat_vb,
+                estimate_sat_vb,
+                max_multiplier,
+            } => write!(
+                f,
+                "fee rate {fee_rate_sat_vb:.1} sat/vB is more than {max_multiplier}x the current estimate of {estimate_sat_vb:.1} sat/vB"
+            ),
+            PolicyWarning::FeeShareTooHigh {
+                fee_sat,
+                amount_sat,
+                max_percent,
+            } => write!(
+                f,
+                "fee of {fee_sat} sat is more than {max_percent}% of the {amount_sat} sat being sent"
+            ),
+            PolicyWarning::NonStandardOutput { vout } => {
+                write!(f, "output {vout} has a non-standard script")
+            }
+            PolicyWarning::DustOutput {
+                vout,
+                value_sat,
+                dust_limit_sat,
+            } => write!(
+                f,
+                "output {vout} of {value_sat} sat is below the {dust_limit_sat} sat dust limit"
+            ),
+            PolicyWarning::MempoolRejected { reason } => {
+                write!(f, "rejected by mempool: {reason}")
+            }
+        }
+    }
+}
+
+/// Run the static fee and output checks on a freshly built transaction.
+///
+/// `estimate_sat_vb` is the current next-block fee estimate; when it couldn't
+/// be fetched the relative fee rate check is skipped.
+pub fn check_tx(
+    guard: &FeeGuardConfig,
+    built: &BuildTxResult,
+    estimate_sat_vb: Option<f64>,
+) -> Vec<PolicyWarning> {
+    let tx = &built.psbt.unsigned_tx;
+    let vsize = estimate_taproot_vbytes(tx.input.len(), tx.output.len());
+    check_unsigned_tx(
+        guard,
+        tx,
+        built.fee,
+        vsize,
+        built.send_value_sat,
+        estimate_sat_vb,
+    )
+}
+
+/// `check_tx` for spends built outside `tx_builder`, e.g. from a descriptor
+/// account, whose size the caller estimates. A `send_value_sat` of 0 weighs
+/// the fee against every output.
+pub fn check_unsigned_tx(
+    guard: &FeeGuardConfig,
+    tx: &Transaction,
+    fee_sat: u32,
+    vsize: u64,
+    send_value_sat: u64,
+    estimate_sat_vb: Option<f64>,
+) -> Vec<PolicyWarning> {
+    let mut warnings = Vec::new();
+
+    if fee_sat > guard.max_fee_sat {
+        warnings.push(PolicyWarning::AbsoluteFeeTooHigh {
+            fee_sat,
+            max_fee_sat: guard.max_fee_sat,
+        });
+    }
+
+    let fee_rate_sat_vb = fee_sat as f64 / vsize as f64;
+    if let Some(estimate_sat_vb) = estimate_sat_vb
+        && fee_rate_sat_vb > estimate_sat_vb * guard.max_fee_rate_multiplier
+    {
+        warnings.push(PolicyWarning::FeeRateAboveEstimate {
+            fee_rate_sat_vb,
+            estimate_sat_vb,
+            max_multiplier: guard.max_fee_rate_multiplier,
+        });
+    }
+
+    // A CPFP child pays no one, so weigh its fee against the value it moves.
+    let amount_sat = if send_value_sat > 0 {
+        send_value_sat
+    } else {
+        tx.output.iter().map(|o| o.value.to_sat()).sum()
+    };
+    if amount_sat > 0 && fee_sat as f64 > amount_sat as f64 * guard.max_fee_percent / 100.0 {
+        warnings.push(PolicyWarning::FeeShareTooHigh {
+            fee_sat,
+            amount_sat: amount_sat.to_string(),
+            max_percent: guard.max_fee_percent,
+        });
+    }
+
+    for (vout, output) in tx.output.iter().enumerate() {
+        let vout = vout as u32;
+        let script = output.script_pubkey.as_script();
+        if !is_standard_output(script) {
+            warnings.push(PolicyWarning::NonStandardOutput { vout });
+            continue;
+        }
+        if script.is_op_return() {
+            continue;
+        }
+        let dust_limit = script.minimal_non_dust();
+        if output.value < dust_limit {
+            warnings.push(PolicyWarning::DustOutput {
+                vout,
+                value_sat: output.value.to_sat().to_string(),
+                dust_limit_sat: dust_limit.to_sat().to_string(),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Ask Bitcoin Core whether the signed transaction would be accepted to its
+/// mempool. No-op when no Core RPC is configured; RPC failures are logged and
+/// don't block the send since the check is advisory.
+pub async fn check_mempool_accept(config: &BitcoinConfig, tx: &Transaction) -> Vec<PolicyWarning> {
+    let Some(url) = config.core_rpc_url.as_deref().filter(|u| !u.is_empty()) else {
+        return Vec::new();
+    };
+    match CoreRpcClient::new(url).test_mempool_accept(tx).await {
+        Ok(result) if result.allowed => Vec::new(),
+        Ok(result) => vec![PolicyWarning::MempoolRejected {
+            reason: result
+                .reject_reason
+                .unwrap_or_else(|| "unknown reason".to_string()),
+        }],
+        Err(e) => {
+            tracing::warn!("testmempoolaccept unavailable: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Fail with a readable summary unless there is nothing to report or the user
+/// chose to override.
+pub fn require_override(warnings: &[PolicyWarning], override_policy: bool) -> Result<(), String> {
+    if warnings.is_empty() || override_policy {
+        return Ok(());
+    }
+    let reasons = warnings
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(format!(
+        "transaction blocked by fee policy ({reasons}); override to send anyway"
+    ))
+}
+
+fn is_standard_output(script: &Script) -> bool {
+    if script.is_op_return() {
+        return script.len() <= MAX_OP_RETURN_SCRIPT_LEN;
+    }
+    script.is_p2pkh()
+        || script.is_p2sh()
+        || script.is_witness_program()
+        || script.is_p2pk()
+        || script.is_multisig()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        Amount, OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Witness, absolute::LockTime,
+        psbt::Psbt, script::PushBytesBuf, transaction::Version,
+    };
+
+    use super::*;
+    use crate::chain::btc::key_derivation::{
+        Change, KeyDerivationPath, LabeledKeyDerivationPath, Proposal,
+    };
+
+    fn p2tr_script() -> ScriptBuf {
+        let mut bytes = vec![0x51, 0x20];
+        bytes.extend([7u8; 32]);
+        ScriptBuf::from_bytes(bytes)
+    }
+
+    fn built(outputs: Vec<TxOut>, fee: u32, send_value_sat: u64) -> BuildTxResult {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: outputs,
+        };
+        BuildTxResult {
+            psbt: Psbt::from_unsigned_tx(tx).unwrap(),
+            change_key_path: LabeledKeyDerivationPath {
+                label: "Change".to_string(),
+                path: KeyDerivationPath::new(
+                    Proposal::Taproot,
+                    bitcoin::Network::Regtest,
+                    0,
+                    Change::Internal,
+                    1,
+                ),
+            },
+            fee,
+            recipient: None,
+            send_value_sat,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn output(value: u64, script_pubkey: ScriptBuf) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value),
+            script_pubkey,
+        }
+    }
+
+    #[test]
+    fn reasonable_tx_passes() {
+        let tx = built(vec![output(100_000, p2tr_script())], 1_000, 100_000);
+        assert!(check_tx(&FeeGuardConfig::default(), &tx, Some(10.0)).is_empty());
+    }
+
+    #[test]
+    fn flags_fee_limits() {
+        let guard = FeeGuardConfig::default();
+        let tx = built(vec![output(1_000_000, p2tr_script())], 600_000, 1_000_000);
+        let warnings = check_tx(&guard, &tx, Some(5.0));
+        assert!(matches!(
+            warnings[0],
+            PolicyWarning::AbsoluteFeeTooHigh {
+                fee_sat: 600_000,
+                ..
+            }
+        ));
+        assert!(matches!(
+            warnings[1],
+            PolicyWarning::FeeRateAboveEstimate { .. }
+        ));
+        assert!(matches!(warnings[2], PolicyWarning::FeeShareTooHigh { .. }));
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn skips_relative_check_without_estimate() {
+        let tx = built(vec![output(10_000_000, p2tr_script())], 50_000, 10_000_000);
+        assert!(check_tx(&FeeGuardConfig::default(), &tx, None).is_empty());
+    }
+
+    #[test]
+    fn flags_dust_and_non_standard_outputs() {
+        let mut data = PushBytesBuf::new();
+        data.extend_from_slice(&[0u8; 100]).unwrap();
+        let outputs = vec![
+            output(100, p2tr_script()),
+            output(0, ScriptBuf::new_op_return(&data)),
+            output(50_000, ScriptBuf::from_bytes(vec![0xac])),
+        ];
+        let tx = built(outputs, 500, 50_000);
+        let warnings = check_tx(&FeeGuardConfig::default(), &tx, None);
+        assert_eq!(
+            warnings,
+            vec![
+                PolicyWarning::DustOutput {
+                    vout: 0,
+                    value_sat: "100".to_string(),
+                    dust_limit_sat: "330".to_string(),
+                },
+                PolicyWarning::NonStandardOutput { vout: 1 },
+                PolicyWarning::NonStandardOutput { vout: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn override_releases_blocked_tx() {
+        let warnings = vec![PolicyWarning::NonStandardOutput { vout: 0 }];
+        assert!(require_override(&warnings, false).is_err());
+        assert!(require_override(&warnings, true).is_ok());
+        assert!(require_override(&[], false).is_ok());
+    }
+}
@@ -1,8 +1,12 @@
+use std::ops::RangeInclusive;
+
 use crate::{
     chain::btc::{
         account::AddressPathMap,
+        fee_estimator::FeeHistogramBin,
         providers::{electrum_adapter::ElectrumAdapter, esplora_adapter::EsploraAdapter},
-        utxo::Utxo,
+        utxo::{AddressUtxo, BlockHeader, Utxo},
+        zero_conf::MempoolTx,
     },
     config::Config,
 };
@@ -14,7 +18,10 @@ pub enum BtcNode {
 
 pub fn select_btc_server(config: &Config) -> BtcNode {
     if config.tor.enabled {
-        BtcNode::Electrum(ElectrumAdapter::new_tor(&config.tor.socks5_proxy))
+        BtcNode::Electrum(ElectrumAdapter::new_tor(
+            &config.tor.socks5_proxy,
+            config.btc.network(),
+        ))
     } else {
         BtcNode::Electrum(ElectrumAdapter::new(config.btc.clone()))
     }
@@ -31,6 +38,18 @@ impl BtcNode {
         }
     }
 
+    /// Unspent outputs of arbitrary addresses, e.g. ones derived from a
+    /// descriptor rather than the wallet's own keychain.
+    pub async fn list_unspent(
+        &self,
+        addresses: &[bitcoin::Address],
+    ) -> Result<Vec<AddressUtxo>, String> {
+        match self {
+            BtcNode::Electrum(e) => e.list_unspent(addresses).await,
+            BtcNode::Esplora(e) => e.list_unspent(addresses).await.map_err(|e| e.to_string()),
+        }
+    }
+
     /// Returns one boolean per input address indicating whether it has any
     /// on-chain history. Used by automated wallet discovery to detect used
     /// addresses without pulling full UTXO sets.
@@ -47,6 +66,25 @@ impl BtcNode {
         }
     }
 
+    /// `host:port` of the connected Electrum server.
+    pub fn peer(&self) -> Option<&str> {
+        match self {
+            BtcNode::Electrum(e) => e.peer(),
+            BtcNode::Esplora(_) => None,
+        }
+    }
+
+    /// History of every given address as `(txid, height)` pairs.
+    pub async fn history(
+        &self,
+        addresses: &[bitcoin::Address],
+    ) -> Result<Vec<Vec<(bitcoin::Txid, u32)>>, String> {
+        match self {
+            BtcNode::Electrum(e) => e.history(addresses).await,
+            BtcNode::Esplora(_) => Err("script history is not served by Esplora".to_string()),
+        }
+    }
+
     pub async fn broadcast_tx(&self, tx: &bitcoin::Transaction) -> Result<String, String> {
         match self {
             BtcNode::Electrum(e) => e.broadcast_tx(tx).await,
@@ -54,6 +92,41 @@ impl BtcNode {
         }
     }
 
+    pub async fn tip_height(&self) -> Result<u32, String> {
+        match self {
+            BtcNode::Electrum(e) => e.tip_height().await,
+            BtcNode::Esplora(e) => e.tip_height().await.map_err(|e| e.to_string()),
+        }
+    }
+
+    pub async fn block_headers(
+        &self,
+        heights: RangeInclusive<u32>,
+    ) -> Result<Vec<BlockHeader>, String> {
+        match self {
+            BtcNode::Electrum(e) => e.block_headers(heights).await,
+            BtcNode::Esplora(e) => e.block_headers(heights).await.map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Raw transactions in the order of `txids`.
+    pub async fn get_transactions(
+        &self,
+        txids: &[bitcoin::Txid],
+    ) -> Result<Vec<bitcoin::Transaction>, String> {
+        match self {
+            BtcNode::Electrum(e) => e.get_transactions(txids).await,
+            BtcNode::Esplora(e) => e.get_transactions(txids).await.map_err(|e| e.to_string()),
+        }
+    }
+
+    pub async fn mempool_tx(&self, txid: bitcoin::Txid) -> Result<MempoolTx, String> {
+        match self {
+            BtcNode::Electrum(e) => e.mempool_tx(txid).await,
+            BtcNode::Esplora(e) => e.mempool_tx(txid).await.map_err(|e| e.to_string()),
+        }
+    }
+
     pub async fn estimate_fee(&self, blocks: u32) -> Result<f64, String> {
         match self {
             BtcNode::Electrum(e) => e.estimate_fee(blocks).await,
@@ -63,4 +136,11 @@ impl BtcNode {
                 .map_err(|e| e.to_string()),
         }
     }
+
+    pub async fn get_fee_histogram(&self) -> Result<Vec<FeeHistogramBin>, String> {
+        match self {
+            BtcNode::Electrum(e) => e.get_fee_histogram().await,
+            BtcNode::Esplora(e) => e.get_fee_histogram().await.map_err(|e| e.to_string()),
+        }
+    }
 }
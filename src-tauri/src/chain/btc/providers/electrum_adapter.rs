@@ -1,15 +1,20 @@
-use std::str::FromStr;
+use std::{ops::RangeInclusive, str::FromStr};
 
 use bitcoin::{
-    TxOut, Txid,
+    OutPoint, Transaction, TxOut, Txid,
+    consensus::encode::deserialize_hex,
     hashes::{Hash, sha256},
 };
 use serde::Deserialize;
 use serde_json::json;
 
 use crate::chain::btc::{
-    account::AddressPathMap, config::BitcoinConfig, providers::electrum_client::ElectrumClient,
-    utxo::Utxo,
+    account::AddressPathMap,
+    config::BitcoinConfig,
+    fee_estimator::FeeHistogramBin,
+    providers::electrum_client::ElectrumClient,
+    utxo::{AddressUtxo, BlockHeader, Utxo},
+    zero_conf::MempoolTx,
 };
 
 pub struct ElectrumAdapter {
@@ -23,12 +28,24 @@ impl ElectrumAdapter {
         }
     }
 
-    pub fn new_tor(proxy: &str) -> Self {
+    pub fn new_tor(proxy: &str, network: bitcoin::Network) -> Self {
         Self {
-            client: ElectrumClient::new_tor(proxy),
+            client: ElectrumClient::new_tor(proxy, network),
         }
     }
 
+    /// Adapter bound to one server, used to cross-check the primary one.
+    pub fn pinned(host: &str, port: u16, proxy: Option<&str>) -> Self {
+        Self {
+            client: ElectrumClient::pinned(host, port, proxy),
+        }
+    }
+
+    /// `host:port` of the connected server.
+    pub fn peer(&self) -> Option<&str> {
+        self.client.peer()
+    }
+
     pub async fn estimate_fee(&self, blocks: u32) -> Result<f64, String> {
         let raw = self
             .client
@@ -39,6 +56,61 @@ impl ElectrumAdapter {
             .ok_or_else(|| "invalid fee response".to_string())
     }
 
+    /// Height of the server's best block.
+    pub async fn tip_height(&self) -> Result<u32, String> {
+        let raw = self
+            .client
+            .request("blockchain.headers.subscribe", vec![])
+            .await?;
+        raw.get("height")
+            .and_then(|h| h.as_u64())
+            .map(|h| h as u32)
+            .ok_or_else(|| "invalid headers response".to_string())
+    }
+
+    /// Headers of the best chain at `heights`, in the same order.
+    pub async fn block_headers(
+        &self,
+        heights: RangeInclusive<u32>,
+    ) -> Result<Vec<BlockHeader>, String> {
+        let heights: Vec<u32> = heights.collect();
+        let calls = heights
+            .iter()
+            .map(|h| ("blockchain.block.header", vec![json!(h)]))
+            .collect();
+        let results = self.client.batch(calls).await?;
+        heights
+            .into_iter()
+            .zip(results)
+            .map(|(height, raw)| {
+                let hex = raw
+                    .as_str()
+                    .ok_or_else(|| "block.header: expected hex string".to_string())?;
+                let header: bitcoin::block::Header =
+                    deserialize_hex(hex).map_err(|e| format!("parse block header: {e}"))?;
+                Ok(BlockHeader {
+                    hash: header.block_hash(),
+                    height,
+                })
+            })
+            .collect()
+    }
+
+    /// Mempool fee histogram as `[fee_rate, vsize]` pairs, ordered from the
+    /// highest fee rate to the lowest.
+    pub async fn get_fee_histogram(&self) -> Result<Vec<FeeHistogramBin>, String> {
+        let raw = self
+            .client
+            .request("mempool.get_fee_histogram", vec![])
+            .await?;
+        let pairs: Vec<(f64, u64)> =
+            serde_json::from_value(raw).map_err(|e| format!("parse fee histogram: {e}"))?;
+        Ok(pairs
+            .into_iter()
+            .map(|(fee_rate, vsize)| FeeHistogramBin { fee_rate, vsize })
+            .collect())
+    }
+
     /// Batch query the activity flag for the given addresses. Uses
     /// `blockchain.scripthash.get_history` (much smaller payload than
     /// `listunspent` for empty addresses) and returns one boolean per input
@@ -70,8 +142,63 @@ impl ElectrumAdapter {
             .collect()
     }
 
+    /// History of every given address as `(txid, height)` pairs, height 0
+    /// for transactions still in the mempool.
+    pub async fn history(
+        &self,
+        addresses: &[bitcoin::Address],
+    ) -> Result<Vec<Vec<(Txid, u32)>>, String> {
+        let calls = addresses
+            .iter()
+            .map(|a| {
+                (
+                    "blockchain.scripthash.get_history",
+                    vec![json!(scripthash(a))],
+                )
+            })
+            .collect();
+        self.client
+            .batch(calls)
+            .await?
+            .into_iter()
+            .map(|raw| {
+                let entries: Vec<RawHistoryEntry> =
+                    serde_json::from_value(raw).map_err(|e| format!("parse history: {e}"))?;
+                entries
+                    .into_iter()
+                    .map(|e| {
+                        let txid = Txid::from_str(&e.tx_hash).map_err(|e| format!("txid: {e}"))?;
+                        Ok((txid, e.height.max(0) as u32))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     pub async fn get_utxos(&self, address_path_map: AddressPathMap) -> Result<Vec<Utxo>, String> {
-        let addresses: Vec<_> = address_path_map.keys().collect();
+        let addresses: Vec<_> = address_path_map.keys().cloned().collect();
+        let unspent = self.list_unspent(&addresses).await?;
+        Ok(unspent
+            .into_iter()
+            .map(|u| Utxo {
+                tx_id: u.outpoint.txid,
+                vout: u.outpoint.vout,
+                output: u.output,
+                derivation: address_path_map[&u.address].clone(),
+                height: u.height,
+                silent_payment: None,
+            })
+            .collect())
+    }
+
+    /// Unspent outputs of every given address, fetched in a single batch.
+    pub async fn list_unspent(
+        &self,
+        addresses: &[bitcoin::Address],
+    ) -> Result<Vec<AddressUtxo>, String> {
+        if addresses.is_empty() {
+            return Ok(vec![]);
+        }
         let calls: Vec<_> = addresses
             .iter()
             .map(|a| {
@@ -88,17 +215,18 @@ impl ElectrumAdapter {
         for (address, raw) in addresses.iter().zip(results) {
             let utxos: Vec<RawUtxo> =
                 serde_json::from_value(raw).map_err(|e| format!("parse utxos: {e}"))?;
-            let derivation = address_path_map[*address].clone();
             for u in utxos {
                 let txid = Txid::from_str(&u.tx_hash).map_err(|e| format!("txid: {e}"))?;
-                all.push(Utxo {
-                    tx_id: txid,
-                    vout: u.tx_pos,
+                all.push(AddressUtxo {
+                    address: address.clone(),
+                    outpoint: OutPoint {
+                        txid,
+                        vout: u.tx_pos,
+                    },
                     output: TxOut {
                         value: bitcoin::Amount::from_sat(u.value),
                         script_pubkey: address.script_pubkey(),
                     },
-                    derivation: derivation.clone(),
                     height: u.height,
                 });
             }
@@ -106,6 +234,94 @@ impl ElectrumAdapter {
         Ok(all)
     }
 
+    pub async fn get_transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>, String> {
+        let calls = txids
+            .iter()
+            .map(|txid| ("blockchain.transaction.get", vec![json!(txid.to_string())]))
+            .collect();
+        self.client
+            .batch(calls)
+            .await?
+            .into_iter()
+            .map(|raw| {
+                let hex = raw
+                    .as_str()
+                    .ok_or_else(|| "transaction.get: expected hex string".to_string())?;
+                deserialize_hex(hex).map_err(|e| format!("parse transaction: {e}"))
+            })
+            .collect()
+    }
+
+    /// Look up an unconfirmed transaction with its parents. Electrum has no
+    /// outspend lookup, so conflicts are found among the mempool history of
+    /// each spent output's script.
+    pub async fn mempool_tx(&self, txid: Txid) -> Result<MempoolTx, String> {
+        let tx = self
+            .get_transactions(&[txid])
+            .await?
+            .pop()
+            .ok_or("transaction not found")?;
+        let parent_ids: Vec<Txid> = tx.input.iter().map(|i| i.previous_output.txid).collect();
+        let parents = self.get_transactions(&parent_ids).await?;
+        let prevouts: Vec<TxOut> = tx
+            .input
+            .iter()
+            .zip(&parents)
+            .map(|(input, parent)| {
+                parent
+                    .output
+                    .get(input.previous_output.vout as usize)
+                    .cloned()
+                    .ok_or_else(|| format!("missing prevout {}", input.previous_output))
+            })
+            .collect::<Result<_, String>>()?;
+
+        let calls = prevouts
+            .iter()
+            .map(|o| {
+                (
+                    "blockchain.scripthash.get_mempool",
+                    vec![json!(script_hash(&o.script_pubkey))],
+                )
+            })
+            .collect();
+        let mempools = self.client.batch(calls).await?;
+
+        let mut confirmed = Vec::with_capacity(prevouts.len());
+        let mut candidates = Vec::new();
+        for (input, raw) in tx.input.iter().zip(mempools) {
+            let entries: Vec<RawHistoryEntry> =
+                serde_json::from_value(raw).map_err(|e| format!("parse mempool: {e}"))?;
+            let parent = input.previous_output.txid.to_string();
+            confirmed.push(!entries.iter().any(|e| e.tx_hash == parent));
+            for entry in entries {
+                let other = Txid::from_str(&entry.tx_hash).map_err(|e| format!("txid: {e}"))?;
+                if other != txid && entry.tx_hash != parent && !candidates.contains(&other) {
+                    candidates.push(other);
+                }
+            }
+        }
+        let conflicts = self
+            .get_transactions(&candidates)
+            .await?
+            .into_iter()
+            .filter(|other| {
+                other.input.iter().any(|i| {
+                    tx.input
+                        .iter()
+                        .any(|o| o.previous_output == i.previous_output)
+                })
+            })
+            .map(|other| other.compute_txid())
+            .collect();
+
+        Ok(MempoolTx {
+            prevouts: prevouts.into_iter().zip(confirmed).collect(),
+            tx,
+            conflicts,
+        })
+    }
+
     pub async fn broadcast_tx(&self, tx: &bitcoin::Transaction) -> Result<String, String> {
         let hex = bitcoin::consensus::encode::serialize_hex(tx);
         let raw = self
@@ -118,6 +334,14 @@ impl ElectrumAdapter {
     }
 }
 
+#[derive(Deserialize)]
+struct RawHistoryEntry {
+    tx_hash: String,
+    /// 0 or -1 for mempool transactions.
+    #[serde(default)]
+    height: i64,
+}
+
 #[derive(Deserialize)]
 struct RawUtxo {
     tx_hash: String,
@@ -127,7 +351,10 @@ struct RawUtxo {
 }
 
 fn scripthash(address: &bitcoin::Address) -> String {
-    let script = address.script_pubkey();
+    script_hash(&address.script_pubkey())
+}
+
+fn script_hash(script: &bitcoin::Script) -> String {
     let mut hash = sha256::Hash::hash(script.as_bytes()).to_byte_array();
     hash.reverse();
     hash.iter().map(|b| format!("{b:02x}")).collect()
@@ -1,8 +1,9 @@
 use std::sync::{
-    Arc,
+    Arc, OnceLock,
     atomic::{AtomicU64, Ordering},
 };
 
+use bitcoin::Network;
 use rustls::{ClientConfig as RustlsConfig, RootCertStore, pki_types::ServerName};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -26,6 +27,41 @@ pub const SEEDS: &[(&str, u16)] = &[
     ("electrum.hodlister.co", 50002),
 ];
 
+pub const TESTNET3_SEEDS: &[(&str, u16)] = &[
+    ("electrum.blockstream.info", 60002),
+    ("testnet.aranguren.org", 51002),
+    ("mempool.space", 60002),
+];
+
+pub const TESTNET4_SEEDS: &[(&str, u16)] =
+    &[("mempool.space", 40002), ("blackie.c3-soft.com", 57010)];
+
+pub const SIGNET_SEEDS: &[(&str, u16)] = &[("mempool.space", 60602)];
+
+/// Plain TCP Electrum server of a local regtest setup.
+const REGTEST_SERVER: &str = "127.0.0.1:50001";
+
+/// Operator behind a server, taken as its registrable domain so that
+/// `electrum1.bluewallet.io` and `electrum2.bluewallet.io` count as one.
+pub fn operator(host: &str) -> &str {
+    let mut dots = host.rmatch_indices('.').map(|(i, _)| i);
+    match (dots.next(), dots.next()) {
+        (Some(_), Some(i)) => &host[i + 1..],
+        _ => host,
+    }
+}
+
+/// Public TLS servers for `network`, none for regtest.
+pub fn seeds(network: Network) -> &'static [(&'static str, u16)] {
+    match network {
+        Network::Bitcoin => SEEDS,
+        Network::Testnet => TESTNET3_SEEDS,
+        Network::Testnet4 => TESTNET4_SEEDS,
+        Network::Signet => SIGNET_SEEDS,
+        Network::Regtest => &[],
+    }
+}
+
 // ── Transport
 // ──────────────────────────────────────────────────────────────────
 
@@ -72,13 +108,20 @@ impl Conn {
 
 enum Mode {
     Direct(BitcoinConfig),
-    Tor(String),
+    Tor(String, Network),
+    /// A single TLS server, reached through the SOCKS5 proxy when set.
+    Pinned {
+        host: String,
+        port: u16,
+        proxy: Option<String>,
+    },
 }
 
 pub struct ElectrumClient {
     conn: OnceCell<Mutex<Conn>>,
     mode: Mode,
     request_id: AtomicU64,
+    peer: OnceLock<String>,
 }
 
 impl ElectrumClient {
@@ -87,24 +130,55 @@ impl ElectrumClient {
             conn: OnceCell::new(),
             mode: Mode::Direct(config),
             request_id: AtomicU64::new(1),
+            peer: OnceLock::new(),
         }
     }
 
-    pub fn new_tor(proxy: &str) -> Self {
+    pub fn new_tor(proxy: &str, network: Network) -> Self {
         Self {
             conn: OnceCell::new(),
-            mode: Mode::Tor(proxy.trim_start_matches("socks5://").to_string()),
+            mode: Mode::Tor(proxy.trim_start_matches("socks5://").to_string(), network),
             request_id: AtomicU64::new(1),
+            peer: OnceLock::new(),
         }
     }
 
+    pub fn pinned(host: &str, port: u16, proxy: Option<&str>) -> Self {
+        Self {
+            conn: OnceCell::new(),
+            mode: Mode::Pinned {
+                host: host.to_string(),
+                port,
+                proxy: proxy.map(|p| p.trim_start_matches("socks5://").to_string()),
+            },
+            request_id: AtomicU64::new(1),
+            peer: OnceLock::new(),
+        }
+    }
+
+    /// `host:port` of the server, once connected.
+    pub fn peer(&self) -> Option<&str> {
+        self.peer.get().map(String::as_str)
+    }
+
     async fn get_conn(&self) -> Result<&Mutex<Conn>, String> {
         self.conn
             .get_or_try_init(|| async {
-                let conn = match &self.mode {
+                let (conn, peer) = match &self.mode {
                     Mode::Direct(cfg) => connect_direct(cfg).await?,
-                    Mode::Tor(proxy) => connect_tor(proxy).await?,
+                    Mode::Tor(proxy, network) => connect_tor(proxy, *network).await?,
+                    Mode::Pinned { host, port, proxy } => {
+                        let connector = TlsConnector::from(Arc::new(build_tls_config()));
+                        let conn = match proxy {
+                            Some(proxy) => {
+                                socks_tls_connect(&connector, proxy, host, *port).await?
+                            }
+                            None => tls_connect(&connector, host, *port).await?,
+                        };
+                        (conn, format!("{host}:{port}"))
+                    }
                 };
+                let _ = self.peer.set(peer);
                 Ok(Mutex::new(conn))
             })
             .await
@@ -148,26 +222,28 @@ impl ElectrumClient {
 // ── Connection factories
 // ───────────────────────────────────────────────────────
 
-async fn connect_direct(config: &BitcoinConfig) -> Result<Conn, String> {
-    if config.regtest {
-        let stream = TcpStream::connect("127.0.0.1:50001")
+async fn connect_direct(config: &BitcoinConfig) -> Result<(Conn, String), String> {
+    if config.network() == Network::Regtest {
+        let server = config.electrum_server.as_deref().unwrap_or(REGTEST_SERVER);
+        let stream = TcpStream::connect(server)
             .await
             .map_err(|e| format!("regtest connect: {e}"))?;
-        return Ok(Conn::from_stream(stream));
+        return Ok((Conn::from_stream(stream), server.to_string()));
     }
 
     let connector = TlsConnector::from(Arc::new(build_tls_config()));
 
     if let Some(ref server) = config.electrum_server {
         let (host, port) = parse_host_port(server)?;
-        return tls_connect(&connector, host, port).await;
+        let conn = tls_connect(&connector, host, port).await?;
+        return Ok((conn, server.clone()));
     }
 
-    for (host, port) in SEEDS {
+    for (host, port) in seeds(config.network()) {
         match tls_connect(&connector, host, *port).await {
             Ok(conn) => {
                 tracing::info!("Electrum: connected to {host}:{port}");
-                return Ok(conn);
+                return Ok((conn, format!("{host}:{port}")));
             }
             Err(e) => tracing::warn!("Electrum: {host}:{port} failed: {e}"),
         }
@@ -175,13 +251,13 @@ async fn connect_direct(config: &BitcoinConfig) -> Result<Conn, String> {
     Err("failed to connect to any Electrum server".to_string())
 }
 
-async fn connect_tor(proxy: &str) -> Result<Conn, String> {
+async fn connect_tor(proxy: &str, network: Network) -> Result<(Conn, String), String> {
     let connector = TlsConnector::from(Arc::new(build_tls_config()));
-    for (host, port) in SEEDS {
+    for (host, port) in seeds(network) {
         match socks_tls_connect(&connector, proxy, host, *port).await {
             Ok(conn) => {
                 tracing::info!("Electrum over Tor: connected to {host}:{port}");
-                return Ok(conn);
+                return Ok((conn, format!("{host}:{port}")));
             }
             Err(e) => tracing::warn!("Electrum over Tor: {host}:{port} failed: {e}"),
         }
@@ -1,10 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::RangeInclusive};
 
-use bitcoin::{Address, TxOut};
+use bitcoin::{Address, Network, OutPoint, Transaction, TxOut, Txid};
 use esplora_client::{AsyncClient, Builder, Error, Utxo, r#async::DefaultSleeper};
 use futures::future::join_all;
 
-use crate::chain::btc::{account::AddressPathMap, utxo::Utxo as WalletUtxo};
+use crate::chain::btc::{
+    account::AddressPathMap,
+    fee_estimator::FeeHistogramBin,
+    utxo::{AddressUtxo, BlockHeader, Utxo as WalletUtxo},
+    zero_conf::MempoolTx,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum EsploraProvider {
@@ -22,6 +27,26 @@ impl EsploraProvider {
         }
     }
 
+    /// REST endpoint on `network`, if the provider serves it.
+    pub const fn url(self, network: Network) -> Option<&'static str> {
+        match (self, network) {
+            (_, Network::Bitcoin) => Some(self.main_net()),
+            (EsploraProvider::MempoolSpace, Network::Testnet) => {
+                Some("https://mempool.space/testnet/api")
+            }
+            (EsploraProvider::MempoolSpace, Network::Testnet4) => {
+                Some("https://mempool.space/testnet4/api")
+            }
+            (EsploraProvider::MempoolSpace, Network::Signet) => {
+                Some("https://mempool.space/signet/api")
+            }
+            (EsploraProvider::BlockstreamInfo, Network::Testnet) => {
+                Some("https://blockstream.info/testnet/api")
+            }
+            _ => None,
+        }
+    }
+
     pub const fn onion(self) -> &'static str {
         match self {
             EsploraProvider::MempoolSpace => {
@@ -66,6 +91,20 @@ impl EsploraAdapter {
         self.client.get_fee_estimates().await
     }
 
+    /// The mempool's `(fee rate, vsize)` bins, from the highest fee rate to
+    /// the lowest, as `/mempool` reports them.
+    pub async fn get_fee_histogram(&self) -> Result<Vec<FeeHistogramBin>, Error> {
+        let stats = self.client.get_mempool_stats().await?;
+        Ok(stats
+            .fee_histogram
+            .into_iter()
+            .map(|(fee_rate, vsize)| FeeHistogramBin {
+                fee_rate,
+                vsize: vsize as u64,
+            })
+            .collect())
+    }
+
     /// Returns one boolean per address indicating whether the address has any
     /// on-chain history (confirmed or in-mempool). Esplora has no native batch
     /// endpoint, so the lightweight `/address/:addr` stats requests run
@@ -130,6 +169,7 @@ impl EsploraAdapter {
                         },
                         derivation,
                         height: utxo.status.block_height.unwrap_or(0),
+                        silent_payment: None,
                     })
                 })
             })
@@ -138,6 +178,28 @@ impl EsploraAdapter {
         Ok(result)
     }
 
+    /// Unspent outputs of every given address, flattened.
+    pub async fn list_unspent(&self, addresses: &[Address]) -> Result<Vec<AddressUtxo>, Error> {
+        let utxo_map = self.get_utxos_by_addresses(addresses).await?;
+        Ok(utxo_map
+            .into_iter()
+            .flat_map(|(address, utxos)| {
+                utxos.into_iter().map(move |utxo| AddressUtxo {
+                    outpoint: OutPoint {
+                        txid: utxo.txid,
+                        vout: utxo.vout,
+                    },
+                    output: TxOut {
+                        value: utxo.value,
+                        script_pubkey: address.script_pubkey(),
+                    },
+                    address: address.clone(),
+                    height: utxo.status.block_height.unwrap_or(0),
+                })
+            })
+            .collect())
+    }
+
     /// Broadcast a signed transaction and return its txid.
     pub async fn broadcast_tx(&self, tx: &bitcoin::Transaction) -> Result<String, Error> {
         let txid = tx.compute_txid().to_string();
@@ -145,6 +207,80 @@ impl EsploraAdapter {
         Ok(txid)
     }
 
+    pub async fn get_transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>, Error> {
+        join_all(txids.iter().map(|txid| async move {
+            self.client
+                .get_tx(txid)
+                .await?
+                .ok_or(Error::TransactionNotFound(*txid))
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    /// Look up an unconfirmed transaction with its parents and the spenders
+    /// of its inputs.
+    pub async fn mempool_tx(&self, txid: Txid) -> Result<MempoolTx, Error> {
+        let tx = self
+            .client
+            .get_tx(&txid)
+            .await?
+            .ok_or(Error::TransactionNotFound(txid))?;
+        let mut prevouts = Vec::with_capacity(tx.input.len());
+        let mut conflicts = Vec::new();
+        for input in &tx.input {
+            let outpoint = input.previous_output;
+            let parent = self
+                .client
+                .get_tx(&outpoint.txid)
+                .await?
+                .ok_or(Error::TransactionNotFound(outpoint.txid))?;
+            let prevout = parent
+                .output
+                .get(outpoint.vout as usize)
+                .cloned()
+                .ok_or(Error::InvalidResponse)?;
+            let confirmed = self.client.get_tx_status(&outpoint.txid).await?.confirmed;
+            prevouts.push((prevout, confirmed));
+
+            let spender = self
+                .client
+                .get_output_status(&outpoint.txid, outpoint.vout as u64)
+                .await?
+                .and_then(|s| s.txid);
+            if let Some(spender) = spender
+                && spender != txid
+            {
+                conflicts.push(spender);
+            }
+        }
+        Ok(MempoolTx {
+            tx,
+            prevouts,
+            conflicts,
+        })
+    }
+
+    /// Height of the best block.
+    pub async fn tip_height(&self) -> Result<u32, Error> {
+        self.client.get_height().await
+    }
+
+    /// Headers of the best chain at `heights`, in the same order.
+    pub async fn block_headers(
+        &self,
+        heights: RangeInclusive<u32>,
+    ) -> Result<Vec<BlockHeader>, Error> {
+        join_all(heights.map(|height| async move {
+            let hash = self.client.get_block_hash(height).await?;
+            Ok::<_, Error>(BlockHeader { hash, height })
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+
     /// Estimate fee rate (sat/vB) for the given confirmation target.
     pub async fn estimate_fee_sat_vb(&self, target_blocks: u16) -> Result<f64, Error> {
         let estimates = self.get_fee_estimates().await?;
@@ -1,4 +1,6 @@
 pub mod btc_node;
+pub mod core_rpc;
 pub mod electrum_adapter;
 pub mod electrum_client;
 pub mod esplora_adapter;
+pub mod tweak_index;
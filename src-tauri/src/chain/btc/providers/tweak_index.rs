@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use bitcoin::{Amount, OutPoint, ScriptBuf, TxOut, Txid, secp256k1::PublicKey};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::chain::btc::silent_payments::{BlockScanData, TweakSource};
+
+/// HTTP client for a BlindBit-oracle compatible silent payments tweak index.
+///
+/// Endpoints used:
+/// - `GET /block-height` → `{ "block_height": u32 }`
+/// - `GET /tweaks/:height` → compressed `input_hash·A` per eligible tx, hex
+/// - `GET /utxos/:height` → taproot outputs created in the block
+pub struct TweakIndexClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockHeightResponse {
+    block_height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexedUtxo {
+    txid: String,
+    vout: u32,
+    value: u64,
+    scriptpubkey: String,
+    #[serde(default)]
+    spent: bool,
+}
+
+impl TweakIndexClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn new_tor(base_url: &str, proxy_url: &str) -> Result<Self, String> {
+        let proxy = reqwest::Proxy::all(proxy_url).map_err(|e| format!("invalid proxy: {e}"))?;
+        let client = reqwest::Client::builder()
+            .proxy(proxy)
+            .build()
+            .map_err(|e| format!("failed to build Tor HTTP client: {e}"))?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, String> {
+        let url = format!("{}{path}", self.base_url);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("tweak index {path}: {e}"))?
+            .error_for_status()
+            .map_err(|e| format!("tweak index {path}: {e}"))?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("tweak index {path}: {e}"))?;
+        serde_json::from_str(&body).map_err(|e| format!("tweak index {path}: {e}"))
+    }
+}
+
+impl TweakSource for TweakIndexClient {
+    async fn tip_height(&self) -> Result<u32, String> {
+        let res: BlockHeightResponse = self.get("/block-height").await?;
+        Ok(res.block_height)
+    }
+
+    async fn block_scan_data(&self, height: u32) -> Result<BlockScanData, String> {
+        let tweaks: Vec<String> = self.get(&format!("/tweaks/{height}")).await?;
+        let tweaks = tweaks
+            .iter()
+            .map(|hex| PublicKey::from_str(hex).map_err(|e| format!("invalid tweak: {e}")))
+            .collect::<Result<Vec<_>, String>>()?;
+        if tweaks.is_empty() {
+            return Ok(BlockScanData {
+                height,
+                ..Default::default()
+            });
+        }
+
+        let utxos: Vec<IndexedUtxo> = self.get(&format!("/utxos/{height}")).await?;
+        let outputs = utxos
+            .into_iter()
+            .filter(|u| !u.spent)
+            .map(|u| {
+                let txid = Txid::from_str(&u.txid).map_err(|e| format!("invalid txid: {e}"))?;
+                let script_pubkey = ScriptBuf::from_hex(&u.scriptpubkey)
+                    .map_err(|e| format!("invalid script: {e}"))?;
+                Ok((
+                    OutPoint { txid, vout: u.vout },
+                    TxOut {
+                        value: Amount::from_sat(u.value),
+                        script_pubkey,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(BlockScanData {
+            height,
+            tweaks,
+            outputs,
+        })
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use bitcoin::{Address, OutPoint, Script, ScriptBuf, Txid};
+
+use crate::{
+    chain::btc::{
+        account::UtxoSet,
+        providers::{
+            btc_node::BtcNode,
+            electrum_adapter::ElectrumAdapter,
+            electrum_client::{operator, seeds},
+        },
+        utxo::Utxo,
+    },
+    config::Config,
+};
+
+/// Servers of other operators the primary server is checked against.
+#[derive(Default)]
+pub struct Quorum {
+    /// How many of them have to answer on each check.
+    extra: usize,
+    /// One candidate per operator, in seed order, as `(host:port, adapter)`.
+    witnesses: Vec<(String, ElectrumAdapter)>,
+}
+
+/// What one server reported for the queried addresses, aligned with them.
+#[derive(Debug, Clone)]
+pub struct ServerView {
+    pub server: String,
+    /// Unspent outputs with their height, 0 when unconfirmed.
+    pub unspent: Vec<BTreeMap<OutPoint, u32>>,
+    pub history: Vec<Vec<(Txid, u32)>>,
+}
+
+/// One address on which a witness disagrees with the primary server.
+/// Only confirmed data is compared, mempools legitimately differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discrepancy {
+    pub server: String,
+    pub address: Address,
+    /// Coins the primary server reports and this one doesn't.
+    pub missing_utxos: Vec<OutPoint>,
+    /// Coins only this server reports.
+    pub extra_utxos: Vec<OutPoint>,
+    pub missing_txs: Vec<Txid>,
+    pub extra_txs: Vec<Txid>,
+}
+
+pub struct QuorumCheck {
+    /// Servers that answered, the primary one first.
+    pub servers: Vec<String>,
+    pub discrepancies: Vec<Discrepancy>,
+    /// Known coins the primary server dropped but a witness still reports.
+    pub retained: Vec<Utxo>,
+}
+
+impl Quorum {
+    pub fn new(config: &Config) -> Self {
+        if !config.btc.quorum.enabled {
+            return Self::default();
+        }
+        let proxy = config
+            .tor
+            .enabled
+            .then_some(config.tor.socks5_proxy.as_str());
+        let mut operators = Vec::new();
+        let witnesses = seeds(config.btc.network())
+            .iter()
+            .filter(|(host, _)| {
+                let op = operator(host).to_string();
+                let fresh = !operators.contains(&op);
+                if fresh {
+                    operators.push(op);
+                }
+                fresh
+            })
+            .map(|(host, port)| {
+                (
+                    format!("{host}:{port}"),
+                    ElectrumAdapter::pinned(host, *port, proxy),
+                )
+            })
+            .collect();
+        Self {
+            extra: config.btc.quorum.servers.saturating_sub(1) as usize,
+            witnesses,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.extra > 0 && !self.witnesses.is_empty()
+    }
+
+    /// Send the primary server's queries for `addresses` to witnesses run by
+    /// other operators and compare the answers with `received`.
+    pub async fn cross_check(
+        &self,
+        primary: &BtcNode,
+        addresses: &[Address],
+        received: &[Utxo],
+        known: &UtxoSet,
+    ) -> Result<QuorumCheck, String> {
+        let history = primary.history(addresses).await?;
+        let server = primary.peer().unwrap_or("primary").to_string();
+        let primary_operator = operator(host_of(&server)).to_string();
+        let reference = ServerView::new(
+            server,
+            addresses,
+            received
+                .iter()
+                .map(|u| (u.output.script_pubkey.as_script(), u.outpoint(), u.height)),
+            history,
+        )?;
+
+        let mut views = Vec::new();
+        for (server, adapter) in &self.witnesses {
+            if views.len() == self.extra {
+                break;
+            }
+            if operator(host_of(server)) == primary_operator {
+                continue;
+            }
+            match query(server, adapter, addresses).await {
+                Ok(view) => views.push(view),
+                Err(e) => tracing::warn!("quorum: {server} failed: {e}"),
+            }
+        }
+        if views.len() < self.extra {
+            tracing::warn!(
+                answered = views.len(),
+                wanted = self.extra,
+                "quorum: not enough witnesses answered"
+            );
+        }
+
+        Ok(QuorumCheck {
+            discrepancies: compare(addresses, &reference, &views),
+            retained: retained(known, &reference, &views),
+            servers: std::iter::once(reference.server)
+                .chain(views.into_iter().map(|v| v.server))
+                .collect(),
+        })
+    }
+}
+
+impl ServerView {
+    pub fn new<'a>(
+        server: String,
+        addresses: &[Address],
+        unspent: impl IntoIterator<Item = (&'a Script, OutPoint, u32)>,
+        history: Vec<Vec<(Txid, u32)>>,
+    ) -> Result<Self, String> {
+        if history.len() != addresses.len() {
+            return Err(format!(
+                "{server}: history does not match the queried addresses"
+            ));
+        }
+        let index: HashMap<ScriptBuf, usize> = addresses
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a.script_pubkey(), i))
+            .collect();
+        let mut per_address = vec![BTreeMap::new(); addresses.len()];
+        for (script, outpoint, height) in unspent {
+            if let Some(&i) = index.get(script) {
+                per_address[i].insert(outpoint, height);
+            }
+        }
+        Ok(Self {
+            server,
+            unspent: per_address,
+            history,
+        })
+    }
+
+    fn reports_unspent(&self, outpoint: &OutPoint) -> bool {
+        self.unspent.iter().any(|m| m.contains_key(outpoint))
+    }
+}
+
+async fn query(
+    server: &str,
+    adapter: &ElectrumAdapter,
+    addresses: &[Address],
+) -> Result<ServerView, String> {
+    let unspent = adapter.list_unspent(addresses).await?;
+    let history = adapter.history(addresses).await?;
+    ServerView::new(
+        server.to_string(),
+        addresses,
+        unspent
+            .iter()
+            .map(|u| (u.output.script_pubkey.as_script(), u.outpoint, u.height)),
+        history,
+    )
+}
+
+fn host_of(server: &str) -> &str {
+    server.rsplit_once(':').map_or(server, |(host, _)| host)
+}
+
+/// Per address differences between `reference` and each of `others`.
+pub fn compare(
+    addresses: &[Address],
+    reference: &ServerView,
+    others: &[ServerView],
+) -> Vec<Discrepancy> {
+    let confirmed_utxos = |m: &BTreeMap<OutPoint, u32>| -> BTreeSet<OutPoint> {
+        m.iter().filter(|(_, h)| **h > 0).map(|(o, _)| *o).collect()
+    };
+    let confirmed_txs = |h: &[(Txid, u32)]| -> BTreeSet<Txid> {
+        h.iter().filter(|(_, h)| *h > 0).map(|(t, _)| *t).collect()
+    };
+
+    let mut found = Vec::new();
+    for other in others {
+        for (i, address) in addresses.iter().enumerate() {
+            let (missing_utxos, extra_utxos) = diff(
+                confirmed_utxos(&reference.unspent[i]),
+                confirmed_utxos(&other.unspent[i]),
+            );
+            let (missing_txs, extra_txs) = diff(
+                confirmed_txs(&reference.history[i]),
+                confirmed_txs(&other.history[i]),
+            );
+            if missing_utxos.is_empty()
+                && extra_utxos.is_empty()
+                && missing_txs.is_empty()
+                && extra_txs.is_empty()
+            {
+                continue;
+            }
+            found.push(Discrepancy {
+                server: other.server.clone(),
+                address: address.clone(),
+                missing_utxos,
+                extra_utxos,
+                missing_txs,
+                extra_txs,
+            });
+        }
+    }
+    found
+}
+
+fn diff<T: Ord + Copy>(ours: BTreeSet<T>, theirs: BTreeSet<T>) -> (Vec<T>, Vec<T>) {
+    (
+        ours.difference(&theirs).copied().collect(),
+        theirs.difference(&ours).copied().collect(),
+    )
+}
+
+/// Known coins missing from `reference` that at least one of `others` still
+/// reports unspent. A single server's answer never deletes those, unless one
+/// of our own transactions spent them.
+pub fn retained(known: &UtxoSet, reference: &ServerView, others: &[ServerView]) -> Vec<Utxo> {
+    let reported: HashSet<OutPoint> = reference
+        .unspent
+        .iter()
+        .flat_map(|m| m.keys().copied())
+        .collect();
+    known
+        .entries
+        .values()
+        .filter(|u| !reported.contains(&u.outpoint()) && !known.spent.contains(&u.outpoint()))
+        .filter(|u| others.iter().any(|o| o.reports_unspent(&u.outpoint())))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Amount, Network, TxOut, hashes::Hash};
+
+    use super::*;
+    use crate::chain::btc::{
+        account::Account,
+        key_derivation::{Change, KeyDerivationPath, Proposal},
+    };
+
+    fn address(byte: u8) -> Address {
+        Address::p2wsh(&ScriptBuf::from(vec![byte]), Network::Regtest)
+    }
+
+    fn outpoint(byte: u8) -> OutPoint {
+        OutPoint {
+            txid: Txid::from_byte_array([byte; 32]),
+            vout: 0,
+        }
+    }
+
+    fn view(server: &str, addresses: &[Address], unspent: &[(usize, u8, u32)]) -> ServerView {
+        let scripts: Vec<ScriptBuf> = addresses.iter().map(|a| a.script_pubkey()).collect();
+        let history = addresses
+            .iter()
+            .enumerate()
+            .map(|(i, _)| {
+                unspent
+                    .iter()
+                    .filter(|(a, _, _)| *a == i)
+                    .map(|(_, b, h)| (Txid::from_byte_array([*b; 32]), *h))
+                    .collect()
+            })
+            .collect();
+        ServerView::new(
+            server.to_string(),
+            addresses,
+            unspent
+                .iter()
+                .map(|(a, b, h)| (scripts[*a].as_script(), outpoint(*b), *h)),
+            history,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn operator_is_the_registrable_domain() {
+        assert_eq!(operator("electrum1.bluewallet.io"), "bluewallet.io");
+        assert_eq!(operator("electrum2.bluewallet.io"), "bluewallet.io");
+        assert_eq!(operator("mempool.space"), "mempool.space");
+        assert_eq!(operator("localhost"), "localhost");
+    }
+
+    #[test]
+    fn confirmed_differences_are_reported_per_address() {
+        let addresses = [address(1), address(2)];
+        let primary = view("a:1", &addresses, &[(0, 1, 100), (1, 2, 101), (1, 3, 0)]);
+        let agreeing = view("b:1", &addresses, &[(0, 1, 100), (1, 2, 101)]);
+        assert!(compare(&addresses, &primary, &[agreeing]).is_empty());
+
+        let lying = view("c:1", &addresses, &[(0, 1, 100), (0, 4, 102)]);
+        let found = compare(&addresses, &primary, &[lying]);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].address, addresses[0]);
+        assert_eq!(found[0].extra_utxos, vec![outpoint(4)]);
+        assert_eq!(found[1].server, "c:1");
+        assert_eq!(found[1].missing_utxos, vec![outpoint(2)]);
+        assert_eq!(found[1].missing_txs, vec![outpoint(2).txid]);
+    }
+
+    #[test]
+    fn coins_a_witness_still_reports_are_retained() {
+        let addresses = [address(1)];
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let utxo = |byte: u8| Utxo {
+            tx_id: Txid::from_byte_array([byte; 32]),
+            vout: 0,
+            output: TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: addresses[0].script_pubkey(),
+            },
+            derivation: KeyDerivationPath::new(
+                Proposal::Taproot,
+                Network::Regtest,
+                0,
+                Change::External,
+                0,
+            ),
+            height: 100,
+            silent_payment: None,
+        };
+        account
+            .utxo_set
+            .replace_all(vec![utxo(1), utxo(2), utxo(3)]);
+
+        let primary = view("a:1", &addresses, &[(0, 1, 100)]);
+        let witness = view("b:1", &addresses, &[(0, 1, 100), (0, 2, 100)]);
+        let kept = retained(&account.utxo_set, &primary, &[witness.clone()]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].outpoint(), outpoint(2));
+        assert!(retained(&account.utxo_set, &primary, &[]).is_empty());
+
+        // A lagging witness can't bring back a coin we spent.
+        account.utxo_set.spend(&[outpoint(2)]);
+        account.utxo_set.entries.insert(outpoint(2), utxo(2));
+        assert!(retained(&account.utxo_set, &primary, &[witness]).is_empty());
+
+        let mut reported = vec![utxo(1), utxo(2)];
+        account.utxo_set.drop_spent(&mut reported);
+        assert_eq!(reported.len(), 1);
+        assert!(account.utxo_set.spent.contains(&outpoint(2)));
+        account.utxo_set.drop_spent(&mut vec![utxo(1)]);
+        assert!(account.utxo_set.spent.is_empty());
+    }
+}
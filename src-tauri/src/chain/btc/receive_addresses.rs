@@ -0,0 +1,178 @@
+use bitcoin::Network;
+
+use crate::chain::btc::{
+    account::Account,
+    key_derivation::{Change, KeyDerivationPath, Proposal},
+};
+
+/// BIP44 address gap limit. Wallets restoring from the seed stop scanning
+/// after this many consecutive unused receive addresses.
+pub const RECEIVE_GAP_LIMIT: u32 = 20;
+
+/// Hands out fresh taproot receive addresses so each payment lands on its own
+/// address. Works on the account's revealed external paths and their
+/// recorded on-chain usage.
+pub struct ReceiveAddressManager<'a> {
+    account: &'a Account,
+    network: Network,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NextReceive {
+    pub path: KeyDerivationPath,
+    /// The path isn't in the keychain yet and has to be revealed.
+    pub is_new: bool,
+    /// Distance from the last used receive address once this one is handed
+    /// out.
+    pub gap: u32,
+}
+
+impl<'a> ReceiveAddressManager<'a> {
+    pub fn new(account: &'a Account, network: Network) -> Self {
+        Self { account, network }
+    }
+
+    fn receive_paths(&self) -> impl Iterator<Item = &KeyDerivationPath> + '_ {
+        self.account
+            .keychain
+            .paths_by_change(&Change::External)
+            .map(|p| &p.path)
+            .filter(|p| p.purpose == Proposal::Taproot)
+    }
+
+    fn last_used(&self) -> Option<u32> {
+        self.receive_paths()
+            .filter(|p| self.account.keychain.is_used(p))
+            .map(|p| p.index)
+            .max()
+    }
+
+    fn gap_to(&self, index: u32) -> u32 {
+        match self.last_used() {
+            Some(last) => index.saturating_sub(last),
+            None => index + 1,
+        }
+    }
+
+    /// Lowest revealed receive address that hasn't been paid to yet.
+    pub fn current(&self) -> Option<KeyDerivationPath> {
+        self.receive_paths()
+            .filter(|p| !self.account.keychain.is_used(p))
+            .min_by_key(|p| p.index)
+            .cloned()
+    }
+
+    /// Next address to show the user. Reuses the lowest revealed unused
+    /// address unless `force_new` asks for one nobody has seen yet.
+    pub fn next(&self, force_new: bool) -> NextReceive {
+        if !force_new && let Some(path) = self.current() {
+            return NextReceive {
+                gap: self.gap_to(path.index),
+                path,
+                is_new: false,
+            };
+        }
+        let index = self
+            .receive_paths()
+            .map(|p| p.index)
+            .max()
+            .map_or(0, |i| i + 1);
+        NextReceive {
+            path: KeyDerivationPath::new(
+                Proposal::Taproot,
+                self.network,
+                self.account.index,
+                Change::External,
+                index,
+            ),
+            is_new: true,
+            gap: self.gap_to(index),
+        }
+    }
+}
+
+/// Refuse to reveal an address past the gap limit unless the user accepted
+/// that a seed restore might not find it.
+pub fn check_gap(next: &NextReceive, allow_beyond_gap: bool) -> Result<Option<String>, String> {
+    if !next.is_new || next.gap < RECEIVE_GAP_LIMIT {
+        return Ok(None);
+    }
+    let warning = format!(
+        "{} unused receive addresses in a row; wallets restoring from the seed stop looking after {RECEIVE_GAP_LIMIT}",
+        next.gap
+    );
+    if next.gap > RECEIVE_GAP_LIMIT && !allow_beyond_gap {
+        return Err(format!("{warning}. Confirm to reveal it anyway"));
+    }
+    Ok(Some(warning))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{OutPoint, Txid, hashes::Hash};
+
+    use super::*;
+
+    fn account_with(indexes: &[u32], used: &[u32]) -> Account {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let path =
+            |i| KeyDerivationPath::new(Proposal::Taproot, Network::Regtest, 0, Change::External, i);
+        for &i in indexes {
+            if !account.keychain.contains_path(path(i)) {
+                account.keychain.push(path(i).with_label(String::new()));
+            }
+        }
+        for &i in used {
+            account.keychain.record_funding(
+                &path(i),
+                OutPoint {
+                    txid: Txid::all_zeros(),
+                    vout: i,
+                },
+            );
+        }
+        account
+    }
+
+    #[test]
+    fn reuses_lowest_unused_address() {
+        let account = account_with(&[0, 1, 2], &[0]);
+        let next = ReceiveAddressManager::new(&account, Network::Regtest).next(false);
+        assert_eq!(next.path.index, 1);
+        assert!(!next.is_new);
+        assert_eq!(next.gap, 1);
+    }
+
+    #[test]
+    fn reveals_after_highest_when_all_used() {
+        let account = account_with(&[0, 1], &[0, 1]);
+        let manager = ReceiveAddressManager::new(&account, Network::Regtest);
+        assert_eq!(manager.current(), None);
+        let next = manager.next(false);
+        assert_eq!(next.path.index, 2);
+        assert!(next.is_new);
+    }
+
+    #[test]
+    fn force_new_skips_unused_addresses() {
+        let account = account_with(&[0, 1, 2], &[0]);
+        let next = ReceiveAddressManager::new(&account, Network::Regtest).next(true);
+        assert_eq!(next.path.index, 3);
+        assert_eq!(next.gap, 3);
+    }
+
+    #[test]
+    fn warns_then_blocks_at_gap_limit() {
+        let revealed: Vec<u32> = (0..20).collect();
+        let account = account_with(&revealed, &[0]);
+        let next = ReceiveAddressManager::new(&account, Network::Regtest).next(true);
+        assert_eq!(next.gap, 20);
+        assert!(check_gap(&next, false).unwrap().is_some());
+
+        let revealed: Vec<u32> = (0..21).collect();
+        let account = account_with(&revealed, &[0]);
+        let next = ReceiveAddressManager::new(&account, Network::Regtest).next(true);
+        assert!(check_gap(&next, false).is_err());
+        assert!(check_gap(&next, true).unwrap().is_some());
+    }
+}
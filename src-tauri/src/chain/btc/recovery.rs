@@ -0,0 +1,342 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    Network, ScriptBuf, TapNodeHash, XOnlyPublicKey,
+    bip32::{ChildNumber, DerivationPath, Xpriv, Xpub},
+    key::Secp256k1,
+    opcodes::all::{OP_CHECKSIGVERIFY, OP_CSV},
+    taproot::TaprootBuilder,
+};
+use miniscript::{DescriptorPublicKey, ToPublicKey};
+
+use crate::{
+    chain::btc::{
+        account::Account,
+        descriptor_account::normalize_cosigner_key,
+        key_derivation::{Change, Proposal, coin_type},
+        utxo::Utxo,
+    },
+    chain_trait::AccountIndex,
+};
+
+/// How close to maturity, in blocks, a recovery-locked coin has to be before
+/// a refresh picks it up. Roughly one week.
+pub const DEFAULT_REFRESH_WINDOW_BLOCKS: u32 = 1_008;
+
+/// Inheritance setup of an account: every taproot output stays spendable by
+/// the seed key through the key path, and additionally by a designated
+/// backup key through a script leaf once the coin has been left untouched for
+/// `delay_blocks`.
+///
+/// The leaf is `and_v(v:pk(backup),older(delay))`, so the backup holder can
+/// import [`RecoveryPolicy::descriptor`] as a descriptor account and sweep
+/// the coins with standard tooling. Coins received before the policy was set
+/// keep their BIP86 scripts until they are spent.
+#[derive(Debug, Clone)]
+pub struct RecoveryPolicy {
+    /// Ranged key expression of the backup key, e.g. `[fp/87'/0'/0']xpub/<0;1>/*`.
+    pub key: DescriptorPublicKey,
+    /// Relative timelock, in blocks, before the backup key can spend.
+    pub delay_blocks: u16,
+}
+
+impl RecoveryPolicy {
+    pub fn new(key: &str, delay_blocks: u16) -> Result<Self, String> {
+        if delay_blocks == 0 {
+            return Err("recovery delay must be at least one block".to_string());
+        }
+        let key = normalize_cosigner_key(key)?;
+        let key = DescriptorPublicKey::from_str(&key)
+            .map_err(|e| format!("invalid recovery key {key}: {e}"))?;
+        Ok(Self { key, delay_blocks })
+    }
+
+    /// Backup key guarding the output at `change/index`.
+    pub fn recovery_key(&self, change: Change, index: u32) -> Result<XOnlyPublicKey, String> {
+        let mut branches = self.key.clone().into_single_keys();
+        let i = (change as usize).min(branches.len() - 1);
+        let key = branches
+            .swap_remove(i)
+            .at_derivation_index(index)
+            .map_err(|e| format!("failed to derive recovery key: {e}"))?;
+        Ok(key.to_x_only_pubkey())
+    }
+
+    /// `<backup> OP_CHECKSIGVERIFY <delay> OP_CHECKSEQUENCEVERIFY`
+    pub fn leaf_script(&self, change: Change, index: u32) -> Result<ScriptBuf, String> {
+        Ok(ScriptBuf::builder()
+            .push_x_only_key(&self.recovery_key(change, index)?)
+            .push_opcode(OP_CHECKSIGVERIFY)
+            .push_int(self.delay_blocks as i64)
+            .push_opcode(OP_CSV)
+            .into_script())
+    }
+
+    /// Merkle root of the single-leaf script tree for `internal_key`.
+    pub fn merkle_root(
+        &self,
+        internal_key: XOnlyPublicKey,
+        change: Change,
+        index: u32,
+    ) -> Result<TapNodeHash, String> {
+        let secp = Secp256k1::verification_only();
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, self.leaf_script(change, index)?)
+            .map_err(|e| format!("invalid recovery leaf: {e}"))?
+            .finalize(&secp, internal_key)
+            .map_err(|_| "failed to build recovery script tree".to_string())?;
+        spend_info
+            .merkle_root()
+            .ok_or("recovery script tree has no root".to_string())
+    }
+
+    /// Descriptor of an account under this policy, for the backup key holder.
+    pub fn descriptor(&self, owner_key: &str) -> String {
+        format!(
+            "tr({owner_key},and_v(v:pk({}),older({})))",
+            self.key, self.delay_blocks
+        )
+    }
+}
+
+/// Key expression of the account's BIP86 key, `[fp/86'/coin'/account']xpub/<0;1>/*`,
+/// used as the internal key of the recovery descriptor.
+pub fn owner_key_expression(
+    xpriv: &Xpriv,
+    network: Network,
+    account: AccountIndex,
+) -> Result<String, String> {
+    let secp = Secp256k1::new();
+    let coin = coin_type(network);
+    let origin = [Proposal::Taproot as u32, coin, account]
+        .into_iter()
+        .map(|i| ChildNumber::from_hardened_idx(i).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, String>>()
+        .map(DerivationPath::from)?;
+    let derived = xpriv
+        .derive_priv(&secp, &origin)
+        .map_err(|e| format!("failed to derive account key: {e}"))?;
+    Ok(format!(
+        "[{}/{origin}]{}/<0;1>/*",
+        xpriv.fingerprint(&secp),
+        Xpub::from_priv(&secp, &derived)
+    ))
+}
+
+/// A confirmed recovery-locked coin and the blocks left before the backup
+/// key can spend it.
+pub struct ExpiringUtxo<'a> {
+    pub utxo: &'a Utxo,
+    pub blocks_left: u32,
+}
+
+/// Whether `utxo` sits on an output committing to the account's current
+/// recovery leaf.
+pub fn is_guarded(account: &Account, utxo: &Utxo, xpriv: &Xpriv) -> Result<bool, String> {
+    if account.recovery.is_none()
+        || utxo.derivation.purpose != Proposal::Taproot
+        || utxo.silent_payment.is_some()
+    {
+        return Ok(false);
+    }
+    let child = account.derive(&utxo.derivation, xpriv)?;
+    Ok(child.taproot_address.script_pubkey() == utxo.output.script_pubkey)
+}
+
+/// Coins locked to the account's recovery policy that mature within
+/// `window_blocks` of `tip_height`, soonest first.
+pub fn expiring_utxos<'a>(
+    account: &'a Account,
+    xpriv: &Xpriv,
+    tip_height: u32,
+    window_blocks: u32,
+) -> Result<Vec<ExpiringUtxo<'a>>, String> {
+    let Some(policy) = &account.recovery else {
+        return Ok(Vec::new());
+    };
+    let mut expiring = Vec::new();
+    for utxo in account.utxo_set.entries.values() {
+        if utxo.height == 0
+            || account.utxo_set.is_locked(&utxo.outpoint())
+            || !is_guarded(account, utxo, xpriv)?
+        {
+            continue;
+        }
+        let confirmations = tip_height.saturating_sub(utxo.height) + 1;
+        let blocks_left = (policy.delay_blocks as u32).saturating_sub(confirmations);
+        if blocks_left <= window_blocks {
+            expiring.push(ExpiringUtxo { utxo, blocks_left });
+        }
+    }
+    expiring.sort_by_key(|e| e.blocks_left);
+    Ok(expiring)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bitcoin::{Amount, OutPoint, Psbt, TxOut, Txid, hashes::Hash};
+
+    use super::*;
+    use crate::chain::btc::{
+        account::UtxoSelectionStrategy,
+        descriptor_account::{
+            self, BuildDescriptorPsbtParams, DescriptorAccount, DescriptorTemplate, DescriptorUtxo,
+            own_key_expression,
+        },
+        key_derivation::KeyDerivationPath,
+    };
+
+    const DELAY: u16 = 52_560;
+
+    fn xpriv(seed: u8) -> Xpriv {
+        Xpriv::new_master(Network::Regtest, &[seed; 32]).unwrap()
+    }
+
+    fn policy() -> RecoveryPolicy {
+        let backup = own_key_expression(
+            &xpriv(2),
+            DescriptorTemplate::TaprootMultiA,
+            Network::Regtest,
+            0,
+        )
+        .unwrap();
+        RecoveryPolicy::new(&backup, DELAY).unwrap()
+    }
+
+    fn path(change: Change, index: u32) -> KeyDerivationPath {
+        KeyDerivationPath::new(Proposal::Taproot, Network::Regtest, 0, change, index)
+    }
+
+    fn utxo(account: &Account, index: u32, height: u32) -> Utxo {
+        let derivation = path(Change::External, index);
+        let child = account.derive(&derivation, &xpriv(1)).unwrap();
+        Utxo {
+            tx_id: Txid::all_zeros(),
+            vout: index,
+            output: TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: child.taproot_address.script_pubkey(),
+            },
+            derivation,
+            height,
+            silent_payment: None,
+        }
+    }
+
+    fn heir_account() -> DescriptorAccount {
+        let owner = owner_key_expression(&xpriv(1), Network::Regtest, 0).unwrap();
+        let mut account =
+            DescriptorAccount::import(0, "inheritance".to_string(), &policy().descriptor(&owner))
+                .unwrap();
+        account.relative_timelock = Some(DELAY);
+        account
+    }
+
+    #[test]
+    fn rejects_zero_delay() {
+        let backup = own_key_expression(
+            &xpriv(2),
+            DescriptorTemplate::TaprootMultiA,
+            Network::Regtest,
+            0,
+        )
+        .unwrap();
+        assert!(RecoveryPolicy::new(&backup, 0).is_err());
+    }
+
+    #[test]
+    fn recovery_changes_only_taproot_address() {
+        let plain = path(Change::External, 3).derive(&xpriv(1)).unwrap();
+        let guarded = path(Change::External, 3)
+            .derive_with_recovery(&xpriv(1), Some(&policy()))
+            .unwrap();
+        assert!(plain.merkle_root.is_none());
+        assert!(guarded.merkle_root.is_some());
+        assert_ne!(plain.taproot_address, guarded.taproot_address);
+        assert_eq!(plain.segwit_address, guarded.segwit_address);
+    }
+
+    #[test]
+    fn derived_addresses_match_exported_descriptor() {
+        let heir = heir_account();
+        for change in [Change::External, Change::Internal] {
+            for index in [0, 1, 17] {
+                let owner = path(change, index)
+                    .derive_with_recovery(&xpriv(1), Some(&policy()))
+                    .unwrap();
+                assert_eq!(
+                    owner.taproot_address,
+                    heir.address(change, index, Network::Regtest).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn picks_guarded_coins_near_expiry() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let before_policy = utxo(&account, 0, 100);
+        account.recovery = Some(policy());
+        let old = utxo(&account, 1, 100);
+        let fresh = utxo(&account, 2, 50_000);
+        account
+            .utxo_set
+            .replace_all(vec![before_policy, old.clone(), fresh]);
+
+        let tip = DELAY as u32 + 50;
+        let expiring = expiring_utxos(&account, &xpriv(1), tip, 1_000).unwrap();
+        assert_eq!(expiring.len(), 1);
+        assert_eq!(expiring[0].utxo.outpoint(), old.outpoint());
+        assert_eq!(expiring[0].blocks_left, 49);
+    }
+
+    #[test]
+    fn spends_coins_from_before_the_policy() {
+        let mut account = Account::new(Network::Regtest, 0, "main".to_string());
+        let before_policy = utxo(&account, 0, 100);
+        account.recovery = Some(policy());
+        let child = account.derive_for_utxo(&before_policy, &xpriv(1)).unwrap();
+        assert!(child.merkle_root.is_none());
+        assert!(!is_guarded(&account, &before_policy, &xpriv(1)).unwrap());
+    }
+
+    #[test]
+    fn backup_key_spends_after_timelock() {
+        let mut heir = heir_account();
+        let script_pubkey = heir.derive(Change::External, 0).unwrap().script_pubkey();
+        let outpoint = OutPoint {
+            txid: Txid::all_zeros(),
+            vout: 1,
+        };
+        heir.utxos = HashMap::from([(
+            outpoint,
+            DescriptorUtxo {
+                outpoint,
+                output: TxOut {
+                    value: Amount::from_sat(80_000),
+                    script_pubkey,
+                },
+                change: Change::External,
+                index: 0,
+                height: 10,
+            },
+        )]);
+        assert!(heir.can_sign(xpriv(2).fingerprint(&Secp256k1::new())));
+
+        let mut pending = descriptor_account::build_psbt(&BuildDescriptorPsbtParams {
+            account: &heir,
+            recipient: heir.address(Change::External, 5, Network::Regtest).unwrap(),
+            send_value_sat: 80_000,
+            utxo_selection_method: UtxoSelectionStrategy::Auto(80_000),
+            fee_rate_sat_vb: 1.0,
+        })
+        .unwrap();
+        descriptor_account::sign_psbt(&mut pending.psbt, &xpriv(2)).unwrap();
+        let psbt = Psbt::from_str(&pending.psbt.to_string()).unwrap();
+
+        let tx = descriptor_account::finalize_psbt(psbt).unwrap();
+        assert_eq!(tx.input[0].sequence.to_consensus_u32(), DELAY as u32);
+    }
+}
@@ -0,0 +1,827 @@
+//! BIP352 Silent Payments.
+//!
+//! A silent payment address encodes two public keys, `B_scan` and `B_spend`.
+//! The sender combines the private keys of the inputs it spends with `B_scan`
+//! (ECDH) to derive a one-time taproot output key, so every payment lands on
+//! a fresh output while the recipient publishes a single static code.
+//!
+//! The recipient can't see those outputs without the per-transaction input
+//! data. A tweak index serves `input_hash·A` for every eligible transaction
+//! of a block, which reduces detection to one ECDH per transaction with the
+//! scan key.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use bitcoin::{
+    Address, Network, OutPoint, Psbt, ScriptBuf, TapSighashType, TxIn, TxOut,
+    bech32::{
+        Bech32m, Fe32, Hrp,
+        primitives::{
+            decode::CheckedHrpstring,
+            iter::{ByteIterExt, Fe32IterExt},
+        },
+    },
+    bip32::{DerivationPath, Xpriv},
+    hashes::{Hash, HashEngine, sha256},
+    key::{Keypair, Secp256k1, TweakedPublicKey, XOnlyPublicKey},
+    psbt::{self, raw::ProprietaryKey},
+    secp256k1::{Message, Parity, PublicKey, Scalar, SecretKey},
+    sighash::{Prevouts, SighashCache},
+    taproot,
+};
+
+use crate::{
+    chain::btc::{
+        key_derivation::{KeyDerivationPath, coin_type},
+        utxo::Utxo,
+    },
+    chain_trait::AccountIndex,
+};
+
+const MAINNET_HRP: Hrp = Hrp::parse_unchecked("sp");
+const TESTNET_HRP: Hrp = Hrp::parse_unchecked("tsp");
+
+/// Key-derivation purpose reserved by BIP352.
+const PURPOSE: u32 = 352;
+const SPEND_BRANCH: u32 = 0;
+const SCAN_BRANCH: u32 = 1;
+
+/// Proprietary PSBT field holding `t_k` of a silent payment input, there's
+/// no standard one yet.
+const PSBT_PREFIX: &[u8] = b"satellion";
+const PSBT_SUBTYPE_TWEAK: u8 = 0;
+
+/// Taproot activation height; no mainnet block below it can hold a silent
+/// payment, so scanning starts here.
+pub const MAINNET_SCAN_START_HEIGHT: u32 = 709_632;
+/// Blocks scanned between two saves of the scan progress.
+pub const SCAN_BATCH: u32 = 1_000;
+
+const INPUTS_TAG: &str = "BIP0352/Inputs";
+const SHARED_SECRET_TAG: &str = "BIP0352/SharedSecret";
+
+fn tagged_hash(tag: &str, chunks: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = sha256::Hash::hash(tag.as_bytes());
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag_hash.as_byte_array());
+    engine.input(tag_hash.as_byte_array());
+    for chunk in chunks {
+        engine.input(chunk);
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+fn to_scalar(bytes: [u8; 32]) -> Result<Scalar, String> {
+    Scalar::from_be_bytes(bytes).map_err(|_| "tagged hash is not a valid scalar".to_string())
+}
+
+/// `m/352'/coin'/account'/branch'/0`
+fn key_path(network: Network, account: AccountIndex, branch: u32) -> String {
+    format!(
+        "m/{PURPOSE}'/{}'/{account}'/{branch}'/0",
+        coin_type(network)
+    )
+}
+
+pub fn spend_key_path(network: Network, account: AccountIndex) -> String {
+    key_path(network, account, SPEND_BRANCH)
+}
+
+/// `b_spend + t_k`, the key of a found output. It is the taproot output key
+/// itself, no BIP341 tweak is applied on top.
+fn output_keypair(spend: &SecretKey, tweak: [u8; 32]) -> Result<Keypair, String> {
+    let secret = spend
+        .add_tweak(&to_scalar(tweak)?)
+        .map_err(|e| format!("invalid silent payment tweak: {e}"))?;
+    Ok(Keypair::from_secret_key(&Secp256k1::new(), &secret))
+}
+
+fn derive_key(xpriv: &Xpriv, path: &DerivationPath) -> Result<SecretKey, String> {
+    xpriv
+        .derive_priv(&Secp256k1::new(), path)
+        .map(|child| child.private_key)
+        .map_err(|e| format!("Derivation error: {e}"))
+}
+
+fn psbt_tweak_key() -> ProprietaryKey {
+    ProprietaryKey {
+        prefix: PSBT_PREFIX.to_vec(),
+        subtype: PSBT_SUBTYPE_TWEAK,
+        key: Vec::new(),
+    }
+}
+
+/// Describe an input spending a found output: the origin of the spend key
+/// and `t_k`, which is all `sign_inputs` needs next to the seed. The output
+/// key goes into `tap_internal_key` so the finalizer picks the key path.
+pub fn prepare_input(
+    input: &mut psbt::Input,
+    tweak: [u8; 32],
+    xpriv: &Xpriv,
+    network: Network,
+    account: AccountIndex,
+) -> Result<(), String> {
+    let secp = Secp256k1::new();
+    let path = DerivationPath::from_str(&key_path(network, account, SPEND_BRANCH))
+        .map_err(|e| e.to_string())?;
+    let spend = derive_key(xpriv, &path)?;
+    let output_key = output_keypair(&spend, tweak)?.x_only_public_key().0;
+    input.tap_key_origins.insert(
+        spend.x_only_public_key(&secp).0,
+        (vec![], (xpriv.fingerprint(&secp), path)),
+    );
+    input.tap_internal_key = Some(output_key);
+    input.tap_merkle_root = None;
+    input.proprietary.insert(psbt_tweak_key(), tweak.to_vec());
+    Ok(())
+}
+
+/// Key-path sign the silent payment inputs of `psbt`. `Psbt::sign` can't, it
+/// always applies the BIP341 tweak to the keys it finds.
+pub fn sign_inputs(psbt: &mut Psbt, xpriv: &Xpriv) -> Result<(), String> {
+    if !psbt
+        .inputs
+        .iter()
+        .any(|i| i.proprietary.contains_key(&psbt_tweak_key()))
+    {
+        return Ok(());
+    }
+    let secp = Secp256k1::new();
+    let prevouts = psbt
+        .inputs
+        .iter()
+        .map(|i| {
+            i.witness_utxo
+                .clone()
+                .ok_or("input is missing its witness utxo")
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut cache = SighashCache::new(&psbt.unsigned_tx);
+    for (index, input) in psbt.inputs.iter_mut().enumerate() {
+        let Some(tweak) = input.proprietary.get(&psbt_tweak_key()) else {
+            continue;
+        };
+        let tweak: [u8; 32] = tweak
+            .as_slice()
+            .try_into()
+            .map_err(|_| "invalid silent payment tweak")?;
+        let (_, (_, path)) = input
+            .tap_key_origins
+            .values()
+            .next()
+            .ok_or("silent payment input has no spend key")?;
+        let keypair = output_keypair(&derive_key(xpriv, path)?, tweak)?;
+        if input.tap_internal_key != Some(keypair.x_only_public_key().0) {
+            return Err(format!("silent payment key doesn't match input {index}"));
+        }
+        let sighash = cache
+            .taproot_key_spend_signature_hash(
+                index,
+                &Prevouts::All(&prevouts),
+                TapSighashType::Default,
+            )
+            .map_err(|e| e.to_string())?;
+        let signature =
+            secp.sign_schnorr_no_aux_rand(&Message::from_digest(sighash.to_byte_array()), &keypair);
+        input.tap_key_sig = Some(taproot::Signature {
+            signature,
+            sighash_type: TapSighashType::Default,
+        });
+    }
+    Ok(())
+}
+
+/// `hash_BIP0352/Inputs(outpoint_L || A)` where `outpoint_L` is the smallest
+/// outpoint (by serialization) among the transaction inputs.
+fn input_hash(outpoints: &[OutPoint], sum_pubkey: &PublicKey) -> Result<Scalar, String> {
+    let smallest = outpoints
+        .iter()
+        .map(bitcoin::consensus::serialize)
+        .min()
+        .ok_or("transaction has no inputs")?;
+    to_scalar(tagged_hash(
+        INPUTS_TAG,
+        &[&smallest, &sum_pubkey.serialize()],
+    ))
+}
+
+/// `t_k = hash_BIP0352/SharedSecret(ser_P(ecdh_shared_secret) || ser_32(k))`
+fn shared_secret_tweak(ecdh_shared_secret: &PublicKey, k: u32) -> Result<Scalar, String> {
+    to_scalar(tagged_hash(
+        SHARED_SECRET_TAG,
+        &[&ecdh_shared_secret.serialize(), &k.to_be_bytes()],
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SilentPaymentAddress {
+    pub scan: PublicKey,
+    pub spend: PublicKey,
+    mainnet: bool,
+}
+
+impl SilentPaymentAddress {
+    pub fn new(scan: PublicKey, spend: PublicKey, network: Network) -> Self {
+        Self {
+            scan,
+            spend,
+            mainnet: network == Network::Bitcoin,
+        }
+    }
+
+    /// `sp` addresses are mainnet only, `tsp` covers every test network.
+    pub fn require_network(self, network: Network) -> Result<Self, String> {
+        if self.mainnet == (network == Network::Bitcoin) {
+            Ok(self)
+        } else {
+            Err(format!("silent payment address is not valid for {network}"))
+        }
+    }
+
+    fn hrp(&self) -> &Hrp {
+        if self.mainnet {
+            &MAINNET_HRP
+        } else {
+            &TESTNET_HRP
+        }
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut payload = [0u8; 66];
+        payload[..33].copy_from_slice(&self.scan.serialize());
+        payload[33..].copy_from_slice(&self.spend.serialize());
+        let encoded: String = payload
+            .iter()
+            .copied()
+            .bytes_to_fes()
+            .with_checksum::<Bech32m>(self.hrp())
+            .with_witness_version(Fe32::Q)
+            .chars()
+            .collect();
+        f.write_str(&encoded)
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut checked = CheckedHrpstring::new::<Bech32m>(s)
+            .map_err(|e| format!("invalid silent payment address: {e}"))?;
+        let mainnet = match checked.hrp() {
+            hrp if hrp == MAINNET_HRP => true,
+            hrp if hrp == TESTNET_HRP => false,
+            hrp => return Err(format!("unknown silent payment prefix: {hrp}")),
+        };
+        let version = checked
+            .remove_witness_version()
+            .ok_or("silent payment address has no version")?
+            .to_u8();
+        let payload: Vec<u8> = checked.byte_iter().collect();
+        // Version 31 is reserved for a backwards incompatible change; later
+        // versions may only append data to the v0 payload.
+        let valid_length = match version {
+            0 => payload.len() == 66,
+            31 => return Err("unsupported silent payment address version".to_string()),
+            _ => payload.len() >= 66,
+        };
+        if !valid_length {
+            return Err("invalid silent payment address length".to_string());
+        }
+        let scan = PublicKey::from_slice(&payload[..33]).map_err(|e| e.to_string())?;
+        let spend = PublicKey::from_slice(&payload[33..66]).map_err(|e| e.to_string())?;
+        Ok(Self {
+            scan,
+            spend,
+            mainnet,
+        })
+    }
+}
+
+/// Private key of an input spent by a silent payment sender.
+pub struct SenderInput {
+    pub outpoint: OutPoint,
+    pub secret: SecretKey,
+    /// Taproot inputs contribute the key matching their even-Y x-only output
+    /// key, so `secret` has to be the tweaked output key.
+    pub taproot: bool,
+}
+
+/// One-time taproot output script paying the `k`-th output to `recipient`.
+pub fn sender_output_script(
+    recipient: &SilentPaymentAddress,
+    inputs: &[SenderInput],
+    k: u32,
+) -> Result<ScriptBuf, String> {
+    let secp = Secp256k1::new();
+
+    let mut keys = inputs.iter().map(|input| {
+        if input.taproot
+            && Keypair::from_secret_key(&secp, &input.secret)
+                .x_only_public_key()
+                .1
+                == Parity::Odd
+        {
+            input.secret.negate()
+        } else {
+            input.secret
+        }
+    });
+    let first = keys
+        .next()
+        .ok_or("silent payments need at least one input")?;
+    let sum = keys.try_fold(first, |acc, key| {
+        acc.add_tweak(&Scalar::from(key))
+            .map_err(|_| "input keys sum to zero".to_string())
+    })?;
+    let sum_pubkey = PublicKey::from_secret_key(&secp, &sum);
+
+    let outpoints: Vec<OutPoint> = inputs.iter().map(|i| i.outpoint).collect();
+    let input_hash = input_hash(&outpoints, &sum_pubkey)?;
+    let ecdh_secret = sum
+        .mul_tweak(&input_hash)
+        .map_err(|e| format!("invalid input hash: {e}"))?;
+    let shared = recipient
+        .scan
+        .mul_tweak(&secp, &Scalar::from(ecdh_secret))
+        .map_err(|e| format!("invalid scan key: {e}"))?;
+
+    let t_k = shared_secret_tweak(&shared, k)?;
+    let output_key = recipient
+        .spend
+        .add_exp_tweak(&secp, &t_k)
+        .map_err(|e| format!("invalid spend key: {e}"))?;
+    let (x_only, _) = output_key.x_only_public_key();
+    Ok(ScriptBuf::new_p2tr_tweaked(
+        TweakedPublicKey::dangerous_assume_tweaked(x_only),
+    ))
+}
+
+/// Public key an input contributes to the silent payment sum, or `None` when
+/// the input type isn't eligible (BIP352 "Inputs For Shared Secret
+/// Derivation"). Used by tweak indexers to compute `input_hash·A`.
+pub fn input_public_key(txin: &TxIn, prevout: &TxOut) -> Option<PublicKey> {
+    let script = &prevout.script_pubkey;
+    if script.is_p2tr() {
+        let x_only = XOnlyPublicKey::from_slice(&script.as_bytes()[2..34]).ok()?;
+        return Some(PublicKey::from_x_only_public_key(x_only, Parity::Even));
+    }
+    if script.is_p2wpkh() || script.is_p2sh() {
+        // P2SH is only eligible when it wraps P2WPKH: the witness then
+        // carries exactly a signature and a compressed key.
+        return match txin.witness.len() {
+            2 => txin
+                .witness
+                .nth(1)
+                .filter(|key| key.len() == 33)
+                .and_then(|key| PublicKey::from_slice(key).ok()),
+            _ => None,
+        };
+    }
+    if script.is_p2pkh() {
+        let last_push = txin
+            .script_sig
+            .instructions()
+            .filter_map(Result::ok)
+            .last()?;
+        return last_push
+            .push_bytes()
+            .filter(|key| key.len() == 33)
+            .and_then(|key| PublicKey::from_slice(key.as_bytes()).ok());
+    }
+    None
+}
+
+/// The per-transaction tweak `input_hash·A` served by tweak indexes.
+pub fn compute_tweak(
+    outpoints: &[OutPoint],
+    input_keys: &[PublicKey],
+) -> Result<PublicKey, String> {
+    let refs: Vec<&PublicKey> = input_keys.iter().collect();
+    let sum = PublicKey::combine_keys(&refs).map_err(|e| format!("invalid input keys: {e}"))?;
+    let secp = Secp256k1::verification_only();
+    sum.mul_tweak(&secp, &input_hash(outpoints, &sum)?)
+        .map_err(|e| format!("invalid input hash: {e}"))
+}
+
+/// Outputs of a block that may belong to a silent payment recipient, along
+/// with the tweaks of every eligible transaction in it.
+#[derive(Debug, Clone, Default)]
+pub struct BlockScanData {
+    pub height: u32,
+    pub tweaks: Vec<PublicKey>,
+    /// Unspent taproot outputs created in the block.
+    pub outputs: Vec<(OutPoint, TxOut)>,
+}
+
+/// Backend that serves silent payment tweak data, see
+/// `providers::tweak_index`.
+pub trait TweakSource {
+    fn tip_height(&self) -> impl Future<Output = Result<u32, String>> + Send;
+
+    fn block_scan_data(
+        &self,
+        height: u32,
+    ) -> impl Future<Output = Result<BlockScanData, String>> + Send;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SilentPaymentOutput {
+    pub outpoint: OutPoint,
+    pub output: TxOut,
+    /// `t_k`; the output is spent with `b_spend + t_k`.
+    pub tweak: [u8; 32],
+    pub height: u32,
+}
+
+impl SilentPaymentOutput {
+    /// The output as a coin of the account `derivation` belongs to, see
+    /// `Utxo::silent_payment`.
+    pub fn to_utxo(&self, derivation: KeyDerivationPath) -> Utxo {
+        Utxo {
+            tx_id: self.outpoint.txid,
+            vout: self.outpoint.vout,
+            output: self.output.clone(),
+            derivation,
+            height: self.height,
+            silent_payment: Some(self.tweak),
+        }
+    }
+}
+
+/// Silent payment receive state of an account. Found outputs are kept here
+/// since they aren't tied to a derivation path, and merged into the UTXO set
+/// after every sync so they can be spent.
+#[derive(Debug, Clone, Default)]
+pub struct SilentPaymentState {
+    pub outputs: HashMap<OutPoint, SilentPaymentOutput>,
+    /// Last block checked against the tweak index.
+    pub scan_height: u32,
+}
+
+impl SilentPaymentState {
+    pub fn total_value(&self) -> u64 {
+        self.outputs.values().map(|o| o.output.value.to_sat()).sum()
+    }
+
+    /// Height the next scan continues after.
+    pub fn scanned_height(&self, network: Network) -> u32 {
+        match network {
+            Network::Bitcoin => self.scan_height.max(MAINNET_SCAN_START_HEIGHT - 1),
+            _ => self.scan_height,
+        }
+    }
+
+    pub fn apply_scan(&mut self, report: ScanReport) {
+        self.outputs
+            .extend(report.found.into_iter().map(|o| (o.outpoint, o)));
+        // Another scan may have finished first.
+        self.scan_height = self.scan_height.max(report.scanned_to);
+    }
+
+    /// Addresses of the found outputs, for asking the server which of them
+    /// are still unspent.
+    pub fn addresses(&self, network: Network) -> Vec<Address> {
+        self.outputs
+            .values()
+            .filter_map(|o| Address::from_script(&o.output.script_pubkey, network).ok())
+            .collect()
+    }
+
+    /// Forget outputs that were spent, `unspent` being what the server
+    /// reports for `addresses`.
+    pub fn retain_unspent(&mut self, unspent: &HashSet<OutPoint>) {
+        self.outputs
+            .retain(|outpoint, _| unspent.contains(outpoint));
+    }
+
+    pub fn remove(&mut self, outpoints: &[OutPoint]) {
+        for outpoint in outpoints {
+            self.outputs.remove(outpoint);
+        }
+    }
+}
+
+pub struct SilentPaymentKeys {
+    scan: SecretKey,
+    spend: SecretKey,
+    network: Network,
+}
+
+impl SilentPaymentKeys {
+    /// Scan key at `m/352'/coin'/account'/1'/0`, spend key at
+    /// `m/352'/coin'/account'/0'/0`.
+    pub fn derive(xpriv: &Xpriv, network: Network, account: AccountIndex) -> Result<Self, String> {
+        let derive = |branch: u32| -> Result<SecretKey, String> {
+            let path = DerivationPath::from_str(&key_path(network, account, branch))
+                .map_err(|e| e.to_string())?;
+            derive_key(xpriv, &path)
+        };
+        Ok(Self {
+            scan: derive(SCAN_BRANCH)?,
+            spend: derive(SPEND_BRANCH)?,
+            network,
+        })
+    }
+
+    /// Key controlling a found output, see `SilentPaymentOutput::tweak`.
+    pub fn output_keypair(&self, tweak: [u8; 32]) -> Result<Keypair, String> {
+        output_keypair(&self.spend, tweak)
+    }
+
+    pub fn address(&self) -> SilentPaymentAddress {
+        let secp = Secp256k1::new();
+        SilentPaymentAddress::new(
+            PublicKey::from_secret_key(&secp, &self.scan),
+            PublicKey::from_secret_key(&secp, &self.spend),
+            self.network,
+        )
+    }
+
+    /// Match the block's taproot outputs against every tweak. Outputs to the
+    /// same recipient within a transaction use increasing `k`, so the search
+    /// for a tweak stops at the first `k` without a match.
+    pub fn scan_block(&self, block: &BlockScanData) -> Vec<SilentPaymentOutput> {
+        let secp = Secp256k1::new();
+        let spend = PublicKey::from_secret_key(&secp, &self.spend);
+
+        let mut by_key: HashMap<XOnlyPublicKey, Vec<&(OutPoint, TxOut)>> = HashMap::new();
+        for entry in &block.outputs {
+            let script = &entry.1.script_pubkey;
+            if let Some(x_only) = script
+                .is_p2tr()
+                .then(|| XOnlyPublicKey::from_slice(&script.as_bytes()[2..34]).ok())
+                .flatten()
+            {
+                by_key.entry(x_only).or_default().push(entry);
+            }
+        }
+
+        let mut found = Vec::new();
+        for tweak in &block.tweaks {
+            let Ok(shared) = tweak.mul_tweak(&secp, &Scalar::from(self.scan)) else {
+                continue;
+            };
+            for k in 0.. {
+                let Ok(t_k) = shared_secret_tweak(&shared, k) else {
+                    break;
+                };
+                let Ok(candidate) = spend.add_exp_tweak(&secp, &t_k) else {
+                    break;
+                };
+                let Some(matches) = by_key.get(&candidate.x_only_public_key().0) else {
+                    break;
+                };
+                found.extend(
+                    matches
+                        .iter()
+                        .map(|(outpoint, output)| SilentPaymentOutput {
+                            outpoint: *outpoint,
+                            output: output.clone(),
+                            tweak: t_k.to_be_bytes(),
+                            height: block.height,
+                        }),
+                );
+            }
+        }
+        found
+    }
+}
+
+pub struct ScanReport {
+    pub found: Vec<SilentPaymentOutput>,
+    pub scanned_to: u32,
+}
+
+/// Scan every block after `from_height` up to `to_height`.
+pub async fn scan<S: TweakSource>(
+    source: &S,
+    keys: &SilentPaymentKeys,
+    from_height: u32,
+    to_height: u32,
+) -> Result<ScanReport, String> {
+    let mut found = Vec::new();
+    for height in from_height + 1..=to_height {
+        let block = source.block_scan_data(height).await?;
+        found.extend(keys.scan_block(&block));
+    }
+    Ok(ScanReport {
+        found,
+        scanned_to: to_height.max(from_height),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Amount, Txid, key::TapTweak};
+
+    use super::*;
+
+    fn keys() -> SilentPaymentKeys {
+        let xpriv = Xpriv::new_master(Network::Regtest, &[7u8; 32]).unwrap();
+        SilentPaymentKeys::derive(&xpriv, Network::Regtest, 0).unwrap()
+    }
+
+    fn outpoint(byte: u8, vout: u32) -> OutPoint {
+        OutPoint {
+            txid: Txid::from_byte_array([byte; 32]),
+            vout,
+        }
+    }
+
+    #[test]
+    fn address_roundtrip() {
+        let address = keys().address();
+        let encoded = address.to_string();
+        assert!(encoded.starts_with("tsp1q"));
+        assert_eq!(encoded.parse::<SilentPaymentAddress>().unwrap(), address);
+        assert!(address.require_network(Network::Bitcoin).is_err());
+        assert!(address.require_network(Network::Regtest).is_ok());
+    }
+
+    #[test]
+    fn rejects_non_silent_payment_strings() {
+        assert!(
+            "bcrt1p04x2uthh0arxzuct6hpetdtg2p7c23yuu855z3srs332ga4k9gasjv0av6"
+                .parse::<SilentPaymentAddress>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn receiver_finds_sender_output() {
+        let secp = Secp256k1::new();
+        let receiver = keys();
+        let address = receiver.address();
+
+        // One taproot and one segwit input, as build_psbt would feed them.
+        let taproot_internal = Keypair::from_seckey_slice(&secp, &[3u8; 32]).unwrap();
+        let taproot_output = taproot_internal.tap_tweak(&secp, None).to_keypair();
+        let segwit = SecretKey::from_slice(&[5u8; 32]).unwrap();
+        let inputs = [
+            SenderInput {
+                outpoint: outpoint(2, 1),
+                secret: taproot_output.secret_key(),
+                taproot: true,
+            },
+            SenderInput {
+                outpoint: outpoint(1, 0),
+                secret: segwit,
+                taproot: false,
+            },
+        ];
+        let script = sender_output_script(&address, &inputs, 0).unwrap();
+
+        // What a tweak index computes from the public transaction data.
+        let input_keys = [
+            PublicKey::from_x_only_public_key(taproot_output.x_only_public_key().0, Parity::Even),
+            PublicKey::from_secret_key(&secp, &segwit),
+        ];
+        let tweak = compute_tweak(&[outpoint(2, 1), outpoint(1, 0)], &input_keys).unwrap();
+
+        let paid = outpoint(9, 0);
+        let block = BlockScanData {
+            height: 10,
+            tweaks: vec![tweak],
+            outputs: vec![
+                (
+                    outpoint(8, 0),
+                    TxOut {
+                        value: Amount::from_sat(1_000),
+                        script_pubkey: ScriptBuf::new_p2tr_tweaked(
+                            TweakedPublicKey::dangerous_assume_tweaked(
+                                taproot_output.x_only_public_key().0,
+                            ),
+                        ),
+                    },
+                ),
+                (
+                    paid,
+                    TxOut {
+                        value: Amount::from_sat(25_000),
+                        script_pubkey: script,
+                    },
+                ),
+            ],
+        };
+
+        let found = receiver.scan_block(&block);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].outpoint, paid);
+        assert_eq!(found[0].height, 10);
+
+        // The spend key for the found output controls the output key.
+        let spend_key = receiver
+            .spend
+            .add_tweak(&Scalar::from_be_bytes(found[0].tweak).unwrap())
+            .unwrap();
+        let (x_only, _) = Keypair::from_secret_key(&secp, &spend_key).x_only_public_key();
+        assert_eq!(
+            &found[0].output.script_pubkey.as_bytes()[2..34],
+            &x_only.serialize()
+        );
+    }
+
+    #[test]
+    fn found_output_is_spendable() {
+        use bitcoin::{Sequence, Transaction, Witness, absolute::LockTime, transaction::Version};
+        use miniscript::psbt::PsbtExt;
+
+        let secp = Secp256k1::new();
+        let xpriv = Xpriv::new_master(Network::Regtest, &[7u8; 32]).unwrap();
+        let receiver = keys();
+        let sender_key = SecretKey::from_slice(&[5u8; 32]).unwrap();
+        let inputs = [SenderInput {
+            outpoint: outpoint(1, 0),
+            secret: sender_key,
+            taproot: false,
+        }];
+        let script = sender_output_script(&receiver.address(), &inputs, 0).unwrap();
+        let tweak = compute_tweak(
+            &[outpoint(1, 0)],
+            &[PublicKey::from_secret_key(&secp, &sender_key)],
+        )
+        .unwrap();
+        let paid = TxOut {
+            value: Amount::from_sat(25_000),
+            script_pubkey: script,
+        };
+        let found = receiver.scan_block(&BlockScanData {
+            height: 10,
+            tweaks: vec![tweak],
+            outputs: vec![(outpoint(9, 0), paid.clone())],
+        });
+        assert_eq!(found.len(), 1);
+
+        let mut psbt = Psbt::from_unsigned_tx(Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: found[0].outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(24_000),
+                script_pubkey: ScriptBuf::new_op_return([1u8]),
+            }],
+        })
+        .unwrap();
+        psbt.inputs[0].witness_utxo = Some(paid);
+        prepare_input(
+            &mut psbt.inputs[0],
+            found[0].tweak,
+            &xpriv,
+            Network::Regtest,
+            0,
+        )
+        .unwrap();
+
+        // Same steps as `tx_builder::sign_psbt`; finalizing runs the
+        // interpreter, which checks the signature against the output key.
+        sign_inputs(&mut psbt, &xpriv).unwrap();
+        psbt.sign(&xpriv, &secp).unwrap();
+        psbt.finalize_mut(&secp).unwrap();
+        let tx = psbt.extract_tx().unwrap();
+        assert_eq!(tx.input[0].witness.len(), 1);
+    }
+
+    #[test]
+    fn other_recipient_finds_nothing() {
+        let sender_key = SecretKey::from_slice(&[5u8; 32]).unwrap();
+        let inputs = [SenderInput {
+            outpoint: outpoint(1, 0),
+            secret: sender_key,
+            taproot: false,
+        }];
+        let script = sender_output_script(&keys().address(), &inputs, 0).unwrap();
+        let secp = Secp256k1::new();
+        let tweak = compute_tweak(
+            &[outpoint(1, 0)],
+            &[PublicKey::from_secret_key(&secp, &sender_key)],
+        )
+        .unwrap();
+
+        let xpriv = Xpriv::new_master(Network::Regtest, &[8u8; 32]).unwrap();
+        let stranger = SilentPaymentKeys::derive(&xpriv, Network::Regtest, 0).unwrap();
+        let block = BlockScanData {
+            height: 1,
+            tweaks: vec![tweak],
+            outputs: vec![(
+                outpoint(9, 0),
+                TxOut {
+                    value: Amount::from_sat(1_000),
+                    script_pubkey: script,
+                },
+            )],
+        };
+        assert!(stranger.scan_block(&block).is_empty());
+    }
+}
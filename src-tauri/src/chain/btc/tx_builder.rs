@@ -1,11 +1,11 @@
-use std::cmp::max;
+use std::{cmp::max, fmt, str::FromStr};
 
 use bitcoin::{
-    Address, OutPoint, Sequence, Transaction, TxIn, TxOut, Witness,
+    Address, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
     absolute::LockTime,
     address::NetworkChecked,
     bip32::{KeySource, Xpriv},
-    key::Secp256k1,
+    key::{Secp256k1, TapTweak},
     psbt::Psbt,
     transaction::Version,
 };
@@ -17,15 +17,54 @@ use crate::{
         account::{Account, UtxoSelectionStrategy},
         config::BitcoinConfig,
         key_derivation::{Change, KeyDerivationPath, LabeledKeyDerivationPath, Proposal},
+        policy::PolicyWarning,
+        silent_payments::{self, SenderInput, SilentPaymentAddress, SilentPaymentKeys},
+        utxo::Utxo,
     },
     chain_trait::SecureKey,
 };
 
 const UTXO_DUST_VALUE: u64 = 330;
 
+#[derive(Debug, Clone)]
+pub enum Recipient {
+    Address(Address<NetworkChecked>),
+    /// BIP352 static payment code; the output key is derived from our inputs.
+    SilentPayment(SilentPaymentAddress),
+}
+
+impl Recipient {
+    /// Parse an on-chain address or an `sp1`/`tsp1` silent payment address.
+    pub fn parse(s: &str, network: Network) -> Result<Self, String> {
+        if let Ok(address) = SilentPaymentAddress::from_str(s) {
+            return Ok(Recipient::SilentPayment(address.require_network(network)?));
+        }
+        let address = Address::from_str(s)
+            .map_err(|e| format!("invalid recipient address: {e}"))?
+            .require_network(network)
+            .map_err(|e| format!("recipient address network mismatch: {e}"))?;
+        Ok(Recipient::Address(address))
+    }
+}
+
+impl From<Address<NetworkChecked>> for Recipient {
+    fn from(address: Address<NetworkChecked>) -> Self {
+        Recipient::Address(address)
+    }
+}
+
+impl fmt::Display for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recipient::Address(address) => address.fmt(f),
+            Recipient::SilentPayment(address) => address.fmt(f),
+        }
+    }
+}
+
 pub struct BuildPsbtParams<'a> {
     pub send_value_sat: u64,
-    pub recipient: Address<NetworkChecked>,
+    pub recipient: Recipient,
     pub utxo_selection_method: UtxoSelectionStrategy,
     pub miner_fee_vbytes: f64,
     pub config: BitcoinConfig,
@@ -43,6 +82,8 @@ pub struct BuildTxResult {
     pub recipient: Option<String>,
     /// Amount paid to the recipient in satoshis (0 for CPFP).
     pub send_value_sat: u64,
+    /// Fee policy violations found before signing, see `policy::check_tx`.
+    pub warnings: Vec<PolicyWarning>,
 }
 
 const MIN_RELAY_FEE: u32 = 16;
@@ -51,10 +92,32 @@ const MIN_RELAY_FEE: u32 = 16;
 /// - Output 0: change returned to the wallet's next unused **internal** address
 /// - Output 1: recipient payment
 pub fn build_psbt(p: &BuildPsbtParams) -> Result<BuildTxResult, String> {
-    let utxos = p.account.utxo_set.select(p.utxo_selection_method.clone());
+    let utxos = p.account.utxo_set.select(
+        p.utxo_selection_method.clone(),
+        p.config.spend_risky_unconfirmed,
+    );
+    if let Some(utxo) = utxos
+        .iter()
+        .find(|u| p.account.utxo_set.is_locked(&u.outpoint()))
+    {
+        return Err(format!(
+            "utxo {} is reserved by a pending transaction",
+            utxo.outpoint()
+        ));
+    }
     if utxos.is_empty() {
         return Err("no utxos selected for transaction".to_string());
     }
+    if utxos.len() > 1
+        && let Some(utxo) = utxos
+            .iter()
+            .find(|u| p.account.utxo_set.is_quarantined(&u.outpoint()))
+    {
+        return Err(format!(
+            "utxo {} is quarantined as dust and can only be spent alone",
+            utxo.outpoint()
+        ));
+    }
     let input_count = utxos.len();
     let total_input = utxos.iter().map(|u| u.output.value.to_sat()).sum();
 
@@ -93,8 +156,9 @@ pub fn build_psbt(p: &BuildPsbtParams) -> Result<BuildTxResult, String> {
     );
 
     if amounts.has_change {
-        let change_child_key = change_key_path
-            .derive(p.xpriv)
+        let change_child_key = p
+            .account
+            .derive(&change_key_path, p.xpriv)
             .map_err(|e| format!("failed to derive change key: {e}"))?;
         output.push(TxOut {
             value: bitcoin::Amount::from_sat(amounts.change_value_sat),
@@ -105,7 +169,7 @@ pub fn build_psbt(p: &BuildPsbtParams) -> Result<BuildTxResult, String> {
     // Create the recipient output
     output.push(TxOut {
         value: bitcoin::Amount::from_sat(amounts.send_value_sat),
-        script_pubkey: p.recipient.script_pubkey(),
+        script_pubkey: recipient_script(&p.recipient, p.account, &utxos, p.xpriv)?,
     });
 
     // Create PSBT from unsigned transaction
@@ -117,18 +181,51 @@ pub fn build_psbt(p: &BuildPsbtParams) -> Result<BuildTxResult, String> {
     })
     .map_err(|e| format!("Failed to create PSBT: {e}"))?;
 
-    // Add witness UTXOs and BIP32 derivation info to inputs
+    add_input_keys(&mut psbt, &utxos, p.account, p.xpriv)?;
+
+    Ok(BuildTxResult {
+        fee: amounts.fee,
+        psbt,
+        change_key_path: LabeledKeyDerivationPath {
+            label: "Change".to_string(),
+            path: change_key_path,
+        },
+        recipient: Some(p.recipient.to_string()),
+        send_value_sat: amounts.send_value_sat,
+        warnings: Vec::new(),
+    })
+}
+
+/// Add witness UTXOs and BIP32 derivation info to the inputs spending
+/// `utxos`, in order. Silent payment inputs get their spend key origin and
+/// tweak instead.
+pub fn add_input_keys(
+    psbt: &mut Psbt,
+    utxos: &[&Utxo],
+    account: &Account,
+    xpriv: &Xpriv,
+) -> Result<(), String> {
     let secp = Secp256k1::new();
-    let master_fingerprint = p.xpriv.fingerprint(&secp);
+    let master_fingerprint = xpriv.fingerprint(&secp);
 
     for (i, utxo) in utxos.iter().enumerate() {
         // Add witness UTXO
         psbt.inputs[i].witness_utxo = Some(utxo.output.clone());
 
+        if let Some(tweak) = utxo.silent_payment {
+            silent_payments::prepare_input(
+                &mut psbt.inputs[i],
+                tweak,
+                xpriv,
+                utxo.derivation.network,
+                account.index,
+            )?;
+            continue;
+        }
+
         // Derive key and add BIP32 derivation info
-        let child = utxo
-            .derivation
-            .derive(p.xpriv)
+        let child = account
+            .derive_for_utxo(utxo, xpriv)
             .map_err(|e| format!("failed to derive child key: {e}"))?;
         let xonly_pubkey = child.keypair.x_only_public_key().0;
 
@@ -140,25 +237,70 @@ pub fn build_psbt(p: &BuildPsbtParams) -> Result<BuildTxResult, String> {
             .tap_key_origins
             .insert(xonly_pubkey, (vec![], key_source));
 
-        // Taproot internal key (BIP86 key-path spend)
+        // Taproot internal key, key-path spend committing to the recovery
+        // leaf if the output has one
         psbt.inputs[i].tap_internal_key = Some(xonly_pubkey);
+        psbt.inputs[i].tap_merkle_root = child.merkle_root;
     }
+    Ok(())
+}
 
-    Ok(BuildTxResult {
-        fee: amounts.fee,
-        psbt,
-        change_key_path: LabeledKeyDerivationPath {
-            label: "Change".to_string(),
-            path: change_key_path,
-        },
-        recipient: Some(p.recipient.to_string()),
-        send_value_sat: amounts.send_value_sat,
-    })
+fn recipient_script(
+    recipient: &Recipient,
+    account: &Account,
+    utxos: &[&Utxo],
+    xpriv: &Xpriv,
+) -> Result<ScriptBuf, String> {
+    match recipient {
+        Recipient::Address(address) => Ok(address.script_pubkey()),
+        Recipient::SilentPayment(address) => {
+            let secp = Secp256k1::new();
+            let inputs = utxos
+                .iter()
+                .map(|utxo| {
+                    if let Some(tweak) = utxo.silent_payment {
+                        // The output key is the tweaked spend key as is.
+                        let keys = SilentPaymentKeys::derive(
+                            xpriv,
+                            utxo.derivation.network,
+                            account.index,
+                        )?;
+                        return Ok(SenderInput {
+                            outpoint: utxo.outpoint(),
+                            secret: keys.output_keypair(tweak)?.secret_key(),
+                            taproot: true,
+                        });
+                    }
+                    let child = account
+                        .derive_for_utxo(utxo, xpriv)
+                        .map_err(|e| format!("failed to derive child key: {e}"))?;
+                    let taproot = utxo.derivation.purpose == Proposal::Taproot;
+                    // Taproot inputs contribute their tweaked output key.
+                    let secret = if taproot {
+                        child
+                            .keypair
+                            .tap_tweak(&secp, child.merkle_root)
+                            .to_keypair()
+                            .secret_key()
+                    } else {
+                        child.keypair.secret_key()
+                    };
+                    Ok(SenderInput {
+                        outpoint: utxo.outpoint(),
+                        secret,
+                        taproot,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            silent_payments::sender_output_script(address, &inputs, 0)
+        }
+    }
 }
 
 pub fn sign_psbt(mut psbt: Psbt, prk: &Prk) -> Result<Transaction, String> {
     let secp = Secp256k1::new();
 
+    silent_payments::sign_inputs(&mut psbt, prk.expose())?;
     psbt.sign(prk.expose(), &secp)
         .map_err(|e| format!("Failed to sign PSBT: {:?}", e))?;
 
@@ -228,3 +370,24 @@ pub fn estimate_taproot_vbytes(input_count: usize, output_count: usize) -> u64 {
     // vBytes is Weight / 4, rounded up to the nearest integer
     weight_units.div_ceil(4)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipient_must_match_the_network() {
+        let testnet = Address::p2wsh(&ScriptBuf::new(), Network::Testnet4).to_string();
+        let regtest = Address::p2wsh(&ScriptBuf::new(), Network::Regtest).to_string();
+        let mainnet = Address::p2wsh(&ScriptBuf::new(), Network::Bitcoin).to_string();
+
+        // `tb1` addresses are shared by every public test network.
+        for network in [Network::Testnet, Network::Testnet4, Network::Signet] {
+            assert!(Recipient::parse(&testnet, network).is_ok());
+            assert!(Recipient::parse(&mainnet, network).is_err());
+            assert!(Recipient::parse(&regtest, network).is_err());
+        }
+        assert!(Recipient::parse(&regtest, Network::Regtest).is_ok());
+        assert!(Recipient::parse(&testnet, Network::Bitcoin).is_err());
+    }
+}
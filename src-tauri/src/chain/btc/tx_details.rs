@@ -0,0 +1,182 @@
+use bitcoin::{Address, Network, Script, Transaction, TxOut};
+
+use crate::chain::btc::{
+    account::{AddressPathMap, KeyDerivationPathLabelMap},
+    dtos::{OwnedOutputView, TxDetailsView, TxInputView, TxOutputView},
+    key_derivation::Change,
+};
+
+/// Wallet addresses and labels to match inputs and outputs against.
+pub struct Ownership<'a> {
+    pub addresses: &'a AddressPathMap,
+    pub labels: &'a KeyDerivationPathLabelMap,
+    pub network: Network,
+}
+
+impl Ownership<'_> {
+    fn address(&self, script: &Script) -> Option<Address> {
+        Address::from_script(script, self.network).ok()
+    }
+
+    fn owner(&self, address: Option<&Address>) -> Option<OwnedOutputView> {
+        let path = self.addresses.get(address?)?;
+        let label = match path.change {
+            Change::Internal => Some("Change".to_string()),
+            Change::External => self.labels.get(&path.to_slice()).cloned(),
+        };
+        Some(OwnedOutputView {
+            deriv_path: path.to_string(),
+            label,
+        })
+    }
+}
+
+/// Decode `tx` against the wallet. `prevouts` holds the output spent by each
+/// input, `None` where it couldn't be fetched. Block and CPFP links are left
+/// empty for the caller to fill from the history.
+pub fn decode(tx: &Transaction, prevouts: &[Option<TxOut>], owned: &Ownership) -> TxDetailsView {
+    let mut net_value: i64 = 0;
+
+    let inputs = tx
+        .input
+        .iter()
+        .zip(prevouts)
+        .map(|(input, prevout)| {
+            let address = prevout
+                .as_ref()
+                .and_then(|o| owned.address(&o.script_pubkey));
+            let owner = owned.owner(address.as_ref());
+            if let (Some(_), Some(prevout)) = (&owner, prevout) {
+                net_value -= prevout.value.to_sat() as i64;
+            }
+            TxInputView {
+                previous_output: (!tx.is_coinbase()).then(|| input.previous_output.into()),
+                sequence: input.sequence.to_consensus_u32(),
+                value_sat: prevout.as_ref().map(|o| o.value.to_sat().to_string()),
+                address: address.map(|a| a.to_string()),
+                owned: owner,
+            }
+        })
+        .collect();
+
+    let outputs = tx
+        .output
+        .iter()
+        .enumerate()
+        .map(|(vout, output)| {
+            let address = owned.address(&output.script_pubkey);
+            let owner = owned.owner(address.as_ref());
+            if owner.is_some() {
+                net_value += output.value.to_sat() as i64;
+            }
+            TxOutputView {
+                vout: vout as u32,
+                value_sat: output.value.to_sat().to_string(),
+                address: address.map(|a| a.to_string()),
+                owned: owner,
+            }
+        })
+        .collect();
+
+    let vsize = tx.vsize();
+    let fee = fee_sat(tx, prevouts);
+    TxDetailsView {
+        tx_id: tx.compute_txid().to_string(),
+        version: tx.version.0,
+        lock_time: tx.lock_time.to_consensus_u32(),
+        lock_time_enabled: tx.is_lock_time_enabled() && tx.lock_time.to_consensus_u32() > 0,
+        rbf: tx.is_explicitly_rbf(),
+        size: tx.total_size() as u32,
+        vsize: vsize as u32,
+        weight: tx.weight().to_wu() as u32,
+        fee_sat: fee.map(|f| f.to_string()),
+        fee_rate_sat_vb: fee.map(|f| f as f64 / vsize as f64),
+        net_value_sat: net_value.to_string(),
+        block_height: None,
+        confirmations: 0,
+        cpfp_parent: None,
+        cpfp_children: Vec::new(),
+        inputs,
+        outputs,
+    }
+}
+
+/// Inputs minus outputs, None for a coinbase or while a prevout is missing.
+fn fee_sat(tx: &Transaction, prevouts: &[Option<TxOut>]) -> Option<u64> {
+    if tx.is_coinbase() {
+        return None;
+    }
+    let input = prevouts
+        .iter()
+        .map(|o| o.as_ref().map(|o| o.value.to_sat()))
+        .sum::<Option<u64>>()?;
+    let output: u64 = tx.output.iter().map(|o| o.value.to_sat()).sum();
+    input.checked_sub(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::{
+        Amount, OutPoint, ScriptBuf, Sequence, TxIn, Txid, Witness, absolute::LockTime,
+        hashes::Hash, transaction::Version,
+    };
+
+    use super::*;
+    use crate::chain::btc::key_derivation::{KeyDerivationPath, Proposal};
+
+    const OURS: &str = "bcrt1p04x2uthh0arxzuct6hpetdtg2p7c23yuu855z3srs332ga4k9gasjv0av6";
+
+    fn output(value: u64, address: &Address) -> TxOut {
+        TxOut {
+            value: Amount::from_sat(value),
+            script_pubkey: address.script_pubkey(),
+        }
+    }
+
+    fn spend(outputs: Vec<TxOut>) -> Transaction {
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::from_height(500).unwrap(),
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), 1),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: outputs,
+        }
+    }
+
+    #[test]
+    fn decodes_owned_outputs_and_fee() {
+        let path =
+            KeyDerivationPath::new(Proposal::Taproot, Network::Regtest, 0, Change::External, 3);
+        let ours = Address::from_str(OURS).unwrap().assume_checked();
+        let addresses: AddressPathMap = [(ours.clone(), path.clone())].into();
+        let labels: KeyDerivationPathLabelMap = [(path.to_slice(), "Savings".to_string())].into();
+        let owned = Ownership {
+            addresses: &addresses,
+            labels: &labels,
+            network: Network::Regtest,
+        };
+        let other = Address::p2wsh(&ScriptBuf::new(), Network::Regtest);
+        let tx = spend(vec![output(40_000, &ours), output(59_000, &other)]);
+
+        let details = decode(&tx, &[Some(output(100_000, &other))], &owned);
+        assert_eq!(details.fee_sat.as_deref(), Some("1000"));
+        assert!(details.rbf);
+        assert!(details.lock_time_enabled);
+        assert_eq!(details.net_value_sat, "40000");
+        assert!(details.inputs[0].owned.is_none());
+        let mine = details.outputs[0].owned.as_ref().unwrap();
+        assert_eq!(mine.label.as_deref(), Some("Savings"));
+        assert!(details.outputs[1].owned.is_none());
+
+        // Without the spent output the fee stays unknown.
+        let details = decode(&tx, &[None], &owned);
+        assert_eq!(details.fee_sat, None);
+        assert_eq!(details.fee_rate_sat_vb, None);
+    }
+}
@@ -1,16 +1,26 @@
-use bitcoin::{BlockHash, OutPoint, TxOut, Txid};
+use bitcoin::{Address, BlockHash, OutPoint, TxOut, Txid};
 
 use crate::chain::btc::{
     account::KeyDerivationPathLabelMap,
     key_derivation::{Change, KeyDerivationPath},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockHeader {
     pub hash: BlockHash,
     pub height: u32,
 }
 
+/// An unspent output paying to a watched address, before it is attributed to
+/// a wallet key.
+#[derive(Debug, Clone)]
+pub struct AddressUtxo {
+    pub address: Address,
+    pub outpoint: OutPoint,
+    pub output: TxOut,
+    pub height: u32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Utxo {
     pub tx_id: Txid,
@@ -18,6 +28,10 @@ pub struct Utxo {
     pub output: TxOut,
     pub derivation: KeyDerivationPath,
     pub height: u32,
+    /// BIP352 tweak of an output found by a silent payment scan. Its key is
+    /// the account's spend key plus this tweak, `derivation` only files the
+    /// coin under the account.
+    pub silent_payment: Option<[u8; 32]>,
 }
 
 impl Utxo {
@@ -29,6 +43,9 @@ impl Utxo {
     }
 
     pub fn label(&self, schema_label_map: &KeyDerivationPathLabelMap) -> Option<String> {
+        if self.silent_payment.is_some() {
+            return Some("Silent payment".to_string());
+        }
         let label: Option<String> = match self.derivation.change {
             Change::Internal => Some("Change".to_string()),
             Change::External => schema_label_map.get(&self.derivation.to_slice()).cloned(),
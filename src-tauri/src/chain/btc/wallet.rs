@@ -1,6 +1,6 @@
 use bip39::Language;
 use bitcoin::{
-    Network,
+    Network, OutPoint,
     bip32::{self, Xpriv},
 };
 use shush_rs::ExposeSecret;
@@ -8,9 +8,15 @@ use shush_rs::ExposeSecret;
 use crate::{
     chain::btc::{
         account::Account,
+        chain_tip::TipTracker,
+        descriptor_account::DescriptorAccount,
+        drafts::TxDraft,
         dtos::{AccountSummary, BitcoinUnlock},
         key_derivation::{Change, KeyDerivationPath, Proposal},
+        pending_tx::PendingTx,
+        persistence::UnreadableDraft,
         providers::btc_node::{BtcNode, select_btc_server},
+        quorum::Quorum,
         tx_builder::BuildTxResult,
     },
     chain_trait::{AccountIndex, SecureKey},
@@ -40,9 +46,19 @@ pub struct BitcoinWallet {
     pub(crate) secret: Secretik,
     pub active_account: AccountIndex,
     pub accounts: Vec<Account>,
+    pub descriptor_accounts: Vec<DescriptorAccount>,
     pub server: BtcNode,
+    /// Servers of other operators `server` is cross-checked against.
+    pub quorum: Quorum,
     pub config: Config,
-    pub pending_tx: Option<BuildTxResult>,
+    /// Transaction built by `build_tx` and not yet broadcast or abandoned.
+    pub pending_tx: Option<PendingTx>,
+    /// Named transactions saved for later review, ordered by id.
+    pub drafts: Vec<TxDraft>,
+    /// Saved drafts that failed to load, kept until the user deletes them.
+    pub unreadable_drafts: Vec<UnreadableDraft>,
+    /// Recent headers of the best chain, used for confirmations and reorgs.
+    pub tip: TipTracker,
 }
 
 impl BitcoinWallet {
@@ -50,13 +66,19 @@ impl BitcoinWallet {
         let active_account = 0;
         let account = Account::new(config.btc.network(), active_account, "main".to_string());
         let server = select_btc_server(&config);
+        let quorum = Quorum::new(&config);
         BitcoinWallet {
             secret,
             config,
             active_account,
             accounts: vec![account],
+            descriptor_accounts: Vec::new(),
             server,
+            quorum,
             pending_tx: None,
+            drafts: Vec::new(),
+            unreadable_drafts: Vec::new(),
+            tip: TipTracker::default(),
         }
     }
 
@@ -77,7 +99,12 @@ impl BitcoinWallet {
 
         Ok(BitcoinUnlock {
             accounts: self.list_all_accounts(network)?,
-            active_account: account.info(&prk, network)?,
+            active_account: account.info(
+                &prk,
+                network,
+                &self.tip,
+                self.config.btc.explorer().as_ref(),
+            )?,
         })
     }
 
@@ -164,6 +191,164 @@ impl BitcoinWallet {
         Ok(path)
     }
 
+    pub fn get_descriptor_account(
+        &self,
+        index: AccountIndex,
+    ) -> Result<&DescriptorAccount, String> {
+        self.descriptor_accounts
+            .iter()
+            .find(|each| each.index == index)
+            .ok_or("descriptor account not found".to_string())
+    }
+
+    pub fn get_descriptor_account_mut(
+        &mut self,
+        index: AccountIndex,
+    ) -> Result<&mut DescriptorAccount, String> {
+        self.descriptor_accounts
+            .iter_mut()
+            .find(|each| each.index == index)
+            .ok_or("descriptor account not found".to_string())
+    }
+
+    pub fn next_descriptor_account_index(&self) -> AccountIndex {
+        self.descriptor_accounts
+            .iter()
+            .map(|a| a.index + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Stage a freshly built transaction, abandoning the previous draft.
+    pub fn stage_pending_tx(&mut self, built: BuildTxResult, now: u64) -> Result<(), String> {
+        self.abandon_pending_tx()?;
+        let account = self.get_active_account_mut()?;
+        self.pending_tx = Some(PendingTx::reserve(account, built, now));
+        Ok(())
+    }
+
+    /// Release the reservations of the pending transaction, if any.
+    pub fn abandon_pending_tx(&mut self) -> Result<bool, String> {
+        let Some(mut pending) = self.pending_tx.take() else {
+            return Ok(false);
+        };
+        pending.abandon(self.get_account_mut(pending.account)?);
+        Ok(true)
+    }
+
+    /// Drop the pending transaction once it outlived `PENDING_TX_TTL_SECS`.
+    pub fn expire_pending_tx(&mut self, now: u64) -> Result<(), String> {
+        if self.pending_tx.as_ref().is_some_and(|p| p.is_expired(now)) {
+            tracing::info!("releasing expired pending transaction");
+            self.abandon_pending_tx()?;
+        }
+        Ok(())
+    }
+
+    /// Mark coins mined at or above `fork_height` unconfirmed again after a
+    /// reorg. Returns the affected outpoints.
+    pub fn rollback_utxos(&mut self, fork_height: u32) -> Vec<OutPoint> {
+        let mut rolled_back = Vec::new();
+        let heights = self
+            .accounts
+            .iter_mut()
+            .flat_map(|a| a.utxo_set.entries.values_mut())
+            .map(|u| (u.outpoint(), &mut u.height))
+            .chain(
+                self.descriptor_accounts
+                    .iter_mut()
+                    .flat_map(|a| a.utxos.values_mut())
+                    .map(|u| (u.outpoint, &mut u.height)),
+            );
+        for (outpoint, height) in heights {
+            if *height >= fork_height && *height > 0 {
+                *height = 0;
+                rolled_back.push(outpoint);
+            }
+        }
+        rolled_back
+    }
+
+    pub fn get_draft(&self, id: u32) -> Result<&TxDraft, String> {
+        match self.drafts.iter().find(|d| d.id == id) {
+            Some(draft) => Ok(draft),
+            None => Err(self.missing_draft(id)),
+        }
+    }
+
+    pub fn get_draft_mut(&mut self, id: u32) -> Result<&mut TxDraft, String> {
+        let error = self.missing_draft(id);
+        self.drafts.iter_mut().find(|d| d.id == id).ok_or(error)
+    }
+
+    fn missing_draft(&self, id: u32) -> String {
+        match self.unreadable_drafts.iter().find(|d| d.id() == id) {
+            Some(d) => format!("draft {} can't be read: {}", d.name(), d.error),
+            None => "draft not found".to_string(),
+        }
+    }
+
+    pub fn next_draft_id(&self) -> u32 {
+        self.drafts
+            .iter()
+            .map(|d| d.id)
+            .chain(self.unreadable_drafts.iter().map(UnreadableDraft::id))
+            .map(|id| id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Keep a saved draft that failed to load, holding back its coins.
+    pub fn keep_unreadable_draft(&mut self, draft: UnreadableDraft) {
+        if let Ok(account) = self.get_account_mut(draft.account()) {
+            account.utxo_set.lock(draft.inputs());
+        }
+        self.unreadable_drafts.push(draft);
+    }
+
+    /// Delete a draft, an unreadable one too, releasing its reservations.
+    pub fn delete_draft(&mut self, id: u32) -> Result<(), String> {
+        let Some(position) = self.unreadable_drafts.iter().position(|d| d.id() == id) else {
+            return self.take_draft(id).map(|_| ());
+        };
+        let draft = self.unreadable_drafts.remove(position);
+        if let Ok(account) = self.get_account_mut(draft.account()) {
+            account.utxo_set.unlock(&draft.inputs());
+        }
+        Ok(())
+    }
+
+    /// Remove a draft and release its reservations.
+    pub fn take_draft(&mut self, id: u32) -> Result<TxDraft, String> {
+        let position = self
+            .drafts
+            .iter()
+            .position(|d| d.id == id)
+            .ok_or_else(|| self.missing_draft(id))?;
+        let draft = self.drafts.remove(position);
+        draft.tx.release(self.get_account_mut(draft.tx.account)?);
+        Ok(draft)
+    }
+
+    /// Store a draft and reserve its inputs and change path.
+    pub fn put_draft(&mut self, draft: TxDraft) -> Result<(), String> {
+        if self
+            .drafts
+            .iter()
+            .any(|d| d.id != draft.id && d.name == draft.name)
+            || self
+                .unreadable_drafts
+                .iter()
+                .any(|d| d.id() != draft.id && d.name() == draft.name)
+        {
+            return Err(format!("a draft named {} already exists", draft.name));
+        }
+        draft.tx.hold(self.get_account_mut(draft.tx.account)?);
+        let position = self.drafts.partition_point(|d| d.id < draft.id);
+        self.drafts.insert(position, draft);
+        Ok(())
+    }
+
     pub fn get_active_account_mut(&mut self) -> Result<&mut Account, String> {
         let active_index = self.active_account;
         self.accounts
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use bitcoin::{Transaction, TxOut, Txid};
+use serde::Serialize;
+use specta::Type;
+
+use crate::chain::btc::{providers::btc_node::BtcNode, utxo::Utxo};
+
+/// An incoming transaction paying less than this share of the current fee
+/// estimate may sit in the mempool long enough to be double-spent.
+pub const LOW_FEE_RATE_RATIO: f64 = 0.5;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ZeroConfRisk {
+    /// Confirmed.
+    #[default]
+    None,
+    /// Unconfirmed without warning signs, or our own change.
+    Low,
+    Medium,
+    /// Likely to be replaced or already conflicting.
+    High,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RiskReason {
+    /// Opts in to replace-by-fee (BIP125).
+    SignalsRbf,
+    /// Spends outputs of transactions that aren't confirmed either.
+    UnconfirmedParents,
+    LowFeeRate {
+        fee_rate_sat_vb: f64,
+        estimate_sat_vb: f64,
+    },
+    /// Another transaction spends one of the same inputs.
+    ConflictingSpend { tx_id: String },
+    /// The transaction couldn't be looked up.
+    Unverified,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Type)]
+pub struct ZeroConfAssessment {
+    pub risk: ZeroConfRisk,
+    pub reasons: Vec<RiskReason>,
+}
+
+/// Mempool view of an unconfirmed transaction as served by the backend.
+#[derive(Debug, Clone)]
+pub struct MempoolTx {
+    pub tx: Transaction,
+    /// Output spent by each input and whether its transaction is confirmed.
+    pub prevouts: Vec<(TxOut, bool)>,
+    /// Other mempool transactions spending one of the same inputs.
+    pub conflicts: Vec<Txid>,
+}
+
+impl MempoolTx {
+    pub fn fee_rate_sat_vb(&self) -> Option<f64> {
+        let input: u64 = self.prevouts.iter().map(|(o, _)| o.value.to_sat()).sum();
+        let output: u64 = self.tx.output.iter().map(|o| o.value.to_sat()).sum();
+        let fee = input.checked_sub(output)?;
+        Some(fee as f64 / self.tx.vsize() as f64)
+    }
+}
+
+impl ZeroConfAssessment {
+    pub fn low() -> Self {
+        Self {
+            risk: ZeroConfRisk::Low,
+            reasons: Vec::new(),
+        }
+    }
+
+    pub fn unverified() -> Self {
+        Self {
+            risk: ZeroConfRisk::Medium,
+            reasons: vec![RiskReason::Unverified],
+        }
+    }
+
+    /// Rate an incoming unconfirmed transaction. A conflicting spend is high
+    /// risk on its own, otherwise each warning sign raises the level.
+    pub fn assess(mempool_tx: &MempoolTx, fee_estimate_sat_vb: Option<f64>) -> Self {
+        let mut reasons = Vec::new();
+        if mempool_tx.tx.is_explicitly_rbf() {
+            reasons.push(RiskReason::SignalsRbf);
+        }
+        if mempool_tx.prevouts.iter().any(|(_, confirmed)| !confirmed) {
+            reasons.push(RiskReason::UnconfirmedParents);
+        }
+        if let (Some(fee_rate), Some(estimate)) =
+            (mempool_tx.fee_rate_sat_vb(), fee_estimate_sat_vb)
+            && fee_rate < estimate * LOW_FEE_RATE_RATIO
+        {
+            reasons.push(RiskReason::LowFeeRate {
+                fee_rate_sat_vb: fee_rate,
+                estimate_sat_vb: estimate,
+            });
+        }
+        let conflicting = !mempool_tx.conflicts.is_empty();
+        reasons.extend(
+            mempool_tx
+                .conflicts
+                .iter()
+                .map(|txid| RiskReason::ConflictingSpend {
+                    tx_id: txid.to_string(),
+                }),
+        );
+
+        let risk = match reasons.len() {
+            _ if conflicting => ZeroConfRisk::High,
+            0 => ZeroConfRisk::Low,
+            1 => ZeroConfRisk::Medium,
+            _ => ZeroConfRisk::High,
+        };
+        Self { risk, reasons }
+    }
+
+    /// Too risky for automatic coin selection.
+    pub fn is_risky(&self) -> bool {
+        self.risk >= ZeroConfRisk::Medium
+    }
+}
+
+/// Assess the transactions that created the given unconfirmed coins.
+pub async fn assess_incoming(
+    server: &BtcNode,
+    utxos: &[&Utxo],
+    fee_estimate_sat_vb: Option<f64>,
+) -> HashMap<Txid, ZeroConfAssessment> {
+    let mut assessed = HashMap::new();
+    for utxo in utxos {
+        if assessed.contains_key(&utxo.tx_id) {
+            continue;
+        }
+        let assessment = match server.mempool_tx(utxo.tx_id).await {
+            Ok(mempool_tx) => ZeroConfAssessment::assess(&mempool_tx, fee_estimate_sat_vb),
+            Err(e) => {
+                tracing::warn!(txid = %utxo.tx_id, "can't assess unconfirmed tx: {e}");
+                ZeroConfAssessment::unverified()
+            }
+        };
+        assessed.insert(utxo.tx_id, assessment);
+    }
+    assessed
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        Amount, OutPoint, ScriptBuf, Sequence, TxIn, Witness, absolute::LockTime, hashes::Hash,
+        transaction::Version,
+    };
+
+    use super::*;
+    use crate::chain::btc::{
+        account::{Account, UtxoSelectionStrategy},
+        key_derivation::{Change, KeyDerivationPath, Proposal},
+    };
+
+    fn mempool_tx(sequence: Sequence, fee: u64, parent_confirmed: bool) -> MempoolTx {
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(100_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        MempoolTx {
+            tx,
+            prevouts: vec![(
+                TxOut {
+                    value: Amount::from_sat(100_000 + fee),
+                    script_pubkey: ScriptBuf::new(),
+                },
+                parent_confirmed,
+            )],
+            conflicts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn final_well_paid_tx_is_low_risk() {
+        let tx = mempool_tx(Sequence::MAX, 10_000, true);
+        let assessment = ZeroConfAssessment::assess(&tx, Some(5.0));
+        assert_eq!(assessment.risk, ZeroConfRisk::Low);
+        assert!(!assessment.is_risky());
+    }
+
+    #[test]
+    fn each_warning_sign_raises_risk() {
+        let rbf = mempool_tx(Sequence::ENABLE_RBF_NO_LOCKTIME, 10_000, true);
+        let assessment = ZeroConfAssessment::assess(&rbf, Some(5.0));
+        assert_eq!(assessment.risk, ZeroConfRisk::Medium);
+        assert_eq!(assessment.reasons, vec![RiskReason::SignalsRbf]);
+
+        let cheap_child = mempool_tx(Sequence::ENABLE_RBF_NO_LOCKTIME, 10, false);
+        let assessment = ZeroConfAssessment::assess(&cheap_child, Some(5.0));
+        assert_eq!(assessment.risk, ZeroConfRisk::High);
+        assert_eq!(assessment.reasons.len(), 3);
+    }
+
+    #[test]
+    fn conflict_is_high_risk() {
+        let mut tx = mempool_tx(Sequence::MAX, 10_000, true);
+        tx.conflicts.push(Txid::all_zeros());
+        let assessment = ZeroConfAssessment::assess(&tx, None);
+        assert_eq!(assessment.risk, ZeroConfRisk::High);
+    }
+
+    #[test]
+    fn auto_selection_skips_risky_incoming_coins() {
+        let mut account = Account::new(bitcoin::Network::Regtest, 0, "main".to_string());
+        let utxo = |byte: u8, change: Change, height: u32| Utxo {
+            tx_id: Txid::from_byte_array([byte; 32]),
+            vout: 0,
+            output: TxOut {
+                value: Amount::from_sat(10_000 * byte as u64),
+                script_pubkey: ScriptBuf::new(),
+            },
+            derivation: KeyDerivationPath::new(
+                Proposal::Taproot,
+                bitcoin::Network::Regtest,
+                0,
+                change,
+                0,
+            ),
+            height,
+            silent_payment: None,
+        };
+        account.utxo_set.replace_all(vec![
+            utxo(1, Change::External, 100),
+            utxo(2, Change::Internal, 0),
+            utxo(3, Change::External, 0),
+            utxo(4, Change::External, 0),
+        ]);
+        account
+            .utxo_set
+            .zero_conf
+            .insert(Txid::from_byte_array([3; 32]), ZeroConfAssessment::low());
+
+        let selected = |target, allow_risky| {
+            let mut ids: Vec<u8> = account
+                .utxo_set
+                .select(UtxoSelectionStrategy::Auto(target), allow_risky)
+                .iter()
+                .map(|u| u.tx_id.to_byte_array()[0])
+                .collect();
+            ids.sort();
+            ids
+        };
+        // The unassessed coin 4 only goes in when explicitly allowed.
+        assert_eq!(selected(60_000, false), vec![1, 2, 3]);
+        assert!(selected(100_000, false).is_empty());
+        assert_eq!(selected(100_000, true), vec![1, 2, 3, 4]);
+    }
+}
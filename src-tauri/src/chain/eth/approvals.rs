@@ -0,0 +1,299 @@
+//! Allowances the account granted with `approve`.
+//!
+//! `Approval` logs name every `(token, spender)` pair the account ever
+//! approved. The pairs are kept, while the allowances themselves are read
+//! from the token contracts: `transferFrom` lowers them without an event.
+//! The first scan starts at the block of the account's first transaction,
+//! no `approve` of it can be older. When the node can't tell that block the
+//! scan is partial, its oldest block is kept and older ones can be scanned
+//! on request.
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+    rpc::types::Filter,
+    sol_types::SolEvent,
+};
+use alloy_provider::DynProvider;
+
+use crate::{
+    config::BlockChain,
+    eth::{
+        EvmClient,
+        erc20_retriver::Erc20Contract,
+        indexer::{AdaptiveRange, floor_scope, older_range, scan_start},
+        wallet::parse_addres,
+    },
+    repository::{ApprovalRepository, CheckpointKey, CheckpointRepository, TxRepository},
+};
+
+const APPROVALS_SCOPE: &str = "approvals";
+/// How far back the first scan goes when the node can't tell when the account
+/// first sent a transaction, or it never did and only `permit` could approve.
+const FIRST_SCAN_DEPTH: u64 = 1_000_000;
+/// Some tokens store allowances as `uint96` and cap `type(uint256).max` there.
+const UINT96_MAX: U256 = U256::from_limbs([u64::MAX, u32::MAX as u64, 0, 0]);
+
+/// Whether `allowance` lets the spender take any amount: `type(uint256).max`,
+/// or what's left of it after spending, or the `uint96` cap.
+pub fn is_unlimited(allowance: U256) -> bool {
+    allowance >= U256::MAX >> 1 || allowance == UINT96_MAX
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ApprovalLog {
+    token: Address,
+    spender: Address,
+    tx_hash: String,
+    block: u64,
+}
+
+async fn approval_logs(
+    provider: &DynProvider,
+    owner: Address,
+    from: u64,
+    to: u64,
+) -> Result<Vec<ApprovalLog>, String> {
+    let filter = Filter::new()
+        .event_signature(Erc20Contract::Approval::SIGNATURE_HASH)
+        .topic1(owner.into_word())
+        .from_block(from)
+        .to_block(to);
+    let logs = provider
+        .get_logs(&filter)
+        .await
+        .map_err(|e| format!("eth_getLogs failed: {e}"))?;
+    Ok(logs
+        .iter()
+        .filter(|log| !log.removed)
+        .filter_map(|log| {
+            // ERC-721 shares the signature with an indexed token id, which
+            // doesn't decode as an ERC-20 approval.
+            let decoded = log.log_decode::<Erc20Contract::Approval>().ok()?;
+            Some(ApprovalLog {
+                token: decoded.inner.address,
+                spender: decoded.inner.data.spender,
+                tx_hash: log.transaction_hash?.to_string(),
+                block: log.block_number?,
+            })
+        })
+        .collect())
+}
+
+/// Lowest block up to `latest` at which `nonce_at` is above zero, `None` when
+/// it's zero at `latest` too.
+async fn first_sent_block<F, Fut>(latest: u64, nonce_at: F) -> Result<Option<u64>, String>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<u64, String>>,
+{
+    if nonce_at(latest).await? == 0 {
+        return Ok(None);
+    }
+    let (mut low, mut high) = (0, latest);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if nonce_at(mid).await? > 0 {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(Some(low))
+}
+
+pub struct ApprovalScanner<'a> {
+    pub client: &'a EvmClient,
+    pub approvals: &'a ApprovalRepository,
+    pub checkpoints: &'a CheckpointRepository,
+    pub history: &'a TxRepository,
+}
+
+impl ApprovalScanner<'_> {
+    /// Every `(token, spender)` pair `owner` approved on the network, after
+    /// scanning the blocks since the last run.
+    pub async fn scan(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+    ) -> Result<Vec<(Address, Address)>, String> {
+        let address = owner.to_string();
+        let floor = floor_scope(APPROVALS_SCOPE);
+        let key = self.key(wallet_name, &address, APPROVALS_SCOPE);
+        let latest = self
+            .client
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| format!("failed to get block number: {e}"))?;
+        let from = match self.checkpoints.get(&key)? {
+            Some(checkpoint) => scan_start(Some(checkpoint), latest, FIRST_SCAN_DEPTH),
+            None => {
+                let (from, partial) = self.first_scan_block(owner, latest).await;
+                if partial {
+                    self.checkpoints
+                        .set(&self.key(wallet_name, &address, &floor), from)?;
+                }
+                from
+            }
+        };
+        self.scan_range(wallet_name, owner, from, latest, Some(&key))
+            .await?;
+
+        self.approvals
+            .list(wallet_name, &self.client.network.id, &address)?
+            .iter()
+            .map(|(token, spender)| Ok((parse_addres(token)?, parse_addres(spender)?)))
+            .collect()
+    }
+
+    /// Oldest block scanned when the scan didn't reach the account's first
+    /// transaction, `None` once it did.
+    pub fn scanned_from(&self, wallet_name: &str, owner: Address) -> Result<Option<u64>, String> {
+        let address = owner.to_string();
+        let floor = floor_scope(APPROVALS_SCOPE);
+        Ok(self
+            .checkpoints
+            .get(&self.key(wallet_name, &address, &floor))?
+            .filter(|block| *block > 0))
+    }
+
+    /// Scan [`FIRST_SCAN_DEPTH`] blocks below the oldest one scanned so far.
+    pub async fn scan_older(&self, wallet_name: &str, owner: Address) -> Result<(), String> {
+        let address = owner.to_string();
+        let floor = floor_scope(APPROVALS_SCOPE);
+        let floor_key = self.key(wallet_name, &address, &floor);
+        let Some((from, to)) = self
+            .checkpoints
+            .get(&floor_key)?
+            .and_then(|block| older_range(block, FIRST_SCAN_DEPTH))
+        else {
+            return Ok(());
+        };
+        self.scan_range(wallet_name, owner, from, to, None).await?;
+        self.checkpoints.set(&floor_key, from)
+    }
+
+    /// Record the approvals between `from` and `to`, moving `checkpoint` along.
+    async fn scan_range(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+        mut from: u64,
+        to: u64,
+        checkpoint: Option<&CheckpointKey<'_>>,
+    ) -> Result<(), String> {
+        let network = &self.client.network.id;
+        let address = owner.to_string();
+        let mut range = AdaptiveRange::default();
+        while from <= to {
+            let end = range.end(from, to);
+            let logs = match approval_logs(&self.client.provider, owner, from, end).await {
+                Ok(logs) => logs,
+                Err(e) if range.shrink() => {
+                    tracing::debug!("{network}: shrinking approval log range: {e}");
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let pairs: Vec<_> = logs
+                .iter()
+                .map(|l| (l.token.to_string(), l.spender.to_string()))
+                .collect();
+            self.approvals.add(wallet_name, network, &address, &pairs)?;
+            self.confirm_sent(wallet_name, &logs)?;
+            if let Some(key) = checkpoint {
+                self.checkpoints.set(key, end)?;
+            }
+            range.grow();
+            from = end + 1;
+        }
+        Ok(())
+    }
+
+    fn key<'k>(
+        &'k self,
+        wallet_name: &'k str,
+        address: &'k str,
+        scope: &'k str,
+    ) -> CheckpointKey<'k> {
+        CheckpointKey {
+            wallet_name,
+            chain: BlockChain::Ethereum,
+            network: &self.client.network.id,
+            address,
+            scope,
+        }
+    }
+
+    /// Block of the first transaction of `owner`, found by bisecting its
+    /// nonce, which needs a node keeping historical state. Flagged partial
+    /// when the bisect failed and the scan falls back to recent blocks.
+    async fn first_scan_block(&self, owner: Address, latest: u64) -> (u64, bool) {
+        let provider = &self.client.provider;
+        let nonce_at = |block| async move {
+            provider
+                .get_transaction_count(owner)
+                .number(block)
+                .await
+                .map_err(|e| format!("failed to get nonce at block {block}: {e}"))
+        };
+        match first_sent_block(latest, nonce_at).await {
+            Ok(Some(block)) => (block, false),
+            Ok(None) => (latest.saturating_sub(FIRST_SCAN_DEPTH), false),
+            Err(e) => {
+                tracing::warn!(
+                    "{}: scanning recent approvals only: {e}",
+                    self.client.network.id
+                );
+                (latest.saturating_sub(FIRST_SCAN_DEPTH), true)
+            }
+        }
+    }
+
+    /// `approve` calls leave no `Transfer` log for the indexer, so the ones
+    /// the wallet sent are confirmed here.
+    fn confirm_sent(&self, wallet_name: &str, logs: &[ApprovalLog]) -> Result<(), String> {
+        for log in logs {
+            self.history.confirm_pending(
+                wallet_name,
+                BlockChain::Ethereum,
+                &log.tx_hash,
+                log.block,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_allowances() {
+        assert!(is_unlimited(U256::MAX));
+        assert!(is_unlimited(U256::MAX - U256::from(1_000_000u64)));
+        assert!(is_unlimited(UINT96_MAX));
+        assert_eq!(UINT96_MAX, (U256::from(1) << 96) - U256::from(1));
+
+        assert!(!is_unlimited(U256::ZERO));
+        assert!(!is_unlimited(U256::from(1_000_000_000_000u64)));
+        assert!(!is_unlimited(UINT96_MAX - U256::from(1)));
+    }
+
+    #[tokio::test]
+    async fn first_scan_starts_at_the_first_transaction() {
+        let nonce_at = |block: u64| async move { Ok(if block >= 1_234 { 3 } else { 0 }) };
+        assert_eq!(first_sent_block(10_000, nonce_at).await, Ok(Some(1_234)));
+        assert_eq!(first_sent_block(1_000, nonce_at).await, Ok(None));
+        assert_eq!(first_sent_block(1_234, nonce_at).await, Ok(Some(1_234)));
+
+        let active_from_genesis = |_| async { Ok(1) };
+        assert_eq!(
+            first_sent_block(500, active_from_genesis).await,
+            Ok(Some(0))
+        );
+        let failing = |_| async { Err("no state".to_string()) };
+        assert!(first_sent_block(500, failing).await.is_err());
+    }
+}
@@ -1,28 +1,52 @@
+use std::collections::HashMap;
+
 use alloy::{
     eips::{BlockId, BlockNumberOrTag},
-    primitives::utils::{format_units, parse_units},
+    primitives::{
+        U256, hex,
+        utils::{format_units, parse_ether, parse_units},
+    },
     providers::Provider,
 };
-use alloy_provider::{DynProvider, ext::AnvilApi};
+use alloy_provider::ext::AnvilApi;
+use chrono::Utc;
 use specta::specta;
 
 use crate::{
     chain_trait::{AssetTracker, SecureKey},
-    config::BlockChain,
+    config::{BlockChain, Config},
     eth::{
-        self, PriceFeed,
-        constants::{ETH, ETH_USD_PRICE_FEED},
+        self, EvmClient, EvmProviders, NonceManager,
+        approvals::{ApprovalScanner, is_unlimited},
+        constants::NATIVE_TOKEN,
+        contract::{self, ContractAbi},
         dtos::{
-            EthereumActiveAccountView, NetworkStatus, TokenBalance, TrackedTokenInfo,
-            TransferEstimation, TransferRequest, WalletBalance,
+            AbiValueView, BalanceChange, ContractCallRequest, ContractSummary, ContractView,
+            EthereumActiveAccountView, EvmNetworkView, FunctionKind, HistorySync, NetworkStatus,
+            NftCollectionView, NftTransferRequest, NftView, SignPreview, SignRequest,
+            SignatureCheck, SignedPayload, SimulationResult, TokenApproval, TokenApprovals,
+            TokenBalance, TrackedTokenInfo, TransferEstimation, TransferRequest, TransferResult,
+            WalletBalance,
         },
-        erc20_retriver::Erc20Retriever,
-        transfer_builder::TransferPayload,
+        ens::{self, EnsNames},
+        fee_estimator::{FeeEstimator, FeeMode},
+        indexer::{IndexTarget, Indexer, NativeSource},
+        nft::{NftItem, NftRetriever, NftScanner},
+        nonce_manager::NonceState,
+        replacement::{OriginalTx, Replacement, TRANSFER_GAS, bump_fees, noop},
+        signing::{Payload, SignContext},
+        simulation::{Delta, ForkSource, Simulator, shortfall},
+        token::Token,
+        transfer_builder::{TransactionMetadata, TransferPayload, sign_and_send},
         wallet::parse_addres,
     },
-    repository::{EthChainData, NewTx, TxDirection, TxRepository, TxStatus},
+    repository::{
+        AbiRepository, ApprovalRepository, CheckpointRepository, EthChainData, NewTx,
+        NftRepository, StoredAbi, TxDirection, TxRepository, TxStatus,
+    },
     session::SK,
     utils,
+    wallet::Wallet,
 };
 
 #[specta]
@@ -39,6 +63,53 @@ pub async fn ethereum_account_info(
     Ok(EthereumActiveAccountView {
         index: account.index,
         address: prk.expose().address().to_string(),
+        network: account.network.clone(),
+    })
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_evm_networks", skip_all, err)]
+pub async fn list_evm_networks(sk: tauri::State<'_, SK>) -> Result<Vec<EvmNetworkView>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let active = &wallet.eth.active_account()?.network;
+
+    Ok(wallet
+        .config
+        .eth
+        .networks
+        .iter()
+        .map(|n| EvmNetworkView {
+            id: n.id.clone(),
+            name: n.name.clone(),
+            chain_id: n.chain_id,
+            native_symbol: n.native_symbol.clone(),
+            active: n.id == *active,
+        })
+        .collect())
+}
+
+/// Move the active account to another network once one of its endpoints has
+/// answered with the expected chain id.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "switch_eth_network", skip_all, err)]
+pub async fn switch_eth_network(
+    network: String,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+) -> Result<EthereumActiveAccountView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    providers.client(&wallet.config, &network).await?;
+    wallet.mutate_eth(|eth| eth.switch_network(&network))?;
+
+    let account = wallet.eth.active_account()?;
+    Ok(EthereumActiveAccountView {
+        index: account.index,
+        address: wallet.eth.prk()?.expose().address().to_string(),
+        network: account.network.clone(),
     })
 }
 
@@ -46,9 +117,16 @@ pub async fn ethereum_account_info(
 #[tauri::command]
 #[tracing::instrument(name = "get_network_status", skip_all, err)]
 pub async fn get_network_status(
-    provider: tauri::State<'_, DynProvider>,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
 ) -> Result<NetworkStatus, String> {
-    let block = provider
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+    let block = client
+        .provider
         .get_block(BlockId::Number(BlockNumberOrTag::Latest))
         .await
         .map_err(|e| e.to_string())?;
@@ -57,6 +135,8 @@ pub async fn get_network_status(
     }
     let block = block.unwrap();
     Ok(NetworkStatus {
+        network: client.network.id.clone(),
+        chain_id: client.network.chain_id,
         block_number: block.header.number.to_string(),
         block_hash: block.header.hash.to_string(),
         base_fee_per_gas: block.header.base_fee_per_gas.map(|fee| fee.to_string()),
@@ -68,21 +148,25 @@ pub async fn get_network_status(
 #[tracing::instrument(name = "get_wallet_balance", skip_all, err)]
 pub async fn get_wallet_balance(
     address: String,
-    provider: tauri::State<'_, DynProvider>,
-    erc20_retriever: tauri::State<'_, Erc20Retriever>,
+    providers: tauri::State<'_, EvmProviders>,
+    ens_names: tauri::State<'_, EnsNames>,
     sk: tauri::State<'_, SK>,
 ) -> Result<WalletBalance, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
 
-    let provider = provider.inner();
     let address = parse_addres(&address)?;
-    let wei_balance = provider
+    let wei_balance = client
+        .provider
         .get_balance(address)
         .await
         .map_err(|e| e.to_string())?;
 
-    let token_balances = erc20_retriever
+    let token_balances = client
+        .erc20()
         .balances(address, wallet.eth.active_tracked_tokens()?.to_vec())
         .await
         .map_err(|e| e.to_string())?;
@@ -96,20 +180,26 @@ pub async fn get_wallet_balance(
             address: b.token.address.to_string(),
         })
         .collect();
-    let eth = eth::constants::ETH.clone();
+    let native = client.network.native_token();
 
-    let eth_balance = format_units(wei_balance, "ether").map_err(|e| e.to_string())?;
+    let native_balance = format_units(wei_balance, "ether").map_err(|e| e.to_string())?;
     token_balances.push(TokenBalance {
-        balance: eth_balance,
-        symbol: eth.symbol.clone(),
-        decimals: eth.decimals,
-        address: eth.address.to_string(),
+        balance: native_balance,
+        symbol: native.symbol.clone(),
+        decimals: native.decimals,
+        address: native.address.to_string(),
     });
 
+    let ens_name = ens_names
+        .lookup_for(&providers, &wallet.config, &client.network, [address])
+        .await
+        .remove(&address);
+
     wallet.persist()?;
     Ok(WalletBalance {
         wei: wei_balance.to_string(),
         tokens: token_balances,
+        ens_name,
     })
 }
 
@@ -118,9 +208,11 @@ pub async fn get_wallet_balance(
 #[tracing::instrument(name = "estimate_transfer", skip_all, err)]
 pub async fn estimate_transfer(
     req: TransferRequest,
-    tx_builder: tauri::State<'_, tokio::sync::Mutex<eth::TxBuilder>>,
+    tx_builder: tauri::State<'_, tokio::sync::Mutex<Option<eth::TxBuilder>>>,
     sk: tauri::State<'_, SK>,
-    price_feed: tauri::State<'_, PriceFeed>,
+    providers: tauri::State<'_, EvmProviders>,
+    nonces: tauri::State<'_, NonceManager>,
+    tx_repository: tauri::State<'_, TxRepository>,
 ) -> Result<TransferEstimation, String> {
     let TransferRequest {
         amount,
@@ -132,10 +224,14 @@ pub async fn estimate_transfer(
     let wallet = sk.wallet()?;
     let prk = wallet.eth.prk()?;
     let sender = prk.expose().address();
-    let recipient = parse_addres(&recipient)?;
     let token_address = parse_addres(&token_address)?;
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+    let recipient =
+        ens::resolve_recipient(&providers, &wallet.config, &client.network, &recipient).await?;
 
-    let token = if token_address != ETH.address {
+    let token = if token_address != NATIVE_TOKEN {
         let tracked_tokens = wallet.eth.active_tracked_tokens()?;
         if let Some(t) = tracked_tokens.iter().find(|t| t.address == token_address) {
             t.clone()
@@ -146,31 +242,58 @@ pub async fn estimate_transfer(
             ));
         }
     } else {
-        ETH.clone()
+        client.network.native_token()
     };
 
+    let recorded =
+        tx_repository.pending_eth_nonces(&wallet.name, &client.network.id, &sender.to_string())?;
+    let nonce = nonces.state(&client, sender, &recorded).await?;
+
     let mut builder = tx_builder.try_lock().map_err(|e| e.to_string())?;
+    let builder = builder.insert(
+        eth::TxBuilder::new(client.batched.clone(), client.chain_id())
+            .with_nonce(nonce.next)
+            .with_recipient(&recipient),
+    );
     let res = builder
         .create_transfer(TransferPayload {
             token,
             raw_amount: amount,
             sender,
-            recipient,
+            recipient: recipient.address,
             fee_mode,
+            nft: None,
         })
         .await
         .map_err(|e| e.to_string())?;
+    transfer_estimation(&client, builder, &wallet.config, res, &nonce).await
+}
 
-    let eth_price = price_feed.get_price(ETH_USD_PRICE_FEED).await?;
-
-    let fee_in_eth = format_units(res.fee_ceiling, "ether").map_err(|e| e.to_string())?;
-    let eth_price_f64: f64 = eth_price
-        .parse()
-        .map_err(|_| "Failed to parse ETH price".to_string())?;
-    let fee_in_eth_f64: f64 = fee_in_eth
-        .parse()
-        .map_err(|_| "Failed to parse fee".to_string())?;
-    let fee_in_usd = eth_price_f64 * fee_in_eth_f64;
+async fn transfer_estimation(
+    client: &EvmClient,
+    builder: &eth::TxBuilder,
+    config: &Config,
+    res: TransactionMetadata,
+    nonce: &NonceState,
+) -> Result<TransferEstimation, String> {
+    let simulation = simulate_pending(client, builder, config).await;
+    let (_, meta) = builder.pending().ok_or("Transaction not prepared")?;
+    // Networks without a price feed show no fiat value.
+    let fee_in_usd = match client.network.native_usd_feed() {
+        Some(feed) => {
+            let native_price = client.price_feed().get_price(feed).await?;
+            let fee_in_native =
+                format_units(res.fee_ceiling, "ether").map_err(|e| e.to_string())?;
+            let native_price_f64: f64 = native_price
+                .parse()
+                .map_err(|_| "Failed to parse native currency price".to_string())?;
+            let fee_in_native_f64: f64 = fee_in_native
+                .parse()
+                .map_err(|_| "Failed to parse fee".to_string())?;
+            native_price_f64 * fee_in_native_f64
+        }
+        None => 0.0,
+    };
 
     let fee_ceiling_u64 = res.fee_ceiling.saturating_to::<u64>() / 10u64.pow(9);
     Ok(TransferEstimation {
@@ -178,25 +301,303 @@ pub async fn estimate_transfer(
         max_fee_per_gas: res.estimator.max_fee_per_gas.to_string(),
         fee_ceiling: fee_ceiling_u64.to_string(),
         fee_in_usd,
+        nonce: nonce.next.to_string(),
+        queued: nonce.queued.len() as u32,
+        nonce_gaps: nonce.gaps.iter().map(|n| n.to_string()).collect(),
+        nonce_dropped: nonce.dropped.iter().map(|n| n.to_string()).collect(),
+        simulation,
+        recipient: meta.payload.recipient.to_string(),
+        recipient_name: meta.recipient_name.clone(),
+        recipient_warning: meta.recipient_warning.clone(),
+    })
+}
+
+/// Dry run of the transaction prepared in `builder`. Failing to run it
+/// doesn't hold the transfer back, it's only logged.
+async fn simulate_pending(
+    client: &EvmClient,
+    builder: &eth::TxBuilder,
+    config: &Config,
+) -> Option<SimulationResult> {
+    let (tx, meta) = builder.pending()?;
+    let payload = &meta.payload;
+    let simulation = Simulator {
+        client,
+        fork: ForkSource::new(config, &client.network),
+    }
+    .run(tx, &[payload.sender, payload.recipient])
+    .await
+    .inspect_err(|e| tracing::warn!("{}: simulation failed: {e}", client.network.id))
+    .ok()?;
+
+    let mut warnings = Vec::new();
+    let plain_transfer = !meta.approval
+        && meta.call.is_none()
+        && payload.nft.is_none()
+        && payload.sender != payload.recipient;
+    if let (Some(deltas), true) = (&simulation.deltas, plain_transfer) {
+        let sent = parse_units(&payload.raw_amount, payload.token.decimals)
+            .map(|u| u.get_absolute())
+            .unwrap_or_default();
+        let missing = shortfall(deltas, payload.recipient, payload.token.address, sent);
+        if !missing.is_zero() {
+            warnings.push(format!(
+                "The recipient gets {} {} less than sent, the token takes a fee on transfers",
+                format_units(missing, payload.token.decimals).unwrap_or_default(),
+                payload.token.symbol
+            ));
+        }
+    }
+    let balance_changes = match simulation.deltas {
+        Some(deltas) => Some(balance_changes(client, payload, deltas).await),
+        None if simulation.revert.is_none() => {
+            warnings.push("The node can't trace transactions, balance changes are unknown".into());
+            None
+        }
+        None => None,
+    };
+    Some(SimulationResult {
+        source: simulation.source,
+        revert_reason: simulation.revert,
+        balance_changes,
+        warnings,
+    })
+}
+
+async fn balance_changes(
+    client: &EvmClient,
+    payload: &TransferPayload,
+    deltas: Vec<Delta>,
+) -> Vec<BalanceChange> {
+    let mut tokens = HashMap::from([
+        (NATIVE_TOKEN, client.network.native_token()),
+        (payload.token.address, payload.token.clone()),
+    ]);
+    let mut changes = Vec::new();
+    for delta in deltas {
+        let token = match tokens.get(&delta.asset) {
+            Some(token) => token.clone(),
+            None => {
+                let token = client
+                    .erc20()
+                    .token_info(delta.asset)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("no metadata for token {}: {e}", delta.asset);
+                        Token::new(delta.asset, "???".to_string(), 0)
+                    });
+                tokens.insert(delta.asset, token.clone());
+                token
+            }
+        };
+        let amount = format_units(delta.amount.unsigned_abs(), token.decimals).unwrap_or_default();
+        changes.push(BalanceChange {
+            address: delta.holder.to_string(),
+            is_sender: delta.holder == payload.sender,
+            token_address: token.address.to_string(),
+            symbol: token.symbol,
+            amount: if delta.amount.is_negative() {
+                format!("-{amount}")
+            } else {
+                format!("+{amount}")
+            },
+        });
+    }
+    changes
+}
+
+/// Allowances the active account granted on its network, unlimited ones
+/// first.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_token_approvals", skip_all, err)]
+pub async fn list_token_approvals(
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    checkpoints: tauri::State<'_, CheckpointRepository>,
+    approvals: tauri::State<'_, ApprovalRepository>,
+) -> Result<TokenApprovals, String> {
+    let (wallet_name, owner, tracked, client, explorer) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let account = wallet.eth.active_account()?;
+        let client = providers.client(&wallet.config, &account.network).await?;
+        (
+            wallet.name.clone(),
+            wallet.eth.prk()?.expose().address(),
+            account.tokens().to_vec(),
+            client,
+            wallet.config.eth.explorer(&account.network),
+        )
+    };
+    // The first scan reaches far back, the session stays usable meanwhile.
+    let scanner = ApprovalScanner {
+        client: &client,
+        approvals: &approvals,
+        checkpoints: &checkpoints,
+        history: &tx_repository,
+    };
+    let pairs = scanner.scan(&wallet_name, owner).await?;
+    let scanned_from = scanner.scanned_from(&wallet_name, owner)?;
+
+    let erc20 = client.erc20();
+    let allowances = erc20.allowances(owner, &pairs).await;
+    let mut tokens: HashMap<_, _> = tracked.into_iter().map(|t| (t.address, t)).collect();
+    let mut views = Vec::new();
+    for ((token_address, spender), allowance) in pairs.into_iter().zip(allowances) {
+        let allowance = allowance.inspect_err(|e| tracing::warn!("{e}"));
+        if allowance.as_ref().is_ok_and(|a| a.is_zero()) {
+            continue;
+        }
+        let token = match tokens.get(&token_address) {
+            Some(token) => token.clone(),
+            None => {
+                let token = erc20.token_info(token_address).await.unwrap_or_else(|e| {
+                    tracing::warn!("no metadata for token {token_address}: {e}");
+                    Token::new(token_address, "???".to_string(), 0)
+                });
+                tokens.insert(token_address, token.clone());
+                token
+            }
+        };
+        views.push(TokenApproval {
+            token_address: token.address.to_string(),
+            token_symbol: token.symbol,
+            token_decimals: token.decimals,
+            spender: spender.to_string(),
+            spender_url: explorer
+                .as_ref()
+                .and_then(|e| e.address_url(&spender.to_string())),
+            allowance: match &allowance {
+                Ok(a) => format_units(*a, token.decimals).map_err(|e| e.to_string())?,
+                Err(_) => String::new(),
+            },
+            unlimited: allowance.as_ref().is_ok_and(|a| is_unlimited(*a)),
+            error: allowance.err(),
+        });
+    }
+    views.sort_by_key(|v| !v.unlimited);
+    Ok(TokenApprovals {
+        approvals: views,
+        scanned_from: scanned_from.map(|b| b.to_string()),
     })
 }
 
+/// Scan for approvals older than the oldest block scanned, when the first
+/// scan couldn't find the account's first transaction.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "scan_older_token_approvals", skip_all, err)]
+pub async fn scan_older_token_approvals(
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    checkpoints: tauri::State<'_, CheckpointRepository>,
+    approvals: tauri::State<'_, ApprovalRepository>,
+) -> Result<(), String> {
+    let (wallet_name, owner, client) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let account = wallet.eth.active_account()?;
+        let client = providers.client(&wallet.config, &account.network).await?;
+        (
+            wallet.name.clone(),
+            wallet.eth.prk()?.expose().address(),
+            client,
+        )
+    };
+    ApprovalScanner {
+        client: &client,
+        approvals: &approvals,
+        checkpoints: &checkpoints,
+        history: &tx_repository,
+    }
+    .scan_older(&wallet_name, owner)
+    .await
+}
+
+/// Prepare an `approve(spender, 0)` call, sent with [`execute_transfer`].
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "estimate_revoke", skip_all, err)]
+pub async fn estimate_revoke(
+    token_address: String,
+    spender: String,
+    fee_mode: FeeMode,
+    tx_builder: tauri::State<'_, tokio::sync::Mutex<Option<eth::TxBuilder>>>,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    nonces: tauri::State<'_, NonceManager>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<TransferEstimation, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let sender = wallet.eth.prk()?.expose().address();
+    let token_address = parse_addres(&token_address)?;
+    let spender = parse_addres(&spender)?;
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+    let token = match wallet.eth.get_tracked_token(token_address) {
+        Some(token) => token.clone(),
+        None => client.erc20().token_info(token_address).await?,
+    };
+
+    let recorded =
+        tx_repository.pending_eth_nonces(&wallet.name, &client.network.id, &sender.to_string())?;
+    let nonce = nonces.state(&client, sender, &recorded).await?;
+
+    let mut builder = tx_builder.try_lock().map_err(|e| e.to_string())?;
+    let builder = builder.insert(
+        eth::TxBuilder::new(client.batched.clone(), client.chain_id()).with_nonce(nonce.next),
+    );
+    let res = builder
+        .create_approval(TransferPayload {
+            token,
+            raw_amount: "0".to_string(),
+            sender,
+            recipient: spender,
+            fee_mode,
+            nft: None,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    transfer_estimation(&client, builder, &wallet.config, res, &nonce).await
+}
+
 #[specta]
 #[tauri::command]
 #[tracing::instrument(name = "execute_transfer", skip_all, err)]
 pub async fn execute_transfer(
-    builder: tauri::State<'_, tokio::sync::Mutex<eth::TxBuilder>>,
+    builder: tauri::State<'_, tokio::sync::Mutex<Option<eth::TxBuilder>>>,
     sk: tauri::State<'_, SK>,
+    nonces: tauri::State<'_, NonceManager>,
     tx_repository: tauri::State<'_, TxRepository>,
-) -> Result<String, String> {
+) -> Result<TransferResult, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
     let mut builder = builder.try_lock().map_err(|e| e.to_string())?;
+    let builder = builder.as_mut().ok_or("Transaction not prepared")?;
+    let network = wallet.eth.active_network()?.clone();
+    if builder.chain_id() != network.chain_id() {
+        builder.discard_pending();
+        return Err(format!(
+            "The account moved to {} after the estimate, estimate the transfer again",
+            network.name
+        ));
+    }
     let prk = wallet.eth.prk()?;
     let hash = builder.sign_and_send_tx(prk.expose()).await?;
     let hash_str = hash.to_string();
 
     if let Some(meta) = builder.take_pending_meta() {
+        nonces
+            .mark_sent(builder.chain_id(), meta.payload.sender, meta.nonce)
+            .await;
+        let value = parse_units(&meta.payload.raw_amount, meta.payload.token.decimals)
+            .map(|u| u.get_absolute())
+            .ok();
         let chain_data = EthChainData {
             nonce: meta.nonce,
             token_address: meta.payload.token.address.to_string(),
@@ -205,28 +606,360 @@ pub async fn execute_transfer(
             max_fee_per_gas: meta.metadata_max_fee_per_gas.to_string(),
             max_priority_fee_per_gas: meta.metadata_max_priority_fee_per_gas.to_string(),
             gas_limit: meta.metadata_estimated_gas.to_string(),
+            value: value.map(|v| v.to_string()),
+            replaces: None,
+            replaced_by: None,
+            spender: meta.approval.then(|| meta.payload.recipient.to_string()),
+            nft: meta.payload.nft.clone(),
+            call: meta.call.clone(),
+            recipient_name: meta.recipient_name.clone(),
         };
         let fee_wei = meta.metadata_estimated_gas as u128 * meta.metadata_max_fee_per_gas;
         let _ = tx_repository.insert(NewTx {
             wallet_name: wallet.name.clone(),
             chain: BlockChain::Ethereum,
+            network: network.id.clone(),
             account_index: wallet.eth.active_account as i32,
             tx_hash: hash_str.clone(),
             direction: TxDirection::Outgoing,
             status: TxStatus::Pending,
             from_address: Some(meta.payload.sender.to_string()),
             to_address: Some(meta.payload.recipient.to_string()),
-            amount: parse_units(&meta.payload.raw_amount, meta.payload.token.decimals)
-                .map(|u| u.get_absolute().try_into().unwrap_or(i64::MAX))
-                .unwrap_or(0),
+            amount: value.map(|v| v.try_into().unwrap_or(i64::MAX)).unwrap_or(0),
+            fee: Some(fee_wei.min(i32::MAX as u128) as i32),
+            block_height: None,
+            chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+            created_at: utils::now() as i64,
+        });
+    }
+
+    Ok(TransferResult {
+        explorer_url: wallet
+            .config
+            .eth
+            .explorer(&network.id)
+            .map(|e| e.tx_url(&hash_str)),
+        tx_hash: hash_str,
+    })
+}
+
+/// Re-sign a pending transfer with the same nonce and higher fees.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "speed_up_transfer", skip_all, err)]
+pub async fn speed_up_transfer(
+    tx_hash: String,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<TransferResult, String> {
+    replace_transfer(Replacement::SpeedUp, tx_hash, sk, providers, tx_repository).await
+}
+
+/// Void a pending transfer with a 0-value transfer to self on its nonce.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "cancel_transfer", skip_all, err)]
+pub async fn cancel_transfer(
+    tx_hash: String,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<TransferResult, String> {
+    replace_transfer(Replacement::Cancel, tx_hash, sk, providers, tx_repository).await
+}
+
+async fn replace_transfer(
+    kind: Replacement,
+    tx_hash: String,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<TransferResult, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let record = tx_repository
+        .get(&wallet.name, BlockChain::Ethereum, &tx_hash)?
+        .ok_or(format!("transaction {tx_hash} is not in the history"))?;
+    if record.status != TxStatus::Pending {
+        return Err(format!("transaction {tx_hash} is no longer pending"));
+    }
+    let data: EthChainData =
+        serde_json::from_str(&record.chain_data).map_err(|e| format!("decode chain_data: {e}"))?;
+    let original = OriginalTx::from_record(&record, &data)?;
+
+    let prk = wallet.eth.build_account_prk(record.account_index as u32)?;
+    if prk.expose().address() != original.sender {
+        return Err("transaction was not sent by this account".to_string());
+    }
+    let client = providers.client(&wallet.config, &record.network).await?;
+    let mined = client
+        .provider
+        .get_transaction_count(original.sender)
+        .await
+        .map_err(|e| e.to_string())?;
+    if mined > original.nonce {
+        return Err(format!(
+            "nonce {} is already mined, nothing to replace",
+            original.nonce
+        ));
+    }
+
+    let current = *FeeEstimator::new(client.provider.clone())
+        .calc_fees()
+        .await?
+        .get(FeeMode::Increased);
+    let fees = bump_fees(original.fees, &current);
+    let tx = original.replacement(kind, client.chain_id(), fees);
+    let hash = sign_and_send(&client.provider, tx, prk.expose())
+        .await?
+        .to_string();
+
+    let (direction, to_address, amount, value) = match kind {
+        Replacement::SpeedUp => (
+            record.direction,
+            record.to_address.clone(),
+            record.amount.parse().unwrap_or(0),
+            data.value.clone(),
+        ),
+        Replacement::Cancel => (
+            TxDirection::SelfTransfer,
+            record.from_address.clone(),
+            0,
+            Some("0".to_string()),
+        ),
+    };
+    let gas_limit = original.gas_limit(kind);
+    let chain_data = EthChainData {
+        max_fee_per_gas: fees.max_fee_per_gas.to_string(),
+        max_priority_fee_per_gas: fees.max_priority_fee_per_gas.to_string(),
+        gas_limit: gas_limit.to_string(),
+        value,
+        replaces: Some(tx_hash.clone()),
+        replaced_by: None,
+        spender: match kind {
+            Replacement::SpeedUp => data.spender.clone(),
+            Replacement::Cancel => None,
+        },
+        nft: match kind {
+            Replacement::SpeedUp => data.nft.clone(),
+            Replacement::Cancel => None,
+        },
+        call: match kind {
+            Replacement::SpeedUp => data.call.clone(),
+            Replacement::Cancel => None,
+        },
+        recipient_name: match kind {
+            Replacement::SpeedUp => data.recipient_name.clone(),
+            Replacement::Cancel => None,
+        },
+        ..data
+    };
+    let fee_wei = gas_limit as u128 * fees.max_fee_per_gas;
+    tx_repository.insert(NewTx {
+        wallet_name: wallet.name.clone(),
+        chain: BlockChain::Ethereum,
+        network: record.network.clone(),
+        account_index: record.account_index,
+        tx_hash: hash.clone(),
+        direction,
+        status: TxStatus::Pending,
+        from_address: record.from_address.clone(),
+        to_address,
+        amount,
+        fee: Some(fee_wei.min(i32::MAX as u128) as i32),
+        block_height: None,
+        chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+        created_at: utils::now() as i64,
+    })?;
+    tx_repository.mark_replaced(&wallet.name, BlockChain::Ethereum, &tx_hash, &hash)?;
+
+    Ok(TransferResult {
+        explorer_url: wallet
+            .config
+            .eth
+            .explorer(&record.network)
+            .map(|e| e.tx_url(&hash)),
+        tx_hash: hash,
+    })
+}
+
+/// Send a 0-value transfer to self on every nonce gap of the active account,
+/// unblocking the transfers queued above.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "fill_nonce_gaps", skip_all, err)]
+pub async fn fill_nonce_gaps(
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    nonces: tauri::State<'_, NonceManager>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<Vec<TransferResult>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let account = wallet.eth.active_account()?;
+    let client = providers.client(&wallet.config, &account.network).await?;
+    let prk = wallet.eth.prk()?;
+    let sender = prk.expose().address();
+    let recorded =
+        tx_repository.pending_eth_nonces(&wallet.name, &client.network.id, &sender.to_string())?;
+    let gaps = nonces.state(&client, sender, &recorded).await?.gaps;
+    if gaps.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let fees = *FeeEstimator::new(client.provider.clone())
+        .calc_fees()
+        .await?
+        .get(FeeMode::Increased);
+    let native = client.network.native_token();
+    let explorer = wallet.config.eth.explorer(&client.network.id);
+    let mut sent = Vec::with_capacity(gaps.len());
+    for nonce in gaps {
+        let tx = noop(sender, nonce, client.chain_id(), fees);
+        let hash = sign_and_send(&client.provider, tx, prk.expose())
+            .await?
+            .to_string();
+        nonces.mark_sent(client.chain_id(), sender, nonce).await;
+
+        let chain_data = EthChainData {
+            nonce,
+            token_address: native.address.to_string(),
+            token_symbol: native.symbol.clone(),
+            token_decimals: native.decimals,
+            max_fee_per_gas: fees.max_fee_per_gas.to_string(),
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas.to_string(),
+            gas_limit: TRANSFER_GAS.to_string(),
+            value: Some("0".to_string()),
+            replaces: None,
+            replaced_by: None,
+            spender: None,
+            nft: None,
+            call: None,
+            recipient_name: None,
+        };
+        let fee_wei = TRANSFER_GAS as u128 * fees.max_fee_per_gas;
+        let _ = tx_repository.insert(NewTx {
+            wallet_name: wallet.name.clone(),
+            chain: BlockChain::Ethereum,
+            network: client.network.id.clone(),
+            account_index: wallet.eth.active_account as i32,
+            tx_hash: hash.clone(),
+            direction: TxDirection::SelfTransfer,
+            status: TxStatus::Pending,
+            from_address: Some(sender.to_string()),
+            to_address: Some(sender.to_string()),
+            amount: 0,
             fee: Some(fee_wei.min(i32::MAX as u128) as i32),
             block_height: None,
             chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
             created_at: utils::now() as i64,
         });
+        sent.push(TransferResult {
+            explorer_url: explorer.as_ref().map(|e| e.tx_url(&hash)),
+            tx_hash: hash,
+        });
+    }
+    Ok(sent)
+}
+
+/// Speed up the active account's pending transfers the node doesn't hold,
+/// broadcasting them again on their own nonce.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "rebroadcast_dropped_transfers", skip_all, err)]
+pub async fn rebroadcast_dropped_transfers(
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    nonces: tauri::State<'_, NonceManager>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<Vec<TransferResult>, String> {
+    let dropped: Vec<String> = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let account = wallet.eth.active_account()?;
+        let client = providers.client(&wallet.config, &account.network).await?;
+        let sender = wallet.eth.prk()?.expose().address();
+        let recorded = tx_repository.pending_eth_transfers(
+            &wallet.name,
+            &client.network.id,
+            &sender.to_string(),
+        )?;
+        let known: Vec<u64> = recorded.iter().map(|(nonce, _)| *nonce).collect();
+        let state = nonces.state(&client, sender, &known).await?;
+        recorded
+            .into_iter()
+            .filter(|(nonce, _)| state.dropped.contains(nonce))
+            .map(|(_, hash)| hash)
+            .collect()
+    };
+    let mut sent = Vec::with_capacity(dropped.len());
+    for hash in dropped {
+        sent.push(
+            replace_transfer(
+                Replacement::SpeedUp,
+                hash,
+                sk.clone(),
+                providers.clone(),
+                tx_repository.clone(),
+            )
+            .await?,
+        );
     }
+    Ok(sent)
+}
 
-    Ok(hash_str)
+/// Record the active account's transfers found on chain since the last sync.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "sync_eth_history", skip_all, err)]
+pub async fn sync_eth_history(
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    checkpoints: tauri::State<'_, CheckpointRepository>,
+) -> Result<HistorySync, String> {
+    let (wallet_name, account_index, address, tokens, client, native) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let account = wallet.eth.active_account()?;
+        let tokens: Vec<_> = account
+            .tokens()
+            .iter()
+            .filter(|t| t.address != NATIVE_TOKEN)
+            .cloned()
+            .collect();
+        let client = providers.client(&wallet.config, &account.network).await?;
+        let native = NativeSource::new(&wallet.config, &client)?;
+        (
+            wallet.name.clone(),
+            account.index as i32,
+            wallet.eth.prk()?.expose().address(),
+            tokens,
+            client,
+            native,
+        )
+    };
+    // Scanning can take a while, the session stays usable meanwhile.
+    let indexer = Indexer {
+        client: &client,
+        history: &tx_repository,
+        checkpoints: &checkpoints,
+        native,
+    };
+    let report = indexer
+        .sync(&IndexTarget {
+            wallet_name: &wallet_name,
+            account_index,
+            address,
+            tokens: &tokens,
+        })
+        .await?;
+    Ok(HistorySync {
+        added: report.added,
+        latest_block: report.latest_block.to_string(),
+        blocks_behind: report.blocks_behind.to_string(),
+    })
 }
 
 #[specta]
@@ -234,14 +967,18 @@ pub async fn execute_transfer(
 #[tracing::instrument(name = "track_token", skip_all, err)]
 pub async fn track_token(
     address: String,
-    erc20_retriever: tauri::State<'_, Erc20Retriever>,
+    providers: tauri::State<'_, EvmProviders>,
     sk: tauri::State<'_, SK>,
 ) -> Result<TrackedTokenInfo, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
 
     let address = parse_addres(&address)?;
-    let token_info = erc20_retriever
+    let token_info = client
+        .erc20()
         .token_info(address)
         .await
         .map_err(|e| format!("Failed to fetch token info: {}", e))?;
@@ -283,18 +1020,507 @@ pub async fn untrack_token(token_address: String, sk: tauri::State<'_, SK>) -> R
     Ok(())
 }
 
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "track_nft_collection", skip_all, err)]
+pub async fn track_nft_collection(
+    address: String,
+    providers: tauri::State<'_, EvmProviders>,
+    sk: tauri::State<'_, SK>,
+) -> Result<NftCollectionView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+
+    let address = parse_addres(&address)?;
+    let collection = NftRetriever::new(client.provider.clone())
+        .collection_info(address)
+        .await?;
+    wallet.mutate_eth(|eth| eth.track(collection.clone()))?;
+
+    Ok(NftCollectionView {
+        address: collection.address.to_string(),
+        name: collection.name,
+        standard: collection.standard,
+        tokens: Vec::new(),
+        scanned_from: None,
+        error: None,
+    })
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "untrack_nft_collection", skip_all, err)]
+pub async fn untrack_nft_collection(
+    address: String,
+    sk: tauri::State<'_, SK>,
+) -> Result<(), String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+
+    let address = parse_addres(&address)?;
+    let collection = wallet
+        .eth
+        .get_tracked_collection(address)
+        .ok_or("Collection with this address hasn't been tracked")?
+        .clone();
+    wallet.mutate_eth(|eth| eth.untrack(collection))
+}
+
+/// Tokens the active account holds in its tracked collections.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_nfts", skip_all, err)]
+pub async fn list_nfts(
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    checkpoints: tauri::State<'_, CheckpointRepository>,
+    nfts: tauri::State<'_, NftRepository>,
+) -> Result<Vec<NftCollectionView>, String> {
+    let (wallet_name, owner, collections, client) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let account = wallet.eth.active_account()?;
+        let client = providers.client(&wallet.config, &account.network).await?;
+        (
+            wallet.name.clone(),
+            wallet.eth.prk()?.expose().address(),
+            account.collections().to_vec(),
+            client,
+        )
+    };
+    let scanner = NftScanner {
+        client: &client,
+        nfts: &nfts,
+        checkpoints: &checkpoints,
+        history: &tx_repository,
+    };
+
+    let mut views = Vec::with_capacity(collections.len());
+    for collection in collections {
+        // One unreachable contract shouldn't hide the other collections.
+        let (holdings, error) = match scanner.owned(&wallet_name, owner, &collection).await {
+            Ok(holdings) => (Some(holdings), None),
+            Err(e) => {
+                tracing::warn!("failed to list NFTs of {}: {e}", collection.address);
+                (None, Some(e))
+            }
+        };
+        let (tokens, scanned_from) =
+            holdings.map_or((Vec::new(), None), |h| (h.tokens, h.scanned_from));
+        views.push(NftCollectionView {
+            address: collection.address.to_string(),
+            name: collection.name,
+            standard: collection.standard,
+            tokens: tokens
+                .into_iter()
+                .map(|nft| NftView {
+                    token_id: nft.token_id.to_string(),
+                    amount: nft.amount.to_string(),
+                    metadata_uri: nft.metadata_uri,
+                })
+                .collect(),
+            scanned_from: scanned_from.map(|b| b.to_string()),
+            error,
+        });
+    }
+    Ok(views)
+}
+
+/// Scan older transfers of a collection that can't list its tokens, one
+/// more step back from the oldest block scanned.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "scan_older_nfts", skip_all, err)]
+pub async fn scan_older_nfts(
+    address: String,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    checkpoints: tauri::State<'_, CheckpointRepository>,
+    nfts: tauri::State<'_, NftRepository>,
+) -> Result<(), String> {
+    let (wallet_name, owner, collection, client) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let account = wallet.eth.active_account()?;
+        let client = providers.client(&wallet.config, &account.network).await?;
+        let collection = wallet
+            .eth
+            .get_tracked_collection(parse_addres(&address)?)
+            .ok_or("Collection with this address hasn't been tracked")?
+            .clone();
+        (
+            wallet.name.clone(),
+            wallet.eth.prk()?.expose().address(),
+            collection,
+            client,
+        )
+    };
+    NftScanner {
+        client: &client,
+        nfts: &nfts,
+        checkpoints: &checkpoints,
+        history: &tx_repository,
+    }
+    .scan_older(&wallet_name, owner, &collection)
+    .await
+}
+
+/// Prepare a `safeTransferFrom` of an NFT, sent with [`execute_transfer`].
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "estimate_nft_transfer", skip_all, err)]
+pub async fn estimate_nft_transfer(
+    req: NftTransferRequest,
+    tx_builder: tauri::State<'_, tokio::sync::Mutex<Option<eth::TxBuilder>>>,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    nonces: tauri::State<'_, NonceManager>,
+    tx_repository: tauri::State<'_, TxRepository>,
+) -> Result<TransferEstimation, String> {
+    let NftTransferRequest {
+        collection,
+        token_id,
+        amount,
+        recipient,
+        fee_mode,
+    } = req;
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let sender = wallet.eth.prk()?.expose().address();
+    let collection = wallet
+        .eth
+        .get_tracked_collection(parse_addres(&collection)?)
+        .ok_or("Collection with this address hasn't been tracked")?
+        .clone();
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+    let recipient =
+        ens::resolve_recipient(&providers, &wallet.config, &client.network, &recipient).await?;
+
+    let recorded =
+        tx_repository.pending_eth_nonces(&wallet.name, &client.network.id, &sender.to_string())?;
+    let nonce = nonces.state(&client, sender, &recorded).await?;
+
+    let mut builder = tx_builder.try_lock().map_err(|e| e.to_string())?;
+    let builder = builder.insert(
+        eth::TxBuilder::new(client.batched.clone(), client.chain_id())
+            .with_nonce(nonce.next)
+            .with_recipient(&recipient),
+    );
+    let res = builder
+        .create_transfer(TransferPayload {
+            token: Token::new(collection.address, collection.name, 0),
+            raw_amount: amount,
+            sender,
+            recipient: recipient.address,
+            fee_mode,
+            nft: Some(NftItem {
+                standard: collection.standard,
+                token_id,
+            }),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    transfer_estimation(&client, builder, &wallet.config, res, &nonce).await
+}
+
+/// Contracts with a stored ABI on the active network.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "list_contracts", skip_all, err)]
+pub async fn list_contracts(
+    sk: tauri::State<'_, SK>,
+    abis: tauri::State<'_, AbiRepository>,
+) -> Result<Vec<ContractSummary>, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let network = &wallet.eth.active_network()?.id;
+
+    Ok(abis
+        .list(network)?
+        .into_iter()
+        .map(|stored| ContractSummary {
+            address: stored.address,
+            name: stored.name,
+        })
+        .collect())
+}
+
+/// Store the ABI of a contract on the active network, pasted or read from
+/// a file, replacing the one stored before.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "save_contract", skip_all, err)]
+pub async fn save_contract(
+    address: String,
+    name: String,
+    abi: String,
+    sk: tauri::State<'_, SK>,
+    abis: tauri::State<'_, AbiRepository>,
+) -> Result<ContractView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let network = &wallet.eth.active_network()?.id;
+
+    let address = parse_addres(&address)?.to_string();
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name the contract".to_string());
+    }
+    let abi = ContractAbi::parse(&abi)?;
+    abis.save(network, &address, name, &abi.to_json()?)?;
+    Ok(ContractView {
+        address,
+        name: name.to_string(),
+        functions: abi.functions(),
+    })
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "get_contract", skip_all, err)]
+pub async fn get_contract(
+    address: String,
+    sk: tauri::State<'_, SK>,
+    abis: tauri::State<'_, AbiRepository>,
+) -> Result<ContractView, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let network = &wallet.eth.active_network()?.id;
+
+    let (stored, abi) = stored_contract(&abis, network, &address)?;
+    Ok(ContractView {
+        address: stored.address,
+        name: stored.name,
+        functions: abi.functions(),
+    })
+}
+
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "remove_contract", skip_all, err)]
+pub async fn remove_contract(
+    address: String,
+    sk: tauri::State<'_, SK>,
+    abis: tauri::State<'_, AbiRepository>,
+) -> Result<(), String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let network = &wallet.eth.active_network()?.id;
+
+    abis.delete(network, &parse_addres(&address)?.to_string())?;
+    Ok(())
+}
+
+/// Run a `view` or `pure` function of a stored contract.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "read_contract", skip_all, err)]
+pub async fn read_contract(
+    address: String,
+    signature: String,
+    args: Vec<String>,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    abis: tauri::State<'_, AbiRepository>,
+) -> Result<Vec<AbiValueView>, String> {
+    let (client, sender, abi) = {
+        let mut sk = sk.lock().await;
+        let wallet = sk.wallet()?;
+        let network = wallet.eth.active_network()?.id.clone();
+        let client = providers.client(&wallet.config, &network).await?;
+        let (_, abi) = stored_contract(&abis, &network, &address)?;
+        (client, wallet.eth.prk()?.expose().address(), abi)
+    };
+    let function = abi.function(&signature)?;
+    if contract::kind(function) != FunctionKind::Read {
+        return Err(format!(
+            "{signature} changes state, send it as a transaction"
+        ));
+    }
+    contract::read(
+        &client.provider,
+        sender,
+        parse_addres(&address)?,
+        function,
+        &args,
+    )
+    .await
+}
+
+/// Prepare a call of a state changing function of a stored contract, sent
+/// with [`execute_transfer`].
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "estimate_contract_call", skip_all, err)]
+pub async fn estimate_contract_call(
+    req: ContractCallRequest,
+    tx_builder: tauri::State<'_, tokio::sync::Mutex<Option<eth::TxBuilder>>>,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
+    nonces: tauri::State<'_, NonceManager>,
+    tx_repository: tauri::State<'_, TxRepository>,
+    abis: tauri::State<'_, AbiRepository>,
+) -> Result<TransferEstimation, String> {
+    let ContractCallRequest {
+        address,
+        signature,
+        args,
+        value,
+        fee_mode,
+    } = req;
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let sender = wallet.eth.prk()?.expose().address();
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+    let (stored, abi) = stored_contract(&abis, &client.network.id, &address)?;
+    let function = abi.function(&signature)?;
+    let value = match value.trim() {
+        "" => "0",
+        value => value,
+    };
+    match contract::kind(function) {
+        FunctionKind::Read => {
+            return Err(format!("{signature} only reads state, nothing to send"));
+        }
+        FunctionKind::Write if parse_ether(value).map_err(|e| e.to_string())? != U256::ZERO => {
+            return Err(format!("{signature} is not payable, it can't receive ETH"));
+        }
+        _ => {}
+    }
+    let input = contract::encode_input(function, &args)?;
+
+    let recorded =
+        tx_repository.pending_eth_nonces(&wallet.name, &client.network.id, &sender.to_string())?;
+    let nonce = nonces.state(&client, sender, &recorded).await?;
+
+    let mut builder = tx_builder.try_lock().map_err(|e| e.to_string())?;
+    let builder = builder.insert(
+        eth::TxBuilder::new(client.batched.clone(), client.chain_id()).with_nonce(nonce.next),
+    );
+    let res = builder
+        .create_contract_call(
+            TransferPayload {
+                token: client.network.native_token(),
+                raw_amount: value.to_string(),
+                sender,
+                recipient: parse_addres(&stored.address)?,
+                fee_mode,
+                nft: None,
+            },
+            signature,
+            input,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    transfer_estimation(&client, builder, &wallet.config, res, &nonce).await
+}
+
+fn stored_contract(
+    abis: &AbiRepository,
+    network: &str,
+    address: &str,
+) -> Result<(StoredAbi, ContractAbi), String> {
+    let address = parse_addres(address)?.to_string();
+    let stored = abis
+        .get(network, &address)?
+        .ok_or("No ABI stored for this contract")?;
+    let abi = ContractAbi::parse(&stored.abi)?;
+    Ok((stored, abi))
+}
+
+fn sign_context(wallet: &Wallet, origin: Option<String>) -> Result<SignContext, String> {
+    Ok(SignContext {
+        address: wallet.eth.prk()?.expose().address(),
+        chain_id: wallet.eth.active_network()?.chain_id(),
+        origin,
+        now: Utc::now(),
+    })
+}
+
+/// Human-readable rendering of a message or typed data, with what to watch
+/// out for before signing it.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "preview_signature", skip_all, err)]
+pub async fn preview_signature(
+    req: SignRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<SignPreview, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let payload = Payload::parse(req.kind, &req.payload)?;
+    Ok(payload.preview(&sign_context(wallet, req.origin)?))
+}
+
+/// Sign a message or typed data with the active account, unless its preview
+/// has errors.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "sign_payload", skip_all, err)]
+pub async fn sign_payload(
+    req: SignRequest,
+    sk: tauri::State<'_, SK>,
+) -> Result<SignedPayload, String> {
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let payload = Payload::parse(req.kind, &req.payload)?;
+    let preview = payload.preview(&sign_context(wallet, req.origin)?);
+    if !preview.errors.is_empty() {
+        return Err(preview.errors.join("; "));
+    }
+    let prk = wallet.eth.prk()?;
+    let signature = payload.sign(prk.expose())?;
+    Ok(SignedPayload {
+        signature: hex::encode_prefixed(signature.as_bytes()),
+        signer: prk.expose().address().to_string(),
+    })
+}
+
+/// Check `signature` over a message or typed data was made by `address`.
+#[specta]
+#[tauri::command]
+#[tracing::instrument(name = "verify_signature", skip_all, err)]
+pub async fn verify_signature(
+    req: SignRequest,
+    signature: String,
+    address: String,
+) -> Result<SignatureCheck, String> {
+    let address = parse_addres(&address)?;
+    let signer = Payload::parse(req.kind, &req.payload)?.recover(&signature)?;
+    Ok(SignatureCheck {
+        valid: signer == address,
+        signer: signer.to_string(),
+    })
+}
+
 #[specta]
 #[tauri::command]
 #[tracing::instrument(name = "anvil_set_initial_balances", skip_all, err)]
 pub async fn anvil_set_initial_balances(
     address: String,
-    provider: tauri::State<'_, DynProvider>,
+    sk: tauri::State<'_, SK>,
+    providers: tauri::State<'_, EvmProviders>,
 ) -> Result<String, String> {
     use alloy::primitives::utils::{parse_ether, parse_units};
 
     use crate::eth::constants::USDT;
 
-    let p = provider.inner();
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    // The local node forks mainnet, where USDT lives.
+    let client = providers
+        .client(&wallet.config, &wallet.eth.active_account()?.network)
+        .await?;
+    let p = &client.provider;
     let addr = parse_addres(&address)?;
     let token = USDT.clone();
 
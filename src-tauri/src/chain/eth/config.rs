@@ -4,18 +4,67 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+use crate::{
+    chain::eth::network::{EvmNetwork, MAINNET, default_networks},
+    config::explorer::{Explorer, ExplorerConfig, ExplorerPreset},
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
 #[serde(default)]
 #[schemars(title = "Ethereum")]
 pub struct EthereumConfig {
-    /// Ethereum JSON-RPC endpoint URL
-    #[schemars(title = "RPC URL")]
+    /// EVM networks accounts can switch between
+    #[schemars(title = "Networks")]
+    pub networks: Vec<EvmNetwork>,
+    /// Legacy single mainnet endpoint, folded into `networks`
+    #[schemars(skip)]
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub rpc_url: String,
     #[schemars(skip)]
     pub anvil: bool,
+    /// Local Anvil node, e.g. an Otterscan template
+    #[schemars(title = "Anvil Block Explorer")]
+    pub anvil_explorer: ExplorerConfig,
 }
 
 impl EthereumConfig {
+    pub fn network(&self, id: &str) -> Result<&EvmNetwork, String> {
+        self.networks
+            .iter()
+            .find(|n| n.id == id)
+            .ok_or(format!("unknown EVM network '{id}'"))
+    }
+
+    /// Network new accounts start on, mainnet unless it was removed.
+    pub fn default_network(&self) -> Result<&EvmNetwork, String> {
+        self.network(MAINNET).or_else(|_| {
+            self.networks
+                .first()
+                .ok_or("no EVM network configured".to_string())
+        })
+    }
+
+    /// Explorer links for `network`, if any are set up.
+    pub fn explorer(&self, network: &str) -> Option<Explorer> {
+        if self.anvil {
+            self.anvil_explorer.resolve(|_| None)
+        } else {
+            self.network(network).ok()?.explorer()
+        }
+    }
+
+    /// Fold the legacy `rpc_url` into the mainnet entry, ahead of its defaults.
+    pub fn migrate_legacy(&mut self) {
+        let url = std::mem::take(&mut self.rpc_url);
+        if url.is_empty() {
+            return;
+        }
+        if let Some(mainnet) = self.networks.iter_mut().find(|n| n.id == MAINNET) {
+            mainnet.rpc_urls.retain(|u| *u != url);
+            mainnet.rpc_urls.insert(0, url);
+        }
+    }
+
     pub fn anvil_bin(&self) -> PathBuf {
         let home = std::env::var("HOME").expect("env HOME is not set");
         let mut path = PathBuf::from(home);
@@ -27,8 +76,29 @@ impl EthereumConfig {
 impl Default for EthereumConfig {
     fn default() -> Self {
         Self {
-            rpc_url: "https://ethereum-rpc.publicnode.com".to_string(),
+            networks: default_networks(),
+            rpc_url: String::new(),
             anvil: false,
+            anvil_explorer: ExplorerConfig::preset(ExplorerPreset::Disabled),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_rpc_url_becomes_the_first_mainnet_endpoint() {
+        let mut config: EthereumConfig =
+            serde_json::from_str(r#"{"rpc_url":"http://127.0.0.1:8545"}"#).unwrap();
+        config.migrate_legacy();
+
+        let mainnet = config.network(MAINNET).unwrap();
+        assert_eq!(mainnet.rpc_urls[0], "http://127.0.0.1:8545");
+        assert_eq!(mainnet.rpc_urls.len(), 2);
+        assert!(config.rpc_url.is_empty());
+        assert_eq!(config.network("base").unwrap().chain_id, 8453);
+        assert!(config.network("goerli").is_err());
+    }
+}
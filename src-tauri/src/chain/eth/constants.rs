@@ -3,13 +3,11 @@ use once_cell::sync::Lazy;
 
 use crate::eth::token::Token;
 
-pub static ETH: Lazy<Token> = Lazy::<Token>::new(|| {
-    Token::new(
-        address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE"),
-        "ETH".to_string(),
-        18,
-    )
-});
+/// Placeholder address standing for a chain's native currency.
+pub const NATIVE_TOKEN: Address = address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+pub static ETH: Lazy<Token> =
+    Lazy::<Token>::new(|| Token::new(NATIVE_TOKEN, "ETH".to_string(), 18));
 
 /// Circle USD.
 pub static USDC: Lazy<Token> = Lazy::<Token>::new(|| {
@@ -28,10 +26,3 @@ pub static USDT: Lazy<Token> = Lazy::<Token>::new(|| {
         6,
     )
 });
-
-pub const ETH_USD_PRICE_FEED: Address = address!("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
-pub const BTC_USD_PRICE_FEED: Address = address!("0xF4030086522a5bEEa4988F8cA5B36dbC97BeE88c");
-
-pub fn default_tokens() -> Vec<Token> {
-    vec![USDC.to_owned(), USDT.to_owned()]
-}
@@ -0,0 +1,321 @@
+//! Calling any contract from a JSON ABI.
+//!
+//! The ABI is only known at runtime, so calls go through `alloy`'s dynamic
+//! ABI types instead of `sol!` bindings. Arguments arrive as text and are
+//! parsed against the declared input types before anything is encoded.
+//! Read functions run as `eth_call`. Write functions become transactions
+//! built by the [`TxBuilder`](crate::eth::TxBuilder).
+use alloy::{
+    dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt, Specifier},
+    json_abi::{Function, JsonAbi, Param, StateMutability},
+    network::TransactionBuilder,
+    primitives::{Address, Bytes, hex},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+};
+use alloy_provider::DynProvider;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+
+use crate::eth::dtos::{AbiFunctionView, AbiParamView, AbiValueView, FunctionKind};
+
+/// A write call sent by the wallet, kept to sign it again when sped up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct ContractCall {
+    /// Canonical signature of the function called.
+    pub function: String,
+    /// ABI encoded call data, `0x` hex.
+    pub input: String,
+}
+
+impl ContractCall {
+    pub fn input(&self) -> Result<Bytes, String> {
+        self.input
+            .parse()
+            .map_err(|e| format!("invalid call data '{}': {e}", self.input))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractAbi(JsonAbi);
+
+impl ContractAbi {
+    /// Parse an ABI array, or a compiler artifact holding it under `abi`.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json).map_err(|e| format!("invalid JSON: {e}"))?;
+        let abi = match value {
+            Value::Object(mut artifact) => artifact
+                .remove("abi")
+                .ok_or("expected an ABI array or an artifact with an `abi` field")?,
+            abi => abi,
+        };
+        let abi: JsonAbi = serde_json::from_value(abi).map_err(|e| format!("invalid ABI: {e}"))?;
+        if abi.functions().next().is_none() {
+            return Err("the ABI declares no functions".to_string());
+        }
+        Ok(Self(abi))
+    }
+
+    /// The ABI alone, artifacts dropping their bytecode and metadata.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.0).map_err(|e| e.to_string())
+    }
+
+    /// Functions by name, overloads next to each other.
+    pub fn functions(&self) -> Vec<AbiFunctionView> {
+        self.0.functions().map(function_view).collect()
+    }
+
+    pub fn function(&self, signature: &str) -> Result<&Function, String> {
+        self.0
+            .functions()
+            .find(|f| f.signature() == signature)
+            .ok_or_else(|| format!("the ABI has no function {signature}"))
+    }
+}
+
+pub fn kind(function: &Function) -> FunctionKind {
+    match function.state_mutability {
+        StateMutability::Pure | StateMutability::View => FunctionKind::Read,
+        StateMutability::NonPayable => FunctionKind::Write,
+        StateMutability::Payable => FunctionKind::Payable,
+    }
+}
+
+fn function_view(function: &Function) -> AbiFunctionView {
+    let params = |params: &[Param]| {
+        params
+            .iter()
+            .map(|p| AbiParamView {
+                name: p.name.clone(),
+                ty: p.selector_type().into_owned(),
+            })
+            .collect()
+    };
+    AbiFunctionView {
+        name: function.name.clone(),
+        signature: function.signature(),
+        kind: kind(function),
+        inputs: params(&function.inputs),
+        outputs: params(&function.outputs),
+    }
+}
+
+/// Call data for `function`, every argument checked against its type.
+pub fn encode_input(function: &Function, args: &[String]) -> Result<Bytes, String> {
+    if args.len() != function.inputs.len() {
+        return Err(format!(
+            "{} takes {} arguments, got {}",
+            function.name,
+            function.inputs.len(),
+            args.len()
+        ));
+    }
+    let values = function
+        .inputs
+        .iter()
+        .enumerate()
+        .zip(args)
+        .map(|((i, param), arg)| parse_arg(i, param, arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    function
+        .abi_encode_input(&values)
+        .map(Bytes::from)
+        .map_err(|e| format!("failed to encode {}: {e}", function.name))
+}
+
+fn parse_arg(index: usize, param: &Param, arg: &str) -> Result<DynSolValue, String> {
+    let name = if param.name.is_empty() {
+        format!("argument {}", index + 1)
+    } else {
+        param.name.clone()
+    };
+    let ty = param
+        .resolve()
+        .map_err(|e| format!("{name}: unsupported type {}: {e}", param.ty))?;
+    ty.coerce_str(arg.trim())
+        .map_err(|e| format!("{name}: '{arg}' is not a valid {}: {e}", ty.sol_type_name()))
+}
+
+/// Return values of `function`, named after its outputs.
+pub fn decode_output(function: &Function, data: &[u8]) -> Result<Vec<AbiValueView>, String> {
+    let values = function
+        .abi_decode_output(data)
+        .map_err(|e| format!("failed to decode the result of {}: {e}", function.name))?;
+    Ok(function
+        .outputs
+        .iter()
+        .zip(values)
+        .map(|(param, value)| AbiValueView {
+            name: param.name.clone(),
+            ty: param.selector_type().into_owned(),
+            value: format_value(&value, false),
+        })
+        .collect())
+}
+
+/// Text a value would be typed in as, strings quoted only inside arrays and
+/// tuples.
+fn format_value(value: &DynSolValue, nested: bool) -> String {
+    let list = |items: &[DynSolValue]| {
+        items
+            .iter()
+            .map(|v| format_value(v, true))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match value {
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Address(address) => address.to_string(),
+        DynSolValue::Function(f) => f.to_string(),
+        DynSolValue::Bytes(bytes) => hex::encode_prefixed(bytes),
+        DynSolValue::String(s) if nested => format!("{s:?}"),
+        DynSolValue::String(s) => s.clone(),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+            format!("[{}]", list(items))
+        }
+        DynSolValue::Tuple(items) => format!("({})", list(items)),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Run a read function with `eth_call` on the latest block.
+pub async fn read(
+    provider: &DynProvider,
+    from: Address,
+    contract: Address,
+    function: &Function,
+    args: &[String],
+) -> Result<Vec<AbiValueView>, String> {
+    let tx = TransactionRequest::default()
+        .with_from(from)
+        .with_to(contract)
+        .with_input(encode_input(function, args)?);
+    let output = provider
+        .call(tx)
+        .await
+        .map_err(|e| format!("{} failed: {e}", function.name))?;
+    decode_output(function, &output)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{U256, address},
+        sol_types::SolCall,
+    };
+
+    use super::*;
+    use crate::eth::erc20_retriver::Erc20Contract;
+
+    const ABI: &str = r#"{
+        "contractName": "Vault",
+        "bytecode": "0x6080",
+        "abi": [
+            {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+             "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}],
+             "outputs": [{"name": "", "type": "bool"}]},
+            {"type": "function", "name": "deposit", "stateMutability": "payable",
+             "inputs": [], "outputs": []},
+            {"type": "function", "name": "position", "stateMutability": "view",
+             "inputs": [{"name": "id", "type": "uint64"}],
+             "outputs": [
+                {"name": "pos", "type": "tuple", "components": [
+                    {"name": "owner", "type": "address"},
+                    {"name": "label", "type": "string"}]},
+                {"name": "shares", "type": "uint128[]"}]},
+            {"type": "event", "name": "Deposit", "anonymous": false,
+             "inputs": [{"name": "amount", "type": "uint256", "indexed": false}]}
+        ]
+    }"#;
+
+    #[test]
+    fn artifacts_are_reduced_to_their_functions() {
+        let abi = ContractAbi::parse(ABI).unwrap();
+        let functions = abi.functions();
+        let names: Vec<_> = functions.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(
+            names,
+            ["deposit()", "position(uint64)", "transfer(address,uint256)"]
+        );
+        let kinds: Vec<_> = functions.iter().map(|f| f.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                FunctionKind::Payable,
+                FunctionKind::Read,
+                FunctionKind::Write
+            ]
+        );
+        assert_eq!(functions[1].outputs[0].ty, "(address,string)");
+
+        let stored = abi.to_json().unwrap();
+        assert!(!stored.contains("bytecode"));
+        assert_eq!(ContractAbi::parse(&stored).unwrap().functions(), functions);
+
+        assert!(ContractAbi::parse("{\"bytecode\": \"0x\"}").is_err());
+        assert!(ContractAbi::parse("[]").is_err());
+    }
+
+    #[test]
+    fn arguments_are_checked_against_their_types() {
+        let abi = ContractAbi::parse(ABI).unwrap();
+        let transfer = abi.function("transfer(address,uint256)").unwrap();
+        let to = address!("0x2222222222222222222222222222222222222222");
+
+        let input = encode_input(transfer, &[to.to_string(), " 1500 ".to_string()]).unwrap();
+        let expected = Erc20Contract::transferCall {
+            _to: to,
+            _value: U256::from(1500),
+        }
+        .abi_encode();
+        assert_eq!(input.to_vec(), expected);
+
+        let err = encode_input(transfer, &["0x1234".to_string(), "1".to_string()]).unwrap_err();
+        assert!(err.starts_with("to: "), "{err}");
+        let err = encode_input(transfer, &[to.to_string(), "-1".to_string()]).unwrap_err();
+        assert!(err.starts_with("amount: "), "{err}");
+        assert!(encode_input(transfer, &[to.to_string()]).is_err());
+        assert!(abi.function("transfer(address)").is_err());
+    }
+
+    #[test]
+    fn results_read_like_arguments() {
+        let abi = ContractAbi::parse(ABI).unwrap();
+        let position = abi.function("position(uint64)").unwrap();
+        let owner = address!("0x1111111111111111111111111111111111111111");
+        let output = DynSolValue::Tuple(vec![
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(owner),
+                DynSolValue::String("main".to_string()),
+            ]),
+            DynSolValue::Array(vec![
+                DynSolValue::Uint(U256::from(5), 128),
+                DynSolValue::Uint(U256::from(7), 128),
+            ]),
+        ])
+        .abi_encode_params();
+
+        let values = decode_output(position, &output).unwrap();
+        assert_eq!(
+            values,
+            [
+                AbiValueView {
+                    name: "pos".to_string(),
+                    ty: "(address,string)".to_string(),
+                    value: format!("({owner}, \"main\")"),
+                },
+                AbiValueView {
+                    name: "shares".to_string(),
+                    ty: "uint128[]".to_string(),
+                    value: "[5, 7]".to_string(),
+                },
+            ]
+        );
+        assert!(decode_output(position, &[0u8; 4]).is_err());
+    }
+}
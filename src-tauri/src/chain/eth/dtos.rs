@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::{chain::eth::fee_estimator::FeeMode, config::BlockChain};
+use crate::{
+    chain::eth::{fee_estimator::FeeMode, nft::NftStandard},
+    config::BlockChain,
+};
 
 #[derive(Serialize, Type)]
 pub struct EthereumUnlock {
@@ -14,16 +17,31 @@ pub struct EthereumAccountSummary {
     pub index: u32,
     pub name: String,
     pub address: String,
+    /// Id of the network the account is on.
+    pub network: String,
 }
 
 #[derive(Serialize, Type)]
 pub struct EthereumActiveAccountView {
     pub index: u32,
     pub address: String,
+    pub network: String,
+}
+
+#[derive(Serialize, Type)]
+pub struct EvmNetworkView {
+    pub id: String,
+    pub name: String,
+    pub chain_id: u32,
+    pub native_symbol: String,
+    /// Whether the active account is on it.
+    pub active: bool,
 }
 
 #[derive(Serialize, Type)]
 pub struct NetworkStatus {
+    pub network: String,
+    pub chain_id: u32,
     pub block_number: String,
     pub block_hash: String,
     pub base_fee_per_gas: Option<String>,
@@ -41,12 +59,15 @@ pub struct TokenBalance {
 pub struct WalletBalance {
     pub wei: String,
     pub tokens: Vec<TokenBalance>,
+    /// Primary ENS name of the address, when it resolves back to it.
+    pub ens_name: Option<String>,
 }
 
 #[derive(Type, Deserialize, Debug, PartialEq)]
 pub struct TransferRequest {
     pub token_address: String,
     pub amount: String,
+    /// Address or ENS name.
     pub recipient: String,
     pub fee_mode: FeeMode,
 }
@@ -57,6 +78,234 @@ pub struct TransferEstimation {
     pub max_fee_per_gas: String,
     pub fee_ceiling: String,
     pub fee_in_usd: f64,
+    pub nonce: String,
+    /// Earlier transfers of the account not mined yet.
+    pub queued: u32,
+    /// Missing nonces the transfer would wait on until they're filled.
+    pub nonce_gaps: Vec<String>,
+    /// Earlier transfers the node doesn't hold, see `NonceState::dropped`.
+    pub nonce_dropped: Vec<String>,
+    /// Dry run of the transaction, `None` when the node couldn't be asked.
+    pub simulation: Option<SimulationResult>,
+    /// Address the transaction goes to, EIP-55 checksummed.
+    pub recipient: String,
+    /// ENS name the recipient was entered as.
+    pub recipient_name: Option<String>,
+    /// Set when the name has no address for the network and its Ethereum
+    /// address is used instead.
+    pub recipient_warning: Option<String>,
+}
+
+#[derive(Type, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationSource {
+    /// Plain `eth_call`, telling whether the transaction reverts only.
+    Call,
+    SimulateV1,
+    TraceCall,
+    /// A local anvil fork, for nodes that can't trace.
+    AnvilFork,
+}
+
+#[derive(Type, Serialize, Debug, PartialEq)]
+pub struct BalanceChange {
+    pub address: String,
+    pub is_sender: bool,
+    pub token_address: String,
+    pub symbol: String,
+    /// Signed amount in token units, e.g. `-1.5`.
+    pub amount: String,
+}
+
+#[derive(Type, Serialize, Debug, PartialEq)]
+pub struct SimulationResult {
+    pub source: SimulationSource,
+    /// Why the transaction would revert, the gas being paid all the same.
+    pub revert_reason: Option<String>,
+    /// Balance changes of the sender and the recipient, gas excluded.
+    /// `None` when the node can't trace the transaction.
+    pub balance_changes: Option<Vec<BalanceChange>>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Type, Serialize)]
+pub struct TokenApproval {
+    pub token_address: String,
+    pub token_symbol: String,
+    pub token_decimals: u8,
+    pub spender: String,
+    pub spender_url: Option<String>,
+    /// Allowance in token units, empty when it couldn't be read.
+    pub allowance: String,
+    pub unlimited: bool,
+    /// Why the allowance couldn't be read, the approval may still be live.
+    pub error: Option<String>,
+}
+
+#[derive(Type, Serialize)]
+pub struct TokenApprovals {
+    pub approvals: Vec<TokenApproval>,
+    /// Oldest block scanned when the account's first transaction couldn't be
+    /// found, older approvals are missing until scanned.
+    pub scanned_from: Option<String>,
+}
+
+#[derive(Type, Serialize)]
+pub struct NftView {
+    pub token_id: String,
+    /// Copies held, always 1 for ERC-721.
+    pub amount: String,
+    /// Where the token metadata lives, not fetched by the wallet.
+    pub metadata_uri: Option<String>,
+}
+
+#[derive(Type, Serialize)]
+pub struct NftCollectionView {
+    pub address: String,
+    pub name: String,
+    pub standard: NftStandard,
+    pub tokens: Vec<NftView>,
+    /// Oldest block scanned for transfers when the collection can't list the
+    /// tokens itself, ones received earlier are missing until scanned.
+    pub scanned_from: Option<String>,
+    /// Why the tokens couldn't be listed.
+    pub error: Option<String>,
+}
+
+#[derive(Type, Deserialize, Debug, PartialEq)]
+pub struct NftTransferRequest {
+    pub collection: String,
+    pub token_id: String,
+    /// Copies to send, 1 for ERC-721.
+    pub amount: String,
+    /// Address or ENS name.
+    pub recipient: String,
+    pub fee_mode: FeeMode,
+}
+
+#[derive(Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignKind {
+    /// EIP-191 `personal_sign`, Sign-In with Ethereum messages included.
+    PersonalSign,
+    /// EIP-712 typed data.
+    TypedData,
+}
+
+#[derive(Type, Deserialize, Debug)]
+pub struct SignRequest {
+    pub kind: SignKind,
+    /// Message text or `0x` hex bytes, or the typed data JSON.
+    pub payload: String,
+    /// Site asking for the signature, checked against sign-in messages.
+    pub origin: Option<String>,
+}
+
+#[derive(Type, Serialize, Debug, PartialEq)]
+pub struct SignField {
+    pub label: String,
+    /// `None` for a struct or an array heading its members.
+    pub value: Option<String>,
+    pub depth: u32,
+}
+
+#[derive(Type, Serialize, Debug)]
+pub struct SignPreview {
+    pub kind: SignKind,
+    pub title: String,
+    /// Typed data domain, or the fields of a sign-in message.
+    pub domain: Vec<SignField>,
+    pub message: Vec<SignField>,
+    pub warnings: Vec<String>,
+    /// Problems signing is refused for.
+    pub errors: Vec<String>,
+}
+
+#[derive(Type, Serialize)]
+pub struct SignedPayload {
+    /// 65-byte `r || s || v` signature, hex encoded.
+    pub signature: String,
+    pub signer: String,
+}
+
+#[derive(Type, Serialize)]
+pub struct SignatureCheck {
+    pub valid: bool,
+    /// Address recovered from the signature.
+    pub signer: String,
+}
+
+#[derive(Type, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionKind {
+    /// `view` and `pure` functions, run with `eth_call`.
+    Read,
+    Write,
+    /// Write functions accepting ETH.
+    Payable,
+}
+
+#[derive(Type, Serialize, Debug, PartialEq)]
+pub struct AbiParamView {
+    pub name: String,
+    /// Solidity type, structs spelled out as tuples.
+    pub ty: String,
+}
+
+#[derive(Type, Serialize, Debug, PartialEq)]
+pub struct AbiFunctionView {
+    pub name: String,
+    /// Canonical signature such as `transfer(address,uint256)`, telling
+    /// overloads apart.
+    pub signature: String,
+    pub kind: FunctionKind,
+    pub inputs: Vec<AbiParamView>,
+    pub outputs: Vec<AbiParamView>,
+}
+
+#[derive(Type, Serialize)]
+pub struct ContractSummary {
+    pub address: String,
+    pub name: String,
+}
+
+#[derive(Type, Serialize)]
+pub struct ContractView {
+    pub address: String,
+    pub name: String,
+    pub functions: Vec<AbiFunctionView>,
+}
+
+#[derive(Type, Serialize, Debug, PartialEq)]
+pub struct AbiValueView {
+    pub name: String,
+    pub ty: String,
+    pub value: String,
+}
+
+#[derive(Type, Deserialize, Debug, PartialEq)]
+pub struct ContractCallRequest {
+    pub address: String,
+    pub signature: String,
+    /// One argument per input, as text: numbers, `0x` hex, `true`, and
+    /// `[..]` or `(..)` for arrays and tuples.
+    pub args: Vec<String>,
+    /// ETH sent along to a payable function.
+    pub value: String,
+    pub fee_mode: FeeMode,
+}
+
+#[derive(Type, Serialize)]
+pub struct HistorySync {
+    /// Transfers recorded for the first time.
+    pub added: u32,
+    pub latest_block: String,
+    /// Blocks of native transfers left to scan, picked up by the next sync.
+    pub blocks_behind: String,
+}
+
+#[derive(Type, Serialize)]
+pub struct TransferResult {
+    pub tx_hash: String,
+    /// Block explorer link, when one is configured for the network.
+    pub explorer_url: Option<String>,
 }
 
 #[derive(Type, Serialize)]
@@ -0,0 +1,698 @@
+//! ENS names, resolved to addresses and looked up for them.
+//!
+//! Names resolve as ENSIP-10 has it: the resolver of the closest ancestor
+//! with one answers for the whole subtree when it implements
+//! `resolve(bytes,bytes)`. Such resolvers often keep their records offchain
+//! and point to a gateway with an EIP-3668 `OffchainLookup` revert, the
+//! gateway's answer going back to the resolver to be verified. Calls go
+//! through the configured provider and gateways are reached over Tor when
+//! it's on, over https and outside the local network only.
+//!
+//! Accounts on other chains take the ENSIP-11 address of their chain, or the
+//! ENSIP-19 default EVM one. The Ethereum address stands in only when the
+//! name has neither, which is reported with the resolution.
+//!
+//! Anyone can point their reverse record at any name, so a name is shown for
+//! an address only when it resolves back to it.
+//!
+//! Names are taken lowercased and in ASCII only. ENSIP-15 normalization of
+//! other scripts and emoji isn't implemented, such names are refused rather
+//! than hashed in a form that may not be the registered one.
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{Address, B256, Bytes, FixedBytes, U256, address, hex, keccak256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    sol,
+    sol_types::{SolCall, SolError, SolValue},
+    transports::RpcError,
+};
+use alloy_provider::DynProvider;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::sync::Mutex;
+
+use crate::{
+    config::Config,
+    eth::{
+        EvmProviders,
+        network::{EvmNetwork, MAINNET},
+        simulation::revert_reason,
+        wallet::parse_addres,
+    },
+};
+
+sol!(
+    #[sol(rpc)]
+    EnsRegistryContract,
+    "src/chain/eth/abi/ens_registry.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    EnsResolverContract,
+    "src/chain/eth/abi/ens_resolver.json"
+);
+
+use EnsResolverContract::OffchainLookup;
+
+/// The registry has the same address on mainnet and the testnets.
+pub const ENS_REGISTRY: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+/// Mainnet, Sepolia and Holesky. Names used on other networks resolve on
+/// mainnet.
+const ENS_CHAINS: [u64; 3] = [1, 11_155_111, 17_000];
+/// SLIP-44 coin type of Ethereum addresses.
+const ETH_COIN_TYPE: u64 = 60;
+/// ENSIP-19 coin type of an address valid on every EVM chain.
+const DEFAULT_EVM_COIN_TYPE: u64 = 0x8000_0000;
+/// ENSIP-10 `IExtendedResolver` interface id.
+const EXTENDED_RESOLVER: FixedBytes<4> = FixedBytes([0x90, 0x61, 0xb9, 0x23]);
+/// Offchain lookups one call may chain.
+const MAX_OFFCHAIN_LOOKUPS: usize = 4;
+const GATEWAY_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a looked up name is shown before it's checked again.
+const NAME_TTL: Duration = Duration::from_secs(15 * 60);
+/// Reverse lookups per listing, the other addresses are shown without a
+/// name until a later one.
+const MAX_LOOKUPS: usize = 20;
+
+/// Whether `input` is meant as a name rather than an address.
+pub fn is_name(input: &str) -> bool {
+    input.trim().contains('.')
+}
+
+/// `input` in the form it's hashed in.
+pub fn normalize(input: &str) -> Result<String, String> {
+    let name = input.trim().to_ascii_lowercase();
+    if !name.is_ascii() {
+        return Err(format!("{name}: only ASCII ENS names are supported"));
+    }
+    if name.split('.').count() < 2 || name.split('.').any(str::is_empty) {
+        return Err(format!("'{name}' is not an ENS name"));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        return Err(format!("{name}: '{c}' is not allowed in ENS names"));
+    }
+    Ok(name)
+}
+
+/// EIP-137 node of a normalized name.
+pub fn namehash(name: &str) -> B256 {
+    if name.is_empty() {
+        return B256::ZERO;
+    }
+    name.rsplit('.').fold(B256::ZERO, |node, label| {
+        keccak256([node.as_slice(), keccak256(label).as_slice()].concat())
+    })
+}
+
+/// DNS wire format of a name, as ENSIP-10 `resolve` takes it.
+pub fn dns_encode(name: &str) -> Result<Bytes, String> {
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.') {
+        let len = u8::try_from(label.len())
+            .ok()
+            .filter(|len| *len < 64)
+            .ok_or_else(|| format!("'{label}' is longer than 63 bytes"))?;
+        encoded.push(len);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    Ok(encoded.into())
+}
+
+fn reverse_name(address: Address) -> String {
+    format!("{}.addr.reverse", hex::encode(address))
+}
+
+/// ENSIP-11 coin type of a chain's addresses, Ethereum's on the chains ENS
+/// lives on.
+fn coin_type(chain_id: u64) -> u64 {
+    if ENS_CHAINS.contains(&chain_id) {
+        ETH_COIN_TYPE
+    } else {
+        0x8000_0000 | chain_id
+    }
+}
+
+/// Coin types a name's address is looked up under, in order, for a network
+/// with addresses of `coin_type`.
+fn fallback_coin_types(coin_type: u64) -> Vec<u64> {
+    if coin_type == ETH_COIN_TYPE {
+        vec![ETH_COIN_TYPE]
+    } else {
+        vec![coin_type, DEFAULT_EVM_COIN_TYPE, ETH_COIN_TYPE]
+    }
+}
+
+/// Address in an ENSIP-9 record of an EVM chain, `None` when it's empty.
+fn record_address(name: &str, record: &[u8]) -> Result<Option<Address>, String> {
+    match record.len() {
+        0 => Ok(None),
+        20 => Ok(Some(Address::from_slice(record)).filter(|a| !a.is_zero())),
+        len => Err(format!("{name}: address record of {len} bytes")),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum GatewayRequest {
+    Get(String),
+    Post(String, Value),
+}
+
+impl GatewayRequest {
+    fn url(&self) -> &str {
+        match self {
+            GatewayRequest::Get(url) | GatewayRequest::Post(url, _) => url,
+        }
+    }
+}
+
+/// Refuses gateways a resolver could use to reach services of the user's own
+/// machine or network, and plain http ones. Hosts are checked by name, not
+/// by what they resolve to.
+fn check_gateway(url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(url).map_err(|e| format!("invalid gateway URL: {e}"))?;
+    if url.scheme() != "https" {
+        return Err("gateways are reached over https only".to_string());
+    }
+    let host = url
+        .host_str()
+        .ok_or("gateway URL without a host")?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+    let local = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => is_local_v4(ip),
+        Ok(IpAddr::V6(ip)) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip.to_ipv4_mapped().is_some_and(is_local_v4)
+        }
+        Err(_) => {
+            !host.contains('.')
+                || [".localhost", ".local", ".internal", ".home.arpa"]
+                    .iter()
+                    .any(|suffix| host.ends_with(suffix))
+        }
+    };
+    if local {
+        return Err(format!("{host} is a local address"));
+    }
+    Ok(())
+}
+
+fn is_local_v4(ip: Ipv4Addr) -> bool {
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+}
+
+/// EIP-3668: a URL with `{data}` is fetched, the others are posted a JSON
+/// body.
+fn gateway_request(url: &str, sender: Address, call_data: &[u8]) -> GatewayRequest {
+    let sender = hex::encode_prefixed(sender);
+    let data = hex::encode_prefixed(call_data);
+    let url = url.replace("{sender}", &sender);
+    if url.contains("{data}") {
+        GatewayRequest::Get(url.replace("{data}", &data))
+    } else {
+        GatewayRequest::Post(url, json!({ "data": data, "sender": sender }))
+    }
+}
+
+/// Call of the resolver's callback with the gateway's answer.
+fn callback_data(lookup: &OffchainLookup, response: Bytes) -> Bytes {
+    let args = (response, lookup.extraData.clone()).abi_encode_params();
+    [lookup.callbackFunction.as_slice(), &args].concat().into()
+}
+
+#[derive(Deserialize)]
+struct GatewayResponse {
+    data: Bytes,
+}
+
+/// Address a name resolves to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resolution {
+    pub address: Address,
+    /// Coin type of the record the address was found in.
+    pub coin_type: u64,
+}
+
+/// Recipient as entered, resolved when it's an ENS name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipient {
+    pub address: Address,
+    pub name: Option<String>,
+    /// Set when the name has no address for the network and its Ethereum
+    /// one is taken instead, which the recipient may not control there.
+    pub warning: Option<String>,
+}
+
+/// ENS of the network names are resolved on.
+pub struct Ens {
+    provider: DynProvider,
+    chain_id: u64,
+    /// Coin type of the account's network addresses.
+    coin_type: u64,
+    http: reqwest::Client,
+}
+
+impl Ens {
+    /// ENS for an account on `network`, mainnet's when `network` has none.
+    pub async fn connect(
+        providers: &EvmProviders,
+        config: &Config,
+        network: &EvmNetwork,
+    ) -> Result<Self, String> {
+        let id = if ENS_CHAINS.contains(&network.chain_id()) {
+            network.id.as_str()
+        } else {
+            MAINNET
+        };
+        let client = providers
+            .client(config, id)
+            .await
+            .map_err(|e| format!("ENS names resolve on {id}: {e}"))?;
+        let mut http = reqwest::Client::builder().timeout(GATEWAY_TIMEOUT);
+        if config.tor.enabled {
+            http = http.proxy(
+                reqwest::Proxy::all(&config.tor.socks5_proxy)
+                    .map_err(|e| format!("invalid Tor proxy URL: {e}"))?,
+            );
+        }
+        Ok(Self {
+            chain_id: client.chain_id(),
+            coin_type: coin_type(network.chain_id()),
+            provider: client.provider,
+            http: http
+                .build()
+                .map_err(|e| format!("failed to build ENS gateway client: {e}"))?,
+        })
+    }
+
+    /// Address a normalized name resolves to on the account's network,
+    /// `None` when it has no resolver or no address.
+    pub async fn resolve(&self, name: &str) -> Result<Option<Resolution>, String> {
+        let Some((resolver, own)) = self.find_resolver(name).await? else {
+            return Ok(None);
+        };
+        let extended = EnsResolverContract::new(resolver, self.provider.clone())
+            .supportsInterface(EXTENDED_RESOLVER)
+            .call()
+            .await
+            .unwrap_or(false);
+        // Only extended resolvers answer for the names below theirs.
+        if !extended && !own {
+            return Ok(None);
+        }
+        let node = namehash(name);
+        for coin_type in fallback_coin_types(self.coin_type) {
+            if coin_type == ETH_COIN_TYPE {
+                let call = EnsResolverContract::addr_0Call { node };
+                let output = self.record(resolver, name, extended, call).await?;
+                let address = EnsResolverContract::addr_0Call::abi_decode_returns(&output)
+                    .map_err(|e| format!("invalid address record of {name}: {e}"))?;
+                return Ok((!address.is_zero()).then_some(Resolution { address, coin_type }));
+            }
+            let call = EnsResolverContract::addr_1Call {
+                node,
+                coinType: U256::from(coin_type),
+            };
+            let output = self.record(resolver, name, extended, call).await?;
+            let record = EnsResolverContract::addr_1Call::abi_decode_returns(&output)
+                .map_err(|e| format!("invalid address record of {name}: {e}"))?;
+            if let Some(address) = record_address(name, &record)? {
+                return Ok(Some(Resolution { address, coin_type }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Output of a record `call` to the resolver of `name`, wrapped in
+    /// `resolve` for an extended one.
+    async fn record(
+        &self,
+        resolver: Address,
+        name: &str,
+        extended: bool,
+        call: impl SolCall,
+    ) -> Result<Bytes, String> {
+        if !extended {
+            return self.call(resolver, call.abi_encode().into()).await;
+        }
+        let call = EnsResolverContract::resolveCall {
+            name: dns_encode(name)?,
+            data: call.abi_encode().into(),
+        };
+        let output = self.call(resolver, call.abi_encode().into()).await?;
+        EnsResolverContract::resolveCall::abi_decode_returns(&output)
+            .map_err(|e| format!("invalid record of {name}: {e}"))
+    }
+
+    /// Primary name of `address`, `None` when it has none or the name
+    /// resolves elsewhere.
+    pub async fn lookup(&self, address: Address) -> Result<Option<String>, String> {
+        let node = namehash(&reverse_name(address));
+        let resolver = self.resolver(node).await?;
+        if resolver.is_zero() {
+            return Ok(None);
+        }
+        let call = EnsResolverContract::nameCall { node };
+        let output = self.call(resolver, call.abi_encode().into()).await?;
+        let name = EnsResolverContract::nameCall::abi_decode_returns(&output)
+            .map_err(|e| format!("invalid name record of {address}: {e}"))?;
+        let Ok(name) = normalize(&name) else {
+            return Ok(None);
+        };
+        let resolved = self.resolve(&name).await?.map(|r| r.address);
+        Ok((resolved == Some(address)).then_some(name))
+    }
+
+    async fn resolver(&self, node: B256) -> Result<Address, String> {
+        EnsRegistryContract::new(ENS_REGISTRY, self.provider.clone())
+            .resolver(node)
+            .call()
+            .await
+            .map_err(|e| format!("ENS registry query failed: {e}"))
+    }
+
+    /// Resolver of `name` or of its closest ancestor, and whether it's the
+    /// name's own.
+    async fn find_resolver(&self, name: &str) -> Result<Option<(Address, bool)>, String> {
+        let mut current = name;
+        loop {
+            let resolver = self.resolver(namehash(current)).await?;
+            if !resolver.is_zero() {
+                return Ok(Some((resolver, current == name)));
+            }
+            match current.split_once('.') {
+                Some((_, parent)) => current = parent,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// `eth_call` to a resolver, following the offchain lookups it asks for.
+    async fn call(&self, resolver: Address, mut data: Bytes) -> Result<Bytes, String> {
+        for _ in 0..=MAX_OFFCHAIN_LOOKUPS {
+            let tx = TransactionRequest::default()
+                .with_to(resolver)
+                .with_input(data.clone());
+            let revert = match self.provider.call(tx).await {
+                Ok(output) => return Ok(output),
+                Err(RpcError::ErrorResp(payload)) => payload
+                    .as_revert_data()
+                    .ok_or_else(|| format!("resolver {resolver} failed: {}", payload.message))?,
+                Err(e) => return Err(format!("resolver {resolver} failed: {e}")),
+            };
+            let lookup = OffchainLookup::abi_decode(&revert)
+                .map_err(|_| format!("resolver {resolver} reverted: {}", revert_reason(&revert)))?;
+            if lookup.sender != resolver {
+                return Err(format!(
+                    "resolver {resolver} asked an offchain lookup for {}",
+                    lookup.sender
+                ));
+            }
+            let response = self.fetch(&lookup).await?;
+            data = callback_data(&lookup, response);
+        }
+        Err(format!(
+            "resolver {resolver} chained too many offchain lookups"
+        ))
+    }
+
+    /// Answer of the first gateway of `lookup` that has one. A gateway
+    /// refusing the request stands for all of them.
+    async fn fetch(&self, lookup: &OffchainLookup) -> Result<Bytes, String> {
+        let mut errors = Vec::new();
+        for url in &lookup.urls {
+            let request = gateway_request(url, lookup.sender, &lookup.callData);
+            if let Err(e) = check_gateway(request.url()) {
+                errors.push(format!("{url}: {e}"));
+                continue;
+            }
+            let request = match request {
+                GatewayRequest::Get(url) => self.http.get(url),
+                GatewayRequest::Post(url, body) => self.http.post(url).json(&body),
+            };
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    errors.push(format!("{url}: {e}"));
+                    continue;
+                }
+            };
+            let status = response.status();
+            if status.is_client_error() {
+                return Err(format!("ENS gateway {url} refused the lookup: {status}"));
+            }
+            if !status.is_success() {
+                errors.push(format!("{url}: {status}"));
+                continue;
+            }
+            match response.json::<GatewayResponse>().await {
+                Ok(body) => return Ok(body.data),
+                Err(e) => errors.push(format!("{url}: invalid response: {e}")),
+            }
+        }
+        Err(format!("no ENS gateway answered: {}", errors.join("; ")))
+    }
+}
+
+/// Address `input` stands for, with the name it was resolved from when it's
+/// an ENS name.
+pub async fn resolve_recipient(
+    providers: &EvmProviders,
+    config: &Config,
+    network: &EvmNetwork,
+    input: &str,
+) -> Result<Recipient, String> {
+    if !is_name(input) {
+        return Ok(Recipient {
+            address: parse_addres(input)?,
+            name: None,
+            warning: None,
+        });
+    }
+    let name = normalize(input)?;
+    let ens = Ens::connect(providers, config, network).await?;
+    let resolution = ens
+        .resolve(&name)
+        .await?
+        .ok_or_else(|| format!("{name} doesn't resolve to an address"))?;
+    let warning = (ens.coin_type != resolution.coin_type && resolution.coin_type == ETH_COIN_TYPE)
+        .then(|| {
+            format!(
+                "{name} has no address for {chain}, its Ethereum address is used. Make sure \
+                 the recipient controls it on {chain} too",
+                chain = network.name
+            )
+        });
+    Ok(Recipient {
+        address: resolution.address,
+        name: Some(name),
+        warning,
+    })
+}
+
+/// Primary names looked up lately, per chain and coin type.
+#[derive(Default)]
+pub struct EnsNames {
+    names: Mutex<HashMap<(u64, u64, Address), (Option<String>, Instant)>>,
+}
+
+impl EnsNames {
+    /// Primary names of `addresses`, the ones without one left out. A
+    /// failing lookup is logged and tried again on the next call.
+    pub async fn lookup(
+        &self,
+        ens: &Ens,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> HashMap<Address, String> {
+        let mut found = HashMap::new();
+        let mut missing = Vec::new();
+        {
+            let names = self.names.lock().await;
+            for address in addresses {
+                match names.get(&(ens.chain_id, ens.coin_type, address)) {
+                    Some((name, at)) if at.elapsed() < NAME_TTL => {
+                        if let Some(name) = name {
+                            found.insert(address, name.clone());
+                        }
+                    }
+                    _ if !missing.contains(&address) => missing.push(address),
+                    _ => {}
+                }
+            }
+        }
+        missing.truncate(MAX_LOOKUPS);
+
+        let results = futures::future::join_all(missing.iter().map(|a| ens.lookup(*a))).await;
+        let mut names = self.names.lock().await;
+        for (address, result) in missing.into_iter().zip(results) {
+            match result {
+                Ok(name) => {
+                    if let Some(name) = &name {
+                        found.insert(address, name.clone());
+                    }
+                    names.insert(
+                        (ens.chain_id, ens.coin_type, address),
+                        (name, Instant::now()),
+                    );
+                }
+                Err(e) => tracing::warn!("ENS lookup of {address} failed: {e}"),
+            }
+        }
+        found
+    }
+
+    /// [`Self::lookup`] on the ENS for `network`, no names when it can't be
+    /// reached.
+    pub async fn lookup_for(
+        &self,
+        providers: &EvmProviders,
+        config: &Config,
+        network: &EvmNetwork,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> HashMap<Address, String> {
+        match Ens::connect(providers, config, network).await {
+            Ok(ens) => self.lookup(&ens, addresses).await,
+            Err(e) => {
+                tracing::warn!("ENS names not shown: {e}");
+                HashMap::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+
+    use super::*;
+
+    #[test]
+    fn names_hash_as_in_eip_137() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash(&normalize(" Foo.ETH ").unwrap()),
+            b256!("0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+        assert_eq!(
+            dns_encode("foo.eth").unwrap().to_vec(),
+            b"\x03foo\x03eth\x00".to_vec()
+        );
+        assert!(dns_encode(&format!("{}.eth", "a".repeat(64))).is_err());
+        assert_eq!(
+            reverse_name(address!("0x2222222222222222222222222222222222222222")),
+            "2222222222222222222222222222222222222222.addr.reverse"
+        );
+    }
+
+    #[test]
+    fn only_plain_names_are_taken() {
+        assert!(is_name("vitalik.eth"));
+        assert!(!is_name("0x2222222222222222222222222222222222222222"));
+        assert_eq!(normalize("sub.Wallet.eth").unwrap(), "sub.wallet.eth");
+        assert!(normalize("eth").is_err());
+        assert!(normalize("foo..eth").is_err());
+        assert!(normalize("fоо.eth").is_err(), "Cyrillic look-alikes");
+        assert!(normalize("my wallet.eth").is_err());
+    }
+
+    #[test]
+    fn offchain_lookups_follow_eip_3668() {
+        let sender = address!("0x1111111111111111111111111111111111111111");
+        assert_eq!(
+            gateway_request("https://gw.example/{sender}/{data}.json", sender, &[0xab]),
+            GatewayRequest::Get(
+                "https://gw.example/0x1111111111111111111111111111111111111111/0xab.json"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            gateway_request("https://gw.example/lookup", sender, &[0xab]),
+            GatewayRequest::Post(
+                "https://gw.example/lookup".to_string(),
+                json!({ "data": "0xab", "sender": "0x1111111111111111111111111111111111111111" })
+            )
+        );
+
+        let lookup = OffchainLookup {
+            sender,
+            urls: vec![],
+            callData: Bytes::from_static(&[0xab]),
+            callbackFunction: FixedBytes([0xde, 0xad, 0xbe, 0xef]),
+            extraData: Bytes::from_static(&[0xcd]),
+        };
+        let data = callback_data(&lookup, Bytes::from_static(&[0xef]));
+        assert_eq!(&data[..4], &[0xde, 0xad, 0xbe, 0xef]);
+        let (response, extra) = <(Bytes, Bytes)>::abi_decode_params(&data[4..]).unwrap();
+        assert_eq!(
+            (response.to_vec(), extra.to_vec()),
+            (vec![0xef], vec![0xcd])
+        );
+    }
+
+    #[test]
+    fn gateways_are_public_https_hosts() {
+        assert!(check_gateway("https://ccip.ens.xyz/{sender}/{data}.json").is_ok());
+        assert!(check_gateway("https://93.184.215.14/lookup").is_ok());
+        for url in [
+            "http://ccip.ens.xyz/lookup",
+            "file:///etc/passwd",
+            "https://localhost:8545/",
+            "https://router.local/",
+            "https://intranet/",
+            "https://127.0.0.1/",
+            "https://192.168.1.1/",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/",
+            "https://[fd00::1]/",
+            "https://[::ffff:10.0.0.1]/",
+        ] {
+            assert!(check_gateway(url).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn other_chains_take_their_own_coin_type() {
+        assert_eq!(coin_type(1), ETH_COIN_TYPE);
+        assert_eq!(coin_type(11_155_111), ETH_COIN_TYPE);
+        assert_eq!(coin_type(8_453), 2_147_492_101);
+        assert_eq!(coin_type(42_161), 2_147_525_809);
+        assert_eq!(fallback_coin_types(ETH_COIN_TYPE), vec![60]);
+        assert_eq!(
+            fallback_coin_types(coin_type(8_453)),
+            vec![2_147_492_101, DEFAULT_EVM_COIN_TYPE, ETH_COIN_TYPE]
+        );
+
+        assert_eq!(record_address("a.eth", &[]), Ok(None));
+        assert_eq!(record_address("a.eth", &[0; 20]), Ok(None));
+        assert_eq!(
+            record_address("a.eth", &[0x22; 20]),
+            Ok(Some(address!("0x2222222222222222222222222222222222222222")))
+        );
+        assert!(record_address("a.eth", &[0x22; 32]).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use alloy::{
-    primitives::{Address, Uint},
+    primitives::{Address, U256, Uint},
     providers::Provider,
     sol,
 };
@@ -87,6 +87,42 @@ impl Erc20Retriever {
 
         balances
     }
+
+    /// Current allowance of every `(token, spender)` pair granted by `owner`,
+    /// each failing on its own, e.g. when the token reverts.
+    pub async fn allowances(
+        &self,
+        owner: Address,
+        pairs: &[(Address, Address)],
+    ) -> Vec<Result<U256, String>> {
+        let allowance_futures: Vec<_> = pairs
+            .iter()
+            .map(|(token, spender)| {
+                let provider_clone = self.provider.root();
+                let contract = new_contract_api(self.provider.clone(), *token);
+                let tx_request = contract
+                    .allowance(owner, *spender)
+                    .into_transaction_request();
+                async move {
+                    provider_clone
+                        .call(tx_request)
+                        .decode_resp::<Erc20Contract::allowanceCall>()
+                        .await
+                }
+            })
+            .collect();
+
+        futures::future::join_all(allowance_futures)
+            .await
+            .into_iter()
+            .zip(pairs)
+            .map(|(result, (token, spender))| {
+                result
+                    .map_err(|e| format!("Failed to execute batch call: {}", e))?
+                    .map_err(|e| format!("Failed to fetch allowance of {spender} on {token}: {e}"))
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -103,8 +139,10 @@ mod tests {
         eth::select_provider,
     };
 
-    fn get_config() -> Config {
-        Config::default()
+    fn mainnet_provider() -> DynProvider {
+        let config = Config::default();
+        let url = &config.eth.default_network().unwrap().rpc_urls[0];
+        select_provider(&config, url).unwrap()
     }
 
     #[tokio::test]
@@ -112,7 +150,7 @@ mod tests {
         let tokens: Lazy<Vec<Token>> = Lazy::<Vec<Token>>::new(|| vec![USDC.clone(), USDT.clone()]);
         let address = Address::from_str("d8da6bf26964af9d7eed9e03e53415d37aa96045").unwrap();
 
-        let retriver = Erc20Retriever::new(select_provider(get_config()));
+        let retriver = Erc20Retriever::new(mainnet_provider());
         let result = retriver.balances(address, tokens.to_vec()).await;
         assert!(result.is_ok(), "get_balances should not error");
         println!("res {:?}", result);
@@ -136,7 +174,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_token_info() {
-        let retriver = Erc20Retriever::new(select_provider(get_config()));
+        let retriver = Erc20Retriever::new(mainnet_provider());
         let result = retriver.token_info(USDC.address).await;
         if result.is_ok() {
             let token = result.unwrap();
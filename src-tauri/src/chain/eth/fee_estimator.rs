@@ -152,8 +152,8 @@ mod tests {
 
     fn get_config() -> EthereumConfig {
         EthereumConfig {
-            rpc_url: "https://ethereum-rpc.publicnode.com".to_string(),
             anvil: true,
+            ..Default::default()
         }
     }
 
@@ -231,7 +231,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_calculate_all_fees() {
-        let provider = new_provider(get_config());
+        let provider = new_provider("https://ethereum-rpc.publicnode.com").unwrap();
         let estimator = FeeEstimator::new(provider.clone());
         let all_fees = estimator.calc_fees().await.unwrap();
         let minimal_fee = all_fees.get(FeeMode::Minimal);
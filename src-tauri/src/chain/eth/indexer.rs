@@ -0,0 +1,891 @@
+//! History of incoming and outgoing transfers, found on chain.
+//!
+//! Token transfers come from the `Transfer` logs of the tracked tokens,
+//! fetched with `eth_getLogs` in block ranges that shrink when the node
+//! refuses one and grow back while it answers. Native value transfers leave no
+//! logs, so they come from a [`NativeSource`]. Each scan stores the last block
+//! it covered and the next one resumes there, block scans run in batches up to
+//! a limit per run and report how far they're left behind the tip.
+use std::collections::HashMap;
+
+use alloy::{
+    consensus::Transaction as _,
+    eips::BlockNumberOrTag,
+    network::TransactionResponse,
+    primitives::{Address, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol_types::SolEvent,
+};
+use alloy_provider::DynProvider;
+use futures::{StreamExt, TryStreamExt, stream};
+use serde::Deserialize;
+
+use crate::{
+    config::{BlockChain, Config},
+    eth::{EvmClient, constants::NATIVE_TOKEN, erc20_retriver::Erc20Contract, token::Token},
+    repository::{
+        CheckpointKey, CheckpointRepository, EthChainData, NewTx, TransferKey, TxDirection,
+        TxRepository, TxStatus,
+    },
+    utils,
+};
+
+const LOGS_SCOPE: &str = "logs";
+const NATIVE_SCOPE: &str = "native";
+/// Blocks scanned again on every run, for transfers a reorg moved.
+const REORG_DEPTH: u64 = 12;
+/// How far back the first log scan of an address goes.
+const FIRST_SCAN_DEPTH: u64 = 100_000;
+/// Blocks fetched per batch when no history API is configured, also the depth
+/// of the first scan.
+const BLOCK_SCAN_BATCH: u64 = 500;
+/// Most blocks a run fetches, later runs pick up from there.
+const BLOCK_SCAN_LIMIT: u64 = 10_000;
+/// Block requests in flight at once.
+const BLOCK_SCAN_CONCURRENCY: usize = 16;
+/// Blocks one history API call covers.
+const HISTORY_API_BATCH: u64 = 1_000_000;
+/// Most rows the history API returns for one query, the rest is cut off.
+const HISTORY_API_PAGE: usize = 10_000;
+
+/// A value transfer touching the indexed address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedTransfer {
+    pub tx_hash: String,
+    pub key: TransferKey,
+    pub block: u64,
+    pub timestamp: Option<u64>,
+    pub from: Address,
+    pub to: Address,
+    pub token: Address,
+    /// Amount in the token's base units.
+    pub value: U256,
+    pub failed: bool,
+}
+
+/// `eth_getLogs` span that halves when the node rejects it, e.g. for too
+/// many results, and doubles back while requests succeed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveRange {
+    size: u64,
+    max: u64,
+}
+
+impl AdaptiveRange {
+    pub fn new(size: u64, max: u64) -> Self {
+        Self {
+            size: size.clamp(1, max),
+            max,
+        }
+    }
+
+    /// Last block of the range starting at `from`, at most `latest`.
+    pub fn end(&self, from: u64, latest: u64) -> u64 {
+        from.saturating_add(self.size - 1).min(latest)
+    }
+
+    /// Halve the span, `false` once it's a single block already.
+    pub fn shrink(&mut self) -> bool {
+        if self.size == 1 {
+            return false;
+        }
+        self.size /= 2;
+        true
+    }
+
+    pub fn grow(&mut self) {
+        self.size = self.size.saturating_mul(2).min(self.max);
+    }
+}
+
+impl Default for AdaptiveRange {
+    fn default() -> Self {
+        Self::new(2_000, 10_000)
+    }
+}
+
+/// First block to scan, going back [`REORG_DEPTH`] blocks from the
+/// checkpoint, or `first_depth` from the tip on the first run.
+pub fn scan_start(checkpoint: Option<u64>, latest: u64, first_depth: u64) -> u64 {
+    match checkpoint {
+        Some(block) => (block + 1).saturating_sub(REORG_DEPTH),
+        None => latest.saturating_sub(first_depth),
+    }
+}
+
+/// Scope of the checkpoint holding the oldest block a scan of `scope`
+/// covered, when it didn't start from the first block that matters.
+pub fn floor_scope(scope: &str) -> String {
+    format!("{scope}:floor")
+}
+
+/// Blocks a rescan below `floor` covers, `None` once it reached genesis.
+pub fn older_range(floor: u64, depth: u64) -> Option<(u64, u64)> {
+    (floor > 0).then(|| (floor.saturating_sub(depth), floor - 1))
+}
+
+/// Last block of at most `blocks` starting at `from`, capped at `latest`.
+pub fn scan_end(from: u64, latest: u64, blocks: u64) -> u64 {
+    from.saturating_add(blocks.saturating_sub(1)).min(latest)
+}
+
+pub fn direction(transfer: &IndexedTransfer, address: Address) -> TxDirection {
+    match (transfer.from == address, transfer.to == address) {
+        (true, true) => TxDirection::SelfTransfer,
+        (true, false) => TxDirection::Outgoing,
+        _ => TxDirection::Incoming,
+    }
+}
+
+/// Source of native value transfers, which leave no logs.
+pub trait NativeTransferSource {
+    fn native_transfers(
+        &self,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> impl Future<Output = Result<Vec<IndexedTransfer>, String>> + Send;
+
+    /// Depth of the first scan of an address.
+    fn first_depth(&self) -> u64;
+
+    /// Most blocks one call covers.
+    fn batch_size(&self) -> u64;
+
+    /// Most blocks one run covers.
+    fn max_blocks(&self) -> u64;
+}
+
+pub enum NativeSource {
+    Blocks(BlockScanner),
+    HistoryApi(HistoryApi),
+}
+
+impl NativeSource {
+    /// The network's history API when configured, block scanning otherwise.
+    pub fn new(config: &Config, client: &EvmClient) -> Result<Self, String> {
+        let network = &client.network;
+        Ok(match network.history_api() {
+            Some(url) => Self::HistoryApi(HistoryApi::new(
+                config,
+                url,
+                network.history_api_key.clone(),
+                client.chain_id(),
+            )?),
+            None => Self::Blocks(BlockScanner {
+                provider: client.provider.clone(),
+            }),
+        })
+    }
+}
+
+impl NativeTransferSource for NativeSource {
+    async fn native_transfers(
+        &self,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<IndexedTransfer>, String> {
+        match self {
+            NativeSource::Blocks(s) => s.native_transfers(address, from, to).await,
+            NativeSource::HistoryApi(s) => s.native_transfers(address, from, to).await,
+        }
+    }
+
+    fn first_depth(&self) -> u64 {
+        match self {
+            NativeSource::Blocks(s) => s.first_depth(),
+            NativeSource::HistoryApi(s) => s.first_depth(),
+        }
+    }
+
+    fn batch_size(&self) -> u64 {
+        match self {
+            NativeSource::Blocks(s) => s.batch_size(),
+            NativeSource::HistoryApi(s) => s.batch_size(),
+        }
+    }
+
+    fn max_blocks(&self) -> u64 {
+        match self {
+            NativeSource::Blocks(s) => s.max_blocks(),
+            NativeSource::HistoryApi(s) => s.max_blocks(),
+        }
+    }
+}
+
+/// Reads every transaction of every block. Sees top-level transfers only,
+/// value moved by contract calls needs a [`HistoryApi`].
+pub struct BlockScanner {
+    provider: DynProvider,
+}
+
+impl BlockScanner {
+    async fn block_transfers(
+        &self,
+        address: Address,
+        number: u64,
+    ) -> Result<Vec<IndexedTransfer>, String> {
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Number(number))
+            .full()
+            .await
+            .map_err(|e| format!("failed to get block {number}: {e}"))?
+            .ok_or(format!("block {number} not found"))?;
+        Ok(block
+            .transactions
+            .txns()
+            .filter(|tx| {
+                !tx.value().is_zero() && (tx.from() == address || tx.to() == Some(address))
+            })
+            .map(|tx| IndexedTransfer {
+                tx_hash: tx.tx_hash().to_string(),
+                key: TransferKey::Transaction,
+                block: number,
+                timestamp: Some(block.header.timestamp),
+                from: tx.from(),
+                to: tx.to().unwrap_or_default(),
+                token: NATIVE_TOKEN,
+                value: tx.value(),
+                failed: false,
+            })
+            .collect())
+    }
+}
+
+impl NativeTransferSource for BlockScanner {
+    async fn native_transfers(
+        &self,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<IndexedTransfer>, String> {
+        let blocks: Vec<Vec<IndexedTransfer>> = stream::iter(from..=to)
+            .map(|number| self.block_transfers(address, number))
+            .buffered(BLOCK_SCAN_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(blocks.into_iter().flatten().collect())
+    }
+
+    fn first_depth(&self) -> u64 {
+        BLOCK_SCAN_BATCH
+    }
+
+    fn batch_size(&self) -> u64 {
+        BLOCK_SCAN_BATCH
+    }
+
+    fn max_blocks(&self) -> u64 {
+        BLOCK_SCAN_LIMIT
+    }
+}
+
+/// Etherscan-compatible account API, covering both plain transfers and
+/// value sent by contract calls.
+pub struct HistoryApi {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    chain_id: u64,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiTx {
+    hash: String,
+    block_number: String,
+    time_stamp: String,
+    from: String,
+    to: String,
+    value: String,
+    #[serde(default)]
+    is_error: String,
+}
+
+impl TryFrom<ApiTx> for IndexedTransfer {
+    type Error = String;
+
+    fn try_from(tx: ApiTx) -> Result<Self, Self::Error> {
+        let address = |a: &str| {
+            a.parse::<Address>()
+                .map_err(|e| format!("invalid address '{a}': {e}"))
+        };
+        Ok(Self {
+            block: tx
+                .block_number
+                .parse()
+                .map_err(|e| format!("invalid block number: {e}"))?,
+            timestamp: tx.time_stamp.parse().ok(),
+            from: address(&tx.from)?,
+            // Contract creations have no recipient.
+            to: if tx.to.is_empty() {
+                Address::ZERO
+            } else {
+                address(&tx.to)?
+            },
+            token: NATIVE_TOKEN,
+            value: tx
+                .value
+                .parse()
+                .map_err(|e| format!("invalid value '{}': {e}", tx.value))?,
+            failed: tx.is_error == "1",
+            key: TransferKey::Transaction,
+            tx_hash: tx.hash,
+        })
+    }
+}
+
+impl HistoryApi {
+    pub fn new(
+        config: &Config,
+        url: &str,
+        api_key: Option<String>,
+        chain_id: u64,
+    ) -> Result<Self, String> {
+        let mut builder = reqwest::Client::builder();
+        if config.tor.enabled {
+            builder = builder.proxy(
+                reqwest::Proxy::all(&config.tor.socks5_proxy)
+                    .map_err(|e| format!("invalid Tor proxy URL: {e}"))?,
+            );
+        }
+        Ok(Self {
+            client: builder
+                .build()
+                .map_err(|e| format!("failed to build history API client: {e}"))?,
+            url: url.to_string(),
+            api_key,
+            chain_id,
+        })
+    }
+
+    /// Transfers of `action` between `from` and `to`, over as many queries as
+    /// the row limit takes.
+    async fn list(
+        &self,
+        action: &str,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<IndexedTransfer>, String> {
+        let mut transfers = Vec::new();
+        let mut start = from;
+        loop {
+            let mut page = self.page(action, address, start, to).await?;
+            let next = complete_blocks(&mut page, start, HISTORY_API_PAGE)?;
+            transfers.append(&mut page);
+            match next {
+                Some(block) => start = block,
+                None => return Ok(transfers),
+            }
+        }
+    }
+
+    async fn page(
+        &self,
+        action: &str,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<IndexedTransfer>, String> {
+        let mut query = vec![
+            ("chainid", self.chain_id.to_string()),
+            ("module", "account".to_string()),
+            ("action", action.to_string()),
+            ("address", address.to_string()),
+            ("startblock", from.to_string()),
+            ("endblock", to.to_string()),
+            ("sort", "asc".to_string()),
+            ("page", "1".to_string()),
+            ("offset", HISTORY_API_PAGE.to_string()),
+        ];
+        if let Some(key) = &self.api_key {
+            query.push(("apikey", key.clone()));
+        }
+        let response: ApiResponse = self
+            .client
+            .get(&self.url)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| format!("history API request failed: {e}"))?
+            .json()
+            .await
+            .map_err(|e| format!("invalid history API response: {e}"))?;
+        parse_api_response(response)
+    }
+}
+
+/// A full page may stop halfway through its last block: drops that block and
+/// returns it as the start of the next query.
+fn complete_blocks(
+    page: &mut Vec<IndexedTransfer>,
+    from: u64,
+    limit: usize,
+) -> Result<Option<u64>, String> {
+    if page.len() < limit {
+        return Ok(None);
+    }
+    let last = page.last().map_or(from, |t| t.block);
+    if last <= from {
+        return Err(format!(
+            "history API returned more than {limit} transfers for block {from}"
+        ));
+    }
+    page.retain(|t| t.block < last);
+    Ok(Some(last))
+}
+
+fn parse_api_response(response: ApiResponse) -> Result<Vec<IndexedTransfer>, String> {
+    match response.result {
+        serde_json::Value::Array(txs) => txs
+            .into_iter()
+            .map(|tx| {
+                serde_json::from_value::<ApiTx>(tx)
+                    .map_err(|e| format!("invalid history API transaction: {e}"))
+                    .and_then(IndexedTransfer::try_from)
+            })
+            .collect(),
+        // Some explorers answer an empty history without a result list.
+        _ if response.message.starts_with("No transactions") => Ok(Vec::new()),
+        result => Err(format!(
+            "history API error ({}): {} {}",
+            response.status, response.message, result
+        )),
+    }
+}
+
+impl NativeTransferSource for HistoryApi {
+    async fn native_transfers(
+        &self,
+        address: Address,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<IndexedTransfer>, String> {
+        let mut transfers = self.list("txlist", address, from, to).await?;
+        let mut internal = self.list("txlistinternal", address, from, to).await?;
+        number_traces(&mut internal);
+        transfers.append(&mut internal);
+        transfers.retain(|t| !t.value.is_zero() && (t.from == address || t.to == address));
+        Ok(transfers)
+    }
+
+    fn first_depth(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn batch_size(&self) -> u64 {
+        HISTORY_API_BATCH
+    }
+
+    fn max_blocks(&self) -> u64 {
+        u64::MAX
+    }
+}
+
+/// Keys internal transfers by their position in the transaction, the API
+/// listing them in trace order.
+fn number_traces(internal: &mut [IndexedTransfer]) {
+    let mut seen: HashMap<String, u64> = HashMap::new();
+    for transfer in internal {
+        let position = seen.entry(transfer.tx_hash.clone()).or_default();
+        transfer.key = TransferKey::Trace(*position);
+        *position += 1;
+    }
+}
+
+/// Token transfers from or to `address` between `from` and `to`.
+async fn transfer_logs(
+    provider: &DynProvider,
+    tokens: &[Address],
+    address: Address,
+    from: u64,
+    to: u64,
+) -> Result<Vec<IndexedTransfer>, String> {
+    let filter = Filter::new()
+        .address(tokens.to_vec())
+        .event_signature(Erc20Contract::Transfer::SIGNATURE_HASH)
+        .from_block(from)
+        .to_block(to);
+    let sent = provider
+        .get_logs(&filter.clone().topic1(address.into_word()))
+        .await
+        .map_err(|e| format!("eth_getLogs failed: {e}"))?;
+    let received = provider
+        .get_logs(&filter.topic2(address.into_word()))
+        .await
+        .map_err(|e| format!("eth_getLogs failed: {e}"))?;
+
+    let mut transfers: HashMap<(String, TransferKey), IndexedTransfer> = HashMap::new();
+    for log in sent.iter().chain(&received) {
+        if let Some(t) = decode_transfer(log) {
+            transfers.insert((t.tx_hash.clone(), t.key), t);
+        }
+    }
+    Ok(transfers.into_values().collect())
+}
+
+fn decode_transfer(log: &Log) -> Option<IndexedTransfer> {
+    if log.removed {
+        return None;
+    }
+    let decoded = log.log_decode::<Erc20Contract::Transfer>().ok()?;
+    let event = &decoded.inner.data;
+    Some(IndexedTransfer {
+        tx_hash: log.transaction_hash?.to_string(),
+        key: log
+            .log_index
+            .map_or(TransferKey::Transaction, TransferKey::Log),
+        block: log.block_number?,
+        timestamp: log.block_timestamp,
+        from: event.from,
+        to: event.to,
+        token: decoded.inner.address,
+        value: event.value,
+        failed: false,
+    })
+}
+
+/// Account whose history is indexed.
+pub struct IndexTarget<'a> {
+    pub wallet_name: &'a str,
+    pub account_index: i32,
+    pub address: Address,
+    /// Tracked tokens of the network, the native one excluded.
+    pub tokens: &'a [Token],
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IndexReport {
+    /// Transfers recorded for the first time.
+    pub added: u32,
+    /// Tip of the chain when the scan started.
+    pub latest_block: u64,
+    /// Blocks below the tip the scan didn't reach this run.
+    pub blocks_behind: u64,
+}
+
+pub struct Indexer<'a> {
+    pub client: &'a EvmClient,
+    pub history: &'a TxRepository,
+    pub checkpoints: &'a CheckpointRepository,
+    pub native: NativeSource,
+}
+
+impl Indexer<'_> {
+    pub async fn sync(&self, target: &IndexTarget<'_>) -> Result<IndexReport, String> {
+        let latest = self
+            .client
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| format!("failed to get block number: {e}"))?;
+        let mut report = IndexReport {
+            added: 0,
+            latest_block: latest,
+            blocks_behind: 0,
+        };
+        self.sync_logs(target, latest, &mut report).await?;
+        self.sync_native(target, latest, &mut report).await?;
+        Ok(report)
+    }
+
+    async fn sync_logs(
+        &self,
+        target: &IndexTarget<'_>,
+        latest: u64,
+        report: &mut IndexReport,
+    ) -> Result<(), String> {
+        if target.tokens.is_empty() {
+            return Ok(());
+        }
+        // A checkpoint per token, so a newly tracked one is scanned from the
+        // start while the others share its requests.
+        let scopes: Vec<String> = target
+            .tokens
+            .iter()
+            .map(|t| format!("{LOGS_SCOPE}:{}", t.address))
+            .collect();
+        let address = target.address.to_string();
+        let mut checkpoints = Vec::with_capacity(scopes.len());
+        for scope in &scopes {
+            checkpoints.push(self.checkpoints.get(&self.key(target, &address, scope))?);
+        }
+        let mut from = checkpoints
+            .iter()
+            .map(|c| scan_start(*c, latest, FIRST_SCAN_DEPTH))
+            .min()
+            .unwrap_or(latest);
+        let addresses: Vec<Address> = target.tokens.iter().map(|t| t.address).collect();
+
+        let mut range = AdaptiveRange::default();
+        while from <= latest {
+            let to = range.end(from, latest);
+            let transfers =
+                match transfer_logs(&self.client.provider, &addresses, target.address, from, to)
+                    .await
+                {
+                    Ok(transfers) => transfers,
+                    Err(e) if range.shrink() => {
+                        tracing::debug!("{}: shrinking log range: {e}", self.client.network.id);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+            self.record(target, transfers, report)?;
+            for (scope, checkpoint) in scopes.iter().zip(&checkpoints) {
+                if checkpoint.is_none_or(|c| c < to) {
+                    self.checkpoints
+                        .set(&self.key(target, &address, scope), to)?;
+                }
+            }
+            range.grow();
+            from = to + 1;
+        }
+        Ok(())
+    }
+
+    async fn sync_native(
+        &self,
+        target: &IndexTarget<'_>,
+        latest: u64,
+        report: &mut IndexReport,
+    ) -> Result<(), String> {
+        let address = target.address.to_string();
+        let key = self.key(target, &address, NATIVE_SCOPE);
+        let checkpoint = self.checkpoints.get(&key)?;
+        let mut from = scan_start(checkpoint, latest, self.native.first_depth());
+        let end = scan_end(from, latest, self.native.max_blocks());
+        report.blocks_behind = latest - end;
+        while from <= end {
+            let to = scan_end(from, end, self.native.batch_size());
+            let transfers = self
+                .native
+                .native_transfers(target.address, from, to)
+                .await?;
+            self.record(target, transfers, report)?;
+            self.checkpoints.set(&key, to)?;
+            from = to + 1;
+        }
+        Ok(())
+    }
+
+    fn key<'k>(
+        &'k self,
+        target: &IndexTarget<'k>,
+        address: &'k str,
+        scope: &'k str,
+    ) -> CheckpointKey<'k> {
+        CheckpointKey {
+            wallet_name: target.wallet_name,
+            chain: BlockChain::Ethereum,
+            network: &self.client.network.id,
+            address,
+            scope,
+        }
+    }
+
+    fn record(
+        &self,
+        target: &IndexTarget<'_>,
+        transfers: Vec<IndexedTransfer>,
+        report: &mut IndexReport,
+    ) -> Result<(), String> {
+        let native = self.client.network.native_token();
+        for transfer in transfers {
+            let token = target
+                .tokens
+                .iter()
+                .find(|t| t.address == transfer.token)
+                .unwrap_or(&native);
+            let chain_data = EthChainData {
+                nonce: 0,
+                token_address: token.address.to_string(),
+                token_symbol: token.symbol.clone(),
+                token_decimals: token.decimals,
+                max_fee_per_gas: String::new(),
+                max_priority_fee_per_gas: String::new(),
+                gas_limit: String::new(),
+                value: Some(transfer.value.to_string()),
+                replaces: None,
+                replaced_by: None,
+                spender: None,
+                nft: None,
+                call: None,
+                recipient_name: None,
+            };
+            let tx = NewTx {
+                wallet_name: target.wallet_name.to_string(),
+                chain: BlockChain::Ethereum,
+                network: self.client.network.id.clone(),
+                account_index: target.account_index,
+                tx_hash: transfer.tx_hash.clone(),
+                direction: direction(&transfer, target.address),
+                status: if transfer.failed {
+                    TxStatus::Failed
+                } else {
+                    TxStatus::Confirmed
+                },
+                from_address: Some(transfer.from.to_string()),
+                to_address: Some(transfer.to.to_string()),
+                amount: transfer.value.try_into().unwrap_or(i64::MAX),
+                fee: None,
+                block_height: Some(transfer.block as i64),
+                chain_data: serde_json::to_value(chain_data).unwrap_or_default(),
+                created_at: transfer.timestamp.unwrap_or_else(utils::now) as i64,
+            };
+            if self.history.upsert_transfer(tx, transfer.key)? {
+                report.added += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    #[test]
+    fn range_adapts_to_the_node() {
+        let mut range = AdaptiveRange::new(4, 8);
+        assert_eq!(range.end(10, 100), 13);
+        assert_eq!(range.end(98, 100), 100);
+
+        assert!(range.shrink());
+        assert!(range.shrink());
+        assert_eq!(range.end(10, 100), 10);
+        assert!(!range.shrink());
+
+        for _ in 0..5 {
+            range.grow();
+        }
+        assert_eq!(range.end(10, 100), 17);
+    }
+
+    #[test]
+    fn scan_resumes_before_the_checkpoint() {
+        assert_eq!(scan_start(None, 1_000, 100), 900);
+        assert_eq!(scan_start(None, 50, 100), 0);
+        assert_eq!(scan_start(Some(500), 1_000, 100), 489);
+        assert_eq!(scan_start(Some(3), 1_000, 100), 0);
+    }
+
+    #[test]
+    fn rescans_go_deeper_until_genesis() {
+        assert_eq!(older_range(2_500, 1_000), Some((1_500, 2_499)));
+        assert_eq!(older_range(700, 1_000), Some((0, 699)));
+        assert_eq!(older_range(0, 1_000), None);
+    }
+
+    #[test]
+    fn scan_stops_at_the_run_limit() {
+        assert_eq!(scan_end(100, 1_000, 500), 599);
+        assert_eq!(scan_end(900, 1_000, 500), 1_000);
+        assert_eq!(scan_end(100, 1_000, u64::MAX), 1_000);
+        assert_eq!(scan_end(0, 0, 1), 0);
+    }
+
+    #[test]
+    fn history_api_transfers() {
+        let me = address!("0x1111111111111111111111111111111111111111");
+        let response: ApiResponse = serde_json::from_str(
+            r#"{"status":"1","message":"OK","result":[
+                {"hash":"0xaa","blockNumber":"12","timeStamp":"1700000000",
+                 "from":"0x2222222222222222222222222222222222222222",
+                 "to":"0x1111111111111111111111111111111111111111",
+                 "value":"1000","isError":"0"},
+                {"hash":"0xbb","blockNumber":"13","timeStamp":"1700000012",
+                 "from":"0x1111111111111111111111111111111111111111",
+                 "to":"0x3333333333333333333333333333333333333333",
+                 "value":"0","isError":"0"},
+                {"hash":"0xcc","blockNumber":"14","timeStamp":"1700000024",
+                 "from":"0x1111111111111111111111111111111111111111",
+                 "to":"0x1111111111111111111111111111111111111111",
+                 "value":"5","isError":"1"}
+            ]}"#,
+        )
+        .unwrap();
+        let transfers = parse_api_response(response).unwrap();
+        assert_eq!(transfers.len(), 3);
+        assert_eq!(transfers[0].value, U256::from(1000));
+        assert_eq!(transfers[0].timestamp, Some(1_700_000_000));
+        assert_eq!(transfers[0].key, TransferKey::Transaction);
+        assert_eq!(direction(&transfers[0], me), TxDirection::Incoming);
+        assert!(transfers[2].failed);
+        assert_eq!(direction(&transfers[2], me), TxDirection::SelfTransfer);
+
+        let empty: ApiResponse =
+            serde_json::from_str(r#"{"status":"0","message":"No transactions found","result":[]}"#)
+                .unwrap();
+        assert!(parse_api_response(empty).unwrap().is_empty());
+        let error: ApiResponse =
+            serde_json::from_str(r#"{"status":"0","message":"NOTOK","result":"Invalid API Key"}"#)
+                .unwrap();
+        assert!(parse_api_response(error).is_err());
+    }
+
+    fn api_transfer(hash: &str, block: u64) -> IndexedTransfer {
+        IndexedTransfer {
+            tx_hash: hash.to_string(),
+            key: TransferKey::Transaction,
+            block,
+            timestamp: None,
+            from: Address::ZERO,
+            to: Address::ZERO,
+            token: NATIVE_TOKEN,
+            value: U256::from(1),
+            failed: false,
+        }
+    }
+
+    #[test]
+    fn internal_transfers_are_keyed_by_position() {
+        let mut internal = vec![
+            api_transfer("0xaa", 1),
+            api_transfer("0xaa", 1),
+            api_transfer("0xbb", 2),
+        ];
+        number_traces(&mut internal);
+        let keys: Vec<TransferKey> = internal.iter().map(|t| t.key).collect();
+        assert_eq!(
+            keys,
+            [
+                TransferKey::Trace(0),
+                TransferKey::Trace(1),
+                TransferKey::Trace(0)
+            ]
+        );
+    }
+
+    #[test]
+    fn full_pages_resume_at_their_last_block() {
+        let mut page = vec![api_transfer("0xaa", 5)];
+        assert_eq!(complete_blocks(&mut page, 5, 2), Ok(None));
+        assert_eq!(page.len(), 1);
+
+        let mut page = vec![
+            api_transfer("0xaa", 5),
+            api_transfer("0xbb", 6),
+            api_transfer("0xcc", 8),
+            api_transfer("0xdd", 8),
+        ];
+        assert_eq!(complete_blocks(&mut page, 5, 4), Ok(Some(8)));
+        assert_eq!(page.len(), 2);
+
+        let mut page = vec![api_transfer("0xaa", 8), api_transfer("0xbb", 8)];
+        assert!(complete_blocks(&mut page, 8, 2).is_err());
+    }
+}
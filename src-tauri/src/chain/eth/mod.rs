@@ -1,17 +1,28 @@
+pub mod approvals;
 pub mod commands;
 pub mod config;
 pub mod constants;
+pub mod contract;
 pub mod dtos;
+pub mod ens;
 pub mod erc20_retriver;
 pub mod fee_estimator;
+pub mod indexer;
+pub mod network;
+pub mod nft;
+pub mod nonce_manager;
 pub mod persistence;
 pub mod price_feed;
 pub mod provider;
+pub mod replacement;
+pub mod signing;
+pub mod simulation;
 pub mod token;
 pub mod transfer_builder;
 pub mod wallet;
 
 pub use erc20_retriver::Erc20Retriever;
+pub use nonce_manager::NonceManager;
 pub use price_feed::PriceFeed;
 pub use provider::*;
 pub use transfer_builder::TxBuilder;
@@ -0,0 +1,305 @@
+use std::str::FromStr;
+
+use alloy::primitives::Address;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    chain::eth::{constants::NATIVE_TOKEN, token::Token},
+    config::explorer::{Explorer, ExplorerConfig, ExplorerPreset, ethereum_explorer},
+};
+
+pub const MAINNET: &str = "mainnet";
+/// Etherscan's multichain API, picking the chain by `chainid`.
+pub const ETHERSCAN_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// An EVM chain the wallet can connect to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type, JsonSchema)]
+#[schemars(title = "Network")]
+pub struct EvmNetwork {
+    /// Stable key accounts and history refer to, e.g. `base`
+    #[schemars(title = "Id")]
+    pub id: String,
+    #[schemars(title = "Name")]
+    pub name: String,
+    /// Checked against `eth_chainId` before an RPC endpoint is used
+    #[schemars(title = "Chain Id")]
+    pub chain_id: u32,
+    /// JSON-RPC endpoints, tried in order
+    #[schemars(title = "RPC URLs")]
+    pub rpc_urls: Vec<String>,
+    #[schemars(title = "Native Currency")]
+    pub native_symbol: String,
+    /// Tokens tracked by accounts that switch to this network
+    #[schemars(title = "Default Tokens")]
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+    #[schemars(title = "Price Feeds")]
+    #[serde(default)]
+    pub price_feeds: PriceFeedsConfig,
+    /// Root of the Etherscan-style explorer of this chain
+    #[schemars(title = "Explorer URL")]
+    #[serde(default)]
+    pub explorer_url: Option<String>,
+    #[schemars(title = "Block Explorer")]
+    #[serde(default = "etherscan")]
+    pub explorer: ExplorerConfig,
+    /// Etherscan-compatible API listing native transfers. Defaults to
+    /// Etherscan's `https://api.etherscan.io/v2/api` once a key is set.
+    /// Without either, history of native transfers is gathered by scanning
+    /// blocks, from the first sync on.
+    #[schemars(title = "History API URL")]
+    #[serde(default)]
+    pub history_api_url: Option<String>,
+    /// Key of the history API, required by Etherscan
+    #[schemars(title = "History API Key")]
+    #[serde(default)]
+    pub history_api_key: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type, JsonSchema)]
+#[schemars(title = "Token")]
+pub struct TokenConfig {
+    pub symbol: String,
+    pub address: String,
+    pub decimals: u8,
+}
+
+/// Chainlink aggregators quoting in USD.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+#[schemars(title = "Price Feeds")]
+pub struct PriceFeedsConfig {
+    #[schemars(title = "Native / USD")]
+    pub native_usd: Option<String>,
+    #[schemars(title = "BTC / USD")]
+    pub btc_usd: Option<String>,
+}
+
+fn etherscan() -> ExplorerConfig {
+    ExplorerConfig::preset(ExplorerPreset::Etherscan)
+}
+
+impl EvmNetwork {
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id as u64
+    }
+
+    /// The chain's own currency, addressed by the `0xEeee…` placeholder.
+    pub fn native_token(&self) -> Token {
+        Token::new(NATIVE_TOKEN, self.native_symbol.clone(), 18)
+    }
+
+    pub fn default_tokens(&self) -> Vec<Token> {
+        self.tokens
+            .iter()
+            .filter_map(|t| {
+                Token::try_from(t)
+                    .inspect_err(|e| {
+                        tracing::warn!("{}: skipping token {}: {e}", self.id, t.symbol)
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+
+    pub fn native_usd_feed(&self) -> Option<Address> {
+        parse_feed(self.price_feeds.native_usd.as_deref())
+    }
+
+    pub fn btc_usd_feed(&self) -> Option<Address> {
+        parse_feed(self.price_feeds.btc_usd.as_deref())
+    }
+
+    /// The configured history API, or Etherscan's when only a key is set.
+    pub fn history_api(&self) -> Option<&str> {
+        match (&self.history_api_url, &self.history_api_key) {
+            (Some(url), _) => Some(url),
+            (None, Some(_)) => Some(ETHERSCAN_API_URL),
+            (None, None) => None,
+        }
+    }
+
+    pub fn explorer(&self) -> Option<Explorer> {
+        self.explorer.resolve(|preset| {
+            self.explorer_url
+                .as_deref()
+                .and_then(|url| ethereum_explorer(preset, url.trim_end_matches('/')))
+        })
+    }
+}
+
+fn parse_feed(address: Option<&str>) -> Option<Address> {
+    address.and_then(|a| Address::from_str(a).ok())
+}
+
+impl TryFrom<&TokenConfig> for Token {
+    type Error = String;
+
+    fn try_from(t: &TokenConfig) -> Result<Self, Self::Error> {
+        let address =
+            Address::from_str(&t.address).map_err(|e| format!("invalid token address: {e}"))?;
+        Ok(Token::new(address, t.symbol.clone(), t.decimals))
+    }
+}
+
+fn token(symbol: &str, address: &str, decimals: u8) -> TokenConfig {
+    TokenConfig {
+        symbol: symbol.to_string(),
+        address: address.to_string(),
+        decimals,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn network(
+    id: &str,
+    name: &str,
+    chain_id: u32,
+    rpc_url: &str,
+    tokens: Vec<TokenConfig>,
+    native_usd: &str,
+    btc_usd: &str,
+    explorer_url: &str,
+) -> EvmNetwork {
+    EvmNetwork {
+        id: id.to_string(),
+        name: name.to_string(),
+        chain_id,
+        rpc_urls: vec![rpc_url.to_string()],
+        native_symbol: "ETH".to_string(),
+        tokens,
+        price_feeds: PriceFeedsConfig {
+            native_usd: Some(native_usd.to_string()),
+            btc_usd: Some(btc_usd.to_string()),
+        },
+        explorer_url: Some(explorer_url.to_string()),
+        explorer: etherscan(),
+        history_api_url: None,
+        history_api_key: None,
+    }
+}
+
+/// Networks known out of the box, mainnet first.
+pub fn default_networks() -> Vec<EvmNetwork> {
+    vec![
+        network(
+            MAINNET,
+            "Ethereum",
+            1,
+            "https://ethereum-rpc.publicnode.com",
+            vec![
+                token("USDC", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6),
+                token("USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+            ],
+            "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419",
+            "0xF4030086522a5bEEa4988F8cA5B36dbC97BeE88c",
+            "https://etherscan.io",
+        ),
+        network(
+            "sepolia",
+            "Sepolia",
+            11_155_111,
+            "https://ethereum-sepolia-rpc.publicnode.com",
+            vec![token(
+                "USDC",
+                "0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238",
+                6,
+            )],
+            "0x694AA1769357215DE4FAC081bf1f309aDC325306",
+            "0x1b44F3514812d835EB1BDB0acB33d3fA3351Ee43",
+            "https://sepolia.etherscan.io",
+        ),
+        network(
+            "base",
+            "Base",
+            8_453,
+            "https://mainnet.base.org",
+            vec![token(
+                "USDC",
+                "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+                6,
+            )],
+            "0x71041dddad3595F9CEd3DcCFBe3D1F4b0a16Bb70",
+            "0x64c911996D3c6aC71f9b455B1E8E7266BcbD848F",
+            "https://basescan.org",
+        ),
+        network(
+            "arbitrum",
+            "Arbitrum One",
+            42_161,
+            "https://arb1.arbitrum.io/rpc",
+            vec![
+                token("USDC", "0xaf88d065e77c8cC2239327C5EDb3A432268e5831", 6),
+                token("USDT", "0xFd086bC7CD5C481DCC9C85ebE478A1C0b69FCbb9", 6),
+            ],
+            "0x639Fe6ab55C921f74e7fac1ee960C0B6293ba612",
+            "0x6ce185860a4963106506C203335A2910413708e9",
+            "https://arbiscan.io",
+        ),
+        network(
+            "optimism",
+            "OP Mainnet",
+            10,
+            "https://mainnet.optimism.io",
+            vec![
+                token("USDC", "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85", 6),
+                token("USDT", "0x94b008aA00579c1307B0EF2c499aD98a8ce58e58", 6),
+            ],
+            "0x13e3Ee699D1909E989722E753853AE30b17e08c5",
+            "0xD702DD976Fb76Fffc2D3963D037dfDae5b04E593",
+            "https://optimistic.etherscan.io",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_registry_is_well_formed() {
+        let networks = default_networks();
+        assert_eq!(networks[0].id, MAINNET);
+        for network in &networks {
+            assert_eq!(
+                network.default_tokens().len(),
+                network.tokens.len(),
+                "{}",
+                network.id
+            );
+            assert!(network.native_usd_feed().is_some(), "{}", network.id);
+            assert!(network.explorer().is_some(), "{}", network.id);
+        }
+        let mut ids: Vec<_> = networks.iter().map(|n| n.chain_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), networks.len());
+    }
+
+    #[test]
+    fn explorer_follows_the_network() {
+        let base = default_networks()
+            .into_iter()
+            .find(|n| n.id == "base")
+            .unwrap();
+        assert_eq!(
+            base.explorer().unwrap().tx_url("0xab"),
+            "https://basescan.org/tx/0xab"
+        );
+    }
+
+    #[test]
+    fn etherscan_is_the_default_history_api() {
+        let mut base = default_networks()
+            .into_iter()
+            .find(|n| n.id == "base")
+            .unwrap();
+        assert_eq!(base.history_api(), None);
+        base.history_api_key = Some("key".to_string());
+        assert_eq!(base.history_api(), Some(ETHERSCAN_API_URL));
+        base.history_api_url = Some("https://base.blockscout.com/api".to_string());
+        assert_eq!(base.history_api(), Some("https://base.blockscout.com/api"));
+    }
+}
@@ -0,0 +1,591 @@
+//! Non-fungible tokens of ERC-721 and ERC-1155 collections.
+//!
+//! ERC-721 Enumerable collections list the tokens of an owner themselves.
+//! For the others the token ids come from the transfer logs to the owner,
+//! they're kept and checked against the contract on every listing, as the
+//! tokens may have left since. The first log scan covers recent blocks only,
+//! older ones are scanned on request. Metadata URIs are read from the contracts,
+//! the documents and media behind them are left for the user to open.
+use alloy::{
+    primitives::{Address, Bytes, FixedBytes, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol,
+    sol_types::{SolCall, SolEvent},
+};
+use alloy_provider::DynProvider;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    config::BlockChain,
+    eth::{
+        EvmClient,
+        indexer::{AdaptiveRange, floor_scope, older_range, scan_start},
+    },
+    repository::{CheckpointKey, CheckpointRepository, NftRepository, TxRepository},
+};
+
+sol!(
+    #[sol(rpc)]
+    Erc721Contract,
+    "src/chain/eth/abi/erc721.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    Erc1155Contract,
+    "src/chain/eth/abi/erc1155.json"
+);
+
+/// ERC-165 interface ids.
+const ERC721_INTERFACE: FixedBytes<4> = FixedBytes([0x80, 0xac, 0x58, 0xcd]);
+const ERC721_ENUMERABLE_INTERFACE: FixedBytes<4> = FixedBytes([0x78, 0x0e, 0x9d, 0x63]);
+const ERC1155_INTERFACE: FixedBytes<4> = FixedBytes([0xd9, 0xb6, 0x7a, 0x26]);
+
+const NFTS_SCOPE: &str = "nfts";
+/// How far back the first log scan of a collection goes.
+const FIRST_SCAN_DEPTH: u64 = 1_000_000;
+/// Tokens listed per collection, each costs a few calls.
+const MAX_LISTED: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+/// A collection tracked by the wallet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NftCollection {
+    pub address: Address,
+    pub name: String,
+    pub standard: NftStandard,
+}
+
+/// Token of a collection moved by a transfer, the payload's token being the
+/// collection itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct NftItem {
+    pub standard: NftStandard,
+    /// Decimal token id.
+    pub token_id: String,
+}
+
+impl NftItem {
+    pub fn id(&self) -> Result<U256, String> {
+        self.token_id
+            .parse()
+            .map_err(|e| format!("invalid token id '{}': {e}", self.token_id))
+    }
+
+    /// `safeTransferFrom` calldata moving `amount` of the token, ERC-721
+    /// tokens being sent whole.
+    pub fn transfer_call(&self, from: Address, to: Address, amount: U256) -> Result<Bytes, String> {
+        if self.standard == NftStandard::Erc721 && amount != U256::from(1) {
+            return Err("An ERC-721 token can only be sent whole".to_string());
+        }
+        Ok(transfer_input(self.standard, from, to, self.id()?, amount))
+    }
+}
+
+/// `safeTransferFrom` calldata of either standard, `amount` being ignored for
+/// ERC-721.
+pub fn transfer_input(
+    standard: NftStandard,
+    from: Address,
+    to: Address,
+    id: U256,
+    amount: U256,
+) -> Bytes {
+    match standard {
+        NftStandard::Erc721 => Erc721Contract::safeTransferFromCall {
+            from,
+            to,
+            tokenId: id,
+        }
+        .abi_encode(),
+        NftStandard::Erc1155 => Erc1155Contract::safeTransferFromCall {
+            from,
+            to,
+            id,
+            value: amount,
+            data: Bytes::new(),
+        }
+        .abi_encode(),
+    }
+    .into()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedNft {
+    pub token_id: U256,
+    /// Always 1 for ERC-721.
+    pub amount: U256,
+    pub metadata_uri: Option<String>,
+}
+
+/// Tokens of a collection an owner holds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holdings {
+    pub tokens: Vec<OwnedNft>,
+    /// Oldest block whose transfer logs were read, when the collection can't
+    /// list the tokens itself and older blocks weren't scanned yet.
+    pub scanned_from: Option<u64>,
+}
+
+/// Expand the `{id}` placeholder of an ERC-1155 URI: the id in lowercase hex,
+/// padded to 64 digits.
+pub fn expand_uri(template: &str, id: U256) -> String {
+    template.replace("{id}", &format!("{id:064x}"))
+}
+
+#[derive(Debug)]
+pub struct NftRetriever {
+    provider: DynProvider,
+}
+
+impl NftRetriever {
+    pub fn new(provider: DynProvider) -> Self {
+        Self { provider }
+    }
+
+    /// Standard and name of the collection at `address`, rejecting contracts
+    /// implementing neither standard.
+    pub async fn collection_info(&self, address: Address) -> Result<NftCollection, String> {
+        let standard = if self.supports(address, ERC721_INTERFACE).await {
+            NftStandard::Erc721
+        } else if self.supports(address, ERC1155_INTERFACE).await {
+            NftStandard::Erc1155
+        } else {
+            return Err(format!(
+                "{address} is neither an ERC-721 nor an ERC-1155 contract"
+            ));
+        };
+        // `name` is optional in both standards.
+        let name = Erc721Contract::new(address, self.provider.clone())
+            .name()
+            .call()
+            .await
+            .unwrap_or_else(|_| address.to_string());
+        Ok(NftCollection {
+            address,
+            name,
+            standard,
+        })
+    }
+
+    async fn supports(&self, address: Address, interface: FixedBytes<4>) -> bool {
+        Erc721Contract::new(address, self.provider.clone())
+            .supportsInterface(interface)
+            .call()
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Ids of the tokens `owner` holds, straight from an ERC-721 Enumerable
+    /// contract. `None` when the collection isn't one.
+    async fn enumerate(
+        &self,
+        owner: Address,
+        collection: &NftCollection,
+    ) -> Result<Option<Vec<U256>>, String> {
+        if collection.standard != NftStandard::Erc721
+            || !self
+                .supports(collection.address, ERC721_ENUMERABLE_INTERFACE)
+                .await
+        {
+            return Ok(None);
+        }
+        let contract = Erc721Contract::new(collection.address, self.provider.clone());
+        let balance = contract
+            .balanceOf(owner)
+            .call()
+            .await
+            .map_err(|e| format!("Failed to fetch NFT balance: {e}"))?;
+        let count = balance.saturating_to::<usize>().min(MAX_LISTED);
+        let ids = (0..count).map(|index| {
+            let contract = contract.clone();
+            async move {
+                contract
+                    .tokenOfOwnerByIndex(owner, U256::from(index))
+                    .call()
+                    .await
+                    .map_err(|e| format!("Failed to enumerate NFTs: {e}"))
+            }
+        });
+        futures::future::try_join_all(ids).await.map(Some)
+    }
+
+    /// Amounts of `ids` `owner` holds right now, zero for the ones it doesn't.
+    async fn held(
+        &self,
+        owner: Address,
+        collection: &NftCollection,
+        ids: &[U256],
+    ) -> Result<Vec<U256>, String> {
+        match collection.standard {
+            NftStandard::Erc721 => {
+                let contract = Erc721Contract::new(collection.address, self.provider.clone());
+                let owners = ids.iter().map(|id| {
+                    let contract = contract.clone();
+                    async move {
+                        // Burned tokens revert.
+                        match contract.ownerOf(*id).call().await {
+                            Ok(holder) if holder == owner => U256::from(1),
+                            _ => U256::ZERO,
+                        }
+                    }
+                });
+                Ok(futures::future::join_all(owners).await)
+            }
+            NftStandard::Erc1155 => Erc1155Contract::new(collection.address, self.provider.clone())
+                .balanceOfBatch(vec![owner; ids.len()], ids.to_vec())
+                .call()
+                .await
+                .map_err(|e| format!("Failed to fetch NFT balances: {e}")),
+        }
+    }
+
+    /// Metadata URI of a token, if the contract has one.
+    pub async fn metadata_uri(&self, collection: &NftCollection, id: U256) -> Option<String> {
+        let uri = match collection.standard {
+            NftStandard::Erc721 => Erc721Contract::new(collection.address, self.provider.clone())
+                .tokenURI(id)
+                .call()
+                .await
+                .ok()?,
+            NftStandard::Erc1155 => expand_uri(
+                &Erc1155Contract::new(collection.address, self.provider.clone())
+                    .uri(id)
+                    .call()
+                    .await
+                    .ok()?,
+                id,
+            ),
+        };
+        (!uri.is_empty()).then_some(uri)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct NftLog {
+    token_ids: Vec<U256>,
+    tx_hash: String,
+    block: u64,
+}
+
+fn decode_nft_log(standard: NftStandard, log: &Log) -> Option<NftLog> {
+    if log.removed {
+        return None;
+    }
+    let token_ids = match standard {
+        NftStandard::Erc721 => vec![
+            log.log_decode::<Erc721Contract::Transfer>()
+                .ok()?
+                .inner
+                .data
+                .tokenId,
+        ],
+        NftStandard::Erc1155 => match log.log_decode::<Erc1155Contract::TransferSingle>() {
+            Ok(single) => vec![single.inner.data.id],
+            Err(_) => log
+                .log_decode::<Erc1155Contract::TransferBatch>()
+                .ok()?
+                .inner
+                .data
+                .ids
+                .clone(),
+        },
+    };
+    Some(NftLog {
+        token_ids,
+        tx_hash: log.transaction_hash?.to_string(),
+        block: log.block_number?,
+    })
+}
+
+/// Transfers of `collection` to and from `owner` in `from..=to`.
+async fn nft_logs(
+    provider: &DynProvider,
+    collection: &NftCollection,
+    owner: Address,
+    from: u64,
+    to: u64,
+) -> Result<(Vec<NftLog>, Vec<NftLog>), String> {
+    let base = Filter::new()
+        .address(collection.address)
+        .from_block(from)
+        .to_block(to);
+    let owner = owner.into_word();
+    let (incoming, outgoing) = match collection.standard {
+        NftStandard::Erc721 => {
+            let base = base.event_signature(Erc721Contract::Transfer::SIGNATURE_HASH);
+            (base.clone().topic2(owner), base.topic1(owner))
+        }
+        NftStandard::Erc1155 => {
+            let base = base.event_signature(vec![
+                Erc1155Contract::TransferSingle::SIGNATURE_HASH,
+                Erc1155Contract::TransferBatch::SIGNATURE_HASH,
+            ]);
+            (base.clone().topic3(owner), base.topic2(owner))
+        }
+    };
+    let mut decoded = Vec::with_capacity(2);
+    for filter in [incoming, outgoing] {
+        let logs = provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| format!("eth_getLogs failed: {e}"))?;
+        decoded.push(
+            logs.iter()
+                .filter_map(|log| decode_nft_log(collection.standard, log))
+                .collect::<Vec<_>>(),
+        );
+    }
+    let outgoing = decoded.pop().unwrap_or_default();
+    let incoming = decoded.pop().unwrap_or_default();
+    Ok((incoming, outgoing))
+}
+
+pub struct NftScanner<'a> {
+    pub client: &'a EvmClient,
+    pub nfts: &'a NftRepository,
+    pub checkpoints: &'a CheckpointRepository,
+    pub history: &'a TxRepository,
+}
+
+impl NftScanner<'_> {
+    /// Tokens of `collection` `owner` holds, after scanning the blocks since
+    /// the last run.
+    pub async fn owned(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+        collection: &NftCollection,
+    ) -> Result<Holdings, String> {
+        let received = self.scan(wallet_name, owner, collection).await?;
+        let retriever = NftRetriever::new(self.client.provider.clone());
+        let (ids, scanned_from) = match retriever.enumerate(owner, collection).await? {
+            Some(ids) => (ids, None),
+            None => (received, self.scanned_from(wallet_name, owner, collection)?),
+        };
+        let amounts = retriever.held(owner, collection, &ids).await?;
+
+        let mut owned = Vec::new();
+        for (token_id, amount) in ids.into_iter().zip(amounts) {
+            if amount.is_zero() {
+                continue;
+            }
+            owned.push(OwnedNft {
+                token_id,
+                amount,
+                metadata_uri: retriever.metadata_uri(collection, token_id).await,
+            });
+            if owned.len() == MAX_LISTED {
+                break;
+            }
+        }
+        Ok(Holdings {
+            tokens: owned,
+            scanned_from,
+        })
+    }
+
+    /// Oldest block scanned for transfers of `collection`, `None` once the
+    /// scans reached genesis.
+    pub fn scanned_from(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+        collection: &NftCollection,
+    ) -> Result<Option<u64>, String> {
+        let address = owner.to_string();
+        let floor = floor_scope(&collection_scope(collection));
+        Ok(self
+            .checkpoints
+            .get(&self.key(wallet_name, &address, &floor))?
+            .filter(|block| *block > 0))
+    }
+
+    /// Scan [`FIRST_SCAN_DEPTH`] blocks below the oldest one scanned so far.
+    pub async fn scan_older(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+        collection: &NftCollection,
+    ) -> Result<(), String> {
+        let address = owner.to_string();
+        let floor = floor_scope(&collection_scope(collection));
+        let floor_key = self.key(wallet_name, &address, &floor);
+        let Some((from, to)) = self
+            .checkpoints
+            .get(&floor_key)?
+            .and_then(|block| older_range(block, FIRST_SCAN_DEPTH))
+        else {
+            return Ok(());
+        };
+        self.scan_range(wallet_name, owner, collection, from, to, None)
+            .await?;
+        self.checkpoints.set(&floor_key, from)
+    }
+
+    /// Scan the transfer logs of `collection`, returning every token id
+    /// `owner` ever received. Sent transfers of the wallet are confirmed on
+    /// the way, they leave no log the indexer picks up.
+    async fn scan(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+        collection: &NftCollection,
+    ) -> Result<Vec<U256>, String> {
+        let address = owner.to_string();
+        let scope = collection_scope(collection);
+        let key = self.key(wallet_name, &address, &scope);
+        let latest = self
+            .client
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| format!("failed to get block number: {e}"))?;
+        let checkpoint = self.checkpoints.get(&key)?;
+        let from = scan_start(checkpoint, latest, FIRST_SCAN_DEPTH);
+        if checkpoint.is_none() {
+            let floor = floor_scope(&scope);
+            self.checkpoints
+                .set(&self.key(wallet_name, &address, &floor), from)?;
+        }
+        self.scan_range(wallet_name, owner, collection, from, latest, Some(&key))
+            .await?;
+
+        self.nfts
+            .list(
+                wallet_name,
+                &self.client.network.id,
+                &address,
+                &collection.address.to_string(),
+            )?
+            .iter()
+            .map(|id| {
+                id.parse()
+                    .map_err(|e| format!("invalid stored token id '{id}': {e}"))
+            })
+            .collect()
+    }
+
+    /// Record the tokens received between `from` and `to`, moving
+    /// `checkpoint` along.
+    async fn scan_range(
+        &self,
+        wallet_name: &str,
+        owner: Address,
+        collection: &NftCollection,
+        mut from: u64,
+        to: u64,
+        checkpoint: Option<&CheckpointKey<'_>>,
+    ) -> Result<(), String> {
+        let network = &self.client.network.id;
+        let address = owner.to_string();
+        let contract = collection.address.to_string();
+        let mut range = AdaptiveRange::default();
+        while from <= to {
+            let end = range.end(from, to);
+            let (incoming, outgoing) =
+                match nft_logs(&self.client.provider, collection, owner, from, end).await {
+                    Ok(logs) => logs,
+                    Err(e) if range.shrink() => {
+                        tracing::debug!("{network}: shrinking NFT log range: {e}");
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+            let ids: Vec<_> = incoming
+                .iter()
+                .flat_map(|log| log.token_ids.iter().map(U256::to_string))
+                .collect();
+            self.nfts
+                .add(wallet_name, network, &address, &contract, &ids)?;
+            for log in &outgoing {
+                self.history.confirm_pending(
+                    wallet_name,
+                    BlockChain::Ethereum,
+                    &log.tx_hash,
+                    log.block,
+                )?;
+            }
+            if let Some(key) = checkpoint {
+                self.checkpoints.set(key, end)?;
+            }
+            range.grow();
+            from = end + 1;
+        }
+        Ok(())
+    }
+
+    fn key<'k>(
+        &'k self,
+        wallet_name: &'k str,
+        address: &'k str,
+        scope: &'k str,
+    ) -> CheckpointKey<'k> {
+        CheckpointKey {
+            wallet_name,
+            chain: BlockChain::Ethereum,
+            network: &self.client.network.id,
+            address,
+            scope,
+        }
+    }
+}
+
+fn collection_scope(collection: &NftCollection) -> String {
+    format!("{NFTS_SCOPE}:{}", collection.address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn erc1155_uri_ids_are_padded_hex() {
+        assert_eq!(
+            expand_uri("ipfs://meta/{id}.json", U256::from(314_592u64)),
+            "ipfs://meta/000000000000000000000000000000000000000000000000000000000004cce0.json"
+        );
+        assert_eq!(
+            expand_uri("https://meta/7", U256::from(7)),
+            "https://meta/7"
+        );
+    }
+
+    #[test]
+    fn transfers_use_safe_transfer_from() {
+        let (from, to) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let erc721 = NftItem {
+            standard: NftStandard::Erc721,
+            token_id: "42".to_string(),
+        };
+        let input = erc721.transfer_call(from, to, U256::from(1)).unwrap();
+        let call = Erc721Contract::safeTransferFromCall::abi_decode(&input).unwrap();
+        assert_eq!(
+            (call.from, call.to, call.tokenId),
+            (from, to, U256::from(42))
+        );
+        assert!(erc721.transfer_call(from, to, U256::from(2)).is_err());
+
+        let erc1155 = NftItem {
+            standard: NftStandard::Erc1155,
+            token_id: "7".to_string(),
+        };
+        let input = erc1155.transfer_call(from, to, U256::from(5)).unwrap();
+        let call = Erc1155Contract::safeTransferFromCall::abi_decode(&input).unwrap();
+        assert_eq!((call.id, call.value), (U256::from(7), U256::from(5)));
+        assert!(call.data.is_empty());
+
+        let invalid = NftItem {
+            standard: NftStandard::Erc1155,
+            token_id: "seven".to_string(),
+        };
+        assert!(invalid.transfer_call(from, to, U256::from(1)).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Nonce sequencing for transfers sent before the earlier ones are mined.
+//!
+//! The node's `pending` count stops at the first nonce missing from its pool,
+//! and public endpoints behind a load balancer may not have seen our last
+//! broadcast yet. So the next nonce also accounts for the transfers recorded
+//! as pending in the history and the ones this process sent.
+use std::collections::{BTreeSet, HashMap};
+
+use alloy::primitives::Address;
+use alloy_provider::Provider;
+use tokio::sync::Mutex;
+
+use crate::eth::EvmClient;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceState {
+    /// Transactions of the sender included in the latest block.
+    pub mined: u64,
+    /// The node's count, including the transactions in its pool.
+    pub pending: u64,
+    /// Nonce for the next transfer.
+    pub next: u64,
+    /// Our transfers not mined yet, lowest nonce first.
+    pub queued: Vec<u64>,
+    /// Nonces below `next` neither the node nor the history knows of.
+    /// Everything queued above the first gap waits until it's filled.
+    pub gaps: Vec<u64>,
+    /// Queued transfers at or above the node's `pending` count, so missing
+    /// from its pool: it dropped them or hasn't seen them yet, as right after
+    /// a send through a load balanced endpoint. They aren't gaps, anything
+    /// sent on their nonce replaces them, so they're only rebroadcast.
+    pub dropped: Vec<u64>,
+}
+
+impl NonceState {
+    pub fn new(mined: u64, pending: u64, known: impl IntoIterator<Item = u64>) -> Self {
+        let pending = pending.max(mined);
+        let queued: BTreeSet<u64> = known.into_iter().filter(|n| *n >= mined).collect();
+        let next = queued.last().map_or(pending, |n| pending.max(n + 1));
+        let gaps = (pending..next).filter(|n| !queued.contains(n)).collect();
+        let dropped = queued.range(pending..).copied().collect();
+        Self {
+            mined,
+            pending,
+            next,
+            queued: queued.into_iter().collect(),
+            gaps,
+            dropped,
+        }
+    }
+}
+
+/// Nonces broadcast by this process, per chain id and sender.
+#[derive(Default)]
+pub struct NonceManager {
+    sent: Mutex<HashMap<(u64, Address), BTreeSet<u64>>>,
+}
+
+impl NonceManager {
+    /// Nonce state of `sender`, given the nonces of its transfers recorded as
+    /// pending.
+    pub async fn state(
+        &self,
+        client: &EvmClient,
+        sender: Address,
+        recorded: &[u64],
+    ) -> Result<NonceState, String> {
+        let mined = client
+            .provider
+            .get_transaction_count(sender)
+            .latest()
+            .await
+            .map_err(|e| format!("failed to get mined nonce: {e}"))?;
+        let pending = client
+            .provider
+            .get_transaction_count(sender)
+            .pending()
+            .await
+            .map_err(|e| format!("failed to get pending nonce: {e}"))?;
+
+        let mut sent = self.sent.lock().await;
+        let ours = sent.entry((client.chain_id(), sender)).or_default();
+        ours.retain(|n| *n >= mined);
+        Ok(NonceState::new(
+            mined,
+            pending,
+            recorded.iter().chain(ours.iter()).copied(),
+        ))
+    }
+
+    pub async fn mark_sent(&self, chain_id: u64, sender: Address, nonce: u64) {
+        self.sent
+            .lock()
+            .await
+            .entry((chain_id, sender))
+            .or_default()
+            .insert(nonce);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_nonce_follows_our_queue() {
+        // Nothing in flight.
+        let idle = NonceState::new(5, 5, []);
+        assert_eq!((idle.next, idle.queued.len(), idle.gaps.len()), (5, 0, 0));
+
+        // Two transfers queued, the node already holds both.
+        let queued = NonceState::new(5, 7, [5, 6]);
+        assert_eq!(queued.next, 7);
+        assert_eq!(queued.queued, vec![5, 6]);
+        assert!(queued.gaps.is_empty());
+
+        // The node hasn't seen our last broadcast yet.
+        let lagging = NonceState::new(5, 5, [5]);
+        assert_eq!(lagging.next, 6);
+        assert!(lagging.gaps.is_empty());
+        assert_eq!(lagging.dropped, vec![5]);
+
+        // Mined transfers still marked pending don't count.
+        let stale = NonceState::new(9, 9, [3, 4]);
+        assert_eq!(stale.next, 9);
+        assert!(stale.queued.is_empty());
+    }
+
+    #[test]
+    fn missing_nonces_are_gaps() {
+        let state = NonceState::new(5, 6, [5, 8, 10]);
+        assert_eq!(state.next, 11);
+        assert_eq!(state.gaps, vec![6, 7, 9]);
+        assert_eq!(state.dropped, vec![8, 10]);
+
+        // A pending count behind the mined one is a stale node.
+        let stale = NonceState::new(5, 3, []);
+        assert_eq!((stale.pending, stale.next), (5, 5));
+    }
+
+    #[test]
+    fn recorded_nonces_the_node_lacks_are_dropped() {
+        let state = NonceState::new(5, 5, [5, 6]);
+        assert_eq!(state.next, 7);
+        assert_eq!(state.queued, vec![5, 6]);
+        assert!(state.gaps.is_empty());
+        assert_eq!(state.dropped, vec![5, 6]);
+    }
+}
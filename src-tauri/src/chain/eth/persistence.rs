@@ -1,7 +1,11 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
     chain::eth::{
+        network::MAINNET,
+        nft::{NftCollection, NftStandard},
         token::Token,
         wallet::{Account, EthereumWallet, parse_addres},
     },
@@ -38,6 +42,35 @@ impl TryFrom<TokenStored> for Token {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NftCollectionStored {
+    pub address: String,
+    pub name: String,
+    pub standard: NftStandard,
+}
+
+impl From<&NftCollection> for NftCollectionStored {
+    fn from(c: &NftCollection) -> Self {
+        NftCollectionStored {
+            address: c.address.to_string(),
+            name: c.name.clone(),
+            standard: c.standard,
+        }
+    }
+}
+
+impl TryFrom<NftCollectionStored> for NftCollection {
+    type Error = String;
+
+    fn try_from(s: NftCollectionStored) -> Result<Self, Self::Error> {
+        Ok(NftCollection {
+            address: parse_addres(&s.address)?,
+            name: s.name,
+            standard: s.standard,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WalletStored {
     pub accounts: Vec<AccountStored>,
@@ -48,7 +81,15 @@ pub struct WalletStored {
 pub struct AccountStored {
     pub index: u32,
     pub name: String,
+    /// Mainnet tokens of wallets saved before networks were introduced.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tracked_tokens: Vec<TokenStored>,
+    #[serde(default)]
+    pub network: String,
+    #[serde(default)]
+    pub tokens_by_network: HashMap<String, Vec<TokenStored>>,
+    #[serde(default)]
+    pub collections_by_network: HashMap<String, Vec<NftCollectionStored>>,
 }
 
 impl From<&EthereumWallet> for WalletStored {
@@ -60,10 +101,27 @@ impl From<&EthereumWallet> for WalletStored {
                 .map(|account| AccountStored {
                     index: account.index,
                     name: account.name.clone(),
-                    tracked_tokens: account
+                    tracked_tokens: Vec::new(),
+                    network: account.network.clone(),
+                    tokens_by_network: account
                         .tracked_tokens
                         .iter()
-                        .map(TokenStored::from)
+                        .map(|(network, tokens)| {
+                            (
+                                network.clone(),
+                                tokens.iter().map(TokenStored::from).collect(),
+                            )
+                        })
+                        .collect(),
+                    collections_by_network: account
+                        .tracked_collections
+                        .iter()
+                        .map(|(network, collections)| {
+                            (
+                                network.clone(),
+                                collections.iter().map(NftCollectionStored::from).collect(),
+                            )
+                        })
                         .collect(),
                 })
                 .collect(),
@@ -78,14 +136,42 @@ impl EthereumWallet {
             .accounts
             .into_iter()
             .map(|account| {
+                let mut by_network = account.tokens_by_network;
+                if by_network.is_empty() {
+                    by_network.insert(MAINNET.to_string(), account.tracked_tokens);
+                }
+                let tracked_tokens = by_network
+                    .into_iter()
+                    .map(|(network, tokens)| {
+                        let tokens = tokens
+                            .into_iter()
+                            .map(Token::try_from)
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok::<_, String>((network, tokens))
+                    })
+                    .collect::<Result<HashMap<_, _>, _>>()?;
+                let tracked_collections = account
+                    .collections_by_network
+                    .into_iter()
+                    .map(|(network, collections)| {
+                        let collections = collections
+                            .into_iter()
+                            .map(NftCollection::try_from)
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok::<_, String>((network, collections))
+                    })
+                    .collect::<Result<HashMap<_, _>, _>>()?;
+                let network = if account.network.is_empty() {
+                    MAINNET.to_string()
+                } else {
+                    account.network
+                };
                 Ok::<Account, String>(Account {
                     index: account.index,
                     name: account.name,
-                    tracked_tokens: account
-                        .tracked_tokens
-                        .into_iter()
-                        .map(Token::try_from)
-                        .collect::<Result<Vec<_>, _>>()?,
+                    network,
+                    tracked_tokens,
+                    tracked_collections,
                 })
             })
             .collect::<Result<Vec<_>, _>>()?;
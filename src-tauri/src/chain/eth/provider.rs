@@ -1,34 +1,38 @@
-use std::time::Duration;
+use std::{collections::HashMap, sync::OnceLock, time::Duration};
 
 use alloy::{providers::RootProvider, rpc::client::RpcClient};
 use alloy_provider::{DynProvider, Provider, ProviderBuilder};
 use tauri::Url;
+use tokio::sync::Mutex;
 
-use crate::{config::Config, eth::config::EthereumConfig};
+use crate::{
+    config::Config,
+    eth::{Erc20Retriever, PriceFeed, config::EthereumConfig, network::EvmNetwork},
+};
 
-pub fn select_provider(config: Config) -> DynProvider {
-    if config.eth.anvil {
-        new_provider_anvil(config.eth)
-    } else if config.tor.enabled {
-        new_provider_tor(config.eth, &config.tor.socks5_proxy)
+/// Plain HTTP or Tor provider for `url`, depending on the config.
+pub fn select_provider(config: &Config, url: &str) -> Result<DynProvider, String> {
+    if config.tor.enabled {
+        new_provider_tor(url, &config.tor.socks5_proxy)
     } else {
-        new_provider(config.eth)
+        new_provider(url)
     }
 }
 
-pub fn new_provider(config: EthereumConfig) -> DynProvider {
-    let rpc_url = config.rpc_url.clone();
-    RootProvider::new_http(Url::parse(&rpc_url).expect("Invalid RPC URL for Ethereum")).erased()
+pub fn new_provider(url: &str) -> Result<DynProvider, String> {
+    let url = Url::parse(url).map_err(|e| format!("invalid RPC URL {url}: {e}"))?;
+    Ok(RootProvider::new_http(url).erased())
 }
 
-pub fn new_provider_tor(config: EthereumConfig, proxy_url: &str) -> DynProvider {
-    let proxy = reqwest::Proxy::all(proxy_url).expect("invalid Tor proxy URL");
+pub fn new_provider_tor(url: &str, proxy_url: &str) -> Result<DynProvider, String> {
+    let proxy =
+        reqwest::Proxy::all(proxy_url).map_err(|e| format!("invalid Tor proxy URL: {e}"))?;
     let client = reqwest::Client::builder()
         .proxy(proxy)
         .build()
-        .expect("failed to build Tor HTTP client for Ethereum");
-    let url = Url::parse(&config.rpc_url).expect("Invalid RPC URL for Ethereum");
-    RootProvider::new(RpcClient::new_http_with_client(client, url)).erased()
+        .map_err(|e| format!("failed to build Tor HTTP client for Ethereum: {e}"))?;
+    let url = Url::parse(url).map_err(|e| format!("invalid RPC URL {url}: {e}"))?;
+    Ok(RootProvider::new(RpcClient::new_http_with_client(client, url)).erased())
 }
 
 pub fn new_provider_batched(provider: DynProvider) -> DynProvider {
@@ -45,3 +49,117 @@ pub fn new_provider_anvil(config: EthereumConfig) -> DynProvider {
         .connect_anvil_with_config(|anvil| anvil.fork(rpc_url).path(config.anvil_bin()))
         .erased()
 }
+
+/// Connection to one EVM network whose chain id has been verified.
+#[derive(Clone)]
+pub struct EvmClient {
+    pub network: EvmNetwork,
+    pub provider: DynProvider,
+    /// Batches `eth_call`s issued close together into one request.
+    pub batched: DynProvider,
+}
+
+impl EvmClient {
+    pub fn chain_id(&self) -> u64 {
+        self.network.chain_id()
+    }
+
+    pub fn erc20(&self) -> Erc20Retriever {
+        Erc20Retriever::new(self.provider.clone())
+    }
+
+    pub fn price_feed(&self) -> PriceFeed {
+        PriceFeed::new(self.provider.clone())
+    }
+}
+
+/// Providers per network id, connected on first use.
+#[derive(Default)]
+pub struct EvmProviders {
+    clients: Mutex<HashMap<String, EvmClient>>,
+    /// The local fork lives as long as its provider, so there's only one.
+    anvil: OnceLock<DynProvider>,
+}
+
+impl EvmProviders {
+    pub async fn client(&self, config: &Config, network: &str) -> Result<EvmClient, String> {
+        let network = config.eth.network(network)?;
+        // An edited registry entry gets a fresh connection.
+        if let Some(client) = self.clients.lock().await.get(&network.id)
+            && client.network == *network
+        {
+            return Ok(client.clone());
+        }
+
+        // Connecting may try several endpoints, other networks stay usable
+        // meanwhile.
+        let provider = if config.eth.anvil {
+            let provider = self
+                .anvil
+                .get_or_init(|| new_provider_anvil(config.eth.clone()))
+                .clone();
+            verify_chain_id(&provider, network).await?;
+            provider
+        } else {
+            connect(config, network).await?
+        };
+        let client = EvmClient {
+            network: network.clone(),
+            batched: new_provider_batched(provider.clone()),
+            provider,
+        };
+        let mut clients = self.clients.lock().await;
+        // Another caller may have connected meanwhile, keep its client.
+        if let Some(existing) = clients.get(&network.id)
+            && existing.network == *network
+        {
+            return Ok(existing.clone());
+        }
+        clients.insert(network.id.clone(), client.clone());
+        Ok(client)
+    }
+}
+
+/// First endpoint of `network` that answers with the expected chain id.
+async fn connect(config: &Config, network: &EvmNetwork) -> Result<DynProvider, String> {
+    let mut errors = Vec::new();
+    for url in &network.rpc_urls {
+        let attempt = match select_provider(config, url) {
+            Ok(provider) => verify_chain_id(&provider, network).await.map(|_| provider),
+            Err(e) => Err(e),
+        };
+        match attempt {
+            Ok(provider) => return Ok(provider),
+            Err(e) => {
+                tracing::warn!("{}: {url} rejected: {e}", network.id);
+                errors.push(format!("{url}: {e}"));
+            }
+        }
+    }
+    if errors.is_empty() {
+        return Err(format!("{} has no RPC URL configured", network.name));
+    }
+    Err(format!(
+        "no usable RPC endpoint for {}: {}",
+        network.name,
+        errors.join("; ")
+    ))
+}
+
+async fn verify_chain_id(provider: &DynProvider, network: &EvmNetwork) -> Result<(), String> {
+    let reported = provider
+        .get_chain_id()
+        .await
+        .map_err(|e| format!("eth_chainId failed: {e}"))?;
+    check_chain_id(network, reported)
+}
+
+fn check_chain_id(network: &EvmNetwork, reported: u64) -> Result<(), String> {
+    if reported != network.chain_id() {
+        return Err(format!(
+            "endpoint serves chain {reported}, {} is chain {}",
+            network.name, network.chain_id
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//! Replacing a stuck transaction by signing another one with the same nonce.
+//!
+//! Nodes only swap a pending transaction for one raising both fee caps by at
+//! least 10%, so every replacement is priced off the original as well as off
+//! the current network estimate.
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{Address, Bytes, U256},
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+};
+use alloy_provider::utils::Eip1559Estimation;
+
+use crate::{
+    eth::{
+        constants::NATIVE_TOKEN,
+        contract::ContractCall,
+        erc20_retriver::Erc20Contract,
+        nft::{NftStandard, transfer_input},
+        wallet::parse_addres,
+    },
+    repository::{EthChainData, TxRecord},
+};
+
+/// Gas of a plain value transfer, all a cancellation needs.
+pub const TRANSFER_GAS: u64 = 21_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replacement {
+    /// The same call with higher fees.
+    SpeedUp,
+    /// A 0-value transfer to the sender, voiding the original call.
+    Cancel,
+}
+
+/// The call recorded for a pending history row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OriginalTx {
+    pub sender: Address,
+    pub recipient: Address,
+    pub token: Address,
+    /// Amount in the token's base units, the allowance of an `approve` call.
+    pub value: U256,
+    /// Spender of an `approve` call.
+    pub spender: Option<Address>,
+    /// Standard and id of the NFT sent, `value` being the number of copies.
+    pub nft: Option<(NftStandard, U256)>,
+    /// Call data of a contract call, sent to `recipient` with `value` wei.
+    pub call: Option<Bytes>,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub fees: Eip1559Estimation,
+}
+
+impl OriginalTx {
+    pub fn from_record(record: &TxRecord, data: &EthChainData) -> Result<Self, String> {
+        let address = |a: &Option<String>, what: &str| {
+            a.as_deref()
+                .ok_or(format!("transaction has no {what} address"))
+                .and_then(parse_addres)
+        };
+        let parse = |v: &str, what: &str| {
+            v.parse::<u128>()
+                .map_err(|e| format!("invalid {what} '{v}': {e}"))
+        };
+        // Rows written before `value` was recorded only have the i64 amount.
+        let value = data.value.as_deref().unwrap_or(&record.amount);
+        Ok(Self {
+            sender: address(&record.from_address, "sender")?,
+            recipient: address(&record.to_address, "recipient")?,
+            token: parse_addres(&data.token_address)?,
+            value: value
+                .parse()
+                .map_err(|e| format!("invalid amount '{value}': {e}"))?,
+            spender: data.spender.as_deref().map(parse_addres).transpose()?,
+            nft: data
+                .nft
+                .as_ref()
+                .map(|item| Ok::<_, String>((item.standard, item.id()?)))
+                .transpose()?,
+            call: data.call.as_ref().map(ContractCall::input).transpose()?,
+            nonce: data.nonce,
+            gas_limit: parse(&data.gas_limit, "gas limit")? as u64,
+            fees: Eip1559Estimation {
+                max_fee_per_gas: parse(&data.max_fee_per_gas, "max fee")?,
+                max_priority_fee_per_gas: parse(&data.max_priority_fee_per_gas, "priority fee")?,
+            },
+        })
+    }
+
+    /// Unsigned replacement paying `fees`, see [`bump_fees`].
+    pub fn replacement(
+        &self,
+        kind: Replacement,
+        chain_id: u64,
+        fees: Eip1559Estimation,
+    ) -> TransactionRequest {
+        if kind == Replacement::Cancel {
+            return noop(self.sender, self.nonce, chain_id, fees);
+        }
+        let tx = TransactionRequest::default()
+            .with_from(self.sender)
+            .with_nonce(self.nonce)
+            .with_chain_id(chain_id)
+            .with_max_fee_per_gas(fees.max_fee_per_gas)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+        match kind {
+            Replacement::SpeedUp if self.token == NATIVE_TOKEN => {
+                let tx = tx
+                    .with_to(self.recipient)
+                    .with_value(self.value)
+                    .with_gas_limit(self.gas_limit);
+                match &self.call {
+                    Some(input) => tx.with_input(input.clone()),
+                    None => tx,
+                }
+            }
+            _ => {
+                let input = match (self.spender, self.nft) {
+                    (Some(spender), _) => Erc20Contract::approveCall {
+                        _spender: spender,
+                        _value: self.value,
+                    }
+                    .abi_encode()
+                    .into(),
+                    (None, Some((standard, id))) => {
+                        transfer_input(standard, self.sender, self.recipient, id, self.value)
+                    }
+                    (None, None) => Erc20Contract::transferCall {
+                        _to: self.recipient,
+                        _value: self.value,
+                    }
+                    .abi_encode()
+                    .into(),
+                };
+                tx.with_to(self.token)
+                    .with_input(input)
+                    .with_gas_limit(self.gas_limit)
+            }
+        }
+    }
+
+    pub fn gas_limit(&self, kind: Replacement) -> u64 {
+        match kind {
+            Replacement::SpeedUp => self.gas_limit,
+            Replacement::Cancel => TRANSFER_GAS,
+        }
+    }
+}
+
+/// A 0-value transfer to `sender` that only consumes `nonce`.
+pub fn noop(
+    sender: Address,
+    nonce: u64,
+    chain_id: u64,
+    fees: Eip1559Estimation,
+) -> TransactionRequest {
+    TransactionRequest::default()
+        .with_from(sender)
+        .with_to(sender)
+        .with_value(U256::ZERO)
+        .with_nonce(nonce)
+        .with_chain_id(chain_id)
+        .with_gas_limit(TRANSFER_GAS)
+        .with_max_fee_per_gas(fees.max_fee_per_gas)
+        .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas)
+}
+
+/// Fees for replacing a transaction that paid `original`: at least 10% more
+/// on both caps, and no less than the network asks for right now.
+pub fn bump_fees(original: Eip1559Estimation, current: &Eip1559Estimation) -> Eip1559Estimation {
+    let bump = |fee: u128| fee + fee.div_ceil(10);
+    let max_priority_fee_per_gas =
+        bump(original.max_priority_fee_per_gas).max(current.max_priority_fee_per_gas);
+    let max_fee_per_gas = bump(original.max_fee_per_gas)
+        .max(current.max_fee_per_gas)
+        .max(max_priority_fee_per_gas);
+    Eip1559Estimation {
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+    use crate::eth::nft::Erc721Contract;
+
+    fn fees(max: u128, priority: u128) -> Eip1559Estimation {
+        Eip1559Estimation {
+            max_fee_per_gas: max,
+            max_priority_fee_per_gas: priority,
+        }
+    }
+
+    fn original(token: Address) -> OriginalTx {
+        OriginalTx {
+            sender: address!("0x1111111111111111111111111111111111111111"),
+            recipient: address!("0x2222222222222222222222222222222222222222"),
+            token,
+            value: U256::from(5_000_000u64),
+            spender: None,
+            nft: None,
+            call: None,
+            nonce: 7,
+            gas_limit: 65_000,
+            fees: fees(20_000_000_000, 2_000_000_000),
+        }
+    }
+
+    #[test]
+    fn replacement_fees_clear_the_ten_percent_rule() {
+        let quiet = fees(1_000_000_000, 1_000_000_000);
+        assert_eq!(
+            bump_fees(fees(20_000_000_001, 2_000_000_000), &quiet),
+            fees(22_000_000_002, 2_200_000_000)
+        );
+
+        let busy = fees(50_000_000_000, 3_000_000_000);
+        assert_eq!(bump_fees(fees(20, 2), &busy), busy);
+        assert_eq!(bump_fees(fees(10, 10), &fees(0, 30)), fees(30, 30));
+    }
+
+    #[test]
+    fn speed_up_keeps_the_call_and_cancel_voids_it() {
+        let usdt = address!("0xdAC17F958D2ee523a2206206994597C13D831ec7");
+        let token = original(usdt);
+        let bumped = bump_fees(token.fees, &fees(0, 0));
+
+        let speed_up = token.replacement(Replacement::SpeedUp, 1, bumped);
+        assert_eq!(speed_up.nonce, Some(7));
+        assert_eq!(speed_up.to, Some(usdt.into()));
+        assert_eq!(speed_up.gas, Some(65_000));
+        let input = speed_up.input.input().unwrap();
+        assert_eq!(input[..4], Erc20Contract::transferCall::SELECTOR);
+
+        let cancel = token.replacement(Replacement::Cancel, 1, bumped);
+        assert_eq!(cancel.nonce, Some(7));
+        assert_eq!(cancel.to, Some(token.sender.into()));
+        assert_eq!(cancel.value, Some(U256::ZERO));
+        assert_eq!(cancel.gas, Some(TRANSFER_GAS));
+        assert!(cancel.input.input().is_none());
+
+        let approval = OriginalTx {
+            spender: Some(token.recipient),
+            ..token.clone()
+        }
+        .replacement(Replacement::SpeedUp, 1, bumped);
+        assert_eq!(approval.to, Some(usdt.into()));
+        let input = approval.input.input().unwrap();
+        assert_eq!(input[..4], Erc20Contract::approveCall::SELECTOR);
+
+        let nft = OriginalTx {
+            nft: Some((NftStandard::Erc721, U256::from(42))),
+            value: U256::from(1),
+            ..token.clone()
+        }
+        .replacement(Replacement::SpeedUp, 1, bumped);
+        assert_eq!(nft.to, Some(usdt.into()));
+        let input = nft.input.input().unwrap();
+        assert_eq!(input[..4], Erc721Contract::safeTransferFromCall::SELECTOR);
+
+        let ether = original(NATIVE_TOKEN).replacement(Replacement::SpeedUp, 1, bumped);
+        assert_eq!(ether.to, Some(token.recipient.into()));
+        assert_eq!(ether.value, Some(U256::from(5_000_000u64)));
+        assert!(ether.input.input().is_none());
+
+        let deposit = Bytes::from_static(&[0xd0, 0xe3, 0x0d, 0xb0]);
+        let call = OriginalTx {
+            call: Some(deposit.clone()),
+            ..original(NATIVE_TOKEN)
+        }
+        .replacement(Replacement::SpeedUp, 1, bumped);
+        assert_eq!(call.to, Some(token.recipient.into()));
+        assert_eq!(call.value, Some(U256::from(5_000_000u64)));
+        assert_eq!(call.input.input(), Some(&deposit));
+    }
+}
@@ -0,0 +1,663 @@
+//! Off-chain signatures: EIP-191 `personal_sign` messages, EIP-712 typed data
+//! and Sign-In with Ethereum (EIP-4361) messages.
+//!
+//! Nothing signed here is broadcast, yet a signature can move funds: token
+//! permits and marketplace orders are typed data. Previews spell out what is
+//! signed and flag those patterns, and sign-in messages are checked against
+//! the site asking for them.
+use std::str::FromStr;
+
+use alloy::{
+    dyn_abi::TypedData,
+    primitives::{Address, B256, Signature, U256, address, eip191_hash_message, hex},
+    signers::SignerSync,
+};
+use alloy_signer_local::PrivateKeySigner;
+use chrono::{DateTime, FixedOffset, Utc};
+use serde_json::{Map, Value};
+
+use crate::eth::{
+    approvals::is_unlimited,
+    dtos::{SignField, SignKind, SignPreview},
+};
+
+const SIWE_HEADER: &str = " wants you to sign in with your Ethereum account:";
+/// Uniswap's Permit2, the same address on every chain.
+const PERMIT2: Address = address!("0x000000000022D473030F116dDEE9F6B43aC78BA3");
+const PERMIT2_TYPES: [&str; 5] = [
+    "PermitSingle",
+    "PermitBatch",
+    "PermitTransferFrom",
+    "PermitBatchTransferFrom",
+    "PermitWitnessTransferFrom",
+];
+const SEAPORT_TYPES: [&str; 2] = ["OrderComponents", "BulkOrder"];
+
+/// Who signs, and for whom.
+#[derive(Debug, Clone)]
+pub struct SignContext {
+    pub address: Address,
+    pub chain_id: u64,
+    /// Site asking for the signature, as the user sees it.
+    pub origin: Option<String>,
+    pub now: DateTime<Utc>,
+}
+
+/// What gets signed.
+#[derive(Debug, Clone)]
+pub enum Payload {
+    /// A `personal_sign` message, given as text or `0x` hex bytes.
+    Message(Vec<u8>),
+    /// The parsed typed data along with its JSON, for rendering.
+    TypedData(Box<TypedData>, Value),
+}
+
+impl Payload {
+    pub fn parse(kind: SignKind, payload: &str) -> Result<Self, String> {
+        match kind {
+            SignKind::PersonalSign => {
+                let bytes = match payload.strip_prefix("0x").map(hex::decode) {
+                    Some(Ok(bytes)) => bytes,
+                    _ => payload.as_bytes().to_vec(),
+                };
+                Ok(Payload::Message(bytes))
+            }
+            SignKind::TypedData => {
+                let json: Value = serde_json::from_str(payload)
+                    .map_err(|e| format!("Typed data is not valid JSON: {e}"))?;
+                let typed = serde_json::from_value(json.clone())
+                    .map_err(|e| format!("Invalid typed data: {e}"))?;
+                Ok(Payload::TypedData(Box::new(typed), json))
+            }
+        }
+    }
+
+    /// EIP-191 or EIP-712 digest the signature commits to.
+    pub fn signing_hash(&self) -> Result<B256, String> {
+        match self {
+            Payload::Message(bytes) => Ok(eip191_hash_message(bytes)),
+            Payload::TypedData(typed, _) => typed
+                .eip712_signing_hash()
+                .map_err(|e| format!("Failed to hash typed data: {e}")),
+        }
+    }
+
+    pub fn sign(&self, signer: &PrivateKeySigner) -> Result<Signature, String> {
+        signer
+            .sign_hash_sync(&self.signing_hash()?)
+            .map_err(|e| format!("Failed to sign: {e}"))
+    }
+
+    /// Address that produced `signature` over the payload.
+    pub fn recover(&self, signature: &str) -> Result<Address, String> {
+        let bytes = hex::decode(signature).map_err(|e| format!("Invalid signature: {e}"))?;
+        Signature::from_raw(&bytes)
+            .map_err(|e| format!("Invalid signature: {e}"))?
+            .recover_address_from_prehash(&self.signing_hash()?)
+            .map_err(|e| format!("Failed to recover the signer: {e}"))
+    }
+
+    pub fn preview(&self, ctx: &SignContext) -> SignPreview {
+        match self {
+            Payload::Message(bytes) => message_preview(bytes, ctx),
+            Payload::TypedData(typed, json) => typed_data_preview(typed, json, ctx),
+        }
+    }
+}
+
+fn field(label: impl Into<String>, value: impl Into<String>) -> SignField {
+    SignField {
+        label: label.into(),
+        value: Some(value.into()),
+        depth: 0,
+    }
+}
+
+fn message_preview(bytes: &[u8], ctx: &SignContext) -> SignPreview {
+    let mut preview = SignPreview {
+        kind: SignKind::PersonalSign,
+        title: "Sign message".to_string(),
+        domain: Vec::new(),
+        message: Vec::new(),
+        warnings: Vec::new(),
+        errors: Vec::new(),
+    };
+    // Control characters would hide parts of the text, hashes are full of them.
+    let readable = std::str::from_utf8(bytes)
+        .ok()
+        .filter(|text| !text.chars().any(|c| c.is_control() && !c.is_whitespace()));
+    let Some(text) = readable else {
+        preview
+            .message
+            .push(field("Bytes", hex::encode_prefixed(bytes)));
+        preview.warnings.push(if bytes.len() == 32 {
+            "This is a 32-byte hash, the signature may approve anything it stands for".to_string()
+        } else {
+            "The message isn't readable text".to_string()
+        });
+        return preview;
+    };
+
+    match SiweMessage::parse(text) {
+        None => preview.message.push(field("Message", text)),
+        Some(Err(e)) => {
+            preview.message.push(field("Message", text));
+            preview
+                .errors
+                .push(format!("Malformed Sign-In with Ethereum message: {e}"));
+        }
+        Some(Ok(siwe)) => {
+            preview.title = format!("Sign in to {}", siwe.domain);
+            preview.domain = siwe.fields();
+            if let Some(statement) = &siwe.statement {
+                preview.message.push(field("Statement", statement.as_str()));
+            }
+            let (warnings, errors) = siwe.check(ctx);
+            preview.warnings = warnings;
+            preview.errors = errors;
+        }
+    }
+    preview
+}
+
+fn typed_data_preview(typed: &TypedData, json: &Value, ctx: &SignContext) -> SignPreview {
+    let domain = &typed.domain;
+    let mut domain_fields = Vec::new();
+    if let Some(name) = &domain.name {
+        domain_fields.push(field("Name", name.as_ref()));
+    }
+    if let Some(version) = &domain.version {
+        domain_fields.push(field("Version", version.as_ref()));
+    }
+    if let Some(chain_id) = domain.chain_id {
+        domain_fields.push(field("Chain ID", chain_id.to_string()));
+    }
+    if let Some(contract) = domain.verifying_contract {
+        domain_fields.push(field("Contract", contract.to_string()));
+    }
+    if let Some(salt) = domain.salt {
+        domain_fields.push(field("Salt", salt.to_string()));
+    }
+
+    let types = json
+        .get("types")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let mut message = Vec::new();
+    render(
+        &types,
+        &typed.primary_type,
+        &typed.message,
+        typed.primary_type.clone(),
+        0,
+        &mut message,
+    );
+
+    SignPreview {
+        kind: SignKind::TypedData,
+        title: match &domain.name {
+            Some(name) => format!("{} for {name}", typed.primary_type),
+            None => typed.primary_type.clone(),
+        },
+        domain: domain_fields,
+        message,
+        warnings: typed_data_warnings(typed, ctx),
+        errors: Vec::new(),
+    }
+}
+
+/// Flatten `value` of type `ty` into rows, structs and arrays heading their
+/// members one level deeper.
+fn render(
+    types: &Map<String, Value>,
+    ty: &str,
+    value: &Value,
+    label: String,
+    depth: u32,
+    out: &mut Vec<SignField>,
+) {
+    let header = |out: &mut Vec<SignField>| {
+        out.push(SignField {
+            label: label.clone(),
+            value: None,
+            depth,
+        })
+    };
+    if let (Some(element), Some(items)) = (array_element(ty), value.as_array()) {
+        header(out);
+        for (i, item) in items.iter().enumerate() {
+            render(types, element, item, format!("[{i}]"), depth + 1, out);
+        }
+        return;
+    }
+    if let (Some(members), Some(object)) =
+        (types.get(ty).and_then(Value::as_array), value.as_object())
+    {
+        header(out);
+        for member in members {
+            let (Some(name), Some(member_ty)) = (
+                member.get("name").and_then(Value::as_str),
+                member.get("type").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let member_value = object.get(name).unwrap_or(&Value::Null);
+            render(
+                types,
+                member_ty,
+                member_value,
+                name.to_string(),
+                depth + 1,
+                out,
+            );
+        }
+        return;
+    }
+    out.push(SignField {
+        label,
+        value: Some(scalar(value)),
+        depth,
+    });
+}
+
+/// `T` of an array type `T[]` or `T[n]`.
+fn array_element(ty: &str) -> Option<&str> {
+    ty.strip_suffix(']')?
+        .rsplit_once('[')
+        .map(|(element, _)| element)
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn message_str<'a>(message: &'a Value, key: &str) -> Option<&'a str> {
+    message.get(key).and_then(Value::as_str)
+}
+
+/// Amount described for a warning, "an unlimited amount" for the usual
+/// `type(uint256).max` style values.
+fn describe_amount(value: &Value) -> String {
+    let raw = scalar(value);
+    match U256::from_str(&raw) {
+        Ok(amount) if is_unlimited(amount) => "an unlimited amount".to_string(),
+        _ => raw,
+    }
+}
+
+fn typed_data_warnings(typed: &TypedData, ctx: &SignContext) -> Vec<String> {
+    let domain = &typed.domain;
+    let message = &typed.message;
+    let primary = typed.primary_type.as_str();
+    let contract = domain
+        .verifying_contract
+        .map_or("the token".to_string(), |c| c.to_string());
+    let mut warnings = Vec::new();
+
+    if primary == "Permit" {
+        let spender = message_str(message, "spender").unwrap_or("someone");
+        // DAI's permit has a boolean `allowed` instead of a value.
+        let amount = match message.get("allowed") {
+            Some(Value::Bool(true)) => "an unlimited amount".to_string(),
+            _ => message
+                .get("value")
+                .map_or("tokens".to_string(), describe_amount),
+        };
+        warnings.push(format!(
+            "Permit: lets {spender} spend {amount} of {contract} from this account without a transaction"
+        ));
+    }
+    if domain.verifying_contract == Some(PERMIT2) || PERMIT2_TYPES.contains(&primary) {
+        let spender = message_str(message, "spender").unwrap_or("the spender");
+        warnings.push(format!(
+            "Permit2: lets {spender} move tokens this account approved to Permit2"
+        ));
+    }
+    if domain.name.as_deref() == Some("Seaport") || SEAPORT_TYPES.contains(&primary) {
+        warnings.push(
+            "Seaport order: anyone can fill it and take the offered assets until it expires, check what you receive in return".to_string(),
+        );
+    }
+    if let Some(chain_id) = domain
+        .chain_id
+        .filter(|chain_id| *chain_id != U256::from(ctx.chain_id))
+    {
+        warnings.push(format!(
+            "Signed for chain {chain_id}, the account is on chain {}",
+            ctx.chain_id
+        ));
+    }
+    warnings
+}
+
+/// A parsed EIP-4361 message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub scheme: Option<String>,
+    /// Authority of the site asking for the sign-in, `host[:port]`.
+    pub domain: String,
+    pub address: Address,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<FixedOffset>,
+    pub expiration_time: Option<DateTime<FixedOffset>>,
+    pub not_before: Option<DateTime<FixedOffset>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// `None` when `text` isn't a sign-in message at all.
+    pub fn parse(text: &str) -> Option<Result<Self, String>> {
+        let (first, rest) = text.split_once('\n')?;
+        let authority = first.strip_suffix(SIWE_HEADER)?;
+        Some(Self::parse_body(authority, rest))
+    }
+
+    fn parse_body(authority: &str, rest: &str) -> Result<Self, String> {
+        let (scheme, domain) = match authority.split_once("://") {
+            Some((scheme, domain)) => (Some(scheme.to_string()), domain),
+            None => (None, authority),
+        };
+        if domain.is_empty() || domain.contains(char::is_whitespace) {
+            return Err(format!("invalid domain '{domain}'"));
+        }
+        let mut lines = rest.lines().peekable();
+        let address = lines.next().ok_or("missing address")?.trim();
+        let address = Address::parse_checksummed(address, None)
+            .map_err(|_| format!("address '{address}' is not EIP-55 checksummed"))?;
+
+        // A blank line, an optional statement, another blank line.
+        while lines.next_if(|l| l.is_empty()).is_some() {}
+        let statement = lines
+            .next_if(|l| !l.starts_with("URI: "))
+            .map(str::to_string);
+        while lines.next_if(|l| l.is_empty()).is_some() {}
+
+        let mut fields = std::collections::HashMap::new();
+        let mut resources = Vec::new();
+        while let Some(line) = lines.next() {
+            if line == "Resources:" {
+                while let Some(resource) = lines.next_if(|l| l.starts_with("- ")) {
+                    resources.push(resource[2..].to_string());
+                }
+                continue;
+            }
+            let (key, value) = line
+                .split_once(": ")
+                .ok_or(format!("unexpected line '{line}'"))?;
+            if fields.insert(key, value).is_some() {
+                return Err(format!("'{key}' appears twice"));
+            }
+        }
+        let mut take = |key: &str| fields.remove(key).map(str::to_string);
+        let required = |value: Option<String>, key: &str| value.ok_or(format!("missing '{key}'"));
+        let time = |value: Option<String>, key: &str| {
+            value
+                .map(|v| {
+                    DateTime::parse_from_rfc3339(&v)
+                        .map_err(|e| format!("invalid '{key}' '{v}': {e}"))
+                })
+                .transpose()
+        };
+
+        let uri = required(take("URI"), "URI")?;
+        let version = required(take("Version"), "Version")?;
+        if version != "1" {
+            return Err(format!("unsupported version '{version}'"));
+        }
+        let chain_id = required(take("Chain ID"), "Chain ID")?;
+        let chain_id = chain_id
+            .parse()
+            .map_err(|e| format!("invalid chain id '{chain_id}': {e}"))?;
+        let nonce = required(take("Nonce"), "Nonce")?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("nonce '{nonce}' is not 8+ alphanumeric characters"));
+        }
+        let issued_at = time(take("Issued At"), "Issued At")?.ok_or("missing 'Issued At'")?;
+        let expiration_time = time(take("Expiration Time"), "Expiration Time")?;
+        let not_before = time(take("Not Before"), "Not Before")?;
+        let request_id = take("Request ID");
+        if let Some(key) = fields.keys().next() {
+            return Err(format!("unknown field '{key}'"));
+        }
+
+        Ok(Self {
+            scheme,
+            domain: domain.to_string(),
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+
+    fn fields(&self) -> Vec<SignField> {
+        let mut fields = vec![
+            field("Domain", self.domain.as_str()),
+            field("Address", self.address.to_string()),
+            field("URI", self.uri.as_str()),
+            field("Chain ID", self.chain_id.to_string()),
+            field("Nonce", self.nonce.as_str()),
+            field("Issued At", self.issued_at.to_rfc3339()),
+        ];
+        if let Some(t) = self.expiration_time {
+            fields.push(field("Expiration Time", t.to_rfc3339()));
+        }
+        if let Some(t) = self.not_before {
+            fields.push(field("Not Before", t.to_rfc3339()));
+        }
+        if let Some(id) = &self.request_id {
+            fields.push(field("Request ID", id.as_str()));
+        }
+        fields.extend(self.resources.iter().map(|r| field("Resource", r.as_str())));
+        fields
+    }
+
+    /// Warnings, then the problems that should stop the user from signing:
+    /// a domain other than the requesting site is the phishing case EIP-4361
+    /// asks wallets to catch.
+    pub fn check(&self, ctx: &SignContext) -> (Vec<String>, Vec<String>) {
+        let (mut warnings, mut errors) = (Vec::new(), Vec::new());
+        match ctx
+            .origin
+            .as_deref()
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+        {
+            None => warnings.push(format!(
+                "No requesting site given, make sure you're on {}",
+                self.domain
+            )),
+            Some(origin) => {
+                let (origin_scheme, rest) = match origin.split_once("://") {
+                    Some((scheme, rest)) => (Some(scheme), rest),
+                    None => (None, origin),
+                };
+                let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+                let scheme_differs = matches!(
+                    (origin_scheme, self.scheme.as_deref()),
+                    (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b)
+                );
+                if !authority.eq_ignore_ascii_case(&self.domain) || scheme_differs {
+                    errors.push(format!(
+                        "The message signs in to {}, but {origin} is asking",
+                        self.domain
+                    ));
+                }
+            }
+        }
+        if self.address != ctx.address {
+            errors.push(format!(
+                "The message is for {}, not the active account",
+                self.address
+            ));
+        }
+        if self.chain_id != ctx.chain_id {
+            warnings.push(format!(
+                "The message is for chain {}, the account is on chain {}",
+                self.chain_id, ctx.chain_id
+            ));
+        }
+        if self.expiration_time.is_some_and(|t| t <= ctx.now) {
+            errors.push("The message has expired".to_string());
+        }
+        if let Some(t) = self.not_before.filter(|t| *t > ctx.now) {
+            errors.push(format!(
+                "The message is not valid before {}",
+                t.to_rfc3339()
+            ));
+        }
+        (warnings, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(address: Address, origin: Option<&str>) -> SignContext {
+        SignContext {
+            address,
+            chain_id: 1,
+            origin: origin.map(str::to_string),
+            now: DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z")
+                .unwrap()
+                .to_utc(),
+        }
+    }
+
+    fn siwe(address: Address, expires: &str) -> String {
+        format!(
+            "example.com wants you to sign in with your Ethereum account:\n\
+             {}\n\n\
+             Sign in to the example app.\n\n\
+             URI: https://example.com/login\n\
+             Version: 1\n\
+             Chain ID: 1\n\
+             Nonce: 32891756abc\n\
+             Issued At: 2026-10-18T11:00:00Z\n\
+             Expiration Time: {expires}\n\
+             Resources:\n\
+             - ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/\n\
+             - https://example.com/my-web2-claim.json",
+            address.to_checksum(None)
+        )
+    }
+
+    #[test]
+    fn personal_sign_round_trips() {
+        let signer = PrivateKeySigner::random();
+        let payload = Payload::parse(SignKind::PersonalSign, "hello").unwrap();
+        let signature = payload.sign(&signer).unwrap();
+        assert_eq!(signature, signer.sign_message_sync(b"hello").unwrap());
+
+        let encoded = hex::encode_prefixed(signature.as_bytes());
+        assert_eq!(payload.recover(&encoded).unwrap(), signer.address());
+        let other = Payload::parse(SignKind::PersonalSign, "0x68656c6c6f21").unwrap();
+        assert_ne!(other.recover(&encoded).unwrap(), signer.address());
+
+        let hash = Payload::parse(SignKind::PersonalSign, &B256::repeat_byte(1).to_string());
+        let preview = hash.unwrap().preview(&ctx(signer.address(), None));
+        assert_eq!(preview.warnings.len(), 1);
+    }
+
+    #[test]
+    fn sign_in_messages_are_checked_against_the_site() {
+        let signer = PrivateKeySigner::random();
+        let text = siwe(signer.address(), "2026-10-19T00:00:00Z");
+        let message = SiweMessage::parse(&text).unwrap().unwrap();
+        assert_eq!(message.domain, "example.com");
+        assert_eq!(
+            message.statement.as_deref(),
+            Some("Sign in to the example app.")
+        );
+        assert_eq!(message.nonce, "32891756abc");
+        assert_eq!(message.resources.len(), 2);
+
+        let (_, errors) = message.check(&ctx(signer.address(), Some("https://example.com/app")));
+        assert!(errors.is_empty());
+        let (_, errors) = message.check(&ctx(signer.address(), Some("https://examp1e.com")));
+        assert_eq!(errors.len(), 1);
+        let (warnings, errors) = message.check(&ctx(Address::ZERO, None));
+        assert_eq!((warnings.len(), errors.len()), (1, 1));
+
+        let expired = siwe(signer.address(), "2026-10-18T11:30:00Z");
+        let preview = Payload::parse(SignKind::PersonalSign, &expired)
+            .unwrap()
+            .preview(&ctx(signer.address(), Some("example.com")));
+        assert_eq!(preview.title, "Sign in to example.com");
+        assert_eq!(preview.errors, vec!["The message has expired".to_string()]);
+
+        assert!(SiweMessage::parse("hello\nworld").is_none());
+        let lowercase = text.replace(
+            &signer.address().to_checksum(None),
+            &signer.address().to_string().to_lowercase(),
+        );
+        assert!(SiweMessage::parse(&lowercase).unwrap().is_err());
+    }
+
+    #[test]
+    fn permits_are_flagged() {
+        let signer = PrivateKeySigner::random();
+        let json = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Permit": [
+                    {"name": "owner", "type": "address"},
+                    {"name": "spender", "type": "address"},
+                    {"name": "value", "type": "uint256"},
+                    {"name": "nonce", "type": "uint256"},
+                    {"name": "deadline", "type": "uint256"}
+                ]
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": "USD Coin",
+                "version": "2",
+                "chainId": 137,
+                "verifyingContract": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+            },
+            "message": {
+                "owner": signer.address().to_string(),
+                "spender": "0x1111111111111111111111111111111111111111",
+                "value": U256::MAX.to_string(),
+                "nonce": 0,
+                "deadline": 1_800_000_000u64
+            }
+        });
+        let payload = Payload::parse(SignKind::TypedData, &json.to_string()).unwrap();
+        let preview = payload.preview(&ctx(signer.address(), None));
+        assert_eq!(preview.title, "Permit for USD Coin");
+        assert_eq!(preview.domain.len(), 4);
+        assert_eq!(preview.message.len(), 6);
+        assert_eq!(preview.message[2].depth, 1);
+        assert_eq!(preview.warnings.len(), 2);
+        assert!(preview.warnings[0].contains("an unlimited amount"));
+        assert!(preview.warnings[1].contains("chain 137"));
+
+        let signature = payload.sign(&signer).unwrap();
+        let encoded = hex::encode_prefixed(signature.as_bytes());
+        assert_eq!(payload.recover(&encoded).unwrap(), signer.address());
+    }
+}
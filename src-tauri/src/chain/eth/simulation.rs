@@ -0,0 +1,546 @@
+//! Dry runs of a prepared transaction, so a revert or a token taking a fee
+//! shows up before any gas is paid.
+//!
+//! Every node answers `eth_call`, which is enough to catch a revert. Balance
+//! changes need the transfers the transaction makes, read from
+//! `eth_simulateV1`, then from a `debug_traceCall` call trace. Nodes with
+//! neither get a throwaway anvil fork that runs the transaction.
+use std::{collections::BTreeMap, path::PathBuf};
+
+use alloy::{
+    eips::BlockId,
+    network::ReceiptResponse,
+    node_bindings::Anvil,
+    primitives::{Address, B256, I256, U256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{
+        TransactionRequest,
+        simulate::{SimBlock, SimulatePayload},
+        trace::geth::{
+            CallConfig, CallFrame, GethDebugBuiltInTracerType, GethDebugTracerType,
+            GethDebugTracingCallOptions, GethDebugTracingOptions,
+        },
+    },
+    sol_types::{SolEvent, decode_revert_reason},
+    transports::RpcError,
+};
+use alloy_provider::ext::{AnvilApi, DebugApi};
+
+use crate::{
+    config::Config,
+    eth::{
+        EvmClient, constants::NATIVE_TOKEN, dtos::SimulationSource, erc20_retriver::Erc20Contract,
+        network::EvmNetwork,
+    },
+};
+
+/// An asset changing hands, `NATIVE_TOKEN` standing for the native currency.
+#[derive(Debug, Clone, PartialEq)]
+struct AssetMove {
+    asset: Address,
+    from: Address,
+    to: Address,
+    amount: U256,
+}
+
+/// Net change of an address' balance, gas excluded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    pub holder: Address,
+    pub asset: Address,
+    pub amount: I256,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub source: SimulationSource,
+    /// Why the transaction reverts, `None` when it goes through.
+    pub revert: Option<String>,
+    /// Balance changes of the watched addresses, `None` when the node
+    /// couldn't say.
+    pub deltas: Option<Vec<Delta>>,
+}
+
+/// A node to replay transactions on a local fork of.
+#[derive(Debug, Clone)]
+pub struct ForkSource {
+    pub url: String,
+    pub anvil_bin: PathBuf,
+}
+
+impl ForkSource {
+    /// Fork of `network` for nodes that don't trace, if anvil is installed.
+    /// The fork talks to the node directly, so never over Tor.
+    pub fn new(config: &Config, network: &EvmNetwork) -> Option<Self> {
+        let anvil_bin = config.eth.anvil_bin();
+        if config.tor.enabled || config.eth.anvil || !anvil_bin.exists() {
+            return None;
+        }
+        Some(Self {
+            url: network.rpc_urls.first()?.clone(),
+            anvil_bin,
+        })
+    }
+}
+
+pub struct Simulator<'a> {
+    pub client: &'a EvmClient,
+    pub fork: Option<ForkSource>,
+}
+
+impl Simulator<'_> {
+    /// Run `tx` and net the transfers it makes to or from `holders`.
+    pub async fn run(
+        &self,
+        tx: &TransactionRequest,
+        holders: &[Address],
+    ) -> Result<Simulation, String> {
+        let network = &self.client.network.id;
+        if let Some(revert) = self.call(tx).await? {
+            return Ok(Simulation {
+                source: SimulationSource::Call,
+                revert: Some(revert),
+                deltas: None,
+            });
+        }
+        match self.simulate_v1(tx, holders).await {
+            Ok(simulation) => return Ok(simulation),
+            Err(e) => tracing::debug!("{network}: eth_simulateV1 unavailable: {e}"),
+        }
+        match self.trace_call(tx, holders).await {
+            Ok(simulation) => return Ok(simulation),
+            Err(e) => tracing::debug!("{network}: debug_traceCall unavailable: {e}"),
+        }
+        if let Some(source) = &self.fork {
+            match self.fork(source, tx, holders).await {
+                Ok(simulation) => return Ok(simulation),
+                Err(e) => tracing::warn!("{network}: fork simulation failed: {e}"),
+            }
+        }
+        Ok(Simulation {
+            source: SimulationSource::Call,
+            revert: None,
+            deltas: None,
+        })
+    }
+
+    /// `eth_call` at the pending state, the revert reason when it fails.
+    async fn call(&self, tx: &TransactionRequest) -> Result<Option<String>, String> {
+        match self
+            .client
+            .provider
+            .call(tx.clone())
+            .block(BlockId::pending())
+            .await
+        {
+            Ok(_) => Ok(None),
+            Err(RpcError::ErrorResp(payload)) => Ok(Some(match payload.as_revert_data() {
+                Some(data) => revert_reason(&data),
+                // Refused before running, e.g. for lack of funds.
+                None => payload.message.to_string(),
+            })),
+            Err(e) => Err(format!("eth_call failed: {e}")),
+        }
+    }
+
+    async fn simulate_v1(
+        &self,
+        tx: &TransactionRequest,
+        holders: &[Address],
+    ) -> Result<Simulation, String> {
+        let payload = SimulatePayload {
+            block_state_calls: vec![SimBlock {
+                calls: vec![tx.clone()],
+                ..Default::default()
+            }],
+            // Adds a `Transfer` log from `NATIVE_TOKEN` for every ETH move.
+            trace_transfers: true,
+            ..Default::default()
+        };
+        let blocks = self
+            .client
+            .provider
+            .simulate(&payload)
+            .await
+            .map_err(|e| e.to_string())?;
+        let call = blocks
+            .first()
+            .and_then(|block| block.calls.first())
+            .ok_or("eth_simulateV1 returned no call")?;
+        if !call.status {
+            let revert = match &call.error {
+                Some(error) if call.return_data.is_empty() => error.message.clone(),
+                _ => revert_reason(&call.return_data),
+            };
+            return Ok(reverted(SimulationSource::SimulateV1, revert));
+        }
+        let moves = call
+            .logs
+            .iter()
+            .filter_map(|log| token_move(log.address(), log.topics(), &log.data().data))
+            .collect::<Vec<_>>();
+        Ok(Simulation {
+            source: SimulationSource::SimulateV1,
+            revert: None,
+            deltas: Some(net(&moves, holders)),
+        })
+    }
+
+    async fn trace_call(
+        &self,
+        tx: &TransactionRequest,
+        holders: &[Address],
+    ) -> Result<Simulation, String> {
+        let options = GethDebugTracingCallOptions {
+            tracing_options: GethDebugTracingOptions::default()
+                .with_tracer(GethDebugTracerType::BuiltInTracer(
+                    GethDebugBuiltInTracerType::CallTracer,
+                ))
+                .with_call_config(CallConfig::default().with_log()),
+            ..Default::default()
+        };
+        let frame = self
+            .client
+            .provider
+            .debug_trace_call(tx.clone(), BlockId::latest(), options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_into_call_frame()
+            .map_err(|e| e.to_string())?;
+        if let Some(error) = &frame.error {
+            let revert = match &frame.output {
+                Some(data) if !data.is_empty() => revert_reason(data),
+                _ => frame.revert_reason.clone().unwrap_or(error.clone()),
+            };
+            return Ok(reverted(SimulationSource::TraceCall, revert));
+        }
+        let mut moves = Vec::new();
+        frame_moves(&frame, &mut moves);
+        Ok(Simulation {
+            source: SimulationSource::TraceCall,
+            revert: None,
+            deltas: Some(net(&moves, holders)),
+        })
+    }
+
+    /// Send `tx` on a local fork at the latest block, as the impersonated
+    /// sender.
+    async fn fork(
+        &self,
+        source: &ForkSource,
+        tx: &TransactionRequest,
+        holders: &[Address],
+    ) -> Result<Simulation, String> {
+        let sender = tx.from.ok_or("transaction has no sender")?;
+        let block = self
+            .client
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| format!("failed to get block number: {e}"))?;
+        // Stopped when dropped, at the end of the simulation.
+        let anvil = Anvil::new()
+            .path(&source.anvil_bin)
+            .fork(&source.url)
+            .fork_block_number(block)
+            .try_spawn()
+            .map_err(|e| format!("failed to start anvil: {e}"))?;
+        let fork = ProviderBuilder::new().connect_http(anvil.endpoint_url());
+        fork.anvil_impersonate_account(sender)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut native_before = Vec::new();
+        for holder in holders {
+            native_before.push(fork.get_balance(*holder).await.map_err(|e| e.to_string())?);
+        }
+        // Earlier transfers still pending aren't on the fork.
+        let mut tx = tx.clone();
+        tx.nonce = None;
+        let receipt = fork
+            .send_transaction(tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .get_receipt()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !receipt.status() {
+            return Ok(reverted(
+                SimulationSource::AnvilFork,
+                "reverted on a local fork".to_string(),
+            ));
+        }
+
+        let moves: Vec<_> = receipt
+            .inner
+            .logs()
+            .iter()
+            .filter_map(|log| token_move(log.address(), log.topics(), &log.data().data))
+            .collect();
+        let mut deltas = net(&moves, holders);
+        let gas = U256::from(receipt.gas_used()) * U256::from(receipt.effective_gas_price());
+        for (holder, before) in holders.iter().zip(native_before) {
+            let after = fork.get_balance(*holder).await.map_err(|e| e.to_string())?;
+            let after = if *holder == sender {
+                after + gas
+            } else {
+                after
+            };
+            let amount = I256::from_raw(after).wrapping_sub(I256::from_raw(before));
+            if !amount.is_zero() {
+                deltas.push(Delta {
+                    holder: *holder,
+                    asset: NATIVE_TOKEN,
+                    amount,
+                });
+            }
+        }
+        Ok(Simulation {
+            source: SimulationSource::AnvilFork,
+            revert: None,
+            deltas: Some(deltas),
+        })
+    }
+}
+
+/// How much less than `expected` of `asset` the recipient gets, tokens with
+/// a transfer fee keeping the difference.
+pub fn shortfall(deltas: &[Delta], recipient: Address, asset: Address, expected: U256) -> U256 {
+    let received = deltas
+        .iter()
+        .filter(|d| d.holder == recipient && d.asset == asset)
+        .fold(I256::ZERO, |total, d| total.wrapping_add(d.amount));
+    if received.is_negative() {
+        return expected;
+    }
+    expected.saturating_sub(received.into_raw())
+}
+
+fn reverted(source: SimulationSource, revert: String) -> Simulation {
+    Simulation {
+        source,
+        revert: Some(revert),
+        deltas: None,
+    }
+}
+
+/// `Error(string)` and `Panic(uint256)` spelled out, custom errors named by
+/// selector, their ABI being unknown.
+pub fn revert_reason(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "reverted without a reason".to_string();
+    }
+    decode_revert_reason(data).unwrap_or_else(|| match data.get(..4) {
+        Some(selector) => format!("custom error 0x{}", alloy::hex::encode(selector)),
+        None => format!("reverted with 0x{}", alloy::hex::encode(data)),
+    })
+}
+
+/// An ERC-20 `Transfer` log, ERC-721 ones having the amount indexed as
+/// the token id.
+fn token_move(address: Address, topics: &[B256], data: &[u8]) -> Option<AssetMove> {
+    if topics.len() != 3 || topics[0] != Erc20Contract::Transfer::SIGNATURE_HASH {
+        return None;
+    }
+    Some(AssetMove {
+        asset: address,
+        from: Address::from_word(topics[1]),
+        to: Address::from_word(topics[2]),
+        amount: U256::try_from_be_slice(data)?,
+    })
+}
+
+/// ETH and token moves of a call trace, skipping the calls that reverted
+/// with everything under them.
+fn frame_moves(frame: &CallFrame, moves: &mut Vec<AssetMove>) {
+    if frame.error.is_some() {
+        return;
+    }
+    // A delegate call runs with the caller's value without moving it.
+    let value = frame.value.filter(|v| !v.is_zero());
+    if let (Some(value), Some(to), false) = (value, frame.to, frame.typ == "DELEGATECALL") {
+        moves.push(AssetMove {
+            asset: NATIVE_TOKEN,
+            from: frame.from,
+            to,
+            amount: value,
+        });
+    }
+    for log in &frame.logs {
+        let (Some(address), Some(topics)) = (log.address, &log.topics) else {
+            continue;
+        };
+        let data = log.data.as_deref().unwrap_or_default();
+        moves.extend(token_move(address, topics, data));
+    }
+    for call in &frame.calls {
+        frame_moves(call, moves);
+    }
+}
+
+/// Net of `moves` per holder and asset, in the order of `holders`.
+fn net(moves: &[AssetMove], holders: &[Address]) -> Vec<Delta> {
+    let mut totals: BTreeMap<(usize, Address), I256> = BTreeMap::new();
+    for m in moves {
+        let amount = I256::from_raw(m.amount);
+        for (i, holder) in holders.iter().enumerate() {
+            // A transfer to oneself nets out.
+            if *holder == m.from {
+                let total = totals.entry((i, m.asset)).or_default();
+                *total = total.wrapping_sub(amount);
+            }
+            if *holder == m.to {
+                let total = totals.entry((i, m.asset)).or_default();
+                *total = total.wrapping_add(amount);
+            }
+        }
+    }
+    totals
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|((i, asset), amount)| Delta {
+            holder: holders[i],
+            asset,
+            amount,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Bytes, address},
+        rpc::types::trace::geth::CallLogFrame,
+        sol_types::{Panic, PanicKind, Revert, SolError},
+    };
+
+    use super::*;
+
+    const ALICE: Address = address!("0x1111111111111111111111111111111111111111");
+    const BOB: Address = address!("0x2222222222222222222222222222222222222222");
+    const TOKEN: Address = address!("0xdAC17F958D2ee523a2206206994597C13D831ec7");
+    const FEE_COLLECTOR: Address = address!("0x3333333333333333333333333333333333333333");
+
+    fn transfer_log(from: Address, to: Address, amount: u64) -> CallLogFrame {
+        CallLogFrame {
+            address: Some(TOKEN),
+            topics: Some(vec![
+                Erc20Contract::Transfer::SIGNATURE_HASH,
+                from.into_word(),
+                to.into_word(),
+            ]),
+            data: Some(Bytes::from(U256::from(amount).to_be_bytes_vec())),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn revert_reasons_are_decoded() {
+        let revert = Revert::from("ERC20: transfer amount exceeds balance").abi_encode();
+        assert!(revert_reason(&revert).ends_with("ERC20: transfer amount exceeds balance"));
+        let panic = Panic::from(PanicKind::UnderOverflow).abi_encode();
+        assert!(revert_reason(&panic).contains("overflow"));
+        assert_eq!(
+            revert_reason(&[0xe4, 0x50, 0xd3, 0x8c, 0, 1]),
+            "custom error 0xe450d38c"
+        );
+        assert_eq!(revert_reason(&[]), "reverted without a reason");
+    }
+
+    #[test]
+    fn fee_on_transfer_shows_in_the_trace() {
+        // A token keeping 2% of a 1000 unit transfer, and a reverted inner
+        // call whose transfers never happened.
+        let reverted = CallFrame {
+            from: TOKEN,
+            to: Some(BOB),
+            value: Some(U256::from(5)),
+            error: Some("execution reverted".to_string()),
+            logs: vec![transfer_log(ALICE, BOB, 1)],
+            typ: "CALL".to_string(),
+            ..Default::default()
+        };
+        let frame = CallFrame {
+            from: ALICE,
+            to: Some(TOKEN),
+            value: Some(U256::ZERO),
+            logs: vec![
+                transfer_log(ALICE, BOB, 980),
+                transfer_log(ALICE, FEE_COLLECTOR, 20),
+            ],
+            calls: vec![reverted],
+            typ: "CALL".to_string(),
+            ..Default::default()
+        };
+        let mut moves = Vec::new();
+        frame_moves(&frame, &mut moves);
+
+        let deltas = net(&moves, &[ALICE, BOB]);
+        assert_eq!(
+            deltas,
+            [
+                Delta {
+                    holder: ALICE,
+                    asset: TOKEN,
+                    amount: I256::try_from(-1000i64).unwrap(),
+                },
+                Delta {
+                    holder: BOB,
+                    asset: TOKEN,
+                    amount: I256::try_from(980i64).unwrap(),
+                },
+            ]
+        );
+        assert_eq!(
+            shortfall(&deltas, BOB, TOKEN, U256::from(1000)),
+            U256::from(20)
+        );
+        assert_eq!(shortfall(&deltas, BOB, TOKEN, U256::from(980)), U256::ZERO);
+        assert_eq!(
+            shortfall(&deltas, BOB, NATIVE_TOKEN, U256::from(7)),
+            U256::from(7)
+        );
+    }
+
+    #[test]
+    fn native_moves_and_self_transfers() {
+        let frame = CallFrame {
+            from: ALICE,
+            to: Some(BOB),
+            value: Some(U256::from(42)),
+            typ: "CALL".to_string(),
+            calls: vec![CallFrame {
+                from: BOB,
+                to: Some(TOKEN),
+                value: Some(U256::from(42)),
+                typ: "DELEGATECALL".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut moves = Vec::new();
+        frame_moves(&frame, &mut moves);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].asset, NATIVE_TOKEN);
+
+        let to_self = [AssetMove {
+            asset: TOKEN,
+            from: ALICE,
+            to: ALICE,
+            amount: U256::from(3),
+        }];
+        assert!(net(&to_self, &[ALICE, BOB]).is_empty());
+
+        let nft = token_move(
+            TOKEN,
+            &[
+                Erc20Contract::Transfer::SIGNATURE_HASH,
+                ALICE.into_word(),
+                BOB.into_word(),
+                B256::with_last_byte(7),
+            ],
+            &[],
+        );
+        assert!(nft.is_none());
+    }
+}
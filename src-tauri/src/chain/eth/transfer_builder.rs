@@ -8,7 +8,7 @@ use alloy::{
     consensus::{SignableTransaction, TxEnvelope},
     network::{TransactionBuilder, TxSignerSync},
     primitives::{
-        Address, FixedBytes, U256,
+        Address, Bytes, FixedBytes, U256, hex,
         utils::{format_units, parse_ether, parse_units},
     },
     providers::Provider,
@@ -18,8 +18,12 @@ use alloy_provider::{DynProvider, utils::Eip1559Estimation};
 use alloy_signer_local::PrivateKeySigner;
 
 use crate::eth::{
+    constants::NATIVE_TOKEN,
+    contract::ContractCall,
+    ens::Recipient,
     erc20_retriver::new_contract_api,
     fee_estimator::{FeeEstimator, FeeMode},
+    nft::{Erc721Contract, Erc1155Contract, NftItem, NftStandard},
     token::Token,
 };
 
@@ -93,8 +97,6 @@ impl std::fmt::Display for TransferBuilderError {
 
 impl std::error::Error for TransferBuilderError {}
 
-const ETH_CHAIN_ID: u64 = 1;
-
 #[derive(Debug, Clone)]
 pub struct TransferPayload {
     pub token: Token,
@@ -102,6 +104,9 @@ pub struct TransferPayload {
     pub sender: Address,
     pub recipient: Address,
     pub fee_mode: FeeMode,
+    /// Set when the token is an NFT collection, `raw_amount` being the
+    /// number of copies.
+    pub nft: Option<NftItem>,
 }
 
 #[derive(Debug)]
@@ -122,30 +127,67 @@ pub struct PendingTxMeta {
     pub metadata_max_fee_per_gas: u128,
     pub metadata_max_priority_fee_per_gas: u128,
     pub nonce: u64,
+    /// An `approve` call, the payload's recipient being the spender.
+    pub approval: bool,
+    /// A call built from a contract ABI, the payload's recipient being the
+    /// contract and its amount the ETH sent along.
+    pub call: Option<ContractCall>,
+    /// ENS name the recipient was entered as.
+    pub recipient_name: Option<String>,
+    /// Why the address the name resolved to may be wrong for the network.
+    pub recipient_warning: Option<String>,
 }
 
 pub struct TxBuilder {
     provider: DynProvider,
+    chain_id: u64,
     fee_estimator: FeeEstimator,
     transfer_builder_factory: TransferBuilderFactory,
     pending_tx: Option<TransactionRequest>,
     pending_meta: Option<PendingTxMeta>,
+    /// Nonce picked by the [`NonceManager`](crate::eth::NonceManager), the
+    /// node's pending count when unset.
+    nonce: Option<u64>,
+    recipient_name: Option<String>,
+    recipient_warning: Option<String>,
 }
 
 impl TxBuilder {
-    pub fn new(provider: DynProvider) -> Self {
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    pub fn new(provider: DynProvider, chain_id: u64) -> Self {
         Self {
             fee_estimator: FeeEstimator::new(provider.clone()),
             provider,
+            chain_id,
             pending_tx: None,
             pending_meta: None,
             transfer_builder_factory: TransferBuilderFactory,
+            nonce: None,
+            recipient_name: None,
+            recipient_warning: None,
         }
     }
 
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Keep the ENS name the recipient was resolved from with the pending
+    /// transaction, for the history, and what's doubtful about it.
+    pub fn with_recipient(mut self, recipient: &Recipient) -> Self {
+        self.recipient_name = recipient.name.clone();
+        self.recipient_warning = recipient.warning.clone();
+        self
+    }
+
     async fn get_tx_count(&self, address: Address) -> Result<u64, TransferBuilderError> {
         self.provider
             .get_transaction_count(address)
+            .pending()
             .await
             .map_err(|e| TransferBuilderError::NodeQuery(e.to_string()))
     }
@@ -156,14 +198,16 @@ impl TxBuilder {
         &mut self,
         req: TransferPayload,
     ) -> Result<TransactionMetadata, TransferBuilderError> {
-        let nonce = self.get_tx_count(req.sender).await?;
+        let nonce = match self.nonce {
+            Some(nonce) => nonce,
+            None => self.get_tx_count(req.sender).await?,
+        };
         let ctx = TransferContext {
             provider: self.provider.clone(),
+            chain_id: self.chain_id,
             nonce,
         };
-        let transfer_builder = self
-            .transfer_builder_factory
-            .create_builder(&req.token.symbol);
+        let transfer_builder = self.transfer_builder_factory.create_builder(&req);
         let tx_base = transfer_builder.build_transaction(&req, &ctx).await?;
         let Build {
             transaction,
@@ -178,11 +222,113 @@ impl TxBuilder {
             metadata_max_fee_per_gas: metadata.estimator.max_fee_per_gas,
             metadata_max_priority_fee_per_gas: metadata.estimator.max_priority_fee_per_gas,
             nonce,
+            approval: false,
+            call: None,
+            recipient_name: self.recipient_name.clone(),
+            recipient_warning: self.recipient_warning.clone(),
         });
         self.pending_tx = Some(transaction);
         Ok(metadata)
     }
 
+    /// Method creates an `approve` call setting the allowance of
+    /// `req.recipient` over `req.token` to `req.raw_amount`, e.g. `0` to
+    /// revoke it, and store it in the session
+    pub async fn create_approval(
+        &mut self,
+        req: TransferPayload,
+    ) -> Result<TransactionMetadata, TransferBuilderError> {
+        let nonce = match self.nonce {
+            Some(nonce) => nonce,
+            None => self.get_tx_count(req.sender).await?,
+        };
+        let ctx = TransferContext {
+            provider: self.provider.clone(),
+            chain_id: self.chain_id,
+            nonce,
+        };
+        let allowance = parse_units(&req.raw_amount, req.token.decimals)
+            .map_err(|e| TransferBuilderError::AmountParse(e.to_string()))?
+            .get_absolute();
+        let tx_base = new_contract_api(ctx.provider.clone(), req.token.address)
+            .approve(req.recipient, allowance)
+            .into_transaction_request()
+            .with_from(req.sender)
+            .with_chain_id(ctx.chain_id)
+            .with_nonce(ctx.nonce);
+        let Build {
+            transaction,
+            metadata,
+        } = self.calc_gas(tx_base, req.fee_mode).await?;
+        check_gas_balance(&req, &ctx, metadata.estimated_gas, metadata.estimator).await?;
+        self.pending_meta = Some(PendingTxMeta {
+            payload: req,
+            metadata_estimated_gas: metadata.estimated_gas,
+            metadata_max_fee_per_gas: metadata.estimator.max_fee_per_gas,
+            metadata_max_priority_fee_per_gas: metadata.estimator.max_priority_fee_per_gas,
+            nonce,
+            approval: true,
+            call: None,
+            recipient_name: self.recipient_name.clone(),
+            recipient_warning: self.recipient_warning.clone(),
+        });
+        self.pending_tx = Some(transaction);
+        Ok(metadata)
+    }
+
+    /// Method creates a call of `function` on `req.recipient`, `input` being
+    /// the ABI encoded call and `req.raw_amount` the ETH sent along, and
+    /// store it in the session
+    pub async fn create_contract_call(
+        &mut self,
+        req: TransferPayload,
+        function: String,
+        input: Bytes,
+    ) -> Result<TransactionMetadata, TransferBuilderError> {
+        let nonce = match self.nonce {
+            Some(nonce) => nonce,
+            None => self.get_tx_count(req.sender).await?,
+        };
+        let ctx = TransferContext {
+            provider: self.provider.clone(),
+            chain_id: self.chain_id,
+            nonce,
+        };
+        let call = ContractCall {
+            function,
+            input: hex::encode_prefixed(&input),
+        };
+        let tx_base = EtherTransferBuilder
+            .build_transaction(&req, &ctx)
+            .await?
+            .with_input(input);
+        let Build {
+            transaction,
+            metadata,
+        } = self.calc_gas(tx_base, req.fee_mode).await?;
+        EtherTransferBuilder
+            .check_balance(&req, &ctx, metadata.estimated_gas, metadata.estimator)
+            .await?;
+        self.pending_meta = Some(PendingTxMeta {
+            payload: req,
+            metadata_estimated_gas: metadata.estimated_gas,
+            metadata_max_fee_per_gas: metadata.estimator.max_fee_per_gas,
+            metadata_max_priority_fee_per_gas: metadata.estimator.max_priority_fee_per_gas,
+            nonce,
+            approval: false,
+            call: Some(call),
+            recipient_name: self.recipient_name.clone(),
+            recipient_warning: self.recipient_warning.clone(),
+        });
+        self.pending_tx = Some(transaction);
+        Ok(metadata)
+    }
+
+    /// The transaction prepared by the last `create_*` call, not sent yet.
+    pub fn pending(&self) -> Option<(&TransactionRequest, &PendingTxMeta)> {
+        Some((self.pending_tx.as_ref()?, self.pending_meta.as_ref()?))
+    }
+
     /// Consume the metadata recorded by the last [`Self::create_transfer`]
     /// call. Useful right after [`Self::sign_and_send_tx`] for writing a
     /// history row.
@@ -195,27 +341,7 @@ impl TxBuilder {
         signer: &PrivateKeySigner,
     ) -> Result<FixedBytes<32>, String> {
         let tx = self.pending_tx.take().ok_or("Transaction not prepared")?;
-        // Check if the transaction is complete and return an error if not
-        let is_complete = tx.complete_1559();
-        if let Err(err) = is_complete {
-            let messages = err.to_vec().join(", ");
-            return Err(format!("Transaction is incomplete: {}", messages));
-        }
-        let mut tx_eip1559 = tx.build_1559().map_err(|e| e.to_string())?;
-
-        let signature = signer
-            .sign_transaction_sync(&mut tx_eip1559)
-            .map_err(|e| format!("Failed to sign transaction: {e}"))?;
-
-        let tx_envelope = TxEnvelope::Eip1559(tx_eip1559.into_signed(signature));
-        let pending_tx = self
-            .provider
-            .send_tx_envelope(tx_envelope)
-            .await
-            .map_err(|e| format!("Failed to send transaction: {e}"))?;
-
-        self.pending_tx = None;
-        Ok(*pending_tx.tx_hash())
+        sign_and_send(&self.provider, tx, signer).await
     }
 
     pub fn discard_pending(&mut self) {
@@ -256,6 +382,32 @@ impl TxBuilder {
     }
 }
 
+/// Sign a complete EIP-1559 request and broadcast it.
+pub async fn sign_and_send(
+    provider: &DynProvider,
+    tx: TransactionRequest,
+    signer: &PrivateKeySigner,
+) -> Result<FixedBytes<32>, String> {
+    // Check if the transaction is complete and return an error if not
+    let is_complete = tx.complete_1559();
+    if let Err(err) = is_complete {
+        let messages = err.to_vec().join(", ");
+        return Err(format!("Transaction is incomplete: {}", messages));
+    }
+    let mut tx_eip1559 = tx.build_1559().map_err(|e| e.to_string())?;
+
+    let signature = signer
+        .sign_transaction_sync(&mut tx_eip1559)
+        .map_err(|e| format!("Failed to sign transaction: {e}"))?;
+
+    let tx_envelope = TxEnvelope::Eip1559(tx_eip1559.into_signed(signature));
+    let pending_tx = provider
+        .send_tx_envelope(tx_envelope)
+        .await
+        .map_err(|e| format!("Failed to send transaction: {e}"))?;
+    Ok(*pending_tx.tx_hash())
+}
+
 #[derive(Debug)]
 pub struct Build {
     pub transaction: TransactionRequest,
@@ -265,6 +417,7 @@ pub struct Build {
 #[derive(Debug, Clone)]
 pub struct TransferContext {
     pub provider: DynProvider,
+    pub chain_id: u64,
     pub nonce: u64,
 }
 
@@ -303,6 +456,7 @@ pub trait BalanceChecker {
 pub enum TransferBuilderType {
     Ether(EtherTransferBuilder),
     Token(TokenTransferBuilder),
+    Nft(NftTransferBuilder),
 }
 
 impl TransferBuilder for TransferBuilderType {
@@ -314,6 +468,7 @@ impl TransferBuilder for TransferBuilderType {
         match self {
             TransferBuilderType::Ether(b) => b.build_transaction(req, ctx).await,
             TransferBuilderType::Token(b) => b.build_transaction(req, ctx).await,
+            TransferBuilderType::Nft(b) => b.build_transaction(req, ctx).await,
         }
     }
 }
@@ -333,6 +488,9 @@ impl BalanceChecker for TransferBuilderType {
             TransferBuilderType::Token(b) => {
                 b.check_balance(req, ctx, estimated_gas, estimator).await
             }
+            TransferBuilderType::Nft(b) => {
+                b.check_balance(req, ctx, estimated_gas, estimator).await
+            }
         }
     }
 }
@@ -340,10 +498,13 @@ impl BalanceChecker for TransferBuilderType {
 pub struct TransferBuilderFactory;
 
 impl TransferBuilderFactory {
-    pub fn create_builder(&self, token_symbol: &str) -> TransferBuilderType {
-        match token_symbol.to_uppercase().as_str() {
-            "ETH" => TransferBuilderType::Ether(EtherTransferBuilder),
-            _ => TransferBuilderType::Token(TokenTransferBuilder {}),
+    pub fn create_builder(&self, req: &TransferPayload) -> TransferBuilderType {
+        if req.nft.is_some() {
+            TransferBuilderType::Nft(NftTransferBuilder)
+        } else if req.token.address == NATIVE_TOKEN {
+            TransferBuilderType::Ether(EtherTransferBuilder)
+        } else {
+            TransferBuilderType::Token(TokenTransferBuilder {})
         }
     }
 }
@@ -362,7 +523,7 @@ impl TransferBuilder for EtherTransferBuilder {
             .with_from(req.sender)
             .with_to(req.recipient)
             .with_value(value)
-            .with_chain_id(ETH_CHAIN_ID)
+            .with_chain_id(ctx.chain_id)
             .with_nonce(ctx.nonce);
         Ok(tx)
     }
@@ -427,7 +588,7 @@ impl TransferBuilder for TokenTransferBuilder {
         let tx = transfer_call
             .into_transaction_request()
             .with_from(req.sender)
-            .with_chain_id(ETH_CHAIN_ID)
+            .with_chain_id(ctx.chain_id)
             .with_nonce(ctx.nonce);
         Ok(tx)
     }
@@ -458,23 +619,102 @@ impl BalanceChecker for TokenTransferBuilder {
         if token_balance < transfer_amount {
             return Err(TransferBuilderError::InsufficientTokens);
         }
-        let eth_balance = ctx
-            .provider
-            .get_balance(req.sender)
-            .await
-            .map_err(|e| TransferBuilderError::NodeQuery(e.to_string()))?;
+        check_gas_balance(req, ctx, estimated_gas, estimator).await
+    }
+}
 
-        let fee_ceiling = U256::from(estimated_gas) * U256::from(estimator.max_fee_per_gas);
-        if eth_balance < fee_ceiling {
-            return Err(TransferBuilderError::InsufficientGas {
-                current_balance: format_units(eth_balance, "ether")
-                    .map_err(|e| TransferBuilderError::AmountParse(e.to_string()))?,
-                estimated_fee: format_units(fee_ceiling, "ether")
-                    .map_err(|e| TransferBuilderError::AmountParse(e.to_string()))?,
-            });
+pub struct NftTransferBuilder;
+
+impl NftTransferBuilder {
+    fn item(req: &TransferPayload) -> Result<&NftItem, TransferBuilderError> {
+        req.nft.as_ref().ok_or(TransferBuilderError::AmountParse(
+            "no NFT to send".to_string(),
+        ))
+    }
+
+    fn amount(req: &TransferPayload) -> Result<U256, TransferBuilderError> {
+        req.raw_amount
+            .parse()
+            .map_err(|e| TransferBuilderError::AmountParse(e.to_string()))
+    }
+}
+
+impl TransferBuilder for NftTransferBuilder {
+    async fn build_transaction(
+        &self,
+        req: &TransferPayload,
+        ctx: &TransferContext,
+    ) -> Result<TransactionRequest, TransferBuilderError> {
+        let input = Self::item(req)?
+            .transfer_call(req.sender, req.recipient, Self::amount(req)?)
+            .map_err(TransferBuilderError::AmountParse)?;
+        let tx = TransactionRequest::default()
+            .with_from(req.sender)
+            .with_to(req.token.address)
+            .with_input(input)
+            .with_chain_id(ctx.chain_id)
+            .with_nonce(ctx.nonce);
+        Ok(tx)
+    }
+}
+
+impl BalanceChecker for NftTransferBuilder {
+    /// Checks the sender holds the token, enough copies of an ERC-1155 one,
+    /// and ETH for gas fees.
+    async fn check_balance(
+        &self,
+        req: &TransferPayload,
+        ctx: &TransferContext,
+        estimated_gas: u64,
+        estimator: Eip1559Estimation,
+    ) -> Result<(), TransferBuilderError> {
+        let item = Self::item(req)?;
+        let id = item.id().map_err(TransferBuilderError::AmountParse)?;
+        let held = match item.standard {
+            NftStandard::Erc721 => {
+                let holder = Erc721Contract::new(req.token.address, ctx.provider.clone())
+                    .ownerOf(id)
+                    .call()
+                    .await
+                    .map_err(|e| TransferBuilderError::NodeQuery(e.to_string()))?;
+                U256::from(holder == req.sender)
+            }
+            NftStandard::Erc1155 => Erc1155Contract::new(req.token.address, ctx.provider.clone())
+                .balanceOf(req.sender, id)
+                .call()
+                .await
+                .map_err(|e| TransferBuilderError::NodeQuery(e.to_string()))?,
+        };
+        if held < Self::amount(req)? {
+            return Err(TransferBuilderError::InsufficientTokens);
         }
-        Ok(())
+        check_gas_balance(req, ctx, estimated_gas, estimator).await
+    }
+}
+
+/// Checks the sender holds enough ETH to pay for gas of a call moving no ETH.
+async fn check_gas_balance(
+    req: &TransferPayload,
+    ctx: &TransferContext,
+    estimated_gas: u64,
+    estimator: Eip1559Estimation,
+) -> Result<(), TransferBuilderError> {
+    let eth_balance = ctx
+        .provider
+        .get_balance(req.sender)
+        .await
+        .map_err(|e| TransferBuilderError::NodeQuery(e.to_string()))?;
+
+    let fee_ceiling = U256::from(estimated_gas) * U256::from(estimator.max_fee_per_gas);
+    if eth_balance < fee_ceiling {
+        return Err(TransferBuilderError::InsufficientGas {
+            current_balance: format_units(eth_balance, "ether")
+                .map_err(|e| TransferBuilderError::AmountParse(e.to_string()))?,
+            estimated_fee: format_units(fee_ceiling, "ether")
+                .map_err(|e| TransferBuilderError::AmountParse(e.to_string()))?,
+        });
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -487,13 +727,14 @@ mod tests {
     use crate::eth::{
         config::EthereumConfig,
         constants::{ETH, USDT},
+        contract::{ContractAbi, encode_input, read},
         new_provider_anvil,
     };
 
     fn get_config() -> EthereumConfig {
         EthereumConfig {
-            rpc_url: "".to_string(),
             anvil: true,
+            ..Default::default()
         }
     }
 
@@ -504,6 +745,8 @@ mod tests {
         }
     }
     const ESTIMATED_GAS: u64 = 21000u64;
+    /// Anvil keeps the chain id of the mainnet fork.
+    const MAINNET_CHAIN_ID: u64 = 1;
 
     struct TestContext {
         provider: DynProvider,
@@ -518,6 +761,7 @@ mod tests {
         let provider = new_provider_anvil(get_config());
         let ctx = TransferContext {
             provider: provider.clone(),
+            chain_id: MAINNET_CHAIN_ID,
             nonce: 0,
         };
         let alice = LocalSigner::random();
@@ -528,7 +772,7 @@ mod tests {
                 .await
                 .unwrap();
         }
-        let builder = TxBuilder::new(provider.clone());
+        let builder = TxBuilder::new(provider.clone(), MAINNET_CHAIN_ID);
         TestContext {
             provider,
             ctx,
@@ -556,6 +800,7 @@ mod tests {
                 sender: alice.address(),
                 recipient: bob.address(),
                 fee_mode: FeeMode::Standard,
+                nft: None,
             })
             .await
             .unwrap();
@@ -583,7 +828,7 @@ mod tests {
             bob,
             ..
         } = test_context("0.1").await;
-        let mut builder = TxBuilder::new(provider.clone());
+        let mut builder = TxBuilder::new(provider.clone(), MAINNET_CHAIN_ID);
         let erc20_retriver = crate::eth::erc20_retriver::Erc20Retriever::new(provider.clone());
         let amount = "100.000000";
         provider
@@ -601,6 +846,7 @@ mod tests {
                 sender: alice.address(),
                 recipient: bob.address(),
                 fee_mode: FeeMode::Standard,
+                nft: None,
             })
             .await
             .unwrap();
@@ -621,6 +867,87 @@ mod tests {
         assert_eq!(recepien_balance, amount);
     }
 
+    #[tokio::test]
+    async fn test_revoke_approval() {
+        let TestContext {
+            token,
+            provider,
+            alice,
+            bob,
+            mut builder,
+            ..
+        } = test_context("0.1").await;
+        let erc20_retriver = crate::eth::erc20_retriver::Erc20Retriever::new(provider.clone());
+        let approve = |raw_amount: &str| TransferPayload {
+            token: token.clone(),
+            raw_amount: raw_amount.to_string(),
+            sender: alice.address(),
+            recipient: bob.address(),
+            fee_mode: FeeMode::Standard,
+            nft: None,
+        };
+
+        for (raw_amount, expected) in [("100", U256::from(100_000_000u64)), ("0", U256::ZERO)] {
+            builder.create_approval(approve(raw_amount)).await.unwrap();
+            assert!(builder.take_pending_meta().unwrap().approval);
+            let tx_hash = builder.sign_and_send_tx(&alice).await.unwrap();
+            provider
+                .watch_pending_transaction(PendingTransactionConfig::new(tx_hash))
+                .await
+                .unwrap();
+            let allowance = erc20_retriver
+                .allowances(alice.address(), &[(token.address, bob.address())])
+                .await;
+            assert_eq!(allowance, vec![Ok(expected)]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_contract_call_from_abi() {
+        let TestContext {
+            token,
+            provider,
+            alice,
+            bob,
+            mut builder,
+            ..
+        } = test_context("0.1").await;
+        let abi = ContractAbi::parse(include_str!("abi/erc20.json")).unwrap();
+        let approve = abi.function("approve(address,uint256)").unwrap();
+        let input = encode_input(approve, &[bob.address().to_string(), "250".to_string()]).unwrap();
+
+        builder
+            .create_contract_call(
+                TransferPayload {
+                    token: ETH.clone(),
+                    raw_amount: "0".to_string(),
+                    sender: alice.address(),
+                    recipient: token.address,
+                    fee_mode: FeeMode::Standard,
+                    nft: None,
+                },
+                approve.signature(),
+                input.clone(),
+            )
+            .await
+            .unwrap();
+        let call = builder.take_pending_meta().unwrap().call.unwrap();
+        assert_eq!(call.function, "approve(address,uint256)");
+        assert_eq!(call.input().unwrap(), input);
+        let tx_hash = builder.sign_and_send_tx(&alice).await.unwrap();
+        provider
+            .watch_pending_transaction(PendingTransactionConfig::new(tx_hash))
+            .await
+            .unwrap();
+
+        let allowance = abi.function("allowance(address,address)").unwrap();
+        let args = [alice.address().to_string(), bob.address().to_string()];
+        let values = read(&provider, alice.address(), token.address, allowance, &args)
+            .await
+            .unwrap();
+        assert_eq!(values[0].value, "250");
+    }
+
     #[tokio::test]
     async fn test_insufficient_token_balance_error() {
         let TestContext {
@@ -649,6 +976,7 @@ mod tests {
             sender: alice.address(),
             recipient: bob.address(),
             fee_mode: FeeMode::Standard,
+            nft: None,
         };
         let result = TokenTransferBuilder
             .check_balance(&req, &ctx, ESTIMATED_GAS, get_estimator())
@@ -692,6 +1020,7 @@ mod tests {
             sender: alice.address(),
             recipient: bob.address(),
             fee_mode: FeeMode::Standard,
+            nft: None,
         };
         let result = token_builder
             .check_balance(&req, &ctx, ESTIMATED_GAS, get_estimator())
@@ -729,6 +1058,7 @@ mod tests {
             sender: alice.address(),
             recipient: bob.address(),
             fee_mode: FeeMode::Standard,
+            nft: None,
         };
         let result = EtherTransferBuilder
             .check_balance(&req, &ctx, ESTIMATED_GAS, get_estimator())
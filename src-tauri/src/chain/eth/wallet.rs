@@ -1,11 +1,12 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use crate::{
     chain_trait::{AccountIndex, AssetTracker, SecureKey},
     config::Config,
     eth::{
-        constants::{self},
         dtos::EthereumUnlock,
+        network::{EvmNetwork, MAINNET},
+        nft::NftCollection,
         token::Token,
     },
     wallet::Secretik,
@@ -25,7 +26,50 @@ pub struct EthereumWallet {
 pub struct Account {
     pub index: AccountIndex,
     pub name: String,
-    pub tracked_tokens: Vec<Token>,
+    /// Id of the [`EvmNetwork`] the account is on.
+    pub network: String,
+    /// Tracked tokens per network id.
+    pub tracked_tokens: HashMap<String, Vec<Token>>,
+    /// Tracked NFT collections per network id.
+    pub tracked_collections: HashMap<String, Vec<NftCollection>>,
+}
+
+impl Account {
+    fn new(config: &Config, index: AccountIndex, name: String) -> Self {
+        let mut account = Account {
+            index,
+            name,
+            network: MAINNET.to_string(),
+            tracked_tokens: HashMap::new(),
+            tracked_collections: HashMap::new(),
+        };
+        if let Ok(network) = config.eth.default_network() {
+            account.enter(network);
+        }
+        account
+    }
+
+    /// Move to `network`, tracking its default tokens on the first visit.
+    pub fn enter(&mut self, network: &EvmNetwork) {
+        self.network = network.id.clone();
+        self.tracked_tokens
+            .entry(network.id.clone())
+            .or_insert_with(|| network.default_tokens());
+    }
+
+    /// Tokens tracked on the current network.
+    pub fn tokens(&self) -> &[Token] {
+        self.tracked_tokens
+            .get(&self.network)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// NFT collections tracked on the current network.
+    pub fn collections(&self) -> &[NftCollection] {
+        self.tracked_collections
+            .get(&self.network)
+            .map_or(&[], Vec::as_slice)
+    }
 }
 
 pub struct Prk {
@@ -43,24 +87,61 @@ impl SecureKey for Prk {
 impl AssetTracker<Token> for EthereumWallet {
     fn track(&mut self, asset: Token) -> Result<(), String> {
         let account = self.active_account_mut()?;
-        if account.tracked_tokens.contains(&asset) {
+        let tokens = account
+            .tracked_tokens
+            .entry(account.network.clone())
+            .or_default();
+        if tokens.contains(&asset) {
             return Err(format!("Token {} already tracked", asset.symbol));
         }
-        account.tracked_tokens.push(asset);
+        tokens.push(asset);
         Ok(())
     }
 
     fn untrack(&mut self, token: Token) -> Result<(), String> {
         let account = self.active_account_mut()?;
-        let len_before = account.tracked_tokens.len();
-        account.tracked_tokens.retain(|t| *t != token);
-        if account.tracked_tokens.len() == len_before {
+        let tokens = account
+            .tracked_tokens
+            .entry(account.network.clone())
+            .or_default();
+        let len_before = tokens.len();
+        tokens.retain(|t| *t != token);
+        if tokens.len() == len_before {
             return Err(format!("Token address '{}' not tracked", token.address));
         }
         Ok(())
     }
 }
 
+impl AssetTracker<NftCollection> for EthereumWallet {
+    fn track(&mut self, asset: NftCollection) -> Result<(), String> {
+        let account = self.active_account_mut()?;
+        let collections = account
+            .tracked_collections
+            .entry(account.network.clone())
+            .or_default();
+        if collections.iter().any(|c| c.address == asset.address) {
+            return Err(format!("Collection {} already tracked", asset.name));
+        }
+        collections.push(asset);
+        Ok(())
+    }
+
+    fn untrack(&mut self, asset: NftCollection) -> Result<(), String> {
+        let account = self.active_account_mut()?;
+        let collections = account
+            .tracked_collections
+            .entry(account.network.clone())
+            .or_default();
+        let len_before = collections.len();
+        collections.retain(|c| c.address != asset.address);
+        if collections.len() == len_before {
+            return Err(format!("Collection '{}' not tracked", asset.address));
+        }
+        Ok(())
+    }
+}
+
 impl EthereumWallet {
     pub fn prk(&self) -> Result<Prk, String> {
         self.build_account_prk(self.active_account)
@@ -72,6 +153,7 @@ impl EthereumWallet {
             active_account: crate::eth::dtos::EthereumActiveAccountView {
                 index: self.active_account()?.index,
                 address: self.prk()?.expose().address().to_string(),
+                network: self.active_account()?.network.clone(),
             },
         })
     }
@@ -102,13 +184,34 @@ impl EthereumWallet {
     pub fn get_tracked_token(&self, token: Address) -> Option<&Token> {
         self.active_account()
             .ok()?
-            .tracked_tokens
+            .tokens()
             .iter()
             .find(|each| each.address == token)
     }
 
+    pub fn get_tracked_collection(&self, collection: Address) -> Option<&NftCollection> {
+        self.active_account()
+            .ok()?
+            .collections()
+            .iter()
+            .find(|each| each.address == collection)
+    }
+
     pub fn active_tracked_tokens(&self) -> Result<&[Token], String> {
-        Ok(&self.active_account()?.tracked_tokens)
+        Ok(self.active_account()?.tokens())
+    }
+
+    /// Network the active account is on.
+    pub fn active_network(&self) -> Result<&EvmNetwork, String> {
+        self.config.eth.network(&self.active_account()?.network)
+    }
+
+    /// Move the active account to another configured network. Tokens and
+    /// history of the previous one are kept for when it comes back.
+    pub fn switch_network(&mut self, network: &str) -> Result<(), String> {
+        let network = self.config.eth.network(network)?.clone();
+        self.active_account_mut()?.enter(&network);
+        Ok(())
     }
 
     pub fn get_account(&self, index: AccountIndex) -> Result<&Account, String> {
@@ -146,6 +249,7 @@ impl EthereumWallet {
                     index: account.index,
                     name: account.name.clone(),
                     address,
+                    network: account.network.clone(),
                 })
             })
             .collect()
@@ -159,11 +263,7 @@ impl EthereumWallet {
             .max()
             .map(|index| index + 1)
             .unwrap_or(0);
-        self.accounts.push(Account {
-            index,
-            name,
-            tracked_tokens: constants::default_tokens(),
-        });
+        self.accounts.push(Account::new(&self.config, index, name));
         self.active_account = index;
         index
     }
@@ -188,13 +288,9 @@ impl EthereumWallet {
     pub fn new(config: Config, secret: Secretik) -> Self {
         Self {
             secret,
+            accounts: vec![Account::new(&config, 0, "main".to_string())],
             config,
             active_account: 0,
-            accounts: vec![Account {
-                index: 0,
-                name: "main".to_string(),
-                tracked_tokens: constants::default_tokens(),
-            }],
         }
     }
 }
@@ -205,10 +301,15 @@ pub fn parse_addres(addres: &str) -> Result<Address, String> {
 
 #[cfg(test)]
 mod tests {
+    use alloy::primitives::Address;
+
     use crate::{
         chain_trait::{AssetTracker, SecureKey},
         config::Config,
-        eth::constants::ETH,
+        eth::{
+            constants::ETH,
+            nft::{NftCollection, NftStandard},
+        },
         wallet::WalletSecret,
     };
 
@@ -262,4 +363,57 @@ mod tests {
         wallet.switch_account(second).unwrap();
         assert!(wallet.get_tracked_token(ETH.address).is_some());
     }
+
+    #[test]
+    fn tracked_tokens_are_scoped_per_network() {
+        let mut wallet = new_wallet();
+        assert_eq!(wallet.active_network().unwrap().chain_id, 1);
+        let usdt = wallet.active_tracked_tokens().unwrap()[1].clone();
+        wallet.untrack(usdt.clone()).unwrap();
+
+        wallet.switch_network("base").unwrap();
+        assert_eq!(wallet.active_network().unwrap().chain_id, 8453);
+        let symbols: Vec<_> = wallet
+            .active_tracked_tokens()
+            .unwrap()
+            .iter()
+            .map(|t| t.symbol.as_str())
+            .collect();
+        assert_eq!(symbols, ["USDC"]);
+        wallet.track(ETH.clone()).unwrap();
+
+        wallet.switch_network("mainnet").unwrap();
+        assert!(wallet.get_tracked_token(usdt.address).is_none());
+        assert!(wallet.get_tracked_token(ETH.address).is_none());
+        assert!(wallet.switch_network("goerli").is_err());
+
+        let second = wallet.create_account("cold".to_string());
+        assert_eq!(wallet.get_account(second).unwrap().network, "mainnet");
+        assert!(wallet.get_tracked_token(usdt.address).is_some());
+    }
+
+    #[test]
+    fn nft_collections_are_scoped_per_network() {
+        let mut wallet = new_wallet();
+        let punks = NftCollection {
+            address: Address::repeat_byte(7),
+            name: "Punks".to_string(),
+            standard: NftStandard::Erc721,
+        };
+        wallet.track(punks.clone()).unwrap();
+        let renamed = NftCollection {
+            name: "Other".to_string(),
+            ..punks.clone()
+        };
+        assert!(wallet.track(renamed).is_err());
+        assert!(wallet.get_tracked_collection(punks.address).is_some());
+
+        wallet.switch_network("base").unwrap();
+        assert!(wallet.get_tracked_collection(punks.address).is_none());
+        assert!(wallet.untrack(punks.clone()).is_err());
+
+        wallet.switch_network("mainnet").unwrap();
+        wallet.untrack(punks.clone()).unwrap();
+        assert!(wallet.get_tracked_collection(punks.address).is_none());
+    }
 }
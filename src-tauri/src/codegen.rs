@@ -46,13 +46,40 @@ pub fn handlers() -> tauri_specta::Builder {
                 btc::commands::derive_external_address,
                 btc::commands::next_unused_index,
                 btc::commands::get_external_addresess,
+                btc::commands::next_receive_address,
                 btc::commands::get_utxos,
                 btc::commands::sync_utxos,
+                btc::commands::sync_chain_tip,
+                btc::commands::get_tx_details,
+                btc::commands::list_quarantined_utxos,
+                btc::commands::release_quarantined_utxo,
+                btc::commands::spend_quarantined_utxo,
                 btc::commands::discover_wallet,
                 btc::commands::account_info,
+                btc::commands::estimate_fee_tiers,
                 btc::commands::build_tx,
                 btc::commands::broadcast_tx,
+                btc::commands::abandon_tx,
+                btc::commands::create_tx_draft,
+                btc::commands::list_tx_drafts,
+                btc::commands::update_tx_draft,
+                btc::commands::reestimate_tx_draft,
+                btc::commands::sign_tx_draft,
+                btc::commands::broadcast_tx_draft,
+                btc::commands::delete_tx_draft,
                 btc::commands::bump_fee_cpfp,
+                btc::commands::silent_payment_info,
+                btc::commands::scan_silent_payments,
+                btc::commands::create_descriptor_account,
+                btc::commands::list_descriptor_accounts,
+                btc::commands::sync_descriptor_account,
+                btc::commands::build_descriptor_psbt,
+                btc::commands::import_descriptor_psbt,
+                btc::commands::broadcast_descriptor_psbt,
+                btc::commands::recovery_status,
+                btc::commands::set_recovery_policy,
+                btc::commands::refresh_recovery_outputs,
+                btc::commands::export_recovery_key,
             ])
             .export(lang.clone(), "../src/bindings/btc.ts")
             .expect("Failed to export TypeScript bindings");
@@ -60,12 +87,36 @@ pub fn handlers() -> tauri_specta::Builder {
         tauri_specta::Builder::<tauri::Wry>::new()
             .commands(tauri_specta::collect_commands![
                 eth::commands::ethereum_account_info,
+                eth::commands::list_evm_networks,
+                eth::commands::switch_eth_network,
                 eth::commands::get_network_status,
                 eth::commands::get_wallet_balance,
                 eth::commands::estimate_transfer,
                 eth::commands::execute_transfer,
+                eth::commands::speed_up_transfer,
+                eth::commands::cancel_transfer,
+                eth::commands::fill_nonce_gaps,
+                eth::commands::rebroadcast_dropped_transfers,
+                eth::commands::sync_eth_history,
+                eth::commands::list_token_approvals,
+                eth::commands::scan_older_token_approvals,
+                eth::commands::estimate_revoke,
                 eth::commands::track_token,
                 eth::commands::untrack_token,
+                eth::commands::track_nft_collection,
+                eth::commands::untrack_nft_collection,
+                eth::commands::list_nfts,
+                eth::commands::scan_older_nfts,
+                eth::commands::estimate_nft_transfer,
+                eth::commands::list_contracts,
+                eth::commands::save_contract,
+                eth::commands::get_contract,
+                eth::commands::remove_contract,
+                eth::commands::read_contract,
+                eth::commands::estimate_contract_call,
+                eth::commands::preview_signature,
+                eth::commands::sign_payload,
+                eth::commands::verify_signature,
                 eth::commands::anvil_set_initial_balances,
             ])
             .export(lang, "../src/bindings/eth.ts")
@@ -100,21 +151,72 @@ pub fn handlers() -> tauri_specta::Builder {
         btc::commands::derive_external_address,
         btc::commands::next_unused_index,
         btc::commands::get_external_addresess,
+        btc::commands::next_receive_address,
         btc::commands::get_utxos,
         btc::commands::sync_utxos,
+        btc::commands::sync_chain_tip,
+        btc::commands::get_tx_details,
+        btc::commands::list_quarantined_utxos,
+        btc::commands::release_quarantined_utxo,
+        btc::commands::spend_quarantined_utxo,
         btc::commands::discover_wallet,
         btc::commands::account_info,
+        btc::commands::estimate_fee_tiers,
         btc::commands::build_tx,
         btc::commands::broadcast_tx,
+        btc::commands::abandon_tx,
+        btc::commands::create_tx_draft,
+        btc::commands::list_tx_drafts,
+        btc::commands::update_tx_draft,
+        btc::commands::reestimate_tx_draft,
+        btc::commands::sign_tx_draft,
+        btc::commands::broadcast_tx_draft,
+        btc::commands::delete_tx_draft,
         btc::commands::bump_fee_cpfp,
+        btc::commands::silent_payment_info,
+        btc::commands::scan_silent_payments,
+        btc::commands::create_descriptor_account,
+        btc::commands::list_descriptor_accounts,
+        btc::commands::sync_descriptor_account,
+        btc::commands::build_descriptor_psbt,
+        btc::commands::import_descriptor_psbt,
+        btc::commands::broadcast_descriptor_psbt,
+        btc::commands::recovery_status,
+        btc::commands::set_recovery_policy,
+        btc::commands::refresh_recovery_outputs,
+        btc::commands::export_recovery_key,
         //
         eth::commands::ethereum_account_info,
+        eth::commands::list_evm_networks,
+        eth::commands::switch_eth_network,
         eth::commands::get_network_status,
         eth::commands::get_wallet_balance,
         eth::commands::estimate_transfer,
         eth::commands::execute_transfer,
+        eth::commands::speed_up_transfer,
+        eth::commands::cancel_transfer,
+        eth::commands::fill_nonce_gaps,
+        eth::commands::rebroadcast_dropped_transfers,
+        eth::commands::sync_eth_history,
+        eth::commands::list_token_approvals,
+        eth::commands::scan_older_token_approvals,
+        eth::commands::estimate_revoke,
         eth::commands::track_token,
         eth::commands::untrack_token,
+        eth::commands::track_nft_collection,
+        eth::commands::untrack_nft_collection,
+        eth::commands::list_nfts,
+        eth::commands::scan_older_nfts,
+        eth::commands::estimate_nft_transfer,
+        eth::commands::list_contracts,
+        eth::commands::save_contract,
+        eth::commands::get_contract,
+        eth::commands::remove_contract,
+        eth::commands::read_contract,
+        eth::commands::estimate_contract_call,
+        eth::commands::preview_signature,
+        eth::commands::sign_payload,
+        eth::commands::verify_signature,
         eth::commands::anvil_set_initial_balances,
     ])
 }
@@ -8,15 +8,12 @@ use zeroize::Zeroize;
 
 use crate::{
     biometric,
-    chain::btc,
+    chain::btc::{self, tx_builder::Recipient},
     chain_trait::AccountIndex,
     config::{BlockChain, Config, constants},
-    eth::{
-        self, PriceFeed,
-        constants::{BTC_USD_PRICE_FEED, ETH_USD_PRICE_FEED},
-    },
+    eth::{self, EvmProviders},
     mnemonic,
-    repository::{TxQuery, TxRecord, TxRepository},
+    repository::{EthChainData, TxQuery, TxRecord, TxRepository},
     session::{SK, Session},
     wallet_keeper::{CreationFlow, WalletKeeper},
 };
@@ -39,10 +36,12 @@ pub async fn validate_address(
     let config = config.lock().await;
     match chain {
         BlockChain::Bitcoin => {
-            bitcoin::Address::from_str(&address)
-                .map_err(|e| format!("invalid address: {e}"))?
-                .require_network(config.btc.network())
-                .map_err(|e| format!("invalid address network: {e}"))?;
+            // Silent payment codes are valid payment destinations too.
+            Recipient::parse(&address, config.btc.network())?;
+        }
+        // Names are checked when they're resolved.
+        BlockChain::Ethereum if eth::ens::is_name(&address) => {
+            eth::ens::normalize(&address)?;
         }
         BlockChain::Ethereum => {
             alloy::primitives::Address::from_str(&address).map_err(|e| e.to_string())?;
@@ -178,9 +177,23 @@ pub struct PriceFeedDto {
 #[specta]
 #[tauri::command]
 #[tracing::instrument(name = "price_feed", skip_all, err)]
-pub async fn price_feed(price_feed: tauri::State<'_, PriceFeed>) -> Result<PriceFeedDto, String> {
-    let btc_usd = price_feed.get_price(BTC_USD_PRICE_FEED).await?;
-    let eth_usd = price_feed.get_price(ETH_USD_PRICE_FEED).await?;
+pub async fn price_feed(
+    config: tauri::State<'_, Mutex<Config>>,
+    providers: tauri::State<'_, EvmProviders>,
+) -> Result<PriceFeedDto, String> {
+    let config = config.lock().await.clone();
+    // Prices are quoted from mainnet whatever network accounts are on.
+    let network = config.eth.default_network()?;
+    let client = providers.client(&config, &network.id).await?;
+    let price_feed = client.price_feed();
+    let btc_feed = network
+        .btc_usd_feed()
+        .ok_or(format!("{} has no BTC/USD price feed", network.name))?;
+    let eth_feed = network
+        .native_usd_feed()
+        .ok_or(format!("{} has no ETH/USD price feed", network.name))?;
+    let btc_usd = price_feed.get_price(btc_feed).await?;
+    let eth_usd = price_feed.get_price(eth_feed).await?;
 
     let clean_price = |raw: String| -> Result<u32, String> {
         raw.parse::<f64>()
@@ -375,13 +388,70 @@ pub async fn list_transactions(
     req: ListTransactionsRequest,
     sk: tauri::State<'_, SK>,
     tx_repository: tauri::State<'_, TxRepository>,
+    providers: tauri::State<'_, EvmProviders>,
+    ens_names: tauri::State<'_, eth::ens::EnsNames>,
 ) -> Result<Vec<TxRecord>, String> {
     let mut sk = sk.lock().await;
     let wallet = sk.wallet()?;
-    tx_repository.list(&TxQuery {
+    // Ethereum history follows the network the account is on.
+    let network = match req.chain {
+        BlockChain::Bitcoin => wallet.config.btc.network.as_str().to_string(),
+        BlockChain::Ethereum => wallet
+            .eth
+            .get_account(req.account_index as AccountIndex)?
+            .network
+            .clone(),
+    };
+    let mut records = tx_repository.list(&TxQuery {
         wallet_name: wallet.name.clone(),
         chain: req.chain,
         account_index: req.account_index,
+        network: Some(network.clone()),
         limit: req.limit.map(|l| l as i64),
-    })
+    })?;
+    let explorer = match req.chain {
+        BlockChain::Bitcoin => wallet.config.btc.explorer(),
+        BlockChain::Ethereum => wallet.config.eth.explorer(&network),
+    };
+    if let Some(explorer) = &explorer {
+        for record in &mut records {
+            record.link(explorer);
+        }
+    }
+    if req.chain == BlockChain::Ethereum {
+        // The name typed in when sending first, it's the one the user knows.
+        for record in &mut records {
+            record.counterparty_name = serde_json::from_str::<EthChainData>(&record.chain_data)
+                .ok()
+                .and_then(|data| data.recipient_name);
+        }
+        let unnamed = records
+            .iter()
+            .filter(|r| r.counterparty_name.is_none())
+            .filter_map(|r| r.counterparty())
+            .filter_map(|a| alloy::primitives::Address::from_str(a).ok())
+            .collect::<Vec<_>>();
+        let network = wallet.config.eth.network(&network)?;
+        let names = ens_names
+            .lookup_for(&providers, &wallet.config, network, unnamed)
+            .await;
+        for record in records.iter_mut().filter(|r| r.counterparty_name.is_none()) {
+            record.counterparty_name = record
+                .counterparty()
+                .and_then(|a| alloy::primitives::Address::from_str(a).ok())
+                .and_then(|a| names.get(&a).cloned());
+        }
+    }
+    if req.chain == BlockChain::Bitcoin {
+        for record in &mut records {
+            record.confirmations = Some(
+                record
+                    .block_height
+                    .as_deref()
+                    .and_then(|h| h.parse().ok())
+                    .map_or(0, |h| wallet.btc.tip.confirmations(h)),
+            );
+        }
+    }
+    Ok(records)
 }
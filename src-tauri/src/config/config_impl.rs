@@ -15,7 +15,7 @@ pub struct TorConfig {
     pub enabled: bool,
     /// SOCKS5 proxy address. Tor must be running locally.
     /// Bitcoin routes Electrum connections through this proxy;
-    /// Ethereum routes the configured RPC URL through this proxy.
+    /// Ethereum routes the configured RPC URLs through this proxy.
     #[schemars(title = "SOCKS5 Proxy")]
     pub socks5_proxy: String,
 }
@@ -84,10 +84,12 @@ impl Config {
         }
 
         let raw_config = fs::read_to_string(&config_path)?;
-        let json_config: Config = serde_json::from_str(&raw_config).unwrap_or_else(|e| {
+        let mut json_config: Config = serde_json::from_str(&raw_config).unwrap_or_else(|e| {
             tracing::error!("fail to deserialize config {}", e);
             Self::default()
         });
+        json_config.btc.migrate_legacy();
+        json_config.eth.migrate_legacy();
         tracing::debug!("config {:?}", json_config);
         Ok(json_config)
     }
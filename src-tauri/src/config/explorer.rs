@@ -0,0 +1,222 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+const MEMPOOL_SPACE: &str = "https://mempool.space";
+const MEMPOOL_SPACE_ONION: &str =
+    "http://mempoolhqx4isw62xs7abwphsq7ldayuidyx2v2oethdhhj6mlo2r6ad.onion";
+const BLOCKSTREAM: &str = "https://blockstream.info";
+const BLOCKSTREAM_ONION: &str =
+    "http://explorerzydxu5ecjrkwceayqybizmpjjznk5izmitf2modhcusuqlid.onion";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplorerPreset {
+    MempoolSpace,
+    MempoolSpaceOnion,
+    Blockstream,
+    BlockstreamOnion,
+    Etherscan,
+    /// A mempool or Etherscan-style instance at `base_url`
+    SelfHosted,
+    /// Links built from `tx_template` and `address_template`
+    Custom,
+    Disabled,
+}
+
+/// Block explorer that transaction, address and coin links point to.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, JsonSchema)]
+#[serde(default)]
+#[schemars(title = "Block Explorer")]
+pub struct ExplorerConfig {
+    #[schemars(title = "Explorer")]
+    pub preset: ExplorerPreset,
+    /// Root of a self-hosted explorer, e.g. http://umbrel.local:3006
+    #[schemars(title = "Self-hosted URL")]
+    pub base_url: String,
+    /// Custom transaction link, `{txid}` is replaced with the transaction id
+    #[schemars(title = "Transaction Link")]
+    pub tx_template: String,
+    /// Custom address link, `{address}` is replaced with the address
+    #[schemars(title = "Address Link")]
+    pub address_template: String,
+}
+
+impl ExplorerConfig {
+    pub fn preset(preset: ExplorerPreset) -> Self {
+        Self {
+            preset,
+            base_url: String::new(),
+            tx_template: String::new(),
+            address_template: String::new(),
+        }
+    }
+
+    /// Resolve the configured explorer. `public` maps a public preset to the
+    /// instance serving this chain and network, if there is one.
+    pub fn resolve(&self, public: impl Fn(ExplorerPreset) -> Option<Explorer>) -> Option<Explorer> {
+        match self.preset {
+            ExplorerPreset::Disabled => None,
+            ExplorerPreset::SelfHosted => {
+                let base = self.base_url.trim().trim_end_matches('/');
+                (!base.is_empty()).then(|| Explorer::mempool(base))
+            }
+            ExplorerPreset::Custom => {
+                let tx = self.tx_template.trim();
+                let address = self.address_template.trim();
+                (!tx.is_empty()).then(|| Explorer {
+                    tx: tx.to_string(),
+                    address: (!address.is_empty()).then(|| address.to_string()),
+                    output: None,
+                })
+            }
+            preset => public(preset),
+        }
+    }
+}
+
+impl Default for ExplorerConfig {
+    fn default() -> Self {
+        Self::preset(ExplorerPreset::MempoolSpace)
+    }
+}
+
+/// Resolved link templates with `{txid}`, `{vout}` and `{address}`
+/// placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explorer {
+    tx: String,
+    address: Option<String>,
+    output: Option<String>,
+}
+
+impl Explorer {
+    pub fn mempool(base: &str) -> Self {
+        Self {
+            tx: format!("{base}/tx/{{txid}}"),
+            address: Some(format!("{base}/address/{{address}}")),
+            output: Some(format!("{base}/tx/{{txid}}#vout={{vout}}")),
+        }
+    }
+
+    pub fn esplora(base: &str) -> Self {
+        Self {
+            output: Some(format!("{base}/tx/{{txid}}?output:{{vout}}")),
+            ..Self::mempool(base)
+        }
+    }
+
+    pub fn etherscan(base: &str) -> Self {
+        Self {
+            output: None,
+            ..Self::mempool(base)
+        }
+    }
+
+    pub fn tx_url(&self, txid: &str) -> String {
+        self.tx.replace("{txid}", txid)
+    }
+
+    pub fn address_url(&self, address: &str) -> Option<String> {
+        self.address
+            .as_ref()
+            .map(|t| t.replace("{address}", address))
+    }
+
+    /// Link to a single output, the transaction itself where the explorer
+    /// can't point at one.
+    pub fn output_url(&self, txid: &str, vout: u32) -> String {
+        match &self.output {
+            Some(t) => t
+                .replace("{txid}", txid)
+                .replace("{vout}", &vout.to_string()),
+            None => self.tx_url(txid),
+        }
+    }
+}
+
+/// Public instance of `preset` for Bitcoin. `path` is the network's path on
+/// mempool.space, empty for mainnet.
+pub fn bitcoin_explorer(preset: ExplorerPreset, path: &str) -> Option<Explorer> {
+    match preset {
+        ExplorerPreset::MempoolSpace => Some(Explorer::mempool(&format!("{MEMPOOL_SPACE}{path}"))),
+        ExplorerPreset::MempoolSpaceOnion => {
+            Some(Explorer::mempool(&format!("{MEMPOOL_SPACE_ONION}{path}")))
+        }
+        // Blockstream only runs mainnet and testnet3.
+        ExplorerPreset::Blockstream if matches!(path, "" | "/testnet") => {
+            Some(Explorer::esplora(&format!("{BLOCKSTREAM}{path}")))
+        }
+        ExplorerPreset::BlockstreamOnion if matches!(path, "" | "/testnet") => {
+            Some(Explorer::esplora(&format!("{BLOCKSTREAM_ONION}{path}")))
+        }
+        _ => None,
+    }
+}
+
+/// Public instance of `preset` for an EVM chain whose Etherscan-style
+/// explorer lives at `base`.
+pub fn ethereum_explorer(preset: ExplorerPreset, base: &str) -> Option<Explorer> {
+    match preset {
+        ExplorerPreset::Etherscan => Some(Explorer::etherscan(base)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_build_links_for_the_network() {
+        let explorer = ExplorerConfig::default()
+            .resolve(|p| bitcoin_explorer(p, "/testnet4"))
+            .unwrap();
+        assert_eq!(
+            explorer.tx_url("ab"),
+            "https://mempool.space/testnet4/tx/ab"
+        );
+        assert_eq!(
+            explorer.output_url("ab", 1),
+            "https://mempool.space/testnet4/tx/ab#vout=1"
+        );
+
+        let blockstream = ExplorerConfig::preset(ExplorerPreset::Blockstream);
+        assert!(
+            blockstream
+                .resolve(|p| bitcoin_explorer(p, "/signet"))
+                .is_none()
+        );
+        assert!(
+            ExplorerConfig::preset(ExplorerPreset::Etherscan)
+                .resolve(|p| bitcoin_explorer(p, ""))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn custom_and_self_hosted_templates() {
+        let custom = ExplorerConfig {
+            tx_template: "https://example.org/t/{txid}".to_string(),
+            ..ExplorerConfig::preset(ExplorerPreset::Custom)
+        };
+        let explorer = custom.resolve(|_| None).unwrap();
+        assert_eq!(explorer.tx_url("ab"), "https://example.org/t/ab");
+        assert_eq!(explorer.output_url("ab", 0), "https://example.org/t/ab");
+        assert_eq!(explorer.address_url("bc1q"), None);
+
+        let self_hosted = ExplorerConfig {
+            base_url: "http://umbrel.local:3006/".to_string(),
+            ..ExplorerConfig::preset(ExplorerPreset::SelfHosted)
+        };
+        assert_eq!(
+            self_hosted.resolve(|_| None).unwrap().address_url("bc1q"),
+            Some("http://umbrel.local:3006/address/bc1q".to_string())
+        );
+        assert!(
+            ExplorerConfig::preset(ExplorerPreset::SelfHosted)
+                .resolve(|_| None)
+                .is_none()
+        );
+    }
+}
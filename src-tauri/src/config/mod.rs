@@ -1,5 +1,6 @@
 pub mod config_impl;
 pub mod constants;
+pub mod explorer;
 
 pub use config_impl::*;
 pub use constants::*;
@@ -3,12 +3,18 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter};
 use tauri_specta::{Events, collect_events};
 
+use crate::chain::btc::dtos::{BtcQuorumEvent, BtcReorgEvent};
+
 #[automock]
 pub trait EventEmitterTrait: Send + Sync {
     fn session_expired(&self);
+    fn btc_reorg(&self, event: BtcReorgEvent);
+    fn btc_quorum_mismatch(&self, event: BtcQuorumEvent);
 }
 
 pub const EVENT_SESSION_EXPIRED: &str = "session_expired";
+pub const EVENT_BTC_REORG: &str = "btc_reorg";
+pub const EVENT_BTC_QUORUM_MISMATCH: &str = "btc_quorum_mismatch";
 
 pub fn list_events() -> Events {
     collect_events![]
@@ -24,6 +30,14 @@ impl EventEmitterTrait for EventEmitter {
     fn session_expired(&self) {
         self.emit(EVENT_SESSION_EXPIRED, ());
     }
+
+    fn btc_reorg(&self, event: BtcReorgEvent) {
+        self.emit(EVENT_BTC_REORG, event);
+    }
+
+    fn btc_quorum_mismatch(&self, event: BtcQuorumEvent) {
+        self.emit(EVENT_BTC_QUORUM_MISMATCH, event);
+    }
 }
 
 impl EventEmitter {
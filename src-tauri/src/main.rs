@@ -4,8 +4,17 @@
 use std::time::Duration;
 
 use satellion_lib::{
-    chain::eth, codegen, config::Config, db, event_emitter::EventEmitter, repository::TxRepository,
-    session::SessionKeeper, system, utils, wallet_keeper::WalletKeeper,
+    chain::eth,
+    codegen,
+    config::{BlockChain, Config},
+    db,
+    event_emitter::EventEmitter,
+    repository::{
+        AbiRepository, ApprovalRepository, CheckpointRepository, NftRepository, TxRepository,
+    },
+    session::SessionKeeper,
+    system, utils,
+    wallet_keeper::WalletKeeper,
 };
 use tauri::Manager;
 use tokio::sync::Mutex;
@@ -17,29 +26,35 @@ pub async fn main() {
     db::initialize();
     let db = db::connect();
     let tx_repository = TxRepository::new(db.clone());
+    let checkpoints = CheckpointRepository::new(db.clone());
+    let approvals = ApprovalRepository::new(db.clone());
+    let nfts = NftRepository::new(db.clone());
+    let abis = AbiRepository::new(db.clone());
     let wallet_keeper = WalletKeeper::default();
     let config = Config::new();
+    tx_repository
+        .backfill_network(BlockChain::Bitcoin, config.btc.network.as_str())
+        .expect("failed to backfill the network of bitcoin transactions");
     let tor = system::tor::start_blocking(&config.tor);
 
-    let eth_provider = eth::select_provider(config.clone());
-    let eth_batch_provider = eth::new_provider_batched(eth_provider.clone());
-    let erc20_retriever = eth::Erc20Retriever::new(eth_provider.clone());
-    let tx_builder = eth::TxBuilder::new(eth_batch_provider);
-    let price_feed = eth::PriceFeed::new(eth_provider.clone());
-
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(db.clone())
         .manage(tx_repository)
+        .manage(checkpoints)
+        .manage(approvals)
+        .manage(nfts)
+        .manage(abis)
         .manage(wallet_keeper)
-        .manage(eth_provider.clone())
-        .manage(erc20_retriever)
-        .manage(price_feed)
+        .manage(eth::EvmProviders::default())
+        .manage(eth::NonceManager::default())
+        .manage(eth::ens::EnsNames::default())
         .manage(Mutex::new(config))
         .manage(tor)
-        .manage(Mutex::new(tx_builder))
+        .manage(Mutex::new(None::<eth::TxBuilder>))
         .setup(move |app| {
             let event_emitter = EventEmitter::new(app.handle().clone());
+            app.manage(event_emitter.clone());
             let sk = SessionKeeper::new(Some(event_emitter.clone()), Some(Duration::from_mins(1)));
             app.manage(sk.clone());
 
@@ -35,7 +35,7 @@ impl WalletEntity {
                 self.chain_set.bitcoin.clone(),
                 config.clone(),
                 Arc::clone(&secret),
-            ),
+            )?,
             eth: crate::chain::eth::EthereumWallet::from_dto(
                 self.chain_set.ethereum.clone(),
                 config.clone(),
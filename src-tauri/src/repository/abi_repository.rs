@@ -0,0 +1,143 @@
+use diesel::{prelude::*, r2d2::ConnectionManager, upsert::excluded};
+use r2d2::Pool;
+
+use crate::{repository::base_repository::BaseRepository, schema::contract_abis, utils};
+
+#[derive(Insertable)]
+#[diesel(table_name = contract_abis)]
+struct AbiRow<'a> {
+    network: &'a str,
+    address: &'a str,
+    name: &'a str,
+    abi: &'a str,
+    updated_at: i64,
+}
+
+#[derive(Queryable, Debug, Clone, PartialEq)]
+pub struct StoredAbi {
+    pub network: String,
+    pub address: String,
+    pub name: String,
+    /// The ABI JSON as loaded.
+    pub abi: String,
+    pub updated_at: i64,
+}
+
+/// ABIs of the contracts the user calls, per network and address.
+#[derive(Clone, Debug)]
+pub struct AbiRepository {
+    base: BaseRepository,
+}
+
+impl AbiRepository {
+    pub fn new(db_pool: Pool<ConnectionManager<SqliteConnection>>) -> Self {
+        Self {
+            base: BaseRepository::new(db_pool),
+        }
+    }
+
+    /// Store the ABI of `contract`, replacing the one loaded before.
+    pub fn save(&self, net: &str, contract: &str, label: &str, json: &str) -> Result<(), String> {
+        use crate::schema::contract_abis::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        let row = AbiRow {
+            network: net,
+            address: contract,
+            name: label,
+            abi: json,
+            updated_at: utils::now() as i64,
+        };
+        diesel::insert_into(contract_abis)
+            .values(&row)
+            .on_conflict((network, address))
+            .do_update()
+            .set((
+                name.eq(excluded(name)),
+                abi.eq(excluded(abi)),
+                updated_at.eq(excluded(updated_at)),
+            ))
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn get(&self, net: &str, contract: &str) -> Result<Option<StoredAbi>, String> {
+        use crate::schema::contract_abis::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        contract_abis
+            .filter(network.eq(net))
+            .filter(address.eq(contract))
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    /// The network's contracts, the last loaded first.
+    pub fn list(&self, net: &str) -> Result<Vec<StoredAbi>, String> {
+        use crate::schema::contract_abis::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        contract_abis
+            .filter(network.eq(net))
+            .order(updated_at.desc())
+            .load(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete(&self, net: &str, contract: &str) -> Result<usize, String> {
+        use crate::schema::contract_abis::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        diesel::delete(
+            contract_abis
+                .filter(network.eq(net))
+                .filter(address.eq(contract)),
+        )
+        .execute(&mut conn)
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::db::MIGRATIONS;
+
+    fn make_pool() -> Pool<ConnectionManager<SqliteConnection>> {
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let mut conn = pool.get().unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+        pool
+    }
+
+    #[test]
+    fn abi_is_kept_per_network_and_replaced() {
+        let repo = AbiRepository::new(make_pool());
+
+        repo.save("mainnet", "0xc0ffee", "Vault", "[]").unwrap();
+        repo.save("sepolia", "0xc0ffee", "Test vault", "[]")
+            .unwrap();
+        repo.save(
+            "mainnet",
+            "0xc0ffee",
+            "Vault v2",
+            r#"[{"type":"fallback"}]"#,
+        )
+        .unwrap();
+
+        let stored = repo.get("mainnet", "0xc0ffee").unwrap().unwrap();
+        assert_eq!(stored.name, "Vault v2");
+        assert_eq!(stored.abi, r#"[{"type":"fallback"}]"#);
+        assert_eq!(repo.list("mainnet").unwrap().len(), 1);
+        assert!(repo.get("base", "0xc0ffee").unwrap().is_none());
+
+        assert_eq!(repo.delete("mainnet", "0xc0ffee").unwrap(), 1);
+        assert!(repo.get("mainnet", "0xc0ffee").unwrap().is_none());
+        assert_eq!(repo.list("sepolia").unwrap().len(), 1);
+    }
+}
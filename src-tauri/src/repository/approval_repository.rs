@@ -0,0 +1,127 @@
+use diesel::{prelude::*, r2d2::ConnectionManager};
+use r2d2::Pool;
+
+use crate::{repository::base_repository::BaseRepository, schema::token_approvals};
+
+#[derive(Insertable)]
+#[diesel(table_name = token_approvals)]
+struct ApprovalRow<'a> {
+    wallet_name: &'a str,
+    network: &'a str,
+    owner: &'a str,
+    token: &'a str,
+    spender: &'a str,
+}
+
+/// `(token, spender)` pairs an owner granted an allowance to.
+#[derive(Clone, Debug)]
+pub struct ApprovalRepository {
+    base: BaseRepository,
+}
+
+impl ApprovalRepository {
+    pub fn new(db_pool: Pool<ConnectionManager<SqliteConnection>>) -> Self {
+        Self {
+            base: BaseRepository::new(db_pool),
+        }
+    }
+
+    /// Remember `pairs`, ignoring the known ones. Returns how many were new.
+    pub fn add(
+        &self,
+        wallet: &str,
+        net: &str,
+        holder: &str,
+        pairs: &[(String, String)],
+    ) -> Result<usize, String> {
+        let rows: Vec<ApprovalRow> = pairs
+            .iter()
+            .map(|(token, spender)| ApprovalRow {
+                wallet_name: wallet,
+                network: net,
+                owner: holder,
+                token,
+                spender,
+            })
+            .collect();
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::insert_or_ignore_into(token_approvals::table)
+            .values(&rows)
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn list(
+        &self,
+        wallet: &str,
+        net: &str,
+        holder: &str,
+    ) -> Result<Vec<(String, String)>, String> {
+        use crate::schema::token_approvals::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        token_approvals
+            .filter(wallet_name.eq(wallet))
+            .filter(network.eq(net))
+            .filter(owner.eq(holder))
+            .select((token, spender))
+            .load(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete_for_wallet(&self, wallet: &str) -> Result<usize, String> {
+        use crate::schema::token_approvals::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::delete(token_approvals.filter(wallet_name.eq(wallet)))
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::db::MIGRATIONS;
+
+    fn make_pool() -> Pool<ConnectionManager<SqliteConnection>> {
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let mut conn = pool.get().unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+        pool
+    }
+
+    #[test]
+    fn pairs_are_kept_once_per_owner_and_network() {
+        let repo = ApprovalRepository::new(make_pool());
+        let pair = |t: &str, s: &str| (t.to_string(), s.to_string());
+
+        let added = repo
+            .add(
+                "alice",
+                "mainnet",
+                "0xa",
+                &[pair("0xusdc", "0xrouter"), pair("0xusdt", "0xrouter")],
+            )
+            .unwrap();
+        assert_eq!(added, 2);
+        let added = repo
+            .add("alice", "mainnet", "0xa", &[pair("0xusdc", "0xrouter")])
+            .unwrap();
+        assert_eq!(added, 0);
+        repo.add("alice", "base", "0xa", &[pair("0xusdc", "0xbridge")])
+            .unwrap();
+
+        let mut pairs = repo.list("alice", "mainnet", "0xa").unwrap();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            vec![pair("0xusdc", "0xrouter"), pair("0xusdt", "0xrouter")]
+        );
+        assert!(repo.list("alice", "mainnet", "0xb").unwrap().is_empty());
+        assert_eq!(repo.delete_for_wallet("alice").unwrap(), 3);
+    }
+}
@@ -0,0 +1,135 @@
+use diesel::{prelude::*, r2d2::ConnectionManager, upsert::excluded};
+use r2d2::Pool;
+
+use crate::{
+    config::BlockChain,
+    repository::{base_repository::BaseRepository, tx_repository::chain_as_str},
+    schema::sync_checkpoints,
+};
+
+/// Where a history scan of one address stopped, so the next one resumes
+/// there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointKey<'a> {
+    pub wallet_name: &'a str,
+    pub chain: BlockChain,
+    pub network: &'a str,
+    pub address: &'a str,
+    /// Kind of scan, e.g. `logs` or `native`.
+    pub scope: &'a str,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = sync_checkpoints)]
+struct CheckpointRow<'a> {
+    wallet_name: &'a str,
+    chain: &'a str,
+    network: &'a str,
+    address: &'a str,
+    scope: &'a str,
+    block_height: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CheckpointRepository {
+    base: BaseRepository,
+}
+
+impl CheckpointRepository {
+    pub fn new(db_pool: Pool<ConnectionManager<SqliteConnection>>) -> Self {
+        Self {
+            base: BaseRepository::new(db_pool),
+        }
+    }
+
+    /// Last block the scan covered, `None` before the first one.
+    pub fn get(&self, key: &CheckpointKey) -> Result<Option<u64>, String> {
+        use crate::schema::sync_checkpoints::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        let height: Option<i64> = sync_checkpoints
+            .filter(wallet_name.eq(key.wallet_name))
+            .filter(chain.eq(chain_as_str(key.chain)))
+            .filter(network.eq(key.network))
+            .filter(address.eq(key.address))
+            .filter(scope.eq(key.scope))
+            .select(block_height)
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| e.to_string())?;
+        Ok(height.map(|h| h as u64))
+    }
+
+    pub fn set(&self, key: &CheckpointKey, height: u64) -> Result<(), String> {
+        use crate::schema::sync_checkpoints::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        let row = CheckpointRow {
+            wallet_name: key.wallet_name,
+            chain: chain_as_str(key.chain),
+            network: key.network,
+            address: key.address,
+            scope: key.scope,
+            block_height: height as i64,
+        };
+        diesel::insert_into(sync_checkpoints)
+            .values(&row)
+            .on_conflict((wallet_name, chain, network, address, scope))
+            .do_update()
+            .set(block_height.eq(excluded(block_height)))
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn delete_for_wallet(&self, wallet: &str) -> Result<usize, String> {
+        use crate::schema::sync_checkpoints::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::delete(sync_checkpoints.filter(wallet_name.eq(wallet)))
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::db::MIGRATIONS;
+
+    fn make_pool() -> Pool<ConnectionManager<SqliteConnection>> {
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let mut conn = pool.get().unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+        pool
+    }
+
+    #[test]
+    fn checkpoint_is_kept_per_scope() {
+        let repo = CheckpointRepository::new(make_pool());
+        let logs = CheckpointKey {
+            wallet_name: "alice",
+            chain: BlockChain::Ethereum,
+            network: "mainnet",
+            address: "0xa",
+            scope: "logs",
+        };
+        let native = CheckpointKey {
+            scope: "native",
+            ..logs.clone()
+        };
+        assert_eq!(repo.get(&logs).unwrap(), None);
+
+        repo.set(&logs, 100).unwrap();
+        repo.set(&logs, 250).unwrap();
+        repo.set(&native, 90).unwrap();
+        assert_eq!(repo.get(&logs).unwrap(), Some(250));
+        assert_eq!(repo.get(&native).unwrap(), Some(90));
+
+        assert_eq!(repo.delete_for_wallet("alice").unwrap(), 2);
+        assert_eq!(repo.get(&logs).unwrap(), None);
+    }
+}
@@ -1,5 +1,13 @@
+pub mod abi_repository;
+pub mod approval_repository;
 pub mod base_repository;
+pub mod checkpoint_repository;
+pub mod nft_repository;
 pub mod tx_repository;
 
+pub use abi_repository::*;
+pub use approval_repository::*;
 pub use base_repository::*;
+pub use checkpoint_repository::*;
+pub use nft_repository::*;
 pub use tx_repository::*;
@@ -0,0 +1,126 @@
+use diesel::{prelude::*, r2d2::ConnectionManager};
+use r2d2::Pool;
+
+use crate::{repository::base_repository::BaseRepository, schema::nft_tokens};
+
+#[derive(Insertable)]
+#[diesel(table_name = nft_tokens)]
+struct NftTokenRow<'a> {
+    wallet_name: &'a str,
+    network: &'a str,
+    owner: &'a str,
+    collection: &'a str,
+    token_id: &'a str,
+}
+
+/// Token ids an owner received, per collection.
+#[derive(Clone, Debug)]
+pub struct NftRepository {
+    base: BaseRepository,
+}
+
+impl NftRepository {
+    pub fn new(db_pool: Pool<ConnectionManager<SqliteConnection>>) -> Self {
+        Self {
+            base: BaseRepository::new(db_pool),
+        }
+    }
+
+    /// Remember `ids`, ignoring the known ones. Returns how many were new.
+    pub fn add(
+        &self,
+        wallet: &str,
+        net: &str,
+        holder: &str,
+        contract: &str,
+        ids: &[String],
+    ) -> Result<usize, String> {
+        let rows: Vec<NftTokenRow> = ids
+            .iter()
+            .map(|id| NftTokenRow {
+                wallet_name: wallet,
+                network: net,
+                owner: holder,
+                collection: contract,
+                token_id: id,
+            })
+            .collect();
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::insert_or_ignore_into(nft_tokens::table)
+            .values(&rows)
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn list(
+        &self,
+        wallet: &str,
+        net: &str,
+        holder: &str,
+        contract: &str,
+    ) -> Result<Vec<String>, String> {
+        use crate::schema::nft_tokens::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        nft_tokens
+            .filter(wallet_name.eq(wallet))
+            .filter(network.eq(net))
+            .filter(owner.eq(holder))
+            .filter(collection.eq(contract))
+            .select(token_id)
+            .load(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn delete_for_wallet(&self, wallet: &str) -> Result<usize, String> {
+        use crate::schema::nft_tokens::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::delete(nft_tokens.filter(wallet_name.eq(wallet)))
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel_migrations::MigrationHarness;
+
+    use super::*;
+    use crate::db::MIGRATIONS;
+
+    fn make_pool() -> Pool<ConnectionManager<SqliteConnection>> {
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let mut conn = pool.get().unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+        pool
+    }
+
+    #[test]
+    fn token_ids_are_kept_once_per_collection() {
+        let repo = NftRepository::new(make_pool());
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        let added = repo
+            .add("alice", "mainnet", "0xa", "0xpunks", &ids(&["1", "7"]))
+            .unwrap();
+        assert_eq!(added, 2);
+        let added = repo
+            .add("alice", "mainnet", "0xa", "0xpunks", &ids(&["7", "9"]))
+            .unwrap();
+        assert_eq!(added, 1);
+        repo.add("alice", "mainnet", "0xa", "0xapes", &ids(&["1"]))
+            .unwrap();
+
+        let mut owned = repo.list("alice", "mainnet", "0xa", "0xpunks").unwrap();
+        owned.sort();
+        assert_eq!(owned, ids(&["1", "7", "9"]));
+        assert!(
+            repo.list("alice", "base", "0xa", "0xpunks")
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(repo.delete_for_wallet("alice").unwrap(), 4);
+    }
+}
@@ -7,7 +7,11 @@ use strum::FromRepr;
 use strum::{AsRefStr, Display, EnumString};
 
 use crate::{
-    config::BlockChain, repository::base_repository::BaseRepository, schema::transactions,
+    config::{BlockChain, explorer::Explorer},
+    eth::{contract::ContractCall, nft::NftItem},
+    repository::base_repository::BaseRepository,
+    schema::transactions,
+    utils,
 };
 
 #[repr(i16)]
@@ -28,9 +32,39 @@ pub enum TxStatus {
     Pending,
     Confirmed,
     Failed,
+    /// Superseded by another transaction spending the same nonce.
+    Replaced,
 }
 
-fn chain_as_str(chain: BlockChain) -> &'static str {
+/// `log_index` of rows not matched to a `Transfer` event, e.g. native value
+/// transfers.
+const NO_LOG: i64 = -1;
+
+/// Which part of a transaction moved the value of an indexed transfer, so
+/// several transfers of one transaction get a row each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferKey {
+    /// The value of the transaction itself.
+    Transaction,
+    /// A token's `Transfer` event, by its index in the block.
+    Log(u64),
+    /// Value sent by a contract call, by its position among the
+    /// transaction's internal transfers.
+    Trace(u64),
+}
+
+impl TransferKey {
+    /// Stored in `log_index`: traces count down from below [`NO_LOG`].
+    fn log_index(self) -> i64 {
+        match self {
+            TransferKey::Transaction => NO_LOG,
+            TransferKey::Log(index) => index as i64,
+            TransferKey::Trace(index) => NO_LOG - 1 - index as i64,
+        }
+    }
+}
+
+pub(crate) fn chain_as_str(chain: BlockChain) -> &'static str {
     match chain {
         BlockChain::Bitcoin => "bitcoin",
         BlockChain::Ethereum => "ethereum",
@@ -58,6 +92,8 @@ fn unix_timestamp_to_iso(ts: i64) -> Result<String, String> {
 pub struct NewTx {
     pub wallet_name: String,
     pub chain: BlockChain,
+    /// Network within the chain, e.g. `base` or `signet`.
+    pub network: String,
     pub account_index: i32,
     pub tx_hash: String,
     pub direction: TxDirection,
@@ -76,6 +112,7 @@ pub struct NewTx {
 struct NewTxRow<'a> {
     wallet_name: &'a str,
     chain: &'a str,
+    network: &'a str,
     account_index: i32,
     tx_hash: &'a str,
     direction: i16,
@@ -87,6 +124,32 @@ struct NewTxRow<'a> {
     block_height: Option<i64>,
     chain_data: String,
     created_at: i64,
+    confirmed_at: Option<i64>,
+    log_index: i64,
+}
+
+impl<'a> NewTxRow<'a> {
+    fn new(tx: &'a NewTx, log_index: i64, confirmed_at: Option<i64>) -> Result<Self, String> {
+        Ok(Self {
+            wallet_name: &tx.wallet_name,
+            chain: chain_as_str(tx.chain),
+            network: &tx.network,
+            account_index: tx.account_index,
+            tx_hash: &tx.tx_hash,
+            direction: tx.direction as i16,
+            status: tx.status.as_ref(),
+            from_address: tx.from_address.as_deref(),
+            to_address: tx.to_address.as_deref(),
+            amount: tx.amount,
+            fee: tx.fee,
+            block_height: tx.block_height,
+            chain_data: serde_json::to_string(&tx.chain_data)
+                .map_err(|e| format!("encode chain_data: {e}"))?,
+            created_at: tx.created_at,
+            confirmed_at,
+            log_index,
+        })
+    }
 }
 
 #[derive(Queryable, Selectable, Debug, Clone)]
@@ -106,6 +169,8 @@ struct TxRow {
     chain_data: String,
     created_at: i64,
     confirmed_at: Option<i64>,
+    network: String,
+    log_index: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Type)]
@@ -113,7 +178,11 @@ pub struct TxRecord {
     pub tx_hash: String,
     pub wallet_name: String,
     pub chain: BlockChain,
+    pub network: String,
     pub account_index: i32,
+    /// Position of the token's `Transfer` event in the block, when the row
+    /// was matched to one.
+    pub log_index: Option<u32>,
     pub direction: TxDirection,
     pub status: TxStatus,
     pub from_address: Option<String>,
@@ -126,6 +195,37 @@ pub struct TxRecord {
     pub chain_data: String,
     pub created_at: String,
     pub confirmed_at: Option<String>,
+    /// Filled for Bitcoin from the wallet's chain tip.
+    pub confirmations: Option<u32>,
+    /// Block explorer links, filled when an explorer is configured.
+    pub explorer_url: Option<String>,
+    pub from_address_url: Option<String>,
+    pub to_address_url: Option<String>,
+    /// ENS name of the counterparty, filled for Ethereum.
+    pub counterparty_name: Option<String>,
+}
+
+impl TxRecord {
+    /// The other side of the transaction, none for transfers to self.
+    pub fn counterparty(&self) -> Option<&str> {
+        match self.direction {
+            TxDirection::Incoming => self.from_address.as_deref(),
+            TxDirection::Outgoing => self.to_address.as_deref(),
+            TxDirection::SelfTransfer => None,
+        }
+    }
+
+    pub fn link(&mut self, explorer: &Explorer) {
+        self.explorer_url = Some(explorer.tx_url(&self.tx_hash));
+        self.from_address_url = self
+            .from_address
+            .as_deref()
+            .and_then(|a| explorer.address_url(a));
+        self.to_address_url = self
+            .to_address
+            .as_deref()
+            .and_then(|a| explorer.address_url(a));
+    }
 }
 
 impl TryFrom<TxRow> for TxRecord {
@@ -136,7 +236,9 @@ impl TryFrom<TxRow> for TxRecord {
             tx_hash: row.tx_hash,
             wallet_name: row.wallet_name,
             chain: chain_from_str(&row.chain)?,
+            network: row.network,
             account_index: row.account_index,
+            log_index: u32::try_from(row.log_index).ok(),
             direction: TxDirection::from_repr(row.direction)
                 .ok_or_else(|| format!("unknown tx direction: {}", row.direction))?,
             status: row
@@ -151,6 +253,11 @@ impl TryFrom<TxRow> for TxRecord {
             chain_data: row.chain_data,
             created_at: unix_timestamp_to_iso(row.created_at)?,
             confirmed_at: row.confirmed_at.map(unix_timestamp_to_iso).transpose()?,
+            confirmations: None,
+            explorer_url: None,
+            from_address_url: None,
+            to_address_url: None,
+            counterparty_name: None,
         })
     }
 }
@@ -160,6 +267,8 @@ pub struct TxQuery {
     pub wallet_name: String,
     pub chain: BlockChain,
     pub account_index: i32,
+    /// Only transactions of this network, all of the chain's when unset.
+    pub network: Option<String>,
     pub limit: Option<i64>,
 }
 
@@ -176,24 +285,7 @@ impl TxRepository {
     }
 
     pub fn insert(&self, tx: NewTx) -> Result<TxRecord, String> {
-        let chain_data_str =
-            serde_json::to_string(&tx.chain_data).map_err(|e| format!("encode chain_data: {e}"))?;
-        let row = NewTxRow {
-            wallet_name: &tx.wallet_name,
-            chain: chain_as_str(tx.chain),
-            account_index: tx.account_index,
-            tx_hash: &tx.tx_hash,
-            direction: tx.direction as i16,
-            status: tx.status.as_ref(),
-            from_address: tx.from_address.as_deref(),
-            to_address: tx.to_address.as_deref(),
-            amount: tx.amount,
-            fee: tx.fee,
-            block_height: tx.block_height,
-            chain_data: chain_data_str,
-            created_at: tx.created_at,
-        };
-
+        let row = NewTxRow::new(&tx, NO_LOG, None)?;
         let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
         let inserted: TxRow = diesel::insert_into(transactions::table)
             .values(&row)
@@ -208,15 +300,75 @@ impl TxRepository {
         TxRecord::try_from(inserted)
     }
 
+    /// Record a mined Ethereum transfer found by the indexer, `tx.created_at`
+    /// being its block time.
+    ///
+    /// A transfer the wallet sent itself updates the row written at sending
+    /// time instead of adding a second one. Returns whether a row was added.
+    pub fn upsert_transfer(&self, tx: NewTx, key: TransferKey) -> Result<bool, String> {
+        use crate::schema::transactions::dsl::*;
+        let index = key.log_index();
+        let token = tx.chain_data.get("token_address").and_then(|t| t.as_str());
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        conn.transaction::<_, Error, _>(|conn| {
+            let rows: Vec<TxRow> = transactions
+                .filter(wallet_name.eq(&tx.wallet_name))
+                .filter(chain.eq(chain_as_str(tx.chain)))
+                .filter(account_index.eq(tx.account_index))
+                .filter(tx_hash.eq(&tx.tx_hash))
+                .select(TxRow::as_select())
+                .load(conn)?;
+            let same_token = |row: &TxRow| {
+                serde_json::from_str::<EthChainData>(&row.chain_data)
+                    .is_ok_and(|d| token.is_some_and(|t| d.token_address.eq_ignore_ascii_case(t)))
+            };
+            let existing = rows.iter().find(|r| r.log_index == index).or_else(|| {
+                rows.iter()
+                    .find(|r| index >= 0 && r.log_index == NO_LOG && same_token(r))
+            });
+
+            let Some(existing) = existing else {
+                let row = NewTxRow::new(&tx, index, Some(tx.created_at))
+                    .map_err(|e| Error::QueryBuilderError(e.into()))?;
+                diesel::insert_into(transactions)
+                    .values(&row)
+                    .execute(conn)?;
+                return Ok(true);
+            };
+            diesel::update(
+                transactions
+                    .filter(wallet_name.eq(&tx.wallet_name))
+                    .filter(chain.eq(chain_as_str(tx.chain)))
+                    .filter(account_index.eq(tx.account_index))
+                    .filter(tx_hash.eq(&tx.tx_hash))
+                    .filter(log_index.eq(existing.log_index)),
+            )
+            .set((
+                log_index.eq(index),
+                status.eq(tx.status.as_ref()),
+                block_height.eq(tx.block_height),
+                confirmed_at.eq(existing.confirmed_at.unwrap_or(tx.created_at)),
+            ))
+            .execute(conn)?;
+            Ok(false)
+        })
+        .map_err(|e| e.to_string())
+    }
+
     pub fn list(&self, q: &TxQuery) -> Result<Vec<TxRecord>, String> {
         use crate::schema::transactions::dsl::*;
         let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
 
-        let rows: Vec<TxRow> = transactions
+        let mut query = transactions
             .into_boxed()
             .filter(wallet_name.eq(&q.wallet_name))
             .filter(chain.eq(chain_as_str(q.chain)))
-            .filter(account_index.eq(q.account_index))
+            .filter(account_index.eq(q.account_index));
+        if let Some(n) = &q.network {
+            query = query.filter(network.eq(n));
+        }
+        let rows: Vec<TxRow> = query
             .order(created_at.desc())
             .limit(q.limit.unwrap_or(200))
             .select(TxRow::as_select())
@@ -226,6 +378,50 @@ impl TxRepository {
         rows.into_iter().map(TxRecord::try_from).collect()
     }
 
+    pub fn get(
+        &self,
+        wallet: &str,
+        chain_id: BlockChain,
+        hash: &str,
+    ) -> Result<Option<TxRecord>, String> {
+        use crate::schema::transactions::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        transactions
+            .filter(wallet_name.eq(wallet))
+            .filter(chain.eq(chain_as_str(chain_id)))
+            .filter(tx_hash.eq(hash))
+            .select(TxRow::as_select())
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| e.to_string())?
+            .map(TxRecord::try_from)
+            .transpose()
+    }
+
+    /// Hashes of Bitcoin CPFP children recorded for `parent`, see
+    /// [`BtcChainData::parent_tx_id`].
+    pub fn btc_children(&self, wallet: &str, parent: &str) -> Result<Vec<String>, String> {
+        use crate::schema::transactions::dsl::*;
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+
+        let rows: Vec<(String, String)> = transactions
+            .filter(wallet_name.eq(wallet))
+            .filter(chain.eq(chain_as_str(BlockChain::Bitcoin)))
+            .filter(chain_data.like(format!("%{parent}%")))
+            .select((tx_hash, chain_data))
+            .load(&mut conn)
+            .map_err(|e| e.to_string())?;
+        Ok(rows
+            .into_iter()
+            .filter(|(_, data)| {
+                serde_json::from_str::<BtcChainData>(data)
+                    .is_ok_and(|d| d.parent_tx_id.as_deref() == Some(parent))
+            })
+            .map(|(hash, _)| hash)
+            .collect())
+    }
+
     pub fn update_status(
         &self,
         wallet: &str,
@@ -253,6 +449,165 @@ impl TxRepository {
         .map_err(|e| e.to_string())
     }
 
+    /// Mark `hash` confirmed in `block` if it's still pending, for calls the
+    /// indexer sees no `Transfer` log of. Returns whether it was.
+    /// Give the rows of `chain_id` recorded before networks were tracked the
+    /// network they were recorded on. Returns how many were updated.
+    pub fn backfill_network(&self, chain_id: BlockChain, net: &str) -> Result<usize, String> {
+        use crate::schema::transactions::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::update(
+            transactions
+                .filter(chain.eq(chain_as_str(chain_id)))
+                .filter(network.eq("")),
+        )
+        .set(network.eq(net))
+        .execute(&mut conn)
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn confirm_pending(
+        &self,
+        wallet: &str,
+        chain_id: BlockChain,
+        hash: &str,
+        block: u64,
+    ) -> Result<bool, String> {
+        use crate::schema::transactions::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        diesel::update(
+            transactions
+                .filter(wallet_name.eq(wallet))
+                .filter(chain.eq(chain_as_str(chain_id)))
+                .filter(tx_hash.eq(hash))
+                .filter(status.eq(TxStatus::Pending.as_ref())),
+        )
+        .set((
+            status.eq(TxStatus::Confirmed.as_ref()),
+            block_height.eq(Some(block as i64)),
+            confirmed_at.eq(Some(utils::now() as i64)),
+        ))
+        .execute(&mut conn)
+        .map(|n| n > 0)
+        .map_err(|e| e.to_string())
+    }
+
+    /// Move transactions mined at or above `fork_height` back to pending after
+    /// a reorg. Returns the affected hashes.
+    pub fn rollback_from_height(
+        &self,
+        wallet: &str,
+        chain_id: BlockChain,
+        fork_height: i64,
+    ) -> Result<Vec<String>, String> {
+        use crate::schema::transactions::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        let hashes: Vec<String> = transactions
+            .filter(wallet_name.eq(wallet))
+            .filter(chain.eq(chain_as_str(chain_id)))
+            .filter(block_height.ge(fork_height))
+            .select(tx_hash)
+            .load(&mut conn)
+            .map_err(|e| e.to_string())?;
+        diesel::update(
+            transactions
+                .filter(wallet_name.eq(wallet))
+                .filter(chain.eq(chain_as_str(chain_id)))
+                .filter(tx_hash.eq_any(&hashes)),
+        )
+        .set((
+            status.eq(TxStatus::Pending.as_ref()),
+            block_height.eq(None::<i64>),
+            confirmed_at.eq(None::<i64>),
+        ))
+        .execute(&mut conn)
+        .map_err(|e| e.to_string())?;
+        Ok(hashes)
+    }
+
+    /// Nonces of the Ethereum transfers `sender` has pending on `net`.
+    pub fn pending_eth_nonces(
+        &self,
+        wallet: &str,
+        net: &str,
+        sender: &str,
+    ) -> Result<Vec<u64>, String> {
+        Ok(self
+            .pending_eth_transfers(wallet, net, sender)?
+            .into_iter()
+            .map(|(nonce, _)| nonce)
+            .collect())
+    }
+
+    /// Nonce and hash of the transfers `sender` has pending.
+    pub fn pending_eth_transfers(
+        &self,
+        wallet: &str,
+        net: &str,
+        sender: &str,
+    ) -> Result<Vec<(u64, String)>, String> {
+        use crate::schema::transactions::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        let rows: Vec<(String, String)> = transactions
+            .filter(wallet_name.eq(wallet))
+            .filter(chain.eq(chain_as_str(BlockChain::Ethereum)))
+            .filter(network.eq(net))
+            .filter(from_address.eq(sender))
+            .filter(status.eq(TxStatus::Pending.as_ref()))
+            .select((tx_hash, chain_data))
+            .load(&mut conn)
+            .map_err(|e| e.to_string())?;
+        rows.into_iter()
+            .map(|(hash, data)| {
+                serde_json::from_str::<EthChainData>(&data)
+                    .map(|d| (d.nonce, hash))
+                    .map_err(|e| format!("decode chain_data: {e}"))
+            })
+            .collect()
+    }
+
+    /// Mark `hash` as replaced by `replacement` and link the two in
+    /// `chain_data`.
+    pub fn mark_replaced(
+        &self,
+        wallet: &str,
+        chain_id: BlockChain,
+        hash: &str,
+        replacement: &str,
+    ) -> Result<usize, String> {
+        use crate::schema::transactions::dsl::*;
+
+        let mut conn = self.base.get_conn().map_err(|e| e.to_string())?;
+        let data: String = transactions
+            .filter(wallet_name.eq(wallet))
+            .filter(chain.eq(chain_as_str(chain_id)))
+            .filter(tx_hash.eq(hash))
+            .select(chain_data)
+            .first(&mut conn)
+            .map_err(|e| e.to_string())?;
+        let mut data: serde_json::Value =
+            serde_json::from_str(&data).map_err(|e| format!("decode chain_data: {e}"))?;
+        if let Some(fields) = data.as_object_mut() {
+            fields.insert("replaced_by".to_string(), replacement.into());
+        }
+        diesel::update(
+            transactions
+                .filter(wallet_name.eq(wallet))
+                .filter(chain.eq(chain_as_str(chain_id)))
+                .filter(tx_hash.eq(hash)),
+        )
+        .set((
+            status.eq(TxStatus::Replaced.as_ref()),
+            chain_data.eq(data.to_string()),
+        ))
+        .execute(&mut conn)
+        .map_err(|e| e.to_string())
+    }
+
     pub fn delete_for_wallet(&self, wallet: &str) -> Result<usize, String> {
         use crate::schema::transactions::dsl::*;
 
@@ -285,6 +640,31 @@ pub struct EthChainData {
     pub max_fee_per_gas: String,
     pub max_priority_fee_per_gas: String,
     pub gas_limit: String,
+    /// Transferred amount in the token's base units.
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Hash of the transaction this one replaced.
+    #[serde(default)]
+    pub replaces: Option<String>,
+    /// Hash of the transaction that replaced this one.
+    #[serde(default)]
+    pub replaced_by: Option<String>,
+    /// Set on `approve` calls, whose `value` is the new allowance of this
+    /// spender.
+    #[serde(default)]
+    pub spender: Option<String>,
+    /// Set on NFT transfers, `token_address` being the collection and `value`
+    /// the number of copies.
+    #[serde(default)]
+    pub nft: Option<NftItem>,
+    /// Set on calls made from a contract ABI, `to_address` being the contract
+    /// and `value` the wei sent along.
+    #[serde(default)]
+    pub call: Option<ContractCall>,
+    /// ENS name the recipient was entered as, `to_address` being what it
+    /// resolved to when sent.
+    #[serde(default)]
+    pub recipient_name: Option<String>,
 }
 
 #[cfg(test)]
@@ -315,6 +695,7 @@ mod tests {
         repo.insert(NewTx {
             wallet_name: "alice".into(),
             chain: BlockChain::Bitcoin,
+            network: "regtest".into(),
             account_index: 0,
             tx_hash: "deadbeef".into(),
             direction: TxDirection::Outgoing,
@@ -334,6 +715,7 @@ mod tests {
                 wallet_name: "alice".into(),
                 chain: BlockChain::Bitcoin,
                 account_index: 0,
+                network: None,
                 limit: None,
             })
             .unwrap();
@@ -352,6 +734,7 @@ mod tests {
         let base = NewTx {
             wallet_name: "alice".into(),
             chain: BlockChain::Bitcoin,
+            network: "regtest".into(),
             account_index: 0,
             tx_hash: "0xabc".into(),
             direction: TxDirection::Outgoing,
@@ -369,6 +752,221 @@ mod tests {
         assert!(err.contains("already recorded"));
     }
 
+    #[test]
+    fn mark_replaced_links_the_replacement() {
+        let repo = TxRepository::new(make_pool());
+        let chain_data = EthChainData {
+            nonce: 3,
+            token_address: "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE".into(),
+            token_symbol: "ETH".into(),
+            token_decimals: 18,
+            max_fee_per_gas: "20".into(),
+            max_priority_fee_per_gas: "2".into(),
+            gas_limit: "21000".into(),
+            value: Some("1".into()),
+            replaces: None,
+            replaced_by: None,
+            spender: None,
+            nft: None,
+            call: None,
+            recipient_name: None,
+        };
+        repo.insert(NewTx {
+            wallet_name: "alice".into(),
+            chain: BlockChain::Ethereum,
+            network: "mainnet".into(),
+            account_index: 0,
+            tx_hash: "0xold".into(),
+            direction: TxDirection::Outgoing,
+            status: TxStatus::Pending,
+            from_address: None,
+            to_address: None,
+            amount: 1,
+            fee: None,
+            block_height: None,
+            chain_data: serde_json::to_value(chain_data).unwrap(),
+            created_at: 1,
+        })
+        .unwrap();
+
+        let updated = repo
+            .mark_replaced("alice", BlockChain::Ethereum, "0xold", "0xnew")
+            .unwrap();
+        assert_eq!(updated, 1);
+        let old = repo
+            .get("alice", BlockChain::Ethereum, "0xold")
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.status, TxStatus::Replaced);
+        let data: EthChainData = serde_json::from_str(&old.chain_data).unwrap();
+        assert_eq!(data.replaced_by.as_deref(), Some("0xnew"));
+        assert_eq!(data.nonce, 3);
+        assert!(
+            repo.mark_replaced("alice", BlockChain::Ethereum, "0xmissing", "0xnew")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn upsert_matches_the_sent_row_and_adds_new_transfers() {
+        const USDT: &str = "0xdAC17F958D2ee523a2206206994597C13D831ec7";
+        let repo = TxRepository::new(make_pool());
+        let transfer = |status: TxStatus, token: &str| NewTx {
+            wallet_name: "alice".into(),
+            chain: BlockChain::Ethereum,
+            network: "mainnet".into(),
+            account_index: 0,
+            tx_hash: "0xabc".into(),
+            direction: TxDirection::Outgoing,
+            status,
+            from_address: Some("0xa".into()),
+            to_address: Some("0xb".into()),
+            amount: 5,
+            fee: None,
+            block_height: (status == TxStatus::Confirmed).then_some(42),
+            chain_data: serde_json::to_value(EthChainData {
+                nonce: 0,
+                token_address: token.into(),
+                token_symbol: "USDT".into(),
+                token_decimals: 6,
+                max_fee_per_gas: String::new(),
+                max_priority_fee_per_gas: String::new(),
+                gas_limit: String::new(),
+                value: Some("5".into()),
+                replaces: None,
+                replaced_by: None,
+                spender: None,
+                nft: None,
+                call: None,
+                recipient_name: None,
+            })
+            .unwrap(),
+            created_at: 7,
+        };
+        let rows = || {
+            repo.list(&TxQuery {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Ethereum,
+                account_index: 0,
+                network: None,
+                limit: None,
+            })
+            .unwrap()
+        };
+        repo.insert(transfer(TxStatus::Pending, USDT)).unwrap();
+
+        // The log of the transfer we sent confirms the row written on sending.
+        let lowercase = USDT.to_lowercase();
+        assert!(
+            !repo
+                .upsert_transfer(
+                    transfer(TxStatus::Confirmed, &lowercase),
+                    TransferKey::Log(3)
+                )
+                .unwrap()
+        );
+        let sent = rows();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].status, TxStatus::Confirmed);
+        assert_eq!(sent[0].log_index, Some(3));
+        assert_eq!(sent[0].block_height.as_deref(), Some("42"));
+        assert!(sent[0].confirmed_at.is_some());
+
+        // Scanning the same range again changes nothing.
+        assert!(
+            !repo
+                .upsert_transfer(transfer(TxStatus::Confirmed, USDT), TransferKey::Log(3))
+                .unwrap()
+        );
+        // A second transfer in the same transaction gets its own row.
+        assert!(
+            repo.upsert_transfer(transfer(TxStatus::Confirmed, USDT), TransferKey::Log(4))
+                .unwrap()
+        );
+        assert_eq!(rows().len(), 2);
+
+        // So does each internal transfer, keyed apart from the logs.
+        for trace in [0, 1] {
+            assert!(
+                repo.upsert_transfer(
+                    transfer(TxStatus::Confirmed, USDT),
+                    TransferKey::Trace(trace)
+                )
+                .unwrap()
+            );
+        }
+        assert!(
+            !repo
+                .upsert_transfer(transfer(TxStatus::Confirmed, USDT), TransferKey::Trace(1))
+                .unwrap()
+        );
+        let all = rows();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all.iter().filter(|r| r.log_index.is_none()).count(), 2);
+    }
+
+    #[test]
+    fn pending_nonces_of_one_sender_and_network() {
+        let repo = TxRepository::new(make_pool());
+        let insert = |hash: &str, network: &str, from: &str, nonce: u64, status: TxStatus| {
+            let chain_data = EthChainData {
+                nonce,
+                token_address: "0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE".into(),
+                token_symbol: "ETH".into(),
+                token_decimals: 18,
+                max_fee_per_gas: "20".into(),
+                max_priority_fee_per_gas: "2".into(),
+                gas_limit: "21000".into(),
+                value: None,
+                replaces: None,
+                replaced_by: None,
+                spender: None,
+                nft: None,
+                call: None,
+                recipient_name: None,
+            };
+            repo.insert(NewTx {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Ethereum,
+                network: network.into(),
+                account_index: 0,
+                tx_hash: hash.into(),
+                direction: TxDirection::Outgoing,
+                status,
+                from_address: Some(from.into()),
+                to_address: None,
+                amount: 1,
+                fee: None,
+                block_height: None,
+                chain_data: serde_json::to_value(chain_data).unwrap(),
+                created_at: 1,
+            })
+            .unwrap();
+        };
+        insert("0x1", "mainnet", "0xa", 4, TxStatus::Pending);
+        insert("0x2", "mainnet", "0xa", 5, TxStatus::Pending);
+        insert("0x3", "mainnet", "0xa", 3, TxStatus::Confirmed);
+        insert("0x4", "base", "0xa", 9, TxStatus::Pending);
+        insert("0x5", "mainnet", "0xb", 7, TxStatus::Pending);
+
+        let mut nonces = repo.pending_eth_nonces("alice", "mainnet", "0xa").unwrap();
+        nonces.sort_unstable();
+        assert_eq!(nonces, vec![4, 5]);
+        let mut transfers = repo
+            .pending_eth_transfers("alice", "mainnet", "0xa")
+            .unwrap();
+        transfers.sort_unstable();
+        assert_eq!(
+            transfers,
+            vec![(4, "0x1".to_string()), (5, "0x2".to_string())]
+        );
+        assert!(
+            repo.pending_eth_nonces("bob", "mainnet", "0xa")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn update_status_marks_confirmed() {
         let chain = BlockChain::Ethereum;
@@ -377,6 +975,7 @@ mod tests {
         repo.insert(NewTx {
             wallet_name: "alice".into(),
             chain,
+            network: "mainnet".into(),
             account_index,
             tx_hash: "0xdef".into(),
             direction: TxDirection::Outgoing,
@@ -406,6 +1005,7 @@ mod tests {
                 wallet_name: "alice".into(),
                 chain,
                 account_index,
+                network: None,
                 limit: None,
             })
             .unwrap();
@@ -415,5 +1015,177 @@ mod tests {
             rows[0].confirmed_at,
             Some("1970-01-01T00:03:20+00:00".into())
         );
+        // Already confirmed rows are left alone.
+        assert!(!repo.confirm_pending("alice", chain, "0xdef", 456).unwrap());
+    }
+
+    #[test]
+    fn list_is_scoped_to_the_requested_network() {
+        let repo = TxRepository::new(make_pool());
+        for (hash, network) in [("0x1", "mainnet"), ("0x2", "base"), ("0x3", "base")] {
+            repo.insert(NewTx {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Ethereum,
+                network: network.into(),
+                account_index: 0,
+                tx_hash: hash.into(),
+                direction: TxDirection::Outgoing,
+                status: TxStatus::Pending,
+                from_address: None,
+                to_address: None,
+                amount: 1,
+                fee: None,
+                block_height: None,
+                chain_data: serde_json::Value::Object(Default::default()),
+                created_at: 1,
+            })
+            .unwrap();
+        }
+
+        let query = |network: Option<&str>| TxQuery {
+            wallet_name: "alice".into(),
+            chain: BlockChain::Ethereum,
+            account_index: 0,
+            network: network.map(Into::into),
+            limit: None,
+        };
+        let base = repo.list(&query(Some("base"))).unwrap();
+        assert_eq!(base.len(), 2);
+        assert!(base.iter().all(|r| r.network == "base"));
+        assert_eq!(repo.list(&query(Some("mainnet"))).unwrap().len(), 1);
+        assert_eq!(repo.list(&query(None)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn rows_without_a_network_are_backfilled() {
+        let repo = TxRepository::new(make_pool());
+        for (hash, network) in [("a", ""), ("b", "signet"), ("c", "bitcoin")] {
+            repo.insert(NewTx {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Bitcoin,
+                network: network.into(),
+                account_index: 0,
+                tx_hash: hash.into(),
+                direction: TxDirection::Incoming,
+                status: TxStatus::Pending,
+                from_address: None,
+                to_address: None,
+                amount: 1,
+                fee: None,
+                block_height: None,
+                chain_data: serde_json::Value::Object(Default::default()),
+                created_at: 1,
+            })
+            .unwrap();
+        }
+
+        let signet = || {
+            let rows = repo
+                .list(&TxQuery {
+                    wallet_name: "alice".into(),
+                    chain: BlockChain::Bitcoin,
+                    account_index: 0,
+                    network: Some("signet".into()),
+                    limit: None,
+                })
+                .unwrap();
+            let mut hashes: Vec<_> = rows.into_iter().map(|r| r.tx_hash).collect();
+            hashes.sort();
+            hashes
+        };
+        assert_eq!(signet(), ["b"]);
+
+        assert_eq!(repo.backfill_network(BlockChain::Ethereum, "signet"), Ok(0));
+        assert_eq!(repo.backfill_network(BlockChain::Bitcoin, "signet"), Ok(1));
+        assert_eq!(signet(), ["a", "b"]);
+    }
+
+    #[test]
+    fn rollback_moves_reorged_txs_back_to_pending() {
+        let repo = TxRepository::new(make_pool());
+        for (hash, height) in [("a", 100), ("b", 105)] {
+            repo.insert(NewTx {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Bitcoin,
+                network: "regtest".into(),
+                account_index: 0,
+                tx_hash: hash.into(),
+                direction: TxDirection::Incoming,
+                status: TxStatus::Confirmed,
+                from_address: None,
+                to_address: None,
+                amount: 1,
+                fee: None,
+                block_height: Some(height),
+                chain_data: serde_json::Value::Object(Default::default()),
+                created_at: 1,
+            })
+            .unwrap();
+        }
+
+        let rolled_back = repo
+            .rollback_from_height("alice", BlockChain::Bitcoin, 103)
+            .unwrap();
+        assert_eq!(rolled_back, vec!["b".to_string()]);
+        let rows = repo
+            .list(&TxQuery {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Bitcoin,
+                account_index: 0,
+                network: None,
+                limit: None,
+            })
+            .unwrap();
+        let b = rows.iter().find(|r| r.tx_hash == "b").unwrap();
+        assert_eq!(b.status, TxStatus::Pending);
+        assert_eq!(b.block_height, None);
+        let a = rows.iter().find(|r| r.tx_hash == "a").unwrap();
+        assert_eq!(a.status, TxStatus::Confirmed);
+    }
+
+    #[test]
+    fn finds_cpfp_children_of_a_parent() {
+        let repo = TxRepository::new(make_pool());
+        for (hash, parent) in [("parent", None), ("child", Some("parent")), ("other", None)] {
+            repo.insert(NewTx {
+                wallet_name: "alice".into(),
+                chain: BlockChain::Bitcoin,
+                network: "regtest".into(),
+                account_index: 0,
+                tx_hash: hash.into(),
+                direction: TxDirection::SelfTransfer,
+                status: TxStatus::Pending,
+                from_address: None,
+                to_address: None,
+                amount: 0,
+                fee: None,
+                block_height: None,
+                chain_data: serde_json::to_value(BtcChainData {
+                    vsize: None,
+                    rbf: true,
+                    parent_tx_id: parent.map(Into::into),
+                    change_value_sat: None,
+                })
+                .unwrap(),
+                created_at: 1,
+            })
+            .unwrap();
+        }
+
+        assert_eq!(
+            repo.btc_children("alice", "parent").unwrap(),
+            vec!["child".to_string()]
+        );
+        assert!(repo.btc_children("alice", "child").unwrap().is_empty());
+        let child = repo
+            .get("alice", BlockChain::Bitcoin, "child")
+            .unwrap()
+            .unwrap();
+        assert_eq!(child.direction, TxDirection::SelfTransfer);
+        assert!(
+            repo.get("alice", BlockChain::Bitcoin, "missing")
+                .unwrap()
+                .is_none()
+        );
     }
 }
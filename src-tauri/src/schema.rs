@@ -1,8 +1,50 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
-    transactions (tx_hash) {
+    contract_abis (network, address) {
+        network -> Text,
+        address -> Text,
+        name -> Text,
+        abi -> Text,
+        updated_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    nft_tokens (wallet_name, network, owner, collection, token_id) {
+        wallet_name -> Text,
+        network -> Text,
+        owner -> Text,
+        collection -> Text,
+        token_id -> Text,
+    }
+}
+
+diesel::table! {
+    sync_checkpoints (wallet_name, chain, network, address, scope) {
+        wallet_name -> Text,
+        chain -> Text,
+        network -> Text,
+        address -> Text,
+        scope -> Text,
+        block_height -> BigInt,
+    }
+}
+
+diesel::table! {
+    token_approvals (wallet_name, network, owner, token, spender) {
+        wallet_name -> Text,
+        network -> Text,
+        owner -> Text,
+        token -> Text,
+        spender -> Text,
+    }
+}
+
+diesel::table! {
+    transactions (wallet_name, chain, account_index, tx_hash, log_index) {
         tx_hash -> Text,
+        log_index -> BigInt,
         wallet_name -> Text,
         chain -> Text,
         account_index -> Integer,
@@ -16,5 +58,14 @@ diesel::table! {
         chain_data -> Text,
         created_at -> BigInt,
         confirmed_at -> Nullable<BigInt>,
+        network -> Text,
     }
 }
+
+diesel::allow_tables_to_appear_in_same_query!(
+    contract_abis,
+    nft_tokens,
+    sync_checkpoints,
+    token_approvals,
+    transactions,
+);
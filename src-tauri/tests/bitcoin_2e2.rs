@@ -8,6 +8,7 @@ use satellion_lib::{
     chain::btc::{
         self,
         account::UtxoSelectionStrategy,
+        config::BtcNetwork,
         dtos::OutPointRef,
         key_derivation::{Change, KeyDerivationPath, Proposal},
         tx_builder::{BuildPsbtParams, build_psbt},
@@ -30,7 +31,7 @@ async fn bitcon_e2e() -> Result<(), Box<dyn Error>> {
     let sk = SessionKeeper::new(None, None);
 
     let mut config = Config::new();
-    config.btc.regtest = true;
+    config.btc.network = BtcNetwork::Regtest;
 
     let wallet = Wallet::new(
         config.clone(),
@@ -50,7 +51,7 @@ async fn bitcon_e2e() -> Result<(), Box<dyn Error>> {
     let prk = wallet.btc.prk()?;
     let network = wallet.config.btc.network();
 
-    let account_info = account.info(&prk, network)?;
+    let account_info = account.info(&prk, network, &wallet.btc.tip, None)?;
     let key_derive_path = KeyDerivationPath::new(
         Proposal::Taproot,
         network,
@@ -88,7 +89,7 @@ async fn bitcon_e2e() -> Result<(), Box<dyn Error>> {
     let send_value_sat = 3010;
     let build_res = build_psbt(&BuildPsbtParams {
         send_value_sat,
-        recipient: recipient.clone(),
+        recipient: recipient.clone().into(),
         utxo_selection_method: UtxoSelectionStrategy::Manual(vec![OutPointRef {
             tx_id: utxo.tx_id.to_string(),
             vout: utxo.vout,
@@ -0,0 +1,180 @@
+#[allow(dead_code)]
+mod bitcoind;
+
+use std::{collections::HashMap, error::Error};
+
+use bitcoin::{Block, OutPoint, Transaction, consensus::encode::deserialize_hex};
+use satellion_lib::{
+    chain::btc::{
+        account::UtxoSelectionStrategy,
+        config::BtcNetwork,
+        dtos::OutPointRef,
+        key_derivation::{Change, KeyDerivationPath, Proposal},
+        silent_payments::{self, BlockScanData, SilentPaymentKeys, TweakSource},
+        tx_builder::{BuildPsbtParams, Recipient, build_psbt, sign_psbt},
+    },
+    chain_trait::SecureKey,
+    config::Config,
+    mnemonic::TEST_MNEMONIC,
+    session::{Session, SessionKeeper},
+    utils,
+    wallet::Wallet,
+};
+use serde_json::json;
+
+use crate::bitcoind::BitcoindHarness;
+
+/// Stand-in for a tweak index: computes `input_hash·A` for every block
+/// straight from the regtest node.
+struct LocalTweakIndex {
+    tip: u32,
+    blocks: HashMap<u32, BlockScanData>,
+}
+
+impl LocalTweakIndex {
+    fn build(node: &BitcoindHarness, from: u32) -> Result<Self, Box<dyn Error>> {
+        let client = node.client();
+        let tip = client
+            .call::<serde_json::Value>("getblockcount", &[])?
+            .as_u64()
+            .ok_or("invalid block count")? as u32;
+
+        let fetch_tx = |txid: String| -> Result<Transaction, Box<dyn Error>> {
+            let hex = client.call::<String>("getrawtransaction", &[json!(txid)])?;
+            Ok(deserialize_hex(&hex)?)
+        };
+
+        let mut blocks = HashMap::new();
+        for height in from..=tip {
+            let hash = client.call::<String>("getblockhash", &[json!(height)])?;
+            let hex = client.call::<String>("getblock", &[json!(hash), json!(0)])?;
+            let block: Block = deserialize_hex(&hex)?;
+
+            let mut data = BlockScanData {
+                height,
+                ..Default::default()
+            };
+            for tx in block.txdata.iter().filter(|tx| !tx.is_coinbase()) {
+                let mut input_keys = Vec::new();
+                for txin in &tx.input {
+                    let prev = fetch_tx(txin.previous_output.txid.to_string())?;
+                    let prevout = &prev.output[txin.previous_output.vout as usize];
+                    input_keys.extend(silent_payments::input_public_key(txin, prevout));
+                }
+                if input_keys.is_empty() {
+                    continue;
+                }
+                let outpoints: Vec<OutPoint> = tx.input.iter().map(|i| i.previous_output).collect();
+                data.tweaks
+                    .push(silent_payments::compute_tweak(&outpoints, &input_keys)?);
+
+                let txid = tx.compute_txid();
+                data.outputs.extend(
+                    tx.output
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, o)| o.script_pubkey.is_p2tr())
+                        .map(|(vout, o)| {
+                            (
+                                OutPoint {
+                                    txid,
+                                    vout: vout as u32,
+                                },
+                                o.clone(),
+                            )
+                        }),
+                );
+            }
+            blocks.insert(height, data);
+        }
+        Ok(Self { tip, blocks })
+    }
+}
+
+impl TweakSource for LocalTweakIndex {
+    async fn tip_height(&self) -> Result<u32, String> {
+        Ok(self.tip)
+    }
+
+    async fn block_scan_data(&self, height: u32) -> Result<BlockScanData, String> {
+        Ok(self.blocks.get(&height).cloned().unwrap_or(BlockScanData {
+            height,
+            ..Default::default()
+        }))
+    }
+}
+
+#[tokio::test]
+async fn silent_payment_roundtrip() -> Result<(), Box<dyn Error>> {
+    utils::tracing::init_test("debug");
+    let local_node = BitcoindHarness::start()?;
+    let sk = SessionKeeper::new(None, None);
+
+    let mut config = Config::new();
+    config.btc.network = BtcNetwork::Regtest;
+
+    let wallet = Wallet::new(
+        config.clone(),
+        "test".to_string(),
+        TEST_MNEMONIC.to_string(),
+        "1111".to_string(),
+        None,
+    )
+    .unwrap();
+    sk.lock()
+        .await
+        .set(Session::new(wallet, std::time::Duration::from_secs(300)));
+
+    let mut sk = sk.lock().await;
+    let wallet = sk.wallet()?;
+    let prk = wallet.btc.prk()?;
+    let network = wallet.config.btc.network();
+    let account = wallet.btc.active_account()?;
+    let main_path = KeyDerivationPath::new(
+        Proposal::Taproot,
+        network,
+        account.index,
+        Change::External,
+        0,
+    );
+    let main_address = account.info(&prk, network, &wallet.btc.tip, None)?.address;
+
+    local_node.fund_wallet()?;
+    local_node.send_and_confirm(&main_address, 0.5)?;
+    let utxos = local_node.scanutxoset(main_address, &main_path)?;
+    let utxo = utxos[0].clone();
+    wallet
+        .btc
+        .get_active_account_mut()?
+        .utxo_set
+        .replace_all(utxos);
+
+    // Pay our own static payment code from the taproot main address.
+    let keys = SilentPaymentKeys::derive(prk.expose(), network, 0)?;
+    let send_value_sat = 120_000;
+    let built = build_psbt(&BuildPsbtParams {
+        send_value_sat,
+        recipient: Recipient::parse(&keys.address().to_string(), network)?,
+        utxo_selection_method: UtxoSelectionStrategy::Manual(vec![OutPointRef {
+            tx_id: utxo.tx_id.to_string(),
+            vout: utxo.vout,
+        }]),
+        miner_fee_vbytes: 5.0,
+        config: config.btc,
+        account: wallet.btc.active_account()?,
+        xpriv: prk.expose(),
+    })?;
+    let tx = sign_psbt(built.psbt, &prk)?;
+    local_node.client().send_raw_transaction(&tx)?;
+    local_node.mine_blocks(1)?;
+
+    let index = LocalTweakIndex::build(&local_node, 100)?;
+    let report = silent_payments::scan(&index, &keys, 99).await?;
+
+    assert_eq!(report.scanned_to, index.tip);
+    assert_eq!(report.found.len(), 1, "exactly one silent payment output");
+    let found = &report.found[0];
+    assert_eq!(found.outpoint.txid, tx.compute_txid());
+    assert_eq!(found.output.value.to_sat(), send_value_sat);
+    Ok(())
+}
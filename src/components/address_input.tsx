@@ -36,10 +36,16 @@ export class AddressInputVM {
   }
 }
 
-export const AddressInput = observer(({ state }: { state: AddressInputVM }) => (
-  <>
+export const AddressInput = observer(
+  ({
+    state,
+    placeholder = 'Recipient address',
+  }: {
+    state: AddressInputVM
+    placeholder?: string
+  }) => (
     <Input
-      placeholder="Recipient address"
+      placeholder={placeholder}
       sx={{ width: `${Math.max(20, state.val.length + 2)}ch` }}
       value={state.val}
       onChange={e => {
@@ -48,5 +54,5 @@ export const AddressInput = observer(({ state }: { state: AddressInputVM }) => (
       }}
       error={!!state.val && !state.is_valid}
     />
-  </>
-))
+  ),
+)
@@ -9,6 +9,7 @@ export interface FieldSchema {
   description?: string
   readOnly?: boolean
   properties?: Record<string, FieldSchema>
+  items?: FieldSchema
   anyOf?: FieldSchema[]
   allOf?: FieldSchema[]
   $ref?: string
@@ -114,6 +115,26 @@ const ConfigField = observer(
       )
     }
 
+    if (type === 'array' && resolved.items && Array.isArray(value)) {
+      const items = resolved.items
+      return (
+        <Section title={label}>
+          {value.map((item, i) => (
+            <ConfigField
+              key={i}
+              name={`${label} ${i + 1}`}
+              schema={items}
+              value={item}
+              root={root}
+              onChangePath={(subPath, v) =>
+                onChangePath([String(i), ...subPath], v)
+              }
+            />
+          ))}
+        </Section>
+      )
+    }
+
     if (resolved.readOnly && type === 'boolean') {
       if (!value) return null
       return (
@@ -1,12 +1,67 @@
+import { Alert, Stack } from '@mui/joy'
 import { openUrl } from '@tauri-apps/plugin-opener'
-import { B } from '../../../shortcuts'
+import type {
+  SimulationResult,
+  TransferEstimation,
+} from '../../../bindings/eth'
+import { B, P, Row } from '../../../shortcuts'
 import { explorer_endpoint } from '../constants'
 
-export const OpenExplorerButton = (props: { path: string }) => (
+export const OpenExplorerButton = (props: { path?: string; url?: string }) => (
   <B
     variant="soft"
-    onClick={() => openUrl(`${explorer_endpoint}/${props.path}`)}
+    onClick={() => openUrl(props.url ?? `${explorer_endpoint}/${props.path}`)}
   >
     Open explorer
   </B>
 )
+
+/** The address an ENS name resolved to, to be checked before sending. */
+export const RecipientNotice = ({
+  estimation,
+}: {
+  estimation?: TransferEstimation
+}) => {
+  if (!estimation?.recipient_name) return null
+  return (
+    <Stack gap={0.5}>
+      <Alert color="neutral">
+        {estimation.recipient_name} resolves to {estimation.recipient}
+      </Alert>
+      {estimation.recipient_warning && (
+        <Alert color="warning">{estimation.recipient_warning}</Alert>
+      )}
+    </Stack>
+  )
+}
+
+export const SimulationNotice = ({
+  simulation,
+}: {
+  simulation?: SimulationResult | null
+}) => {
+  if (!simulation) return null
+  return (
+    <Stack gap={0.5}>
+      {simulation.revert_reason && (
+        <Alert color="danger">
+          The transaction would fail: {simulation.revert_reason}. The network
+          fee is paid all the same.
+        </Alert>
+      )}
+      {simulation.warnings.map(w => (
+        <Alert key={w} color="warning">
+          {w}
+        </Alert>
+      ))}
+      {simulation.balance_changes?.map(c => (
+        <Row key={`${c.address}:${c.token_address}`}>
+          <P>{c.is_sender ? 'You' : 'Recipient'}</P>
+          <P color={c.amount.startsWith('-') ? 'danger' : 'success'}>
+            {c.amount} {c.symbol}
+          </P>
+        </Row>
+      ))}
+    </Stack>
+  )
+}
@@ -0,0 +1,121 @@
+import { Card, Chip, Stack, ToggleButtonGroup } from '@mui/joy'
+import { observer } from 'mobx-react-lite'
+import type { FeeMode, TokenApproval } from '../../../bindings/eth'
+import { CompactSrt } from '../../../components/compact_str'
+import { SendTxButton } from '../../../components/send_tx_button'
+import { B, FullScreenModal, P, Row } from '../../../shortcuts'
+import { root_store } from '../../../view_model/root'
+import { OpenExplorerButton, SimulationNotice } from '../utils/shared'
+
+export const ApprovalsModal = observer(() => {
+  const state = root_store.wallet.eth.approvals
+  return (
+    <FullScreenModal open={state.is_open} onClose={() => state.set_open(false)}>
+      <Approvals />
+    </FullScreenModal>
+  )
+})
+
+const Approvals = observer(() => {
+  const state = root_store.wallet.eth.approvals
+  const approvals = state.list.data?.approvals
+  const scanned_from = state.list.data?.scanned_from
+  return (
+    <Stack gap={1}>
+      <P level="h3" color="primary">
+        Token approvals
+      </P>
+      <Row>
+        <FeeModeSelect />
+        <B
+          variant="soft"
+          loading={state.list.loading}
+          onClick={() => state.load()}
+        >
+          Refresh
+        </B>
+      </Row>
+      {scanned_from && (
+        <Row>
+          <P color="warning">
+            Approvals granted before block {scanned_from} were not scanned
+          </P>
+          <B
+            variant="plain"
+            loading={state.scanning_older}
+            onClick={() => state.scan_older()}
+          >
+            Scan older
+          </B>
+        </Row>
+      )}
+      {approvals?.length === 0 && <P>No active approvals</P>}
+      {approvals?.map(a => (
+        <ApprovalCard key={`${a.token_address}:${a.spender}`} approval={a} />
+      ))}
+    </Stack>
+  )
+})
+
+const ApprovalCard = observer(({ approval }: { approval: TokenApproval }) => {
+  const state = root_store.wallet.eth.approvals
+  const revoking = state.is_revoking(approval)
+  return (
+    <Card size="sm" variant="soft">
+      <Row>
+        <P fontWeight={600}>{approval.token_symbol}</P>
+        {approval.error ? (
+          <Chip color="warning">Unknown allowance</Chip>
+        ) : approval.unlimited ? (
+          <Chip color="danger">Unlimited</Chip>
+        ) : (
+          <P>{approval.allowance}</P>
+        )}
+      </Row>
+      {approval.error && <P color="warning">{approval.error}</P>}
+      <Row>
+        <P>Spender</P>
+        <CompactSrt val={approval.spender} copy />
+        {approval.spender_url && (
+          <OpenExplorerButton url={approval.spender_url} />
+        )}
+      </Row>
+      {revoking && state.estimation ? (
+        <Stack gap={1}>
+          <P>
+            Network fee: {state.estimation.fee_ceiling} gwei ~ $
+            {state.estimation.fee_in_usd.toFixed(2)}
+          </P>
+          <SimulationNotice simulation={state.estimation.simulation} />
+          <SendTxButton loading={state.sending} onSend={() => state.revoke()}>
+            Hold to revoke
+          </SendTxButton>
+        </Stack>
+      ) : (
+        <B
+          color="danger"
+          variant="outlined"
+          loading={revoking && state.is_estimating}
+          disabled={state.is_estimating}
+          onClick={() => state.estimate_revoke(approval)}
+        >
+          Revoke
+        </B>
+      )}
+    </Card>
+  )
+})
+
+const FeeModeSelect = observer(() => {
+  const state = root_store.wallet.eth.approvals
+  return (
+    <ToggleButtonGroup
+      value={state.fee_mode}
+      onChange={(_, v) => v && state.set_fee_mode(v)}
+    >
+      <B value={'Minimal' satisfies FeeMode}>Slow</B>
+      <B value={'Standard' satisfies FeeMode}>Standart</B>
+      <B value={'Increased' satisfies FeeMode}>Fast</B>
+    </ToggleButtonGroup>
+  )
+})
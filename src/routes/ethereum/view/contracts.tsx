@@ -0,0 +1,263 @@
+import { Remove } from '@mui/icons-material'
+import {
+  Card,
+  Chip,
+  IconButton,
+  Input,
+  Stack,
+  Textarea,
+  ToggleButtonGroup,
+  Tooltip,
+} from '@mui/joy'
+import { observer } from 'mobx-react-lite'
+import type {
+  AbiFunctionView,
+  ContractView,
+  FeeMode,
+} from '../../../bindings/eth'
+import { CompactSrt } from '../../../components/compact_str'
+import { SendTxButton } from '../../../components/send_tx_button'
+import { B, FullScreenModal, P, Progress, Row } from '../../../shortcuts'
+import { root_store } from '../../../view_model/root'
+import { OpenExplorerButton, SimulationNotice } from '../utils/shared'
+
+export const ContractsModal = observer(() => {
+  const state = root_store.wallet.eth.contracts
+  return (
+    <FullScreenModal open={state.is_open} onClose={() => state.set_open(false)}>
+      {state.contract ? (
+        <Contract contract={state.contract} />
+      ) : (
+        <Contracts />
+      )}
+    </FullScreenModal>
+  )
+})
+
+const Contracts = observer(() => {
+  const state = root_store.wallet.eth.contracts
+  const contracts = state.list.data
+  return (
+    <Stack gap={1}>
+      <P level="h3" color="primary">
+        Contracts
+      </P>
+      {state.list.loading && <Progress />}
+      {contracts?.length === 0 && <P color="neutral">No contracts saved</P>}
+      {contracts?.map(c => (
+        <Row key={c.address} alignItems="center">
+          <B size="sm" variant="plain" onClick={() => state.open(c.address)}>
+            {c.name}
+          </B>
+          <CompactSrt val={c.address} copy />
+          <Tooltip title="Forget ABI" size="sm">
+            <IconButton size="sm" onClick={() => state.remove(c.address)}>
+              <Remove />
+            </IconButton>
+          </Tooltip>
+        </Row>
+      ))}
+      <AddContract />
+    </Stack>
+  )
+})
+
+const AddContract = observer(() => {
+  const state = root_store.wallet.eth.contracts
+  return (
+    <Stack gap={1}>
+      <P level="title-md">Add contract</P>
+      <Input
+        placeholder="Contract address"
+        value={state.address}
+        onChange={e => state.set_address(e.target.value)}
+      />
+      <Input
+        placeholder="Name"
+        value={state.name}
+        onChange={e => state.set_name(e.target.value)}
+      />
+      <Textarea
+        minRows={4}
+        maxRows={12}
+        placeholder="ABI JSON, or a compiler artifact holding it"
+        value={state.abi}
+        onChange={e => state.set_abi(e.target.value)}
+        sx={{ fontFamily: 'monospace' }}
+      />
+      <Row>
+        <B variant="soft" component="label">
+          Open file
+          <input
+            type="file"
+            accept=".json,application/json"
+            hidden
+            onChange={e => {
+              const file = e.target.files?.[0]
+              if (file) state.load_file(file)
+              e.target.value = ''
+            }}
+          />
+        </B>
+        <B
+          loading={state.saving}
+          disabled={state.address.length < 40 || !state.abi}
+          onClick={() => state.save()}
+        >
+          Save
+        </B>
+      </Row>
+    </Stack>
+  )
+})
+
+const Contract = observer(({ contract }: { contract: ContractView }) => {
+  const state = root_store.wallet.eth.contracts
+  const reads = contract.functions.filter(f => f.kind === 'Read')
+  const writes = contract.functions.filter(f => f.kind !== 'Read')
+  return (
+    <Stack gap={1}>
+      <Row alignItems="center">
+        <B variant="plain" onClick={() => state.close_contract()}>
+          Back
+        </B>
+        <P level="h3" color="primary">
+          {contract.name}
+        </P>
+        <CompactSrt val={contract.address} copy />
+      </Row>
+      <Functions title="Read" functions={reads} />
+      <Functions title="Write" functions={writes} />
+      <Call address={contract.address} />
+    </Stack>
+  )
+})
+
+const Functions = observer(
+  ({ title, functions }: { title: string; functions: AbiFunctionView[] }) => {
+    const call = root_store.wallet.eth.contracts.call
+    if (!functions.length) return null
+    return (
+      <Stack gap={0.5}>
+        <P level="title-md">{title}</P>
+        <Row sx={{ flexWrap: 'wrap' }}>
+          {functions.map(f => (
+            <Tooltip key={f.signature} title={f.signature} size="sm">
+              <B
+                size="sm"
+                variant={
+                  call.function?.signature === f.signature ? 'solid' : 'soft'
+                }
+                onClick={() => call.select(f)}
+              >
+                {f.name}
+              </B>
+            </Tooltip>
+          ))}
+        </Row>
+      </Stack>
+    )
+  },
+)
+
+const Call = observer(({ address }: { address: string }) => {
+  const call = root_store.wallet.eth.contracts.call
+  const f = call.function
+  if (!f) return null
+  return (
+    <Card size="sm" variant="soft">
+      <Row alignItems="center">
+        <P sx={{ fontFamily: 'monospace' }}>{f.signature}</P>
+        {f.kind === 'Payable' && <Chip color="warning">Payable</Chip>}
+      </Row>
+      {f.inputs.map((param, i) => (
+        <Input
+          key={`${f.signature}:${i}`}
+          placeholder={`${param.name || `argument ${i + 1}`} (${param.ty})`}
+          value={call.args[i]}
+          onChange={e => call.set_arg(i, e.target.value)}
+        />
+      ))}
+      {f.kind === 'Payable' && (
+        <Input
+          placeholder="ETH to send"
+          inputMode="decimal"
+          value={call.value}
+          onChange={e => call.set_value(e.target.value)}
+        />
+      )}
+      {f.kind === 'Read' ? (
+        <ReadCall address={address} />
+      ) : (
+        <WriteCall address={address} />
+      )}
+    </Card>
+  )
+})
+
+const ReadCall = observer(({ address }: { address: string }) => {
+  const call = root_store.wallet.eth.contracts.call
+  return (
+    <Stack gap={1}>
+      <Row>
+        <B loading={call.reading} onClick={() => call.read(address)}>
+          Call
+        </B>
+      </Row>
+      {call.result?.map((v, i) => (
+        <Row key={`${v.name}:${i}`} sx={{ minWidth: 0 }}>
+          <P fontWeight={500}>{v.name || v.ty}</P>
+          <P sx={{ fontFamily: 'monospace', wordBreak: 'break-all' }}>
+            {v.value}
+          </P>
+        </Row>
+      ))}
+    </Stack>
+  )
+})
+
+const WriteCall = observer(({ address }: { address: string }) => {
+  const call = root_store.wallet.eth.contracts.call
+  return (
+    <Stack gap={1}>
+      <ToggleButtonGroup
+        value={call.fee_mode}
+        onChange={(_, v) => v && call.set_fee_mode(v)}
+      >
+        <B value={'Minimal' satisfies FeeMode}>Slow</B>
+        <B value={'Standard' satisfies FeeMode}>Standart</B>
+        <B value={'Increased' satisfies FeeMode}>Fast</B>
+      </ToggleButtonGroup>
+      <Row>
+        <B loading={call.is_estimating} onClick={() => call.estimate(address)}>
+          Estimate
+        </B>
+      </Row>
+      {call.estimation && (
+        <P>
+          Network fee: {call.estimation.fee_ceiling} gwei ~ $
+          {call.estimation.fee_in_usd.toFixed(2)}
+        </P>
+      )}
+      <SimulationNotice simulation={call.estimation?.simulation} />
+      {call.tx_hash ? (
+        <Stack>
+          <P>
+            Transaction hash <b>{call.tx_hash}</b>
+          </P>
+          <Row>
+            {call.explorer_url && (
+              <OpenExplorerButton url={call.explorer_url} />
+            )}
+          </Row>
+        </Stack>
+      ) : (
+        call.estimation && (
+          <SendTxButton loading={call.sending} onSend={() => call.execute()}>
+            Hold to send
+          </SendTxButton>
+        )
+      )}
+    </Stack>
+  )
+})
@@ -1,4 +1,4 @@
-import { Card, Stack } from '@mui/joy'
+import { Card, Option, Select, Stack } from '@mui/joy'
 import { observer } from 'mobx-react-lite'
 import { useEffect } from 'react'
 import { useNavigate } from 'react-router'
@@ -10,7 +10,11 @@ import { route } from '../../../lib/routes'
 import { B, P, Row } from '../../../shortcuts'
 import { root_store } from '../../../view_model/root'
 import { OpenExplorerButton } from '../utils/shared'
+import { ApprovalsModal } from './approvals'
+import { ContractsModal } from './contracts'
 import { BalanceCard } from './balances'
+import { NftCard } from './nfts'
+import { SigningModal } from './signing'
 import { TransferModal } from './transfer'
 
 export const EthereumWallet = observer(() => {
@@ -31,12 +35,28 @@ export const EthereumWallet = observer(() => {
       <Navbar />
       <Row gap={3}>
         <AccountSelector vm={eth.account_selector} />
+        <Select
+          variant="plain"
+          size="sm"
+          value={eth.network}
+          onChange={(_, v) => v && eth.switch_network(v)}
+          sx={{ width: 'min-content' }}
+        >
+          {eth.networks.map(each => (
+            <Option value={each.id} key={each.id}>
+              {each.name}
+            </Option>
+          ))}
+        </Select>
       </Row>
       {eth && (
         <>
           {addr && (
             <Card size="sm" variant="soft">
               <CompactSrt sx={{ fontWeight: 600 }} val={addr} copy />
+              {eth.balance.data?.ens_name && (
+                <P level="body-sm">{eth.balance.data.ens_name}</P>
+              )}
               <Row>
                 <OpenExplorerButton path={`address/${addr}`} />
                 <B
@@ -46,12 +66,40 @@ export const EthereumWallet = observer(() => {
                 >
                   Send
                 </B>
+                <B
+                  variant="soft"
+                  onClick={() => {
+                    eth.approvals.set_open(true)
+                  }}
+                >
+                  Approvals
+                </B>
+                <B
+                  variant="soft"
+                  onClick={() => {
+                    eth.signing.set_open(true)
+                  }}
+                >
+                  Sign
+                </B>
+                <B
+                  variant="soft"
+                  onClick={() => {
+                    eth.contracts.set_open(true)
+                  }}
+                >
+                  Contracts
+                </B>
                 <TransferModal />
+                <ApprovalsModal />
+                <SigningModal />
+                <ContractsModal />
               </Row>
             </Card>
           )}
 
           <BalanceCard />
+          <NftCard />
 
           <Stack py={2}>
             <P>Ether price ${eth.usd_price}</P>
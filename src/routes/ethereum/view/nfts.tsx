@@ -0,0 +1,255 @@
+import { Remove } from '@mui/icons-material'
+import CachedIcon from '@mui/icons-material/Cached'
+import {
+  Card,
+  IconButton,
+  Input,
+  Modal,
+  ModalClose,
+  ModalDialog,
+  Stack,
+  ToggleButtonGroup,
+  Tooltip,
+} from '@mui/joy'
+import { openUrl } from '@tauri-apps/plugin-opener'
+import { observer } from 'mobx-react-lite'
+import { useState } from 'react'
+import type {
+  FeeMode,
+  NftCollectionView,
+  NftView,
+} from '../../../bindings/eth'
+import { AddressInput } from '../../../components/address_input'
+import { CompactSrt } from '../../../components/compact_str'
+import { SendTxButton } from '../../../components/send_tx_button'
+import { B, FullScreenModal, P, Progress, Row } from '../../../shortcuts'
+import { root_store } from '../../../view_model/root'
+import {
+  OpenExplorerButton,
+  RecipientNotice,
+  SimulationNotice,
+} from '../utils/shared'
+
+export const NftCard = observer(() => {
+  const { nfts } = root_store.wallet.eth
+  return (
+    <Card variant="soft" size="sm">
+      <Row alignItems="flex-start" justifyContent="space-between">
+        <Collections />
+
+        <Row alignItems="center" gap={1}>
+          <TrackCollection />
+          <IconButton onClick={() => nfts.load()} variant="plain">
+            <CachedIcon />
+          </IconButton>
+        </Row>
+      </Row>
+      <NftSendModal />
+    </Card>
+  )
+})
+
+const Collections = observer(() => {
+  const { nfts } = root_store.wallet.eth
+  const collections = nfts.collections.data
+
+  if (nfts.collections.loading) return <Progress />
+  if (!collections?.length) return <P color="neutral">No NFT collections</P>
+
+  return (
+    <Stack spacing={1} sx={{ minWidth: 0 }}>
+      {collections.map(c => (
+        <Collection key={c.address} c={c} />
+      ))}
+    </Stack>
+  )
+})
+
+const Collection = observer(({ c }: { c: NftCollectionView }) => {
+  const { nfts } = root_store.wallet.eth
+  return (
+    <Stack spacing={0.5}>
+      <Row alignItems="center">
+        <P sx={{ fontWeight: 500 }}>{c.name}</P>
+        <P level="body-xs">
+          {c.standard === 'Erc721' ? 'ERC-721' : 'ERC-1155'}
+        </P>
+        <Tooltip title="Do not track" size="sm">
+          <IconButton size="sm" onClick={() => nfts.untrack(c.address)}>
+            <Remove />
+          </IconButton>
+        </Tooltip>
+      </Row>
+      {c.error && <P color="danger">{c.error}</P>}
+      {!c.error && !c.tokens.length && <P color="neutral">None owned</P>}
+      {c.scanned_from && (
+        <Row alignItems="center">
+          <P level="body-xs" color="warning">
+            Tokens received before block {c.scanned_from} are not listed
+          </P>
+          <B
+            size="sm"
+            variant="plain"
+            loading={nfts.scanning_older === c.address}
+            onClick={() => nfts.scan_older(c.address)}
+          >
+            Scan older
+          </B>
+        </Row>
+      )}
+      {c.tokens.map(t => (
+        <Nft key={t.token_id} c={c} t={t} />
+      ))}
+    </Stack>
+  )
+})
+
+// Metadata is only linked: opening it is up to the user, as loading remote
+// documents and media would reveal the wallet to their hosts.
+const Nft = ({ c, t }: { c: NftCollectionView; t: NftView }) => (
+  <Row alignItems="center" sx={{ minWidth: 0 }}>
+    <CompactSrt val={`#${t.token_id}`} n={8} />
+    {t.amount !== '1' && <P>×{t.amount}</P>}
+    {t.metadata_uri && (
+      <Tooltip title={t.metadata_uri} size="sm">
+        <B
+          size="sm"
+          variant="plain"
+          onClick={() => t.metadata_uri && openUrl(t.metadata_uri)}
+        >
+          Metadata
+        </B>
+      </Tooltip>
+    )}
+    <B
+      size="sm"
+      variant="soft"
+      onClick={() => root_store.wallet.eth.nfts.send.open(c, t)}
+    >
+      Send
+    </B>
+  </Row>
+)
+
+const TrackCollection = observer(() => {
+  const [open, setOpen] = useState(false)
+  const [address, setAddress] = useState('')
+  const [loading, setLoading] = useState(false)
+
+  const track = async () => {
+    setLoading(true)
+    const tracked = await root_store.wallet.eth.nfts
+      .track(address)
+      .finally(() => setLoading(false))
+    if (tracked) setOpen(false)
+  }
+
+  return (
+    <>
+      <B
+        variant="plain"
+        onClick={() => {
+          setOpen(true)
+          setAddress('')
+        }}
+        sx={{ fontWeight: 400 }}
+        color="neutral"
+      >
+        Track collection
+      </B>
+
+      <Modal open={open} onClose={() => setOpen(false)}>
+        <ModalDialog sx={{ pr: 6 }}>
+          <ModalClose />
+          <Input
+            autoFocus
+            placeholder="ERC-721 or ERC-1155 contract address"
+            value={address}
+            onChange={e => setAddress(e.target.value)}
+          />
+          <B loading={loading} disabled={address.length < 40} onClick={track}>
+            Track
+          </B>
+        </ModalDialog>
+      </Modal>
+    </>
+  )
+})
+
+const NftSendModal = observer(() => {
+  const state = root_store.wallet.eth.nfts.send
+  return (
+    <FullScreenModal open={state.is_open} onClose={() => state.reset()}>
+      <NftSend />
+    </FullScreenModal>
+  )
+})
+
+const NftSend = observer(() => {
+  const state = root_store.wallet.eth.nfts.send
+  if (!state.collection || !state.token) return null
+  return (
+    <Stack gap={1}>
+      <P level="h3" color="primary">
+        Send {state.collection.name} #{state.token.token_id}
+      </P>
+      <AddressInput
+        state={state.address}
+        placeholder="Recipient address or ENS name"
+      />
+      {state.is_multi_copy && (
+        <Row>
+          <Input
+            placeholder="Copies"
+            value={state.amount}
+            inputMode="numeric"
+            error={!!state.amount_error}
+            onChange={e => state.set_amount(e.target.value)}
+          />
+          {state.amount_error && <P color="danger">{state.amount_error}</P>}
+        </Row>
+      )}
+      <ToggleButtonGroup
+        value={state.fee_mode}
+        onChange={(_, v) => v && state.set_fee_mode(v)}
+      >
+        <B value={'Minimal' satisfies FeeMode}>Slow</B>
+        <B value={'Standard' satisfies FeeMode}>Standart</B>
+        <B value={'Increased' satisfies FeeMode}>Fast</B>
+      </ToggleButtonGroup>
+      <B
+        loading={state.is_estimating}
+        disabled={state.disabled}
+        onClick={() => state.estimate()}
+      >
+        Estimate
+      </B>
+      {state.estimation && (
+        <P>
+          Network fee: {state.estimation.fee_ceiling} gwei ~ $
+          {state.estimation.fee_in_usd.toFixed(2)}
+        </P>
+      )}
+      <RecipientNotice estimation={state.estimation} />
+      <SimulationNotice simulation={state.estimation?.simulation} />
+      {state.tx_hash ? (
+        <Stack>
+          <P>
+            Transaction hash <b>{state.tx_hash}</b>
+          </P>
+          <Row>
+            {state.explorer_url && (
+              <OpenExplorerButton url={state.explorer_url} />
+            )}
+          </Row>
+        </Stack>
+      ) : (
+        state.estimation && (
+          <SendTxButton loading={state.sending} onSend={() => state.execute()}>
+            Hold to send
+          </SendTxButton>
+        )
+      )}
+    </Stack>
+  )
+})
@@ -0,0 +1,146 @@
+import { Alert, Input, Stack, Textarea, ToggleButtonGroup } from '@mui/joy'
+import { observer } from 'mobx-react-lite'
+import type { SignField, SignKind } from '../../../bindings/eth'
+import { CompactSrt } from '../../../components/compact_str'
+import { SendTxButton } from '../../../components/send_tx_button'
+import { B, FullScreenModal, P, Row } from '../../../shortcuts'
+import { root_store } from '../../../view_model/root'
+
+export const SigningModal = observer(() => {
+  const state = root_store.wallet.eth.signing
+  return (
+    <FullScreenModal open={state.is_open} onClose={() => state.set_open(false)}>
+      <Signing />
+    </FullScreenModal>
+  )
+})
+
+const Signing = observer(() => {
+  const state = root_store.wallet.eth.signing
+  return (
+    <Stack gap={1}>
+      <P level="h3" color="primary">
+        Sign a message
+      </P>
+      <ToggleButtonGroup
+        value={state.kind}
+        onChange={(_, v) => v && state.set_kind(v)}
+      >
+        <B value={'PersonalSign' satisfies SignKind}>Message</B>
+        <B value={'TypedData' satisfies SignKind}>Typed data</B>
+      </ToggleButtonGroup>
+      <Input
+        placeholder="Requesting site, e.g. https://app.example.com"
+        value={state.origin}
+        onChange={e => state.set_origin(e.target.value)}
+      />
+      <Textarea
+        minRows={4}
+        maxRows={12}
+        placeholder={
+          state.kind === 'TypedData'
+            ? 'EIP-712 typed data JSON'
+            : 'Message text or 0x hex'
+        }
+        value={state.payload}
+        onChange={e => state.set_payload(e.target.value)}
+        sx={{ fontFamily: 'monospace' }}
+      />
+      <Row>
+        <B
+          loading={state.previewing}
+          disabled={!state.payload}
+          onClick={() => state.load_preview()}
+        >
+          Review
+        </B>
+      </Row>
+      <Preview />
+      <Verify />
+    </Stack>
+  )
+})
+
+const Fields = ({ fields }: { fields: SignField[] }) => (
+  <Stack>
+    {fields.map((f, i) => (
+      <Row key={i} sx={{ pl: f.depth * 2, minWidth: 0 }}>
+        <P fontWeight={f.value === null ? 600 : 500}>{f.label}</P>
+        {f.value !== null && (
+          <P sx={{ fontFamily: 'monospace', wordBreak: 'break-all' }}>
+            {f.value}
+          </P>
+        )}
+      </Row>
+    ))}
+  </Stack>
+)
+
+const Preview = observer(() => {
+  const state = root_store.wallet.eth.signing
+  const preview = state.preview
+  if (!preview) return null
+  return (
+    <Stack gap={1}>
+      <P level="title-lg">{preview.title}</P>
+      {preview.errors.map(e => (
+        <Alert key={e} color="danger">
+          {e}
+        </Alert>
+      ))}
+      {preview.warnings.map(w => (
+        <Alert key={w} color="warning">
+          {w}
+        </Alert>
+      ))}
+      {preview.domain.length > 0 && <Fields fields={preview.domain} />}
+      <Fields fields={preview.message} />
+      {state.signed ? (
+        <Row>
+          <P>Signature</P>
+          <CompactSrt val={state.signed} n={10} copy />
+        </Row>
+      ) : (
+        !preview.errors.length && (
+          <SendTxButton loading={state.signing} onSend={() => state.sign()}>
+            Hold to sign
+          </SendTxButton>
+        )
+      )}
+    </Stack>
+  )
+})
+
+const Verify = observer(() => {
+  const state = root_store.wallet.eth.signing
+  return (
+    <Stack gap={1}>
+      <P level="title-md">Verify a signature</P>
+      <Input
+        placeholder="Signature"
+        value={state.signature}
+        onChange={e => state.set_signature(e.target.value)}
+      />
+      <Input
+        placeholder="Signer address"
+        value={state.signer}
+        onChange={e => state.set_signer(e.target.value)}
+      />
+      <Row>
+        <B
+          variant="soft"
+          disabled={!state.payload || !state.signature || !state.signer}
+          onClick={() => state.verify()}
+        >
+          Verify
+        </B>
+        {state.check &&
+          (state.check.valid ? (
+            <P color="success">Signed by {state.check.signer}</P>
+          ) : (
+            <P color="danger">Signed by {state.check.signer} instead</P>
+          ))}
+      </Row>
+    </Stack>
+  )
+})
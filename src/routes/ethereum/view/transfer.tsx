@@ -5,7 +5,11 @@ import { AddressInput } from '../../../components/address_input'
 import { SendTxButton } from '../../../components/send_tx_button'
 import { B, FullScreenModal, P, Row } from '../../../shortcuts'
 import { root_store } from '../../../view_model/root'
-import { OpenExplorerButton } from '../utils/shared'
+import {
+  OpenExplorerButton,
+  RecipientNotice,
+  SimulationNotice,
+} from '../utils/shared'
 
 export const TransferModal = observer(() => {
   const state = root_store.wallet.eth.transfer
@@ -23,7 +27,10 @@ const Transfer = observer(() => {
       <P level="h3" color="primary">
         Transact on Ethereum
       </P>
-      <AddressInput state={state.address} />
+      <AddressInput
+        state={state.address}
+        placeholder="Recipient address or ENS name"
+      />
       <TokenSelect />
       <CurrentBalance />
       <AmountInput />
@@ -36,6 +43,9 @@ const Transfer = observer(() => {
         Estimate
       </B>
       <TransactionFee />
+      <NonceNotice />
+      <RecipientNotice estimation={state.estimation} />
+      <SimulationNotice simulation={state.estimation?.simulation} />
 
       {!state.tx_hash ? <SendTransaction /> : <TransactionDetails />}
     </Stack>
@@ -115,6 +125,60 @@ const TransactionFee = observer(() => {
   )
 })
 
+const NonceNotice = observer(() => {
+  const state = root_store.wallet.eth.transfer
+  const estimation = state.estimation
+  if (
+    !estimation ||
+    (!estimation.queued &&
+      !estimation.nonce_gaps.length &&
+      !estimation.nonce_dropped.length)
+  ) {
+    return null
+  }
+  return (
+    <Stack gap={0.5}>
+      {estimation.queued > 0 && (
+        <P>
+          Queued behind {estimation.queued} pending transfer
+          {estimation.queued > 1 ? 's' : ''}, nonce {estimation.nonce}
+        </P>
+      )}
+      {estimation.nonce_dropped.length > 0 && (
+        <Row>
+          <P color="warning">
+            The node doesn't hold transfers with nonces{' '}
+            {estimation.nonce_dropped.join(', ')} yet, it may not have seen
+            them or dropped them
+          </P>
+          <B
+            variant="outlined"
+            loading={state.rebroadcasting}
+            onClick={() => state.rebroadcast_dropped()}
+          >
+            Rebroadcast
+          </B>
+        </Row>
+      )}
+      {estimation.nonce_gaps.length > 0 && (
+        <Row>
+          <P color="warning">
+            Nonces {estimation.nonce_gaps.join(', ')} are missing, this
+            transfer won't be mined until they are used
+          </P>
+          <B
+            variant="outlined"
+            loading={state.filling_gaps}
+            onClick={() => state.fill_nonce_gaps()}
+          >
+            Fill gaps
+          </B>
+        </Row>
+      )}
+    </Stack>
+  )
+})
+
 const SendTransaction = observer(() => {
   const { wallet } = root_store
   const state = root_store.wallet.eth.transfer
@@ -141,7 +205,7 @@ const TransactionDetails = observer(() => {
         Transaction hash <b>{state.tx_hash}</b>
       </P>
       <Row>
-        <OpenExplorerButton path={`tx/${state.tx_hash}`} />
+        {state.explorer_url && <OpenExplorerButton url={state.explorer_url} />}
         <B onClick={() => state.reset()}>Reset</B>
       </Row>
     </Stack>
@@ -0,0 +1,106 @@
+import { makeAutoObservable, runInAction } from 'mobx'
+import {
+  commands,
+  type FeeMode,
+  type TokenApproval,
+  type TokenApprovals,
+  type TransferEstimation,
+} from '../../../bindings/eth'
+import { unwrap_result } from '../../../lib/handle_err'
+import { notifier } from '../../../lib/notifier'
+import { Loader } from '../../../view_model/loader'
+
+const approval_key = (a: TokenApproval) => `${a.token_address}:${a.spender}`
+
+export class ApprovalsVM {
+  readonly list = new Loader<TokenApprovals>()
+
+  constructor() {
+    makeAutoObservable(this)
+  }
+
+  is_open: boolean = false
+  set_open(v: boolean) {
+    this.is_open = v
+    if (v) this.load()
+    else this.reset()
+  }
+
+  async load() {
+    this.list.start()
+    const r = await commands.listTokenApprovals().finally(() => {
+      runInAction(() => this.list.stop())
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    this.list.set(r.data)
+  }
+
+  scanning_older = false
+  async scan_older() {
+    this.scanning_older = true
+    const r = await commands.scanOlderTokenApprovals().finally(() => {
+      runInAction(() => {
+        this.scanning_older = false
+      })
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    await this.load()
+  }
+
+  fee_mode: FeeMode = 'Minimal'
+  set_fee_mode(fm: FeeMode) {
+    this.fee_mode = fm
+  }
+
+  // Approval the estimation below is for.
+  revoking?: string
+  estimation?: TransferEstimation
+  is_estimating = false
+  sending = false
+
+  is_revoking(a: TokenApproval) {
+    return this.revoking === approval_key(a)
+  }
+
+  reset() {
+    this.revoking = undefined
+    this.estimation = undefined
+  }
+
+  async estimate_revoke(a: TokenApproval) {
+    this.revoking = approval_key(a)
+    this.estimation = undefined
+    this.is_estimating = true
+    const estimation = await commands
+      .estimateRevoke(a.token_address, a.spender, this.fee_mode)
+      .then(res => unwrap_result(res))
+      .finally(() => {
+        runInAction(() => {
+          this.is_estimating = false
+        })
+      })
+    runInAction(() => {
+      this.estimation = estimation
+    })
+  }
+
+  async revoke() {
+    this.sending = true
+    const r = await commands.executeTransfer()
+    runInAction(() => {
+      this.sending = false
+      this.reset()
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      throw Error(r.error)
+    }
+    notifier.ok('Revoke sent, the list updates once it is mined')
+  }
+}
@@ -0,0 +1,236 @@
+import { makeAutoObservable, runInAction } from 'mobx'
+import {
+  type AbiFunctionView,
+  type AbiValueView,
+  commands,
+  type ContractSummary,
+  type ContractView,
+  type FeeMode,
+  type TransferEstimation,
+} from '../../../bindings/eth'
+import { unwrap_result } from '../../../lib/handle_err'
+import { notifier } from '../../../lib/notifier'
+import { Loader } from '../../../view_model/loader'
+
+export class ContractsVM {
+  readonly list = new Loader<ContractSummary[]>()
+  readonly call = new ContractCallVM()
+
+  constructor() {
+    makeAutoObservable(this)
+  }
+
+  is_open: boolean = false
+  set_open(v: boolean) {
+    this.is_open = v
+    if (v) this.load()
+    else this.reset()
+  }
+
+  async load() {
+    this.list.start()
+    const r = await commands.listContracts().finally(() => {
+      runInAction(() => this.list.stop())
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    this.list.set(r.data)
+  }
+
+  // Contract whose functions are shown.
+  contract?: ContractView
+
+  async open(address: string) {
+    const r = await commands.getContract(address)
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.contract = r.data
+      this.call.reset()
+    })
+  }
+
+  close_contract() {
+    this.contract = undefined
+    this.call.reset()
+  }
+
+  address = ''
+  set_address(address: string) {
+    this.address = address
+  }
+
+  name = ''
+  set_name(name: string) {
+    this.name = name
+  }
+
+  abi = ''
+  set_abi(abi: string) {
+    this.abi = abi
+  }
+
+  async load_file(file: File) {
+    const abi = await file.text()
+    runInAction(() => {
+      this.abi = abi
+      if (!this.name) this.name = file.name.replace(/\.json$/i, '')
+    })
+  }
+
+  saving = false
+
+  async save() {
+    this.saving = true
+    const r = await commands
+      .saveContract(this.address.trim(), this.name, this.abi)
+      .finally(() => {
+        runInAction(() => {
+          this.saving = false
+        })
+      })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    notifier.ok(`${r.data.name} saved`)
+    runInAction(() => {
+      this.address = ''
+      this.name = ''
+      this.abi = ''
+      this.contract = r.data
+      this.call.reset()
+    })
+    await this.load()
+  }
+
+  async remove(address: string) {
+    const r = await commands.removeContract(address)
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    await this.load()
+  }
+
+  reset() {
+    this.contract = undefined
+    this.call.reset()
+  }
+}
+
+export class ContractCallVM {
+  constructor() {
+    makeAutoObservable(this)
+  }
+
+  function?: AbiFunctionView
+  select(f: AbiFunctionView) {
+    this.reset()
+    this.function = f
+    this.args = f.inputs.map(() => '')
+  }
+
+  args: string[] = []
+  set_arg(i: number, arg: string) {
+    this.args[i] = arg
+    this.clear_result()
+  }
+
+  // ETH sent to a payable function.
+  value = ''
+  set_value(value: string) {
+    this.value = value
+    this.clear_result()
+  }
+
+  fee_mode: FeeMode = 'Minimal'
+  set_fee_mode(fm: FeeMode) {
+    this.fee_mode = fm
+    this.estimation = undefined
+  }
+
+  result?: AbiValueView[]
+  reading = false
+
+  estimation?: TransferEstimation
+  is_estimating = false
+  sending = false
+
+  tx_hash?: string
+  explorer_url?: string
+
+  clear_result() {
+    this.result = undefined
+    this.estimation = undefined
+  }
+
+  reset() {
+    this.function = undefined
+    this.args = []
+    this.value = ''
+    this.tx_hash = undefined
+    this.explorer_url = undefined
+    this.clear_result()
+  }
+
+  async read(address: string) {
+    if (!this.function) throw Error('no function selected')
+    this.reading = true
+    const r = await commands
+      .readContract(address, this.function.signature, this.args)
+      .finally(() => {
+        runInAction(() => {
+          this.reading = false
+        })
+      })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.result = r.data
+    })
+  }
+
+  async estimate(address: string) {
+    if (!this.function) throw Error('no function selected')
+    this.is_estimating = true
+    const estimation = await commands
+      .estimateContractCall({
+        address,
+        signature: this.function.signature,
+        args: this.args,
+        value: this.value,
+        fee_mode: this.fee_mode,
+      })
+      .then(res => unwrap_result(res))
+      .finally(() => {
+        runInAction(() => {
+          this.is_estimating = false
+        })
+      })
+    runInAction(() => {
+      this.estimation = estimation
+    })
+  }
+
+  async execute() {
+    this.sending = true
+    const r = await commands.executeTransfer()
+    runInAction(() => {
+      this.sending = false
+      if (r.status === 'error') {
+        notifier.err(r.error)
+        this.estimation = undefined
+        throw Error(r.error)
+      }
+      this.tx_hash = r.data.tx_hash
+      this.explorer_url = r.data.explorer_url ?? undefined
+    })
+  }
+}
@@ -0,0 +1,174 @@
+import { makeAutoObservable, runInAction } from 'mobx'
+import {
+  commands,
+  type FeeMode,
+  type NftCollectionView,
+  type NftView,
+  type TransferEstimation,
+} from '../../../bindings/eth'
+import { commands as shared_commands } from '../../../bindings/index'
+import { AddressInputVM } from '../../../components/address_input'
+import { unwrap_result } from '../../../lib/handle_err'
+import { notifier } from '../../../lib/notifier'
+import { Loader } from '../../../view_model/loader'
+
+export class NftsVM {
+  readonly collections = new Loader<NftCollectionView[]>()
+  readonly send = new NftSendVM()
+
+  constructor() {
+    makeAutoObservable(this)
+  }
+
+  async load() {
+    this.collections.start()
+    const r = await commands.listNfts().finally(() => {
+      runInAction(() => this.collections.stop())
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    this.collections.set(r.data)
+  }
+
+  async track(address: string) {
+    const r = await commands.trackNftCollection(address)
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return false
+    }
+    notifier.ok(`${r.data.name} is now tracked`)
+    await this.load()
+    return true
+  }
+
+  // Collection whose older transfers are being scanned.
+  scanning_older?: string
+  async scan_older(address: string) {
+    this.scanning_older = address
+    const r = await commands.scanOlderNfts(address).finally(() => {
+      runInAction(() => {
+        this.scanning_older = undefined
+      })
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    await this.load()
+  }
+
+  async untrack(address: string) {
+    const r = await commands.untrackNftCollection(address)
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    await this.load()
+  }
+}
+
+export class NftSendVM {
+  constructor() {
+    makeAutoObservable(this)
+  }
+
+  collection?: NftCollectionView
+  token?: NftView
+  open(collection: NftCollectionView, token: NftView) {
+    this.reset()
+    this.collection = collection
+    this.token = token
+  }
+
+  get is_open() {
+    return !!this.token
+  }
+
+  readonly address = new AddressInputVM(addr =>
+    shared_commands.validateAddress('Ethereum', addr),
+  )
+
+  amount = '1'
+  set_amount(amount: string) {
+    this.amount = amount
+  }
+
+  get is_multi_copy() {
+    return this.collection?.standard === 'Erc1155'
+  }
+
+  get amount_error() {
+    if (!/^\d+$/.test(this.amount) || !/[1-9]/.test(this.amount)) {
+      return 'Enter a whole number of copies.'
+    }
+    if (this.token && BigInt(this.amount) > BigInt(this.token.amount)) {
+      return `You hold ${this.token.amount} copies.`
+    }
+    return undefined
+  }
+
+  fee_mode: FeeMode = 'Minimal'
+  set_fee_mode(fm: FeeMode) {
+    this.fee_mode = fm
+  }
+
+  estimation?: TransferEstimation
+  is_estimating = false
+  sending = false
+
+  tx_hash?: string
+  explorer_url?: string
+
+  get disabled() {
+    return !this.address.is_valid || !!this.amount_error
+  }
+
+  reset() {
+    this.collection = undefined
+    this.token = undefined
+    this.address.reset()
+    this.amount = '1'
+    this.estimation = undefined
+    this.tx_hash = undefined
+    this.explorer_url = undefined
+  }
+
+  async estimate() {
+    if (!this.collection || !this.token) throw Error('no NFT selected')
+    this.is_estimating = true
+    const estimation = await commands
+      .estimateNftTransfer({
+        collection: this.collection.address,
+        token_id: this.token.token_id,
+        amount: this.amount,
+        recipient: this.address.val,
+        fee_mode: this.fee_mode,
+      })
+      .then(res => unwrap_result(res))
+      .finally(() => {
+        runInAction(() => {
+          this.is_estimating = false
+        })
+      })
+    runInAction(() => {
+      this.estimation = estimation
+    })
+  }
+
+  async execute() {
+    this.sending = true
+    const r = await commands.executeTransfer()
+    runInAction(() => {
+      this.sending = false
+      if (r.status === 'error') {
+        notifier.err(r.error)
+        this.estimation = undefined
+        throw Error(r.error)
+      }
+      this.tx_hash = r.data.tx_hash
+      this.explorer_url = r.data.explorer_url ?? undefined
+    })
+  }
+}
@@ -0,0 +1,127 @@
+import { makeAutoObservable, runInAction } from 'mobx'
+import {
+  commands,
+  type SignatureCheck,
+  type SignKind,
+  type SignPreview,
+  type SignRequest,
+} from '../../../bindings/eth'
+import { notifier } from '../../../lib/notifier'
+
+export class SigningVM {
+  constructor() {
+    makeAutoObservable(this)
+  }
+
+  is_open: boolean = false
+  set_open(v: boolean) {
+    this.is_open = v
+    if (!v) this.reset()
+  }
+
+  kind: SignKind = 'PersonalSign'
+  set_kind(kind: SignKind) {
+    this.kind = kind
+    this.clear_result()
+  }
+
+  payload = ''
+  set_payload(payload: string) {
+    this.payload = payload
+    this.clear_result()
+  }
+
+  origin = ''
+  set_origin(origin: string) {
+    this.origin = origin
+    this.clear_result()
+  }
+
+  // Signature and signer to verify instead of signing.
+  signature = ''
+  set_signature(signature: string) {
+    this.signature = signature
+    this.check = undefined
+  }
+  signer = ''
+  set_signer(signer: string) {
+    this.signer = signer
+    this.check = undefined
+  }
+
+  preview?: SignPreview
+  signed?: string
+  check?: SignatureCheck
+  previewing = false
+  signing = false
+
+  get request(): SignRequest {
+    return {
+      kind: this.kind,
+      payload: this.payload,
+      origin: this.origin.trim() || null,
+    }
+  }
+
+  clear_result() {
+    this.preview = undefined
+    this.signed = undefined
+    this.check = undefined
+  }
+
+  reset() {
+    this.kind = 'PersonalSign'
+    this.payload = ''
+    this.origin = ''
+    this.signature = ''
+    this.signer = ''
+    this.clear_result()
+  }
+
+  async load_preview() {
+    this.previewing = true
+    const r = await commands.previewSignature(this.request).finally(() => {
+      runInAction(() => {
+        this.previewing = false
+      })
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.preview = r.data
+    })
+  }
+
+  async sign() {
+    this.signing = true
+    const r = await commands.signPayload(this.request).finally(() => {
+      runInAction(() => {
+        this.signing = false
+      })
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.signed = r.data.signature
+    })
+  }
+
+  async verify() {
+    const r = await commands.verifySignature(
+      this.request,
+      this.signature.trim(),
+      this.signer.trim(),
+    )
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.check = r.data
+    })
+  }
+}
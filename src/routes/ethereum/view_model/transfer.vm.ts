@@ -57,8 +57,10 @@ export class TransferVM {
   estimation?: TransferEstimation
 
   tx_hash?: string
-  set_tx_hash(h?: string) {
+  explorer_url?: string
+  set_tx_hash(h?: string, explorer_url?: string) {
     this.tx_hash = h
+    this.explorer_url = explorer_url
   }
 
   reset() {
@@ -68,6 +70,7 @@ export class TransferVM {
     this.token = undefined
     this.estimation = undefined
     this.tx_hash = undefined
+    this.explorer_url = undefined
   }
 
   is_estimating = false
@@ -103,6 +106,40 @@ export class TransferVM {
     })
   }
 
+  filling_gaps = false
+
+  async fill_nonce_gaps() {
+    this.filling_gaps = true
+    const r = await commands.fillNonceGaps().finally(() => {
+      runInAction(() => {
+        this.filling_gaps = false
+      })
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    notifier.ok(`Sent ${r.data.length} gap filling transactions`)
+    await this.estimate()
+  }
+
+  rebroadcasting = false
+
+  async rebroadcast_dropped() {
+    this.rebroadcasting = true
+    const r = await commands.rebroadcastDroppedTransfers().finally(() => {
+      runInAction(() => {
+        this.rebroadcasting = false
+      })
+    })
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    notifier.ok(`Rebroadcast ${r.data.length} transfers`)
+    await this.estimate()
+  }
+
   async execute() {
     this.sending = true
     const r = await commands.executeTransfer()
@@ -114,7 +151,7 @@ export class TransferVM {
         this.estimation = undefined
         throw Error(r.error)
       }
-      this.set_tx_hash(r.data)
+      this.set_tx_hash(r.data.tx_hash, r.data.explorer_url ?? undefined)
     })
   }
 }
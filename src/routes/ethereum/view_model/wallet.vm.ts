@@ -2,6 +2,7 @@ import { makeAutoObservable, runInAction } from 'mobx'
 import type { EthereumUnlock } from '../../../bindings'
 import {
   commands,
+  type EvmNetworkView,
   type NetworkStatus,
   type WalletBalance,
 } from '../../../bindings/eth'
@@ -9,12 +10,20 @@ import { AccountSelectorVM } from '../../../components/account_selector'
 import { unwrap_result } from '../../../lib/handle_err'
 import { notifier } from '../../../lib/notifier'
 import { Loader } from '../../../view_model/loader'
+import { ApprovalsVM } from './approvals.vm'
+import { ContractsVM } from './contracts.vm'
+import { NftsVM } from './nfts.vm'
+import { SigningVM } from './signing.vm'
 import { TransferVM } from './transfer.vm'
 
 export class EthereumWalletVM {
   readonly chain = 'Ethereum' as const
   readonly balance = new Loader<WalletBalance>()
   readonly transfer = new TransferVM()
+  readonly approvals = new ApprovalsVM()
+  readonly contracts = new ContractsVM()
+  readonly nfts = new NftsVM()
+  readonly signing = new SigningVM()
   readonly account_selector = new AccountSelectorVM(this.chain, async _ => {
     await this.load_active_account()
   })
@@ -26,13 +35,43 @@ export class EthereumWalletVM {
   init(unlock: EthereumUnlock) {
     this.account_selector.init(unlock.accounts, unlock.active_account.index)
     this.set_active_account(unlock.active_account.address)
+    this.network = unlock.active_account.network
+    this.load_networks()
     this.getChainInfo()
     this.getBalance()
+    this.nfts.load()
   }
 
   address!: string
   chainInfo!: NetworkStatus
   usd_price = 0
+  network = ''
+  networks: EvmNetworkView[] = []
+
+  async load_networks() {
+    const r = await commands.listEvmNetworks()
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.networks = r.data
+    })
+  }
+
+  async switch_network(id: string) {
+    const r = await commands.switchEthNetwork(id)
+    if (r.status === 'error') {
+      notifier.err(r.error)
+      return
+    }
+    runInAction(() => {
+      this.network = r.data.network
+    })
+    this.getChainInfo()
+    this.nfts.load()
+    await this.getBalance()
+  }
 
   setChainInfo(c: NetworkStatus) {
     this.chainInfo = c
@@ -77,8 +116,11 @@ export class EthereumWalletVM {
 
     runInAction(() => {
       this.set_active_account(result.address)
+      this.network = result.network
       this.account_selector.set_account_address(result.index, result.address)
     })
+    this.getChainInfo()
+    this.nfts.load()
     await this.getBalance()
   }
 